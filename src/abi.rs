@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use thiserror::Error;
+
+/// Error types for ABI type parsing and data decoding.
+#[derive(Error, Debug)]
+pub enum AbiError {
+    /// The type string could not be parsed into a known ABI type.
+    #[error("Invalid ABI type: {0}")]
+    Type(String),
+
+    /// The encoded data is shorter than the types require or contains invalid offsets.
+    #[error("ABI data is too short or malformed")]
+    Data,
+
+    /// The provided ABI or compiler artifact could not be interpreted.
+    #[error("Invalid ABI artifact: {0}")]
+    Artifact(String),
+
+    /// The ABI file could not be parsed as JSON.
+    #[error("ABI JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A Solidity ABI type, as used in function signatures and constructor inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    Address,
+    Bool,
+    /// Unsigned integer with the given bit size.
+    Uint(usize),
+    /// Signed integer with the given bit size.
+    Int(usize),
+    /// Fixed size byte array with the given length (`bytes1` to `bytes32`).
+    FixedBytes(usize),
+    Bytes,
+    String,
+    /// External function pointer, encoded as an address followed by a selector like a `bytes24`.
+    Function,
+    /// Dynamic array, e.g. `address[]`.
+    Array(Box<ParamType>),
+    /// Fixed size array, e.g. `uint256[3]`.
+    FixedArray(Box<ParamType>, usize),
+    /// Tuple of types, e.g. `(address,uint256)`.
+    Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+    /// Parses a type string such as `uint256`, `address[]` or `(bytes32,uint8[2])[]`.
+    ///
+    /// Aliases are expanded (`uint` to `uint256`, `int` to `int256`, `byte` to `bytes1`)
+    /// and surrounding whitespace is ignored.
+    ///
+    /// Returns:
+    /// The parsed `ParamType`, or an `AbiError::Type` if the string is not a valid ABI type.
+    pub fn parse(input: &str) -> Result<Self, AbiError> {
+        let ty = input.trim();
+        let invalid = || AbiError::Type(input.to_string());
+
+        // Array suffixes bind last, e.g. `(uint256,address)[2][]` is an array of `(uint256,address)[2]`
+        if let Some(stripped) = ty.strip_suffix(']') {
+            let open = stripped.rfind('[').ok_or_else(invalid)?;
+            let inner = Box::new(Self::parse(&stripped[..open])?);
+            let size = stripped[open + 1..].trim();
+
+            return match size.is_empty() {
+                true => Ok(Self::Array(inner)),
+                false => Ok(Self::FixedArray(inner, size.parse().map_err(|_| invalid())?)),
+            };
+        }
+
+        if let Some(inner) = ty.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            if inner.trim().is_empty() {
+                return Ok(Self::Tuple(Vec::new()));
+            }
+            return split_top_level(inner).ok_or_else(invalid)?.into_iter().map(Self::parse).collect::<Result<_, _>>().map(Self::Tuple);
+        }
+
+        match ty {
+            "address" => Ok(Self::Address),
+            "bool" => Ok(Self::Bool),
+            "string" => Ok(Self::String),
+            "bytes" => Ok(Self::Bytes),
+            "byte" => Ok(Self::FixedBytes(1)),
+            "function" => Ok(Self::Function),
+            "uint" => Ok(Self::Uint(256)),
+            "int" => Ok(Self::Int(256)),
+            _ => {
+                let bits = |digits: &str| match digits.parse::<usize>() {
+                    Ok(bits) if bits % 8 == 0 && (8..=256).contains(&bits) => Ok(bits),
+                    _ => Err(invalid()),
+                };
+
+                if let Some(digits) = ty.strip_prefix("uint") {
+                    Ok(Self::Uint(bits(digits)?))
+                } else if let Some(digits) = ty.strip_prefix("int") {
+                    Ok(Self::Int(bits(digits)?))
+                } else if let Some(digits) = ty.strip_prefix("bytes") {
+                    match digits.parse::<usize>() {
+                        Ok(len) if (1..=32).contains(&len) => Ok(Self::FixedBytes(len)),
+                        _ => Err(invalid()),
+                    }
+                } else {
+                    Err(invalid())
+                }
+            }
+        }
+    }
+
+    /// Whether the type is dynamically sized and therefore encoded through an offset.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            Self::Bytes | Self::String | Self::Array(_) => true,
+            Self::FixedArray(inner, _) => inner.is_dynamic(),
+            Self::Tuple(types) => types.iter().any(Self::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// The number of bytes the type occupies in the head of an encoding.
+    fn head_size(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => 32,
+            Self::FixedArray(inner, len) => inner.head_size() * len,
+            Self::Tuple(types) => types.iter().map(Self::head_size).sum(),
+            _ => 32,
+        }
+    }
+}
+
+impl Display for ParamType {
+    /// Formats the type in its canonical form, as used for selector hashing.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Address => write!(f, "address"),
+            Self::Bool => write!(f, "bool"),
+            Self::Uint(bits) => write!(f, "uint{bits}"),
+            Self::Int(bits) => write!(f, "int{bits}"),
+            Self::FixedBytes(len) => write!(f, "bytes{len}"),
+            Self::Bytes => write!(f, "bytes"),
+            Self::String => write!(f, "string"),
+            Self::Function => write!(f, "function"),
+            Self::Array(inner) => write!(f, "{inner}[]"),
+            Self::FixedArray(inner, len) => write!(f, "{inner}[{len}]"),
+            Self::Tuple(types) => {
+                let types: Vec<_> = types.iter().map(ToString::to_string).collect();
+                write!(f, "({})", types.join(","))
+            }
+        }
+    }
+}
+
+/// Splits a comma separated list at the top level, ignoring commas nested in parentheses.
+///
+/// Returns `None` if the parentheses are unbalanced.
+pub(crate) fn split_top_level(input: &str) -> Option<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (idx, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                parts.push(&input[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return None;
+    }
+
+    parts.push(&input[start..]);
+    Some(parts)
+}
+
+/// A decoded ABI value.
+///
+/// Integers are kept as decimal strings since they may exceed the native integer sizes,
+/// and byte values are hex encoded with the "0x" prefix. Deserialized values are told apart by their JSON
+/// type only, e.g. every string is read as an `Address`, which serializes them the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AbiValue {
+    Address(String),
+    Bool(bool),
+    Uint(String),
+    Int(String),
+    FixedBytes(String),
+    Bytes(String),
+    String(String),
+    Array(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+impl Display for AbiValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |values: &[AbiValue]| values.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+
+        match self {
+            Self::Address(v) | Self::Uint(v) | Self::Int(v) | Self::FixedBytes(v) | Self::Bytes(v) => write!(f, "{v}"),
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::String(v) => write!(f, "{v:?}"),
+            Self::Array(values) => write!(f, "[{}]", join(values)),
+            Self::Tuple(values) => write!(f, "({})", join(values)),
+        }
+    }
+}
+
+/// Decodes ABI encoded `data` according to the given list of types.
+///
+/// Arguments:
+/// * `types`: The types of the encoded values, in order.
+/// * `data`: The encoded data, without any selector prefix.
+///
+/// Returns:
+/// A `Result` containing one `AbiValue` per type, or an `AbiError::Data` if the data
+/// does not match the types.
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+    decode_params(types, data, 0)
+}
+
+/// Decodes a sequence of values whose head starts at `base`.
+/// Offsets of dynamic values are relative to `base`.
+fn decode_params(types: &[ParamType], data: &[u8], base: usize) -> Result<Vec<AbiValue>, AbiError> {
+    let mut values = Vec::with_capacity(types.len());
+    let mut cursor = base;
+
+    for ty in types {
+        match ty.is_dynamic() {
+            true => {
+                let offset = read_usize(data, cursor)?;
+                values.push(decode_value(ty, data, base.checked_add(offset).ok_or(AbiError::Data)?)?);
+            }
+            false => values.push(decode_value(ty, data, cursor)?),
+        }
+        cursor += ty.head_size();
+    }
+
+    Ok(values)
+}
+
+fn decode_value(ty: &ParamType, data: &[u8], pos: usize) -> Result<AbiValue, AbiError> {
+    match ty {
+        ParamType::Address => Ok(AbiValue::Address(format!("0x{}", hex::encode(&read_word(data, pos)?[12..])))),
+        ParamType::Bool => Ok(AbiValue::Bool(read_word(data, pos)?[31] != 0)),
+        ParamType::Uint(_) => Ok(AbiValue::Uint(word_to_decimal(read_word(data, pos)?))),
+        ParamType::Int(_) => Ok(AbiValue::Int(word_to_signed_decimal(read_word(data, pos)?))),
+        ParamType::FixedBytes(len) => Ok(AbiValue::FixedBytes(format!("0x{}", hex::encode(&read_word(data, pos)?[..*len])))),
+        ParamType::Function => Ok(AbiValue::FixedBytes(format!("0x{}", hex::encode(&read_word(data, pos)?[..24])))),
+        ParamType::Bytes => Ok(AbiValue::Bytes(format!("0x{}", hex::encode(read_dynamic(data, pos)?)))),
+        ParamType::String => Ok(AbiValue::String(String::from_utf8_lossy(read_dynamic(data, pos)?).into_owned())),
+        ParamType::Array(inner) => {
+            let len = read_usize(data, pos)?;
+            // Every element occupies at least one word, which bounds the allocation for hostile lengths
+            if len > data.len() / 32 {
+                return Err(AbiError::Data);
+            }
+            decode_params(&vec![*inner.clone(); len], data, pos + 32).map(AbiValue::Array)
+        }
+        ParamType::FixedArray(inner, len) => decode_params(&vec![*inner.clone(); *len], data, pos).map(AbiValue::Array),
+        ParamType::Tuple(types) => decode_params(types, data, pos).map(AbiValue::Tuple),
+    }
+}
+
+/// Reads the 32-byte word at `pos`.
+pub(crate) fn read_word(data: &[u8], pos: usize) -> Result<&[u8; 32], AbiError> {
+    data.get(pos..pos.checked_add(32).ok_or(AbiError::Data)?)
+        .and_then(|word| word.try_into().ok())
+        .ok_or(AbiError::Data)
+}
+
+/// Reads the word at `pos` as an offset or length, which must fit in a `usize`.
+pub(crate) fn read_usize(data: &[u8], pos: usize) -> Result<usize, AbiError> {
+    let word = read_word(data, pos)?;
+    if word[..24].iter().any(|b| *b != 0) {
+        return Err(AbiError::Data);
+    }
+    usize::try_from(u64::from_be_bytes(word[24..].try_into().unwrap())).map_err(|_| AbiError::Data)
+}
+
+/// Reads a length-prefixed byte sequence at `pos`.
+fn read_dynamic(data: &[u8], pos: usize) -> Result<&[u8], AbiError> {
+    let len = read_usize(data, pos)?;
+    let start = pos + 32;
+    data.get(start..start.checked_add(len).ok_or(AbiError::Data)?).ok_or(AbiError::Data)
+}
+
+/// Converts a big-endian 256-bit word into its decimal representation.
+pub(crate) fn word_to_decimal(word: &[u8; 32]) -> String {
+    let mut value = *word;
+    let mut digits = Vec::new();
+
+    // Long division by 10 until the value is exhausted
+    while value.iter().any(|b| *b != 0) {
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+/// Converts a big-endian two's complement 256-bit word into its signed decimal representation.
+fn word_to_signed_decimal(word: &[u8; 32]) -> String {
+    if word[0] & 0x80 == 0 {
+        return word_to_decimal(word);
+    }
+
+    // Negate: invert all bits and add one
+    let mut value = word.map(|b| !b);
+    for byte in value.iter_mut().rev() {
+        let (sum, overflow) = byte.overflowing_add(1);
+        *byte = sum;
+        if !overflow {
+            break;
+        }
+    }
+
+    format!("-{}", word_to_decimal(&value))
+}
@@ -0,0 +1,378 @@
+use crate::{bytecode::Bytecode, opcode::Opcode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+/// The maximum number of instructions executed while looking for the access check of a function.
+const ACCESS_BUDGET: usize = 4096;
+/// The maximum number of instructions between a failed access check and the `REVERT` it leads to.
+const REVERT_WINDOW: usize = 256;
+/// The maximum number of words of a hash for it to be the slot of a mapping keyed by the caller.
+const MAX_HASHED_WORDS: usize = 4;
+
+/// Who can call a function, inferred from the checks following its entry.
+///
+/// Only unambiguous idioms result in a gated hint, a function whose checks aren't understood is `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessHint {
+    /// Every successful call passes a check comparing `CALLER` with a stored or constant address, like `onlyOwner`.
+    OwnerGated,
+    /// Every successful call passes a check of a boolean mapping keyed by `CALLER`, like `onlyRole`.
+    RoleGated,
+    /// The function can succeed without any access check.
+    Open,
+    /// The checks of the function couldn't be followed, e.g. through a computed jump or a loop.
+    Unknown,
+}
+
+impl AccessHint {
+    /// Whether only some callers can call the function.
+    pub fn is_gated(&self) -> bool {
+        matches!(self, Self::OwnerGated | Self::RoleGated)
+    }
+}
+
+impl Display for AccessHint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::OwnerGated => "owner_gated",
+            Self::RoleGated => "role_gated",
+            Self::Open => "open",
+            Self::Unknown => "unknown",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The kind of access check a value is the outcome of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Check {
+    Owner,
+    Role,
+}
+
+/// The abstract value of a stack item or memory word, as far as access checks are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    /// A constant small enough to be an offset, a jump destination or a mask.
+    Const(usize),
+    /// A larger constant, e.g. an address or an address mask.
+    Word,
+    /// `msg.sender`.
+    Caller,
+    /// A value loaded from a constant storage slot, such as the owner.
+    Stored,
+    /// A hash of memory holding the caller, i.e. the slot of a mapping keyed by the caller.
+    CallerSlot,
+    /// A value loaded from the slot of a mapping keyed by the caller.
+    CallerMapped,
+    /// The outcome of an access check, `passed` if the value is non-zero exactly when the check passes.
+    Check { check: Check, passed: bool },
+    Unknown,
+}
+
+impl Value {
+    /// The value of `ISZERO`.
+    fn is_zero(self) -> Self {
+        match self {
+            Self::Const(value) => Self::Const(usize::from(value == 0)),
+            Self::Check { check, passed } => Self::Check { check, passed: !passed },
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The value of `EQ`, which is an owner check when comparing the caller with a stored or constant address.
+    fn eq(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Caller, Self::Stored | Self::Word) | (Self::Stored | Self::Word, Self::Caller) => Self::Check { check: Check::Owner, passed: true },
+            (value, Self::Const(0)) | (Self::Const(0), value) => value.is_zero(),
+            (Self::Const(a), Self::Const(b)) => Self::Const(usize::from(a == b)),
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The value of `AND`, masking keeps the masked value, and a boolean read from a mapping keyed by the caller is a role check.
+    fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Const(a), Self::Const(b)) => Self::Const(a & b),
+            (Self::CallerMapped, Self::Const(0xff)) | (Self::Const(0xff), Self::CallerMapped) => Self::Check { check: Check::Role, passed: true },
+            (value, Self::Const(_) | Self::Word) | (Self::Const(_) | Self::Word, value) if value != Self::Unknown => value,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// How a path of a function ended.
+enum Outcome {
+    /// The path ended successfully without passing an access check.
+    Open,
+    /// The path passed an access check whose failure reverts.
+    Gated(Check),
+    /// The path reverted.
+    Reverted,
+    /// The path couldn't be followed.
+    Lost,
+}
+
+/// A path through the code, with the abstract stack and memory.
+#[derive(Clone)]
+struct Path {
+    pc: usize,
+    stack: Vec<Value>,
+    memory: HashMap<usize, Value>,
+}
+
+/// What a single instruction did to a path.
+enum Step {
+    Next,
+    /// A conditional jump to `target` on `condition`.
+    Branch { condition: Value, target: usize },
+    Success,
+    Revert,
+    /// An instruction with side effects, e.g. a storage write, a call or a log.
+    Effect,
+    Lost,
+}
+
+/// The decoded code, indexed by offset.
+struct Code {
+    instructions: Vec<Option<(u8, usize)>>,
+}
+
+impl Code {
+    fn new(bytecode: &Bytecode) -> Self {
+        let mut instructions = vec![None; bytecode.len()];
+        for instruction in bytecode.instructions() {
+            instructions[instruction.offset] = Some((instruction.opcode, push_value(instruction.immediate)));
+        }
+        Self { instructions }
+    }
+
+    fn is_jumpdest(&self, offset: usize) -> bool {
+        matches!(self.instructions.get(offset), Some(Some((0x5b, _))))
+    }
+
+    /// Executes the instruction at the `pc` of the path.
+    fn step(&self, path: &mut Path) -> Step {
+        let Some(Some((opcode, push))) = self.instructions.get(path.pc).copied() else {
+            return Step::Lost;
+        };
+        let op = Opcode(opcode);
+        let Some((pops, pushes)) = op.stack_effect() else {
+            return Step::Revert;
+        };
+        // The items below the stack of the entry are unknown
+        let mut args: Vec<Value> = (0..pops).map(|_| path.stack.pop().unwrap_or(Value::Unknown)).collect();
+        path.pc += 1 + op.immediate_size();
+
+        let result = match opcode {
+            0x00 | 0xf3 => return Step::Success,
+            0xfd | 0xfe => return Step::Revert,
+            // Masks are built from constants, e.g. `(1 << 160) - 1`
+            0x01..=0x03 | 0x0a | 0x19 | 0x1b if args.iter().all(|arg| matches!(arg, Value::Const(_) | Value::Word)) => constant(opcode, &args),
+            0x14 => args[0].eq(args[1]),
+            0x15 => args[0].is_zero(),
+            0x16 => args[0].and(args[1]),
+            // DIV and SHR of a packed storage slot keep what it was loaded from
+            0x04 if matches!(args[0], Value::Stored | Value::CallerMapped) => args[0],
+            0x1c if matches!(args[1], Value::Stored | Value::CallerMapped) => args[1],
+            0x20 => match (args[0], args[1]) {
+                (Value::Const(offset), Value::Const(len)) if len <= MAX_HASHED_WORDS * 32 && (offset..offset + len).step_by(32).any(|word| path.memory.get(&word) == Some(&Value::Caller)) => Value::CallerSlot,
+                _ => Value::Unknown,
+            },
+            0x33 => Value::Caller,
+            0x51 => match args[0] {
+                Value::Const(offset) => path.memory.get(&offset).copied().unwrap_or(Value::Unknown),
+                _ => Value::Unknown,
+            },
+            0x52 => {
+                // Writes to allocated memory don't matter, mapping keys are hashed from the scratch space
+                if let Value::Const(offset) = args[0] {
+                    path.memory.insert(offset, args[1]);
+                }
+                return Step::Next;
+            }
+            0x54 => match args[0] {
+                Value::Const(_) => Value::Stored,
+                Value::CallerSlot => Value::CallerMapped,
+                _ => Value::Unknown,
+            },
+            0x56 => {
+                return match args[0] {
+                    Value::Const(target) if self.is_jumpdest(target) => {
+                        path.pc = target;
+                        Step::Next
+                    }
+                    _ => Step::Lost,
+                }
+            }
+            0x57 => {
+                return match args[0] {
+                    Value::Const(target) if self.is_jumpdest(target) => Step::Branch { condition: args[1], target },
+                    _ => Step::Lost,
+                }
+            }
+            0x5f..=0x7f => match push {
+                usize::MAX => Value::Word,
+                value => Value::Const(value),
+            },
+            0x80..=0x8f => {
+                path.stack.extend(args.iter().rev());
+                path.stack.push(args[pops - 1]);
+                return Step::Next;
+            }
+            0x90..=0x9f => {
+                args.swap(0, pops - 1);
+                path.stack.extend(args.iter().rev());
+                return Step::Next;
+            }
+            0x53 | 0x55 | 0xa0..=0xa4 | 0xf0..=0xf2 | 0xf4 | 0xf5 | 0xfa | 0xff => {
+                path.stack.extend((0..pushes).map(|_| Value::Unknown));
+                return Step::Effect;
+            }
+            _ => Value::Unknown,
+        };
+        path.stack.extend((0..pushes).map(|_| result));
+        Step::Next
+    }
+
+    /// Whether the path reverts without any side effect, following the jumps of a revert helper.
+    fn reverts(&self, mut path: Path) -> bool {
+        for _ in 0..REVERT_WINDOW {
+            match self.step(&mut path) {
+                Step::Next => {}
+                Step::Branch { condition: Value::Const(condition), target } => {
+                    if condition != 0 {
+                        path.pc = target;
+                    }
+                }
+                Step::Revert => return true,
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Follows the paths of a function from its entry, until each of them ends, passes an access check or can't be followed.
+    fn access(&self, entry: usize) -> AccessHint {
+        let mut paths = vec![Path { pc: entry, stack: Vec::new(), memory: HashMap::new() }];
+        let mut checks = Vec::new();
+        let mut lost = false;
+        let mut budget = ACCESS_BUDGET;
+
+        while let Some(mut path) = paths.pop() {
+            let outcome = loop {
+                if budget == 0 {
+                    return AccessHint::Unknown;
+                }
+                budget -= 1;
+
+                match self.step(&mut path) {
+                    Step::Next | Step::Effect => {}
+                    Step::Success => break Outcome::Open,
+                    Step::Revert => break Outcome::Reverted,
+                    Step::Lost => break Outcome::Lost,
+                    Step::Branch { condition: Value::Const(condition), target } => {
+                        if condition != 0 {
+                            path.pc = target;
+                        }
+                    }
+                    Step::Branch { condition, target } => {
+                        let mut taken = path.clone();
+                        taken.pc = target;
+                        // A check reverting when it fails gates the other branch, checks reverting when they pass
+                        // such as a deny list aren't access checks, and both branches are followed
+                        if let Value::Check { check, passed } = condition {
+                            let failed = match passed {
+                                true => &path,
+                                false => &taken,
+                            };
+                            if self.reverts(failed.clone()) {
+                                break Outcome::Gated(check);
+                            }
+                        }
+                        paths.push(taken);
+                    }
+                }
+            };
+
+            match outcome {
+                Outcome::Open => return AccessHint::Open,
+                Outcome::Gated(check) => checks.push(check),
+                Outcome::Reverted => {}
+                Outcome::Lost => lost = true,
+            }
+        }
+
+        match checks.split_first().filter(|_| !lost) {
+            Some((Check::Owner, rest)) if rest.iter().all(|check| *check == Check::Owner) => AccessHint::OwnerGated,
+            Some((Check::Role, rest)) if rest.iter().all(|check| *check == Check::Role) => AccessHint::RoleGated,
+            _ => AccessHint::Unknown,
+        }
+    }
+}
+
+/// The value of an arithmetic instruction on constants, a `Word` if it doesn't fit in 8 bytes.
+fn constant(opcode: u8, args: &[Value]) -> Value {
+    // NOT, and any operation on a larger constant, is a larger constant
+    let [Value::Const(a), Value::Const(b)] = *args else {
+        return Value::Word;
+    };
+    let value = match opcode {
+        0x01 => a.checked_add(b),
+        0x02 => a.checked_mul(b),
+        0x03 => a.checked_sub(b),
+        0x0a => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+        // SHL shifts its second argument by the first
+        0x1b => u32::try_from(a).ok().filter(|a| *a < usize::BITS && b.leading_zeros() >= *a).map(|a| b << a),
+        _ => None,
+    };
+    value.filter(|value| *value != usize::MAX).map_or(Value::Word, Value::Const)
+}
+
+/// The value of a push, or `usize::MAX` if it doesn't fit in 8 bytes.
+fn push_value(immediate: &[u8]) -> usize {
+    let significant: Vec<u8> = immediate.iter().copied().skip_while(|byte| *byte == 0).collect();
+    match significant.len() {
+        0..=8 => significant.iter().fold(0, |value, byte| (value << 8) | usize::from(*byte)),
+        _ => usize::MAX,
+    }
+}
+
+/// Infers who can call each function of a contract from the checks following its entry.
+///
+/// The entry of a function is the destination of its dispatcher branch, `PUSH4 <selector> EQ PUSH<n> <offset> JUMPI`.
+/// Its paths are followed with an abstract stack and memory, through internal calls and jumps to constant
+/// destinations, within a bounded number of instructions. A path passing a conditional jump on an access
+/// check, whose failing branch reverts, is gated:
+/// * `CALLER` compared with a value loaded from a constant storage slot or with a constant address is an owner check.
+/// * A boolean loaded from the slot of a mapping keyed by `CALLER`, e.g. `_roles[role].hasRole[msg.sender]`, is a role check.
+///
+/// A function is gated only if no path succeeds without passing a check, and all of its checks are of the same kind.
+/// It's open if a path succeeds without any check, and unknown otherwise, e.g. if a path can't be followed.
+///
+/// Arguments:
+/// * `bytecode`: The runtime code of the contract.
+///
+/// Returns:
+/// The `AccessHint` of each selector with a dispatcher branch, keyed by selector.
+pub fn access_hints(bytecode: &Bytecode) -> BTreeMap<String, AccessHint> {
+    let code = Code::new(bytecode);
+    let instructions: Vec<_> = bytecode.instructions().collect();
+
+    let mut hints = BTreeMap::new();
+    for window in instructions.windows(4) {
+        let [selector, eq, push, jumpi] = window else { continue };
+        if selector.opcode != 0x63 || selector.immediate.len() != 4 || eq.opcode != 0x14 || !(0x60..=0x62).contains(&push.opcode) || jumpi.opcode != 0x57 {
+            continue;
+        }
+        let entry = push_value(push.immediate);
+        if code.is_jumpdest(entry) {
+            hints.entry(hex::encode(selector.immediate)).or_insert_with(|| code.access(entry));
+        }
+    }
+    hints
+}
@@ -1,4 +1,4 @@
-use std::ops::Deref;
+use std::{fmt::Display, ops::Deref};
 use thiserror::Error;
 /// Error types for address validation and creation.
 ///
@@ -34,8 +34,12 @@ pub enum AddressError {
 ///
 /// The `Address` struct also implements Deref to allow using it as a string reference.
 ///
+/// Addresses are normalized to lowercase hex when created, so the same contract given in any casing
+/// compares and hashes equally and is requested, cached and named identically. `Display` formats
+/// the EIP-55 checksum, which is the casing users expect to read.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Address {
-    /// The actual string representation of the address.
+    /// The lowercase string representation of the address.
     /// Stored as a `String` to own the address data.
     inner: String,
 }
@@ -75,6 +79,27 @@ impl Address {
 
         Ok(())
     }
+
+    /// Formats the address with the mixed-case checksum of EIP-55.
+    ///
+    /// Without the `keccak` feature the checksum can't be computed, and the lowercase address is returned.
+    pub fn checksummed(&self) -> String {
+        let hex = &self.inner[2..];
+        let Ok(hash) = crate::signature::try_hash(hex) else {
+            return self.inner.clone();
+        };
+
+        // Letters are uppercased where the corresponding nibble of the hash is 8 or higher
+        let checksummed: String = hex
+            .chars()
+            .zip(hash.chars())
+            .map(|(c, nibble)| match nibble.to_digit(16).unwrap_or(0) >= 8 {
+                true => c.to_ascii_uppercase(),
+                false => c,
+            })
+            .collect();
+        format!("0x{checksummed}")
+    }
 }
 
 impl TryFrom<String> for Address {
@@ -84,8 +109,8 @@ impl TryFrom<String> for Address {
     ///
     /// This method utilizes the `validate` function to ensure the provided
     /// string is a valid Ethereum address. If validation passes, it creates
-    /// and returns an `Address` instance. If validation fails, it returns
-    /// the corresponding `AddressError`.
+    /// and returns an `Address` instance with the lowercase address. If validation
+    /// fails, it returns the corresponding `AddressError`.
     ///
     /// # Arguments
     /// * `address` - The string representation of the address to be converted.
@@ -96,7 +121,7 @@ impl TryFrom<String> for Address {
     fn try_from(address: String) -> Result<Self, Self::Error> {
         Self::validate(&address)?;
 
-        Ok(Self { inner: address })
+        Ok(Self { inner: address.to_lowercase() })
     }
 }
 
@@ -112,3 +137,10 @@ impl Deref for Address {
         &self.inner
     }
 }
+
+impl Display for Address {
+    /// Formats the address with its EIP-55 checksum.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.checksummed())
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+
+/// Error types for parsing the block the state is read at.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BlockError {
+    #[error("BlockError: Invalid block `{0}`, expected a number, latest, earliest, pending, safe, finalized or a 0x-prefixed block hash")]
+    Invalid(String),
+}
+
+/// The block the contract code is read at, selected with `--block`.
+///
+/// Numbers are given in decimal or as hex quantities, and sent as hex quantities per the JSON-RPC spec,
+/// while hashes are sent as an EIP-1898 block hash object.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BlockId {
+    #[default]
+    Latest,
+    Earliest,
+    Pending,
+    Safe,
+    Finalized,
+    Number(u64),
+    /// The hex-encoded hash of the block, lowercase with the "0x" prefix.
+    Hash(String),
+}
+
+impl BlockId {
+    /// The block as a parameter of a JSON-RPC request, e.g. `"latest"`, `"0x112a880"` or `{"blockHash":"0x..."}`.
+    pub fn to_param(&self) -> String {
+        match self {
+            Self::Number(number) => format!(r#""0x{number:x}""#),
+            Self::Hash(hash) => format!(r#"{{"blockHash":"{hash}"}}"#),
+            tag => format!(r#""{tag}""#),
+        }
+    }
+}
+
+impl FromStr for BlockId {
+    type Err = BlockError;
+
+    fn from_str(block: &str) -> Result<Self, Self::Err> {
+        let invalid = || BlockError::Invalid(block.to_string());
+        match block.to_lowercase().as_str() {
+            "latest" => Ok(Self::Latest),
+            "earliest" => Ok(Self::Earliest),
+            "pending" => Ok(Self::Pending),
+            "safe" => Ok(Self::Safe),
+            "finalized" => Ok(Self::Finalized),
+            // A hash is 32 bytes, anything shorter is a hex quantity
+            hash if hash.len() == 66 && hash.starts_with("0x") => match hex::decode(&hash[2..]) {
+                Ok(_) => Ok(Self::Hash(hash.to_string())),
+                Err(_) => Err(invalid()),
+            },
+            number => match number.strip_prefix("0x") {
+                Some(quantity) => u64::from_str_radix(quantity, 16).map(Self::Number).map_err(|_| invalid()),
+                None => number.parse().map(Self::Number).map_err(|_| invalid()),
+            },
+        }
+    }
+}
+
+impl Display for BlockId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Latest => write!(f, "latest"),
+            Self::Earliest => write!(f, "earliest"),
+            Self::Pending => write!(f, "pending"),
+            Self::Safe => write!(f, "safe"),
+            Self::Finalized => write!(f, "finalized"),
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Hash(hash) => write!(f, "{hash}"),
+        }
+    }
+}
+
+impl Serialize for BlockId {
+    /// Serializes the block in the same form it is given on the command line, with numbers in decimal.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockId {
+    /// Deserializes the block from the form it is given on the command line.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    format,
+    signature::{self, Signature},
+};
+use rayon::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// The parameter types combined into candidate parameter lists, most common first.
+const PARAM_TYPES: [&str; 9] = ["address", "uint256", "bool", "bytes32", "bytes", "string", "uint8", "address[]", "uint256[]"];
+
+/// Common parameter lists, tried before the exhaustive combinations of `PARAM_TYPES` since most
+/// functions take one of them, including types such as `uint128` that aren't combined.
+const PARAM_TEMPLATES: [&str; 32] = [
+    "",
+    "address",
+    "uint256",
+    "bool",
+    "address,uint256",
+    "address,address",
+    "uint256,uint256",
+    "address,bool",
+    "uint256,address",
+    "bytes32",
+    "bytes32,address",
+    "address,address,uint256",
+    "address,uint256,uint256",
+    "uint256,uint256,uint256",
+    "uint256,bool",
+    "bytes",
+    "string",
+    "address,bytes",
+    "address,uint256,bytes",
+    "address[]",
+    "uint256[]",
+    "address[],uint256[]",
+    "address[],bool",
+    "address,address,uint256,bytes",
+    "uint8",
+    "uint16",
+    "uint32",
+    "uint64",
+    "uint128",
+    "int256",
+    "address,uint256,address",
+    "bytes32,bytes32",
+];
+
+/// The verbs of the built-in function names, combined with `NOUNS` (e.g. `setFee`) and tried on their own (e.g. `claim`).
+const VERBS: [&str; 44] = [
+    "set", "get", "update", "claim", "withdraw", "deposit", "mint", "burn", "transfer", "approve", "add", "remove", "is", "has", "enable",
+    "disable", "toggle", "change", "initialize", "init", "execute", "swap", "stake", "unstake", "lock", "unlock", "pause", "unpause", "register",
+    "revoke", "grant", "renounce", "accept", "cancel", "create", "close", "open", "start", "stop", "sync", "skim", "harvest", "redeem", "rescue",
+];
+
+/// The nouns of the built-in function names, combined with `VERBS` and tried as getters (e.g. `fee`).
+const NOUNS: [&str; 72] = [
+    "Owner", "Admin", "Operator", "Manager", "Governance", "Guardian", "Treasury", "Fee", "Fees", "FeeRecipient", "FeeReceiver", "TaxFee",
+    "Tax", "Token", "Tokens", "Reward", "Rewards", "RewardRate", "Balance", "Price", "Limit", "Limits", "MaxTx", "MaxWallet", "MaxTxAmount",
+    "MaxWalletSize", "Amount", "Supply", "MaxSupply", "Minter", "Whitelist", "Blacklist", "Allowlist", "Router", "Pair", "Pool", "Oracle",
+    "Vault", "Strategy", "Implementation", "Proxy", "Config", "Paused", "Trading", "TradingEnabled", "Swap", "SwapEnabled", "Threshold",
+    "Delay", "Deadline", "Period", "Duration", "Rate", "Ratio", "Cap", "BaseURI", "URI", "Name", "Symbol", "Decimals", "Nonce", "Signer",
+    "Root", "MerkleRoot", "Role", "Roles", "ETH", "Funds", "Stuck", "StuckTokens", "Liquidity", "Marketing",
+];
+
+/// The maximum number of parameters of a candidate, beyond which the combinations couldn't be exhausted anyway.
+pub(crate) const MAX_PARAMS: usize = 8;
+
+/// The number of candidates hashed in parallel between two checks of the caps.
+const BLOCK_SIZE: usize = 1 << 14;
+
+/// A local resolver guessing the signatures of unresolved selectors.
+///
+/// Candidates combine every function name stem with the common parameter lists of `PARAM_TEMPLATES`,
+/// then with every list of up to `max_params` common types, and are hashed locally to find the ones
+/// matching a selector. The search is bounded by both an iteration and a time cap.
+///
+/// Fields:
+/// * `stems`: The function names to try, e.g. `setFee` or `pause`.
+/// * `max_params`: The maximum number of parameters of a candidate.
+/// * `max_iterations`: The maximum number of candidates hashed.
+/// * `timeout`: The maximum duration of the search.
+#[derive(Debug, Clone)]
+pub struct Bruteforce {
+    pub stems: Vec<String>,
+    pub max_params: usize,
+    pub max_iterations: usize,
+    pub timeout: Duration,
+}
+
+/// The outcome of a search, reported with the hash rate since the search is CPU-bound.
+///
+/// Fields:
+/// * `guessed`: The matching signatures, flagged as `guessed`, in the order they were generated.
+/// * `hashed`: The number of candidates hashed.
+/// * `elapsed`: The duration of the search.
+#[derive(Debug, Clone)]
+pub struct BruteforceReport {
+    pub guessed: Vec<Signature>,
+    pub hashed: usize,
+    pub elapsed: Duration,
+}
+
+impl BruteforceReport {
+    /// The number of candidates hashed per second.
+    pub fn rate(&self) -> f64 {
+        self.hashed as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl std::fmt::Display for BruteforceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "bruteforce: hashed {} candidates in {} ({}), {} guessed",
+            format::human_count(self.hashed),
+            format::human_duration(self.elapsed),
+            format::human_rate(self.hashed, self.elapsed),
+            self.guessed.len()
+        )
+    }
+}
+
+impl Bruteforce {
+    /// Creates a resolver trying the function names of a wordlist, one per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_wordlist(path: &Path, max_params: usize, max_iterations: usize, timeout: Duration) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let stems = content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(str::to_string);
+
+        Ok(Self { stems: stems.collect(), max_params, max_iterations, timeout })
+    }
+
+    /// Creates a resolver trying the built-in function names, the verbs alone, every verb followed by
+    /// every noun, and the nouns as getters.
+    pub fn builtin(max_params: usize, max_iterations: usize, timeout: Duration) -> Self {
+        Self { stems: builtin_stems(), max_params, max_iterations, timeout }
+    }
+
+    /// Guesses the signatures of the given selectors.
+    ///
+    /// The common parameter lists are tried first, then the other ones with fewer parameters first,
+    /// since they are both cheaper and more likely. The search stops once every selector is resolved, or when a cap is reached.
+    /// Nothing can be guessed without the `keccak` feature.
+    ///
+    /// Arguments:
+    /// * `unresolved`: The hex-encoded selectors to guess, without the "0x" prefix.
+    ///
+    /// Returns:
+    /// The matching signatures, flagged as `guessed`.
+    pub fn run(&self, unresolved: &HashSet<String>) -> Vec<Signature> {
+        self.search(unresolved, 1).guessed
+    }
+
+    /// Guesses up to `matches` signatures of each of the given selectors, since a selector may have
+    /// several texts and the first one found isn't necessarily the original.
+    ///
+    /// The candidates are hashed in parallel, in blocks of `BLOCK_SIZE`, and the matches of a block
+    /// are kept in the order they were generated, so the results don't depend on the number of threads.
+    ///
+    /// Arguments:
+    /// * `unresolved`: The hex-encoded selectors to guess, without the "0x" prefix.
+    /// * `matches`: The number of matches after which a selector is no longer searched.
+    ///
+    /// Returns:
+    /// A `BruteforceReport` of the matching signatures and the number of candidates hashed.
+    pub fn search(&self, unresolved: &HashSet<String>, matches: usize) -> BruteforceReport {
+        let start = Instant::now();
+        // Nothing can be hashed without the `keccak` feature
+        if signature::try_hash("").is_err() {
+            return BruteforceReport { guessed: Vec::new(), hashed: 0, elapsed: start.elapsed() };
+        }
+
+        let mut remaining: HashMap<&str, usize> = unresolved.iter().map(|selector| (selector.as_str(), matches)).collect();
+        let mut guessed = Vec::new();
+
+        // The candidates are generated lazily, the combinations of a high `max_params` being far too many to hold
+        let mut candidates = param_lists(self.max_params).flat_map(|params| self.stems.iter().map(move |stem| format!("{stem}({params})"))).take(self.max_iterations);
+
+        let mut hashed = 0;
+        while !remaining.is_empty() && start.elapsed() < self.timeout {
+            let block: Vec<String> = candidates.by_ref().take(BLOCK_SIZE).collect();
+            if block.is_empty() {
+                break;
+            }
+            hashed += block.len();
+
+            let found: Vec<(String, String)> = block
+                .into_par_iter()
+                .filter_map(|text| {
+                    let hash = signature::try_hash(&text).ok()?;
+                    remaining.contains_key(&hash[..8]).then_some((text, hash))
+                })
+                .collect();
+
+            for (text, hash) in found {
+                let Some(left) = remaining.get_mut(&hash[..8]) else { continue };
+                *left -= 1;
+                if *left == 0 {
+                    remaining.remove(&hash[..8]);
+                }
+                guessed.push(Signature { guessed: true, ..Signature::new(text, hash) }.with_source("bruteforce"));
+            }
+        }
+
+        BruteforceReport { guessed, hashed, elapsed: start.elapsed() }
+    }
+}
+
+/// The built-in function name stems, most likely first.
+fn builtin_stems() -> Vec<String> {
+    let mut stems: Vec<String> = VERBS.iter().map(|verb| verb.to_string()).collect();
+    stems.extend(VERBS.iter().flat_map(|verb| NOUNS.iter().map(move |noun| format!("{verb}{noun}"))));
+    stems.extend(NOUNS.iter().map(|noun| {
+        // Getters are named after the noun, e.g. `owner` or `baseURI`
+        let mut chars = noun.chars();
+        chars.next().map(|first| first.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+    }));
+
+    let mut seen = HashSet::new();
+    stems.retain(|stem| seen.insert(stem.clone()));
+    stems
+}
+
+/// The parameter lists of up to `max_params` parameters, the templates first and then the remaining
+/// combinations of `PARAM_TYPES` with fewer parameters first, generated as they are consumed.
+fn param_lists(max_params: usize) -> impl Iterator<Item = String> {
+    let count = |list: &str| if list.is_empty() { 0 } else { list.split(',').count() };
+    let templates = PARAM_TEMPLATES.iter().filter(move |list| count(list) <= max_params).map(|list| list.to_string());
+
+    // The combinations are all distinct, so only the templates need to be skipped
+    let combinations = (0..=max_params).flat_map(ParamLists::new).filter(|list| !PARAM_TEMPLATES.contains(&list.as_str()));
+    templates.chain(combinations)
+}
+
+/// Iterator over the comma separated lists of `count` parameter types, with repetitions.
+///
+/// The lists are enumerated like the digits of a number in base `PARAM_TYPES.len()`.
+struct ParamLists {
+    digits: Vec<usize>,
+    done: bool,
+}
+
+impl ParamLists {
+    fn new(count: usize) -> Self {
+        Self { digits: vec![0; count], done: false }
+    }
+}
+
+impl Iterator for ParamLists {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let list = self.digits.iter().map(|&digit| PARAM_TYPES[digit]).collect::<Vec<_>>().join(",");
+
+        // Increment the last digit, carrying over to the previous ones
+        self.done = true;
+        for digit in self.digits.iter_mut().rev() {
+            *digit += 1;
+            if *digit < PARAM_TYPES.len() {
+                self.done = false;
+                break;
+            }
+            *digit = 0;
+        }
+
+        Some(list)
+    }
+}
@@ -1,4 +1,74 @@
-use std::{collections::HashSet, ops::Deref, path::PathBuf};
+use crate::{
+    format,
+    opcode::Opcode,
+    warning::{Warning, WarningKind},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashSet},
+    fmt::Display,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    path::PathBuf,
+    time::Instant,
+};
+
+/// The maximum number of functions a proxy declares itself, e.g. the administrative ones of a transparent proxy.
+const MAX_PROXY_SELECTORS: usize = 8;
+/// The selector of `proxiableUUID()`, which the UUPS implementations behind a proxy declare.
+const PROXIABLE_UUID_SELECTOR: &str = "52d1902d";
+
+/// The maximum number of instructions between extracting the calldata selector and delegating to the route
+/// loaded from storage with it, including a `require` on the route, for the code to be recognized as a storage-mapped router.
+const ROUTER_WINDOW: usize = 96;
+/// The flag of the values derived from the calldata selector, through arithmetic, memory or hashing.
+const SELECTOR_FLOW: u8 = 1;
+/// The flag of the values loaded from storage at a key derived from the calldata selector.
+const ROUTE_FLOW: u8 = 2;
+/// The flag of the address returned by a `CREATE` or `CREATE2`, the deployed child.
+const CREATED_FLOW: u8 = 4;
+/// The maximum number of offsets recorded for each kind of mutability indicator, so adversarial code
+/// repeating an opcode can't inflate the output.
+const MAX_OFFSETS: usize = 64;
+/// The number of bytes scanned between checks of the extraction deadline.
+const DEADLINE_INTERVAL: usize = 4096;
+/// The maximum number of instructions between pushing the selector of a custom error and reverting with it.
+const ERROR_WINDOW: usize = 32;
+/// The maximum number of `0x00` and `0xff` bytes of a `PUSH32` value for it to be a candidate event topic.
+const MAX_TRIVIAL_TOPIC_BYTES: usize = 4;
+/// The prefix of the EIP-7702 delegation designator an EOA's code is set to, followed by the address of its delegate.
+const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+/// The minimal proxies whose code is a prefix, the `PUSH<n>` of the target address and a suffix holding a one-byte
+/// jump offset, which depends on the length of the address: EIP-1167, and EIP-7511 with `PUSH0` instead of `RETURNDATASIZE`.
+const MINIMAL_PROXIES: [(&[u8], &[u8]); 2] = [
+    (&[0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d], &[0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60]),
+    (&[0x36, 0x5f, 0x5f, 0x37, 0x5f, 0x5f, 0x36, 0x5f], &[0x5a, 0xf4, 0x3d, 0x5f, 0x5f, 0x3e, 0x5f, 0x3d, 0x91, 0x60]),
+];
+/// The end of a minimal proxy after its jump offset, `JUMPI REVERT JUMPDEST RETURN`.
+const MINIMAL_PROXY_END: [u8; 4] = [0x57, 0xfd, 0x5b, 0xf3];
+
+/// How likely an extracted 4-byte value is to be a function selector, ordered from least to most likely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// A `PUSH4` value that isn't compared, e.g. an error selector or a constant, only found by a deep scan.
+    Low,
+    /// A `PUSH4` value compared with `EQ`.
+    Medium,
+    /// A `PUSH4` value compared with `EQ` and followed by a conditional jump, like a dispatcher branch.
+    High,
+}
+
+impl Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        };
+        write!(f, "{name}")
+    }
+}
 
 /// A struct that in this context, represents the bytecode of a smart contract.
 pub struct Bytecode {
@@ -32,18 +102,792 @@ impl Bytecode {
     /// ```
     #[inline]
     pub fn find_function_selectors(&self, deep: bool) -> HashSet<String> {
+        self.find_function_selectors_until(deep, None).0
+    }
+
+    /// Find selectors like `find_function_selectors`, giving up at a deadline.
+    ///
+    /// The scan is linear in the length of the code and looks at a constant-size window per byte,
+    /// the deadline only guards against arbitrarily large inputs.
+    ///
+    /// Arguments:
+    /// * `deep`: Whether every `PUSH4` value is extracted, instead of the compared ones only.
+    /// * `deadline`: The instant after which the scan stops, `None` to scan the whole code.
+    ///
+    /// Returns:
+    /// The selectors found, and whether the whole code was scanned before the deadline.
+    pub fn find_function_selectors_until(&self, deep: bool, deadline: Option<Instant>) -> (HashSet<String>, bool) {
         let mut selectors = HashSet::new();
         let selector_size = 5;
 
         for idx in 0..self.inner.len().saturating_sub(selector_size) {
+            if idx % DEADLINE_INTERVAL == 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return (selectors, false);
+            }
             // since we use `saturating_sub(pattern_length)` the next `pattern_length` bytes will be available
             if self.inner[idx] == 0x63 && (deep || self.inner[idx + selector_size] == 0x14) {
                 selectors.insert(hex::encode(&self.inner[idx + 1..idx + selector_size]));
             }
         }
 
+        (selectors, true)
+    }
+
+    /// Find every `PUSH4` value in the bytecode, with the confidence that it is a function selector.
+    ///
+    /// The confidence is `High` when the value is compared and followed by a conditional jump
+    /// (`PUSH4 <selector> EQ PUSH<n> <offset> JUMPI`), `Medium` when it is only compared with `EQ`,
+    /// which is the default extraction, and `Low` otherwise, which is only extracted by a deep scan.
+    /// A value found several times keeps its highest confidence.
+    ///
+    /// Returns:
+    /// A `BTreeMap` of the hexadecimal selectors and their `Confidence`, so that
+    /// `find_function_selectors(false)` holds the selectors of at least `Medium` confidence.
+    pub fn selector_confidence(&self) -> BTreeMap<String, Confidence> {
+        let mut selectors = BTreeMap::new();
+        let code = &self.inner;
+        let selector_size = 5;
+
+        for idx in 0..code.len().saturating_sub(selector_size) {
+            if code[idx] != 0x63 {
+                continue;
+            }
+
+            let confidence = match code[idx + selector_size] == 0x14 {
+                false => Confidence::Low,
+                true => {
+                    // The jump destination is pushed with PUSH1..PUSH3 right after the comparison
+                    let push = code.get(idx + selector_size + 1).copied().filter(|op| (0x60..=0x62).contains(op));
+                    let jumpi = push.and_then(|op| code.get(idx + selector_size + 2 + usize::from(op - 0x5f)));
+                    match jumpi == Some(&0x57) {
+                        true => Confidence::High,
+                        false => Confidence::Medium,
+                    }
+                }
+            };
+
+            let entry = selectors.entry(hex::encode(&code[idx + 1..idx + selector_size])).or_insert(confidence);
+            *entry = (*entry).max(confidence);
+        }
+
+        selectors
+    }
+
+    /// Find the candidate event topics of the code, the `PUSH32` values of a contract that emits logs.
+    ///
+    /// Solidity pushes the topic of an event with `PUSH32` before its `LOG1..LOG4`, which is often only
+    /// reached after jumping to the encoding of the event data, so every `PUSH32` of a logging contract is
+    /// a candidate. Masks and constants are skipped, since a hash rarely has more than a few `0x00` or `0xff` bytes.
+    ///
+    /// Returns:
+    /// A `BTreeSet` of the hexadecimal topics, without the "0x" prefix.
+    pub fn find_event_topics(&self) -> BTreeSet<String> {
+        let instructions: Vec<Instruction> = self.code_instructions().collect();
+        // LOG1 (0xa1) to LOG4 (0xa4) have a topic, LOG0 doesn't
+        if !instructions.iter().any(|instruction| (0xa1..=0xa4).contains(&instruction.opcode)) {
+            return BTreeSet::new();
+        }
+
+        instructions
+            .iter()
+            .filter(|instruction| instruction.opcode == 0x7f && instruction.immediate.len() == 32)
+            .filter(|instruction| instruction.immediate.iter().filter(|byte| matches!(byte, 0x00 | 0xff)).count() <= MAX_TRIVIAL_TOPIC_BYTES)
+            .map(|instruction| hex::encode(instruction.immediate))
+            .collect()
+    }
+
+    /// Find the selectors of the custom errors the code reverts with.
+    ///
+    /// Solidity encodes a custom error by shifting its selector into the high bytes of a word with
+    /// `PUSH4 <selector> PUSH1 0xe0 SHL`, or by pushing the shifted word with `PUSH32`, and reverts shortly after.
+    /// Values that aren't followed by a `REVERT` within `ERROR_WINDOW` instructions are skipped.
+    ///
+    /// Returns:
+    /// A `BTreeSet` of the hexadecimal selectors, which includes the standard `Error(string)` and `Panic(uint256)`.
+    pub fn find_error_selectors(&self) -> BTreeSet<String> {
+        let instructions: Vec<Instruction> = self.code_instructions().collect();
+        let mut selectors = BTreeSet::new();
+
+        for (idx, instruction) in instructions.iter().enumerate() {
+            let selector = match (instruction.opcode, instruction.immediate) {
+                // PUSH4 <selector>, PUSH1 0xe0, SHL
+                (0x63, selector) if selector.len() == 4 => match (instructions.get(idx + 1), instructions.get(idx + 2)) {
+                    (Some(push), Some(shl)) if push.opcode == 0x60 && push.immediate == [0xe0] && shl.opcode == 0x1b => selector,
+                    _ => continue,
+                },
+                // PUSH32 <selector> followed by 28 zero bytes, except the mask of the calldata selector
+                (0x7f, word) if word.len() == 32 && word[4..].iter().all(|byte| *byte == 0) && word[..4] != [0; 4] && word[..4] != [0xff; 4] => &word[..4],
+                _ => continue,
+            };
+
+            // REVERT
+            if instructions[idx + 1..].iter().take(ERROR_WINDOW).any(|instruction| instruction.opcode == 0xfd) {
+                selectors.insert(hex::encode(selector));
+            }
+        }
+
         selectors
     }
+
+    /// Find the selectors a factory calls on the contracts it deploys, e.g. the `initialize(address,address)`
+    /// a pair factory calls on each pair right after creating it.
+    ///
+    /// After a `CREATE` or `CREATE2`, the `PUSH4` values shifted into the high bytes of a calldata word, with
+    /// `PUSH4 <selector> PUSH1 0xe0 SHL` or `PUSH29 0x01 << 224 MUL` by older compilers, belong to the interface
+    /// of the child when they are followed by a `CALL` whose address derives from the one the creation returned,
+    /// as tracked by a `Dataflow`, before any other creation. The calldata built for calls to other contracts is
+    /// left out, as are the values the dispatcher compares, which are the functions of the factory itself.
+    ///
+    /// Returns:
+    /// A `BTreeSet` of the hexadecimal selectors, without the "0x" prefix.
+    #[inline]
+    pub fn find_child_init_selectors(&self) -> BTreeSet<String> {
+        self.find_child_init_selectors_until(None).0
+    }
+
+    /// Find the selectors of the deployed contracts like `find_child_init_selectors`, giving up at a deadline.
+    ///
+    /// The simulation of a creation stops at the next one, so every instruction is simulated at most once.
+    ///
+    /// Arguments:
+    /// * `deadline`: The instant after which the search stops, `None` to search the whole code.
+    ///
+    /// Returns:
+    /// The selectors found, and whether the whole code was searched before the deadline.
+    pub fn find_child_init_selectors_until(&self, deadline: Option<Instant>) -> (BTreeSet<String>, bool) {
+        let mut selectors = BTreeSet::new();
+        let (own, complete) = self.find_function_selectors_until(false, deadline);
+        if !complete {
+            return (selectors, false);
+        }
+        let instructions: Vec<Instruction> = self.code_instructions().collect();
+
+        // CREATE, CREATE2
+        let mut creations = (0..instructions.len()).filter(|idx| matches!(instructions[*idx].opcode, 0xf0 | 0xf5)).peekable();
+        while let Some(start) = creations.next() {
+            let end = creations.peek().copied().unwrap_or(instructions.len());
+            let mut flow = Dataflow::new(CREATED_FLOW);
+            let mut pending = Vec::new();
+            for (idx, instruction) in instructions.iter().enumerate().take(end).skip(start + 1) {
+                if idx % DEADLINE_INTERVAL == 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return (selectors, false);
+                }
+                if instruction.opcode == 0x63 && shifted_selector(&instructions[idx + 1..]) {
+                    let selector = hex::encode(instruction.immediate);
+                    if !own.contains(&selector) {
+                        pending.push(selector);
+                    }
+                }
+                let Some(popped) = flow.step(instruction) else { break };
+                match instruction.opcode {
+                    // CALL of the created contract, whose address is its second argument
+                    0xf1 if popped[1] & CREATED_FLOW != 0 => selectors.extend(pending.drain(..)),
+                    // The calldata was built for another contract
+                    0xf1 => pending.clear(),
+                    _ => {}
+                }
+                // The created address is gone, nothing further is called on it
+                if !flow.carries(CREATED_FLOW) {
+                    break;
+                }
+            }
+        }
+
+        (selectors, true)
+    }
+
+    /// Iterate over the instructions of the bytecode.
+    ///
+    /// Push immediates are returned as part of their instruction, so data embedded in
+    /// `PUSH1..PUSH32` is never mistaken for an opcode. Undefined opcodes are single byte
+    /// instructions that don't end the iteration, see `Opcode`.
+    pub fn instructions(&self) -> Instructions<'_> {
+        Instructions { code: &self.inner, pc: 0 }
+    }
+
+    /// The length of the compiler metadata trailer at the end of the code, or 0 if there is none.
+    ///
+    /// Solidity and Vyper append a CBOR encoded map followed by its length as a
+    /// 2-byte big-endian integer. The trailer is data, not code, and may contain any byte.
+    pub fn metadata_len(&self) -> usize {
+        let len = self.inner.len();
+        if len < 2 {
+            return 0;
+        }
+
+        let map_len = u16::from_be_bytes([self.inner[len - 2], self.inner[len - 1]]) as usize;
+        match len.checked_sub(map_len + 2).map(|start| self.inner[start]) {
+            // CBOR maps with one to five entries
+            Some(0xa1..=0xa5) if map_len > 0 => map_len + 2,
+            _ => 0,
+        }
+    }
+
+    /// Iterate over the instructions of the code, excluding the metadata trailer.
+    fn code_instructions(&self) -> Instructions<'_> {
+        Instructions { code: &self.inner[..self.inner.len() - self.metadata_len()], pc: 0 }
+    }
+
+    /// Count the instructions of the code, excluding the metadata trailer, and the undefined and deprecated ones among them.
+    ///
+    /// Undefined opcodes are expected in ancient contracts and in data appended to the code, many of them
+    /// hint at code that isn't compiled from Solidity or Vyper, whose extraction may be less reliable.
+    pub fn opcode_stats(&self) -> OpcodeStats {
+        let mut stats = OpcodeStats::default();
+        for instruction in self.code_instructions() {
+            let opcode = Opcode(instruction.opcode);
+            stats.instructions += 1;
+            if !opcode.is_defined() {
+                stats.undefined += 1;
+                if stats.undefined_offsets.len() < MAX_OFFSETS {
+                    stats.undefined_offsets.push(instruction.offset);
+                }
+            }
+            stats.deprecated += usize::from(opcode.is_deprecated());
+        }
+        stats
+    }
+
+    /// Detect whether the code can be removed or replaced.
+    ///
+    /// A contract is `selfdestructable` when `SELFDESTRUCT` appears as an instruction, bytes
+    /// inside push data or the metadata trailer are ignored. It is additionally a `metamorphic_suspect`
+    /// when it contains `CREATE2`, or calls back into its deployer (`CALLER` followed by a call and
+    /// `EXTCODECOPY`) as metamorphic init code does to fetch the code it should deploy.
+    /// Reachability is approximated, every instruction outside push data is considered reachable.
+    /// At most `MAX_OFFSETS` offsets are recorded of each kind.
+    pub fn mutability(&self) -> Mutability {
+        let mut mutability = Mutability::default();
+        // The offset of the last `CALLER` and whether a call followed it
+        let mut callback: Option<(usize, bool)> = None;
+
+        let record = |offsets: &mut Vec<usize>, offset| {
+            if offsets.len() < MAX_OFFSETS {
+                offsets.push(offset);
+            }
+        };
+
+        for instruction in self.code_instructions() {
+            match instruction.opcode {
+                // SELFDESTRUCT
+                0xff => record(&mut mutability.selfdestruct_offsets, instruction.offset),
+                // CREATE2
+                0xf5 => record(&mut mutability.create2_offsets, instruction.offset),
+                // CALLER
+                0x33 => callback = Some((instruction.offset, false)),
+                // CALL, CALLCODE, STATICCALL
+                0xf1 | 0xf2 | 0xfa => callback = callback.map(|(offset, _)| (offset, true)),
+                // EXTCODECOPY
+                0x3c => {
+                    if let Some((offset, true)) = callback.take() {
+                        record(&mut mutability.deployer_callback_offsets, offset);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        mutability.selfdestructable = !mutability.selfdestruct_offsets.is_empty();
+        mutability.metamorphic_suspect = mutability.selfdestructable
+            && (!mutability.create2_offsets.is_empty() || !mutability.deployer_callback_offsets.is_empty());
+
+        mutability
+    }
+
+    /// Classify how the code dispatches calls to its functions.
+    ///
+    /// The instructions following each `PUSH4` tell the structural form apart:
+    /// * `PUSH4 <selector> EQ` compares the calldata selector against a function, which makes
+    ///   up the linear chain emitted by Solidity.
+    ///   Compiled through the IR pipeline, the last comparison of a chain consumes the selector instead of a `DUP1` of it.
+    /// * `PUSH4 <pivot> GT` or `LT` splits the selectors in halves, which Solidity does for larger
+    ///   contracts, resulting in a binary search over linear chains.
+    /// * `PUSH4 <selector> XOR` comparisons, or the selector reduced with `MOD` right after
+    ///   shifting it out of the calldata (`PUSH1 0xe0 SHR`), are the bucketed tables of Vyper.
+    /// * The calldata selector hashed into a storage key whose value is then delegated to is a
+    ///   storage-mapped router, e.g. a diamond or a custom router, whose functions don't appear in
+    ///   the code. The selector is followed through the stack and memory, so a mapping read or a
+    ///   `DELEGATECALL` of a regular function doesn't count. It takes precedence over the other forms,
+    ///   since such routers usually compare a few administrative selectors as well.
+    ///
+    /// Returns:
+    /// The recognized `Dispatcher`, with the `None` style if no dispatcher was found.
+    pub fn dispatcher(&self) -> Dispatcher {
+        let instructions: Vec<Instruction> = self.code_instructions().collect();
+        let (mut entries, mut pivots, mut xors, mut bucketed, mut consumed) = (0, 0, 0, false, false);
+
+        for (idx, instruction) in instructions.iter().enumerate() {
+            let next = |n: usize| instructions.get(idx + n).map(|i| i.opcode);
+
+            match (instruction.opcode, next(1)) {
+                // PUSH4, EQ, comparing a DUP1 of the selector, except for the last comparison of a via-IR chain,
+                // right after the JUMPI of the previous one or the SHR extracting the selector
+                (0x63, Some(0x14)) => {
+                    entries += 1;
+                    consumed |= idx.checked_sub(1).is_some_and(|previous| matches!(instructions[previous].opcode, 0x57 | 0x1c));
+                }
+                // PUSH4, LT or GT
+                (0x63, Some(0x10 | 0x11)) => pivots += 1,
+                // PUSH4, XOR
+                (0x63, Some(0x18)) => xors += 1,
+                // PUSH1 0xe0, SHR, followed by a MOD within the next instructions
+                (0x60, Some(0x1c)) if instruction.immediate == [0xe0] => {
+                    bucketed |= (2..6).any(|n| next(n) == Some(0x06));
+                }
+                _ => {}
+            }
+        }
+
+        let style = match (bucketed || xors > 0, entries + xors, pivots) {
+            _ if storage_mapped(&instructions) => DispatcherStyle::StorageMapped,
+            (_, 0, _) => DispatcherStyle::None,
+            (true, _, _) => DispatcherStyle::VyperTable,
+            (false, _, 0) => DispatcherStyle::LinearEqChain,
+            (false, _, _) => DispatcherStyle::BinarySearch,
+        };
+
+        let via_ir = consumed && matches!(style, DispatcherStyle::LinearEqChain | DispatcherStyle::BinarySearch);
+        Dispatcher { style, entries: entries + xors, pivots, via_ir }
+    }
+
+    /// Whether the code pushes a constant with the `PUSH<n>` of its size, e.g. the EIP-1967 implementation slot
+    /// a proxy loads or the selector of a function it calls.
+    ///
+    /// Arguments:
+    /// * `value`: The hex-encoded constant of 1 to 32 bytes, with the "0x" prefix.
+    pub fn pushes(&self, value: &str) -> bool {
+        let Ok(value) = hex::decode(value.trim_start_matches("0x")) else { return false };
+        if !(1..=32).contains(&value.len()) {
+            return false;
+        }
+        let opcode = 0x5f + value.len() as u8;
+        self.code_instructions().any(|instruction| instruction.opcode == opcode && instruction.immediate == value.as_slice())
+    }
+
+    /// Whether the code looks like a proxy: it delegates calls with `DELEGATECALL` and declares a few functions at most.
+    ///
+    /// A UUPS implementation pushes the EIP-1967 implementation slot like its proxy does, but declares `proxiableUUID()`
+    /// along with its own functions, so it isn't taken for one.
+    pub fn looks_like_proxy(&self) -> bool {
+        if !self.code_instructions().any(|instruction| instruction.opcode == 0xf4) {
+            return false;
+        }
+        let selectors = self.find_function_selectors(false);
+        selectors.len() <= MAX_PROXY_SELECTORS && !selectors.contains(PROXIABLE_UUID_SELECTOR)
+    }
+
+    /// Recognizes a minimal proxy, which forwards every call to a fixed target with `DELEGATECALL`.
+    ///
+    /// * EIP-1167 clones, `363d3d373d3d3d363d73<address>5af43d82803e903d91602b57fd5bf3`, including the ones
+    ///   whose vanity address with leading zero bytes is pushed with a shorter `PUSH<n>`.
+    /// * EIP-7511 clones, which use `PUSH0` instead of `RETURNDATASIZE`.
+    /// * Clones with immutable args, whose code appends the args the target reads from the calldata.
+    ///
+    /// Returns:
+    /// The lowercase, 0x-prefixed address of the target, or `None` if the code isn't a minimal proxy.
+    pub fn minimal_proxy_target(&self) -> Option<String> {
+        let code = self.inner.as_slice();
+        for (prefix, suffix) in MINIMAL_PROXIES {
+            let Some((target, rest)) = code.strip_prefix(prefix).and_then(pushed_address) else { continue };
+            if rest.len() == suffix.len() + 1 + MINIMAL_PROXY_END.len() && rest.starts_with(suffix) && rest.ends_with(&MINIMAL_PROXY_END) {
+                return Some(target);
+            }
+        }
+
+        // The clones of wighawag's ClonesWithImmutableArgs copy the calldata and the args of their given length
+        let rest = code.strip_prefix(&[0x3d, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x3d, 0x37, 0x61])?;
+        let rest = rest.get(2..)?.strip_prefix(&[0x60, 0x37, 0x36, 0x39, 0x36, 0x61])?;
+        let (target, rest) = pushed_address(rest.get(2..)?.strip_prefix(&[0x01, 0x3d])?)?;
+        rest.starts_with(&[0x5a, 0xf4, 0x3d, 0x3d, 0x93, 0x80, 0x3e, 0x60, 0x35, 0x57, 0xfd, 0x5b, 0xf3]).then_some(target)
+    }
+
+    /// Recognizes the EIP-7702 delegation designator of an EOA, `0xef0100` followed by the address of its delegate.
+    ///
+    /// The calls to a delegated EOA run the code of its delegate, while its own code is the 23-byte designator,
+    /// which has no selector at all.
+    ///
+    /// Returns:
+    /// The lowercase, 0x-prefixed address of the delegate, or `None` if the code isn't a delegation designator.
+    pub fn delegation_target(&self) -> Option<String> {
+        match self.inner.strip_prefix(&DELEGATION_PREFIX) {
+            Some(address) if address.len() == 20 => Some(format!("0x{}", hex::encode(address))),
+            _ => None,
+        }
+    }
+
+    /// Collect the hashed opcode k-grams (shingles) of the instruction stream.
+    ///
+    /// Push immediates are masked, so that immutables, addresses and jump offsets
+    /// that differ between deployments of the same code don't affect the result.
+    fn shingles(&self, k: usize) -> HashSet<u64> {
+        let opcodes: Vec<u8> = self.instructions().map(|i| i.opcode).collect();
+
+        opcodes
+            .windows(k)
+            .map(|window| {
+                let mut hasher = DefaultHasher::new();
+                window.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect()
+    }
+}
+
+/// Whether the instructions following a `PUSH4` shift its value into the high bytes of a word, where the selector of calldata goes.
+fn shifted_selector(next: &[Instruction]) -> bool {
+    match next {
+        // PUSH1 0xe0, SHL
+        [push, shl, ..] if push.opcode == 0x60 && push.immediate == [0xe0] && shl.opcode == 0x1b => true,
+        // PUSH29 0x01 followed by 28 zero bytes, MUL
+        [push, mul, ..] if push.opcode == 0x7c && push.immediate.first() == Some(&1) && push.immediate[1..].iter().all(|byte| *byte == 0) && mul.opcode == 0x02 => true,
+        _ => false,
+    }
+}
+
+/// Whether the calldata selector is used as the key of a storage mapping whose value is then delegated to.
+///
+/// The selector is extracted either with `PUSH1 0xe0 SHR` or, as `msg.sig` does, by masking the first
+/// calldata word with `PUSH32 0xffffffff00..00 AND`. Within `ROUTER_WINDOW` instructions of an extraction,
+/// a `SLOAD` must read a key derived from the selector, e.g. hashed with `KECCAK256` from the memory it was
+/// stored to, and a `DELEGATECALL` must call the address it loaded.
+///
+/// Each extraction only looks at a constant-size window, so the check stays linear even for code
+/// repeating the extraction pattern.
+fn storage_mapped(instructions: &[Instruction]) -> bool {
+    let selector_mask = |immediate: &[u8]| immediate.len() == 32 && immediate[..4] == [0xff; 4] && immediate[4..].iter().all(|b| *b == 0);
+
+    instructions.iter().enumerate().any(|(idx, instruction)| {
+        let extracts_selector = match (instruction.opcode, instructions.get(idx + 1).map(|i| i.opcode)) {
+            // PUSH1 0xe0, SHR
+            (0x60, Some(0x1c)) => instruction.immediate == [0xe0],
+            // PUSH32 0xffffffff00..00, AND
+            (0x7f, Some(0x16)) => selector_mask(instruction.immediate),
+            _ => false,
+        };
+        extracts_selector && delegates_route(instructions.get(idx + 2..).unwrap_or_default())
+    })
+}
+
+/// Whether the selector on top of the stack keys a `SLOAD` whose value is the target of a `DELEGATECALL`,
+/// within the first `ROUTER_WINDOW` instructions.
+fn delegates_route(instructions: &[Instruction]) -> bool {
+    let mut flow = Dataflow::new(SELECTOR_FLOW);
+    for instruction in instructions.iter().take(ROUTER_WINDOW) {
+        let Some(popped) = flow.step(instruction) else {
+            return false;
+        };
+        match instruction.opcode {
+            // SLOAD of a key derived from the selector
+            0x54 if popped[0] & SELECTOR_FLOW != 0 => flow.mark_top(ROUTE_FLOW),
+            // DELEGATECALL, whose address is its second argument
+            0xf4 if popped[1] & ROUTE_FLOW != 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// A straight-line simulation of the stack and memory, tracking which values derive from the ones of interest
+/// as bit flags, e.g. the calldata selector.
+///
+/// Jumps aren't followed: the instructions are taken in code order, and after an instruction ending the block
+/// without falling through, e.g. the `REVERT` of a failed `require`, the stack is restored to the one after
+/// the preceding `JUMPI`, which its jump target likely continues with. Items below the simulated stack carry
+/// no flags, and memory is tracked as a whole.
+struct Dataflow {
+    stack: Vec<u8>,
+    branch: Vec<u8>,
+    memory: u8,
+}
+
+impl Dataflow {
+    /// Starts with a single item on the stack, carrying the given flags.
+    fn new(top: u8) -> Self {
+        Self { stack: vec![top], branch: vec![top], memory: 0 }
+    }
+
+    /// Simulates an instruction, whose results carry the flags of its arguments, and of memory for `MLOAD` and `KECCAK256`.
+    ///
+    /// Returns:
+    /// The flags of the items the instruction popped, the top one first, `DUPn` and `SWAPn` popping none,
+    /// or `None` if the opcode is undefined.
+    fn step(&mut self, instruction: &Instruction) -> Option<Vec<u8>> {
+        let (pops, pushes) = instruction.op().stack_effect()?;
+        let depth = match instruction.opcode {
+            // DUPn, SWAPn
+            0x80..=0x9f => pops,
+            _ => 0,
+        };
+        // Items below the simulated stack carry no flags
+        if self.stack.len() < depth {
+            self.stack.splice(0..0, std::iter::repeat_n(0, depth - self.stack.len()));
+        }
+
+        let len = self.stack.len();
+        match instruction.opcode {
+            // DUPn
+            0x80..=0x8f => {
+                self.stack.push(self.stack[len - depth]);
+                return Some(Vec::new());
+            }
+            // SWAPn
+            0x90..=0x9f => {
+                self.stack.swap(len - 1, len - depth);
+                return Some(Vec::new());
+            }
+            _ => {}
+        }
+
+        let popped: Vec<u8> = (0..pops).map(|_| self.stack.pop().unwrap_or(0)).collect();
+        let mut flags = popped.iter().fold(0, |flags, popped| flags | popped);
+        match instruction.opcode {
+            // MSTORE, MSTORE8, storing their second argument
+            0x52 | 0x53 => self.memory |= popped[1],
+            // KECCAK256, MLOAD
+            0x20 | 0x51 => flags |= self.memory,
+            _ => {}
+        }
+        self.stack.extend(std::iter::repeat_n(flags, pushes));
+
+        match instruction.opcode {
+            // JUMPI, whose target continues with the stack it leaves
+            0x57 => self.branch.clone_from(&self.stack),
+            // STOP, JUMP, RETURN, REVERT, INVALID, SELFDESTRUCT
+            0x00 | 0x56 | 0xf3 | 0xfd | 0xfe | 0xff => self.stack.clone_from(&self.branch),
+            _ => {}
+        }
+        Some(popped)
+    }
+
+    /// Whether an item of the stack, or of the one a jump target continues with, carries one of the given flags.
+    fn carries(&self, flags: u8) -> bool {
+        self.stack.iter().chain(&self.branch).chain([&self.memory]).any(|item| item & flags != 0)
+    }
+
+    /// Adds flags to the item on top of the stack, e.g. to mark the result of an instruction as a value of interest.
+    fn mark_top(&mut self, flags: u8) {
+        if let Some(top) = self.stack.last_mut() {
+            *top |= flags;
+        }
+    }
+}
+
+/// Reads the address pushed by the `PUSH1` to `PUSH20` at the start of the code, padded to 20 bytes.
+///
+/// Returns:
+/// The lowercase, 0x-prefixed address and the code after the push, or `None` if the code doesn't start with such a push.
+fn pushed_address(code: &[u8]) -> Option<(String, &[u8])> {
+    let len = usize::from(code.first()?.checked_sub(0x5f).filter(|len| (1..=20).contains(len))?);
+    let address = code.get(1..=len)?;
+    Some((format!("0x{:0>40}", hex::encode(address)), &code[1 + len..]))
+}
+
+/// A single EVM instruction.
+///
+/// Fields:
+/// * `offset`: The byte offset of the instruction in the bytecode.
+/// * `opcode`: The opcode byte.
+/// * `immediate`: The push data following `PUSH1..PUSH32`, which may be truncated at the end of the code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction<'a> {
+    pub offset: usize,
+    pub opcode: u8,
+    pub immediate: &'a [u8],
+}
+
+/// Iterator over the instructions of a bytecode, created by `Bytecode::instructions`.
+pub struct Instructions<'a> {
+    code: &'a [u8],
+    pc: usize,
+}
+
+impl Instruction<'_> {
+    /// The opcode of the instruction, e.g. to get its mnemonic.
+    pub fn op(&self) -> Opcode {
+        Opcode(self.opcode)
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Instruction<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let opcode = *self.code.get(self.pc)?;
+        let offset = self.pc;
+        // PUSH1 (0x60) to PUSH32 (0x7f) are followed by 1 to 32 bytes of data, any other opcode is a single byte
+        let size = Opcode(opcode).immediate_size();
+
+        let start = offset + 1;
+        let end = (start + size).min(self.code.len());
+        self.pc = start + size;
+
+        Some(Instruction { offset, opcode, immediate: &self.code[start..end] })
+    }
+}
+
+/// The structural form of a function dispatcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DispatcherStyle {
+    /// A linear chain of selector comparisons, as emitted by Solidity.
+    LinearEqChain,
+    /// Linear chains split by pivot selectors, as emitted by Solidity for larger contracts.
+    BinarySearch,
+    /// A bucketed selector table, as emitted by Vyper.
+    VyperTable,
+    /// The selector is looked up in a storage mapping and delegated to, the functions can't be enumerated statically.
+    StorageMapped,
+    /// No dispatcher was recognized, selectors can only be found by a deep scan.
+    None,
+}
+
+/// How a contract dispatches calls to its functions, as recognized by `Bytecode::dispatcher`.
+///
+/// Fields:
+/// * `style`: The structural form of the dispatcher.
+/// * `entries`: The number of selector comparisons.
+/// * `pivots`: The number of pivot selectors splitting the search.
+/// * `via_ir`: Whether the Solidity dispatcher was compiled through the IR pipeline, whose last comparison
+///   of a chain consumes the selector instead of comparing a `DUP1` of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dispatcher {
+    pub style: DispatcherStyle,
+    pub entries: usize,
+    pub pivots: usize,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub via_ir: bool,
+}
+
+impl Display for Dispatcher {
+    /// Formats the dispatcher as a single line, e.g. `linear EQ chain (Solidity), 42 entries`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let compiler = if self.via_ir { "via-IR" } else { "Solidity" };
+        let entries = format::plural(self.entries, "entry", "entries");
+        match self.style {
+            DispatcherStyle::LinearEqChain => write!(f, "linear EQ chain ({compiler}), {entries}"),
+            DispatcherStyle::BinarySearch => write!(f, "split binary search ({compiler}), {}, {entries}", format::plural(self.pivots, "pivot", "pivots")),
+            DispatcherStyle::VyperTable => write!(f, "Vyper table, {entries}"),
+            DispatcherStyle::StorageMapped => write!(f, "storage-mapped router, {entries}"),
+            DispatcherStyle::None => write!(f, "no dispatcher found, deep scan only"),
+        }
+    }
+}
+
+/// Indicators that the code of a contract can be removed or replaced.
+///
+/// Fields:
+/// * `selfdestructable`: Whether the code contains a `SELFDESTRUCT` instruction.
+/// * `metamorphic_suspect`: Whether the code could be redeployed with different code at the same address.
+/// * `selfdestruct_offsets`: Byte offsets of the first `SELFDESTRUCT` instructions.
+/// * `create2_offsets`: Byte offsets of the first `CREATE2` instructions.
+/// * `deployer_callback_offsets`: Byte offsets of the first `CALLER` instructions starting a call back into the deployer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mutability {
+    pub selfdestructable: bool,
+    pub metamorphic_suspect: bool,
+    pub selfdestruct_offsets: Vec<usize>,
+    pub create2_offsets: Vec<usize>,
+    pub deployer_callback_offsets: Vec<usize>,
+}
+
+impl Mutability {
+    /// The warnings describing how the code can be removed or replaced, if at all.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if self.metamorphic_suspect {
+            let message = "metamorphic contract suspected, its code may be replaced at the same address";
+            warnings.push(Warning::new(WarningKind::MetamorphicSuspect, message));
+        }
+        if self.selfdestructable {
+            let message = format!("SELFDESTRUCT at offsets {:?}, the code can be removed", self.selfdestruct_offsets);
+            warnings.push(Warning::new(WarningKind::Selfdestruct, message));
+        }
+        warnings
+    }
+}
+
+impl Display for Mutability {
+    /// Formats the warnings for the console, or nothing if the code is immutable.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.warnings().iter().try_for_each(|warning| writeln!(f, "{warning}"))
+    }
+}
+
+/// The opcodes of a code, as counted by `Bytecode::opcode_stats`.
+///
+/// Fields:
+/// * `instructions`: The number of instructions, push data excluded.
+/// * `undefined`: The number of instructions whose opcode no hard fork defines.
+/// * `undefined_offsets`: Byte offsets of the first undefined instructions.
+/// * `deprecated`: The number of `CALLCODE` and `SELFDESTRUCT` instructions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OpcodeStats {
+    pub instructions: usize,
+    pub undefined: usize,
+    pub undefined_offsets: Vec<usize>,
+    pub deprecated: usize,
+}
+
+impl Display for OpcodeStats {
+    /// Formats the counts as a single line, e.g. `1,204 instructions, 3 undefined, 2 deprecated`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} instructions, {} undefined, {} deprecated", format::human_count(self.instructions), self.undefined, self.deprecated)
+    }
+}
+
+/// Code-level similarity between two bytecodes.
+///
+/// Fields:
+/// * `code`: Jaccard index of the opcode k-gram shingles, with push immediates masked.
+/// * `selectors`: Jaccard index of the function selector sets.
+/// * `combined`: The average of both scores, used to derive the verdict.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Similarity {
+    pub code: f64,
+    pub selectors: f64,
+    pub combined: f64,
+}
+
+impl Similarity {
+    /// The size of the opcode k-grams used for shingling.
+    const SHINGLE_SIZE: usize = 5;
+
+    /// Compute the similarity between two bytecodes.
+    ///
+    /// Both scores are normalized in the range `0.0..=1.0`, where `1.0` means identical.
+    pub fn between(a: &Bytecode, b: &Bytecode) -> Self {
+        let code = jaccard(&a.shingles(Self::SHINGLE_SIZE), &b.shingles(Self::SHINGLE_SIZE));
+        let selectors = jaccard(&a.find_function_selectors(false), &b.find_function_selectors(false));
+
+        Self { code, selectors, combined: (code + selectors) / 2.0 }
+    }
+
+    /// A human readable verdict derived from the combined score.
+    pub fn verdict(&self) -> &'static str {
+        match self.combined {
+            c if c >= 0.95 => "identical or near-identical code",
+            c if c >= 0.7 => "likely the same codebase (fork or redeployment)",
+            c if c >= 0.4 => "partially similar (shared libraries or components)",
+            _ => "unrelated",
+        }
+    }
+}
+
+impl Display for Similarity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "code similarity:     {}", format::pct(self.code))?;
+        writeln!(f, "selector similarity: {}", format::pct(self.selectors))?;
+        write!(f, "verdict: {}", self.verdict())
+    }
+}
+
+/// Jaccard index of two sets, where two empty sets are considered identical.
+fn jaccard<T: Eq + Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+    let union = a.union(b).count();
+    match union {
+        0 => 1.0,
+        _ => a.intersection(b).count() as f64 / union as f64,
+    }
 }
 
 impl TryFrom<String> for Bytecode {
@@ -71,18 +915,17 @@ impl TryFrom<&PathBuf> for Bytecode {
 
     /// Tries to create a `Bytecode` instance from a file.
     ///
-    /// Read the file at the given path and attempt to decode the contents
-    /// as hexadecimal bytecode.
+    /// Read the file at the given path and attempt to decode the contents,
+    /// detecting whether they are hex, binary, JSON or base64 encoded.
     ///
     /// Arguments:
     /// `path`: A `PathBuf` representing the path to the file containing the bytecode.
     ///
     /// Returns:
     /// `Result<Bytecode, Box<dyn std::error::Error>>` - Ok if the decoding is successful,
-    /// and an error if the file is not found or the contents are not a valid hexadecimal.
+    /// and an error if the file is not found or the contents can't be decoded.
     fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
-        let bytecode = std::fs::read_to_string(path)?;
-        Bytecode::try_from(bytecode.trim().to_string())
+        Ok(crate::input::read(path, None)?.0)
     }
 }
 
@@ -0,0 +1,373 @@
+use crate::{
+    client::{Client, ClientError},
+    format,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// The name of the signature cache file inside the cache directory.
+const CACHE_FILE: &str = "signatures.json";
+/// The time after which selectors cached as unknown are looked up again, short since the databases grow daily.
+pub(crate) const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(86_400);
+
+/// Error types for reading and writing the signature cache.
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("CacheIoError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("CacheSerdeError: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("{0}")]
+    Client(#[from] ClientError),
+}
+
+/// A cached lookup result for a single selector.
+///
+/// Fields:
+/// * `signatures`: The signature texts known for the selector, in ranking order.
+/// * `source`: The provider the signatures were collected from.
+/// * `updated_at`: Unix timestamp (seconds) of the last update.
+/// * `complete`: Whether the signatures are every match of the selector, looked up with `--all-matches`,
+///   rather than the first page of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub signatures: Vec<String>,
+    pub source: String,
+    pub updated_at: u64,
+    #[serde(default)]
+    pub complete: bool,
+}
+
+impl CacheEntry {
+    /// Whether the entry records that the selector was unknown to every provider.
+    pub fn is_negative(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Whether the entry was updated longer than `ttl` ago.
+    pub fn is_older_than(&self, ttl: Duration) -> bool {
+        self.is_expired(ttl, now())
+    }
+
+    /// Whether the entry was updated longer than `ttl` before the Unix timestamp `now`.
+    ///
+    /// An entry updated after `now` is expired as well, since the clock was moved back
+    /// and its age can't be known, which would otherwise keep it until the clock catches up.
+    pub fn is_expired(&self, ttl: Duration, now: u64) -> bool {
+        self.updated_at > now || now - self.updated_at > ttl.as_secs()
+    }
+}
+
+/// Progress of an interrupted cache warming run, used to resume it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarmCursor {
+    /// The URL of the first page of the listing being warmed from.
+    #[serde(default)]
+    pub listing_url: String,
+    /// The URL of the next listing page to fetch.
+    pub next: String,
+    /// The number of signatures collected so far.
+    pub collected: usize,
+    /// The number of signatures requested.
+    pub top: usize,
+}
+
+/// The persistent on-disk signature cache.
+///
+/// The cache is stored as a single JSON file mapping selectors to `CacheEntry` values. The selectors
+/// no provider knows are kept apart in `negative`, since they expire while resolved selectors don't.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SignatureCache {
+    #[serde(skip)]
+    path: PathBuf,
+    /// The maximum number of signatures stored per selector.
+    #[serde(skip)]
+    max_matches: usize,
+    /// Cached entries, keyed by the hex-encoded selector without the "0x" prefix.
+    pub entries: BTreeMap<String, CacheEntry>,
+    /// Entries of the selectors no provider knows, keyed like `entries`, without any signature.
+    #[serde(default)]
+    pub negative: BTreeMap<String, CacheEntry>,
+    /// Cursor of an unfinished `cache warm` run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_cursor: Option<WarmCursor>,
+    /// Problems encountered while opening the cache, e.g. a corrupted cache file that was reset.
+    #[serde(skip)]
+    warnings: Vec<String>,
+}
+
+impl SignatureCache {
+    /// Opens the cache stored in the given directory.
+    ///
+    /// A missing cache file results in an empty cache, which is created on the first `save`.
+    /// A corrupted cache file results in an empty cache as well, which is reported in `warnings`
+    /// and replaces the corrupted file on the next `save`.
+    ///
+    /// Arguments:
+    /// * `dir`: The cache directory.
+    ///
+    /// Returns:
+    /// A `Result` containing the `SignatureCache`, or a `CacheError` if the file can't be read or parsed.
+    pub fn open(dir: &Path) -> Result<Self, CacheError> {
+        let path = dir.join(CACHE_FILE);
+
+        let mut cache = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| Self {
+                warnings: vec![format!("the signature cache {} is corrupted and was reset ({e})", path.display())],
+                ..Self::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        // Caches written before the negative entries were kept apart mix them with the resolved ones
+        let (negative, entries): (BTreeMap<_, _>, _) = std::mem::take(&mut cache.entries).into_iter().partition(|(_, entry)| entry.is_negative());
+        cache.entries = entries;
+        cache.negative.extend(negative);
+
+        cache.path = path;
+        cache.max_matches = crate::DEFAULT_MAX_MATCHES;
+        Ok(cache)
+    }
+
+    /// Sets the maximum number of signatures stored per selector, so a selector with
+    /// thousands of spam matches can't bloat the cache file.
+    pub fn with_max_matches(mut self, max_matches: usize) -> Self {
+        self.max_matches = max_matches.max(1);
+        self
+    }
+
+    /// The path of the cache file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Problems encountered while opening the cache.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Returns the cached entry for a selector, if any, which is negative if the selector is unknown.
+    pub fn get(&self, selector: &str) -> Option<&CacheEntry> {
+        self.entries.get(selector).or_else(|| self.negative.get(selector))
+    }
+
+    /// Replaces the entry of a selector with the given signature texts, an empty list records the selector as unknown.
+    ///
+    /// `complete` records whether the texts are every match of the selector, see `CacheEntry::complete`.
+    pub fn replace(&mut self, selector: &str, texts: &[String], source: &str, complete: bool) {
+        self.entries.remove(selector);
+        self.insert(selector, texts, source);
+        if let Some(entry) = self.entries.get_mut(selector) {
+            entry.complete = complete;
+        }
+    }
+
+    /// Removes the entry of a selector, resolved or negative, so that it's looked up again on the next run.
+    ///
+    /// Returns:
+    /// Whether the selector was cached.
+    pub fn invalidate(&mut self, selector: &str) -> bool {
+        let positive = self.entries.remove(selector).is_some();
+        self.negative.remove(selector).is_some() || positive
+    }
+
+    /// Keeps the entries, resolved or negative, of the selectors for which `keep` returns `true`.
+    pub fn retain<F: FnMut(&str) -> bool>(&mut self, mut keep: F) {
+        self.entries.retain(|selector, _| keep(selector));
+        self.negative.retain(|selector, _| keep(selector));
+    }
+
+    /// Adds signature texts for a selector, keeping existing texts and their order.
+    ///
+    /// An empty list records the selector as unknown, unless it's already resolved.
+    /// Texts beyond the maximum number of signatures per selector are ignored.
+    pub fn insert(&mut self, selector: &str, texts: &[String], source: &str) {
+        if texts.is_empty() && !self.entries.contains_key(selector) {
+            let entry = CacheEntry { signatures: Vec::new(), source: source.to_string(), updated_at: now(), complete: false };
+            self.negative.insert(selector.to_string(), entry);
+            return;
+        }
+
+        self.negative.remove(selector);
+        let entry = self.entries.entry(selector.to_string()).or_insert_with(|| CacheEntry {
+            signatures: Vec::new(),
+            source: source.to_string(),
+            updated_at: 0,
+            complete: false,
+        });
+
+        for text in texts {
+            if entry.signatures.len() >= self.max_matches {
+                break;
+            }
+            if !entry.signatures.contains(text) {
+                entry.signatures.push(text.clone());
+            }
+        }
+        entry.updated_at = now();
+    }
+
+    /// Writes the cache to disk.
+    ///
+    /// The content is written to a temporary file first and then renamed over the cache file,
+    /// so readers never observe a partially written cache.
+    pub fn save(&self) -> Result<(), CacheError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let tmp = self.path.with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Removes all entries and deletes the cache file.
+    pub fn clear(&mut self) -> Result<(), CacheError> {
+        self.entries.clear();
+        self.negative.clear();
+        self.warm_cursor = None;
+
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Computes statistics about the cache content.
+    pub fn stats(&self) -> CacheStats {
+        let now = now();
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or_default();
+        let mut ages = [0usize; 4];
+
+        for entry in self.entries.values().chain(self.negative.values()) {
+            let days = now.saturating_sub(entry.updated_at) / 86_400;
+            let bucket = match days {
+                0 => 0,
+                1..=6 => 1,
+                7..=29 => 2,
+                _ => 3,
+            };
+            ages[bucket] += 1;
+        }
+
+        CacheStats {
+            entries: self.entries.len() + self.negative.len(),
+            unresolved: self.negative.len(),
+            size,
+            ages: AgeDistribution { day: ages[0], week: ages[1], month: ages[2], older: ages[3] },
+        }
+    }
+
+    /// Pre-populates the cache with the first `top` signatures of a provider listing.
+    ///
+    /// Listing pages are fetched sequentially with a `delay` between them to respect the provider's
+    /// rate limits. Progress is saved after every page, so an interrupted run resumes from the
+    /// last saved page when invoked again with the same `listing_url` and `top`.
+    ///
+    /// Arguments:
+    /// * `client`: The client used to fetch the listing pages.
+    /// * `listing_url`: The URL of the first listing page.
+    /// * `source`: The name of the provider serving the listing, stored as the source of the entries.
+    /// * `top`: The number of signatures to collect.
+    /// * `delay`: The pause between two page requests.
+    ///
+    /// Returns:
+    /// A `Result` containing the number of signatures collected, or a `CacheError` on failure.
+    pub async fn warm(&mut self, client: &Client, listing_url: &str, source: &str, top: usize, delay: Duration) -> Result<usize, CacheError> {
+        let (mut next, mut collected) = match &self.warm_cursor {
+            Some(cursor) if cursor.listing_url == listing_url && cursor.top == top => (Some(cursor.next.clone()), cursor.collected),
+            _ => (Some(listing_url.to_string()), 0),
+        };
+
+        while let Some(url) = next.filter(|_| collected < top) {
+            let page = client.get_signature_listing(&url).await?;
+
+            for item in page.results.iter().take(top - collected) {
+                let selector = item.hex_signature.trim_start_matches("0x").to_lowercase();
+                self.insert(&selector, std::slice::from_ref(&item.text_signature), source);
+                collected += 1;
+            }
+
+            next = page.next;
+            self.warm_cursor = next.clone().filter(|_| collected < top).map(|next| WarmCursor { listing_url: listing_url.to_string(), next, collected, top });
+            self.save()?;
+
+            if self.warm_cursor.is_some() {
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        Ok(collected)
+    }
+}
+
+/// Statistics about the signature cache.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheStats {
+    /// Number of cached selectors.
+    pub entries: usize,
+    /// Number of cached selectors without any known signature.
+    pub unresolved: usize,
+    /// Size of the cache file in bytes.
+    pub size: u64,
+    /// Distribution of the entries by the time since their last update.
+    pub ages: AgeDistribution,
+}
+
+/// Number of cache entries per age bucket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AgeDistribution {
+    pub day: usize,
+    pub week: usize,
+    pub month: usize,
+    pub older: usize,
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "entries:    {} ({} unresolved)", self.entries, self.unresolved)?;
+        writeln!(f, "size:       {}", format::human_bytes(self.size))?;
+        writeln!(f, "< 1 day:    {}", self.ages.day)?;
+        writeln!(f, "< 1 week:   {}", self.ages.week)?;
+        writeln!(f, "< 1 month:  {}", self.ages.month)?;
+        write!(f, "older:      {}", self.ages.older)
+    }
+}
+
+/// The default cache directory.
+///
+/// Uses `$XDG_CACHE_HOME/sigmund` if set, otherwise `$HOME/.cache/sigmund`,
+/// and falls back to a `sigmund` directory in the system temporary directory.
+pub fn default_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir);
+
+    base.join("sigmund")
+}
+
+/// The source recorded for the signatures of a listing: `fourbyte` for the 4byte.directory listing,
+/// otherwise the host of the listing URL, or the URL itself if it has none.
+pub(crate) fn listing_source(listing_url: &str) -> String {
+    let host = reqwest::Url::parse(listing_url).ok().and_then(|url| url.host_str().map(str::to_lowercase));
+    match host {
+        Some(host) if host == "4byte.directory" || host.ends_with(".4byte.directory") => "fourbyte".to_string(),
+        Some(host) => host,
+        None => listing_url.to_string(),
+    }
+}
+
+/// The current Unix timestamp in seconds.
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
@@ -0,0 +1,74 @@
+use crate::signature::{self, Signature};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt::Display, path::Path};
+
+/// The result of checking suspected signatures against the selectors of a contract.
+///
+/// Fields:
+/// * `matched`: The candidates whose selector was extracted from the contract.
+/// * `unmatched_candidates`: The candidates whose selector wasn't extracted.
+/// * `unmatched_selectors`: The extracted selectors that no candidate matched, sorted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandidateCheck {
+    pub matched: Vec<Signature>,
+    pub unmatched_candidates: Vec<String>,
+    pub unmatched_selectors: Vec<String>,
+}
+
+impl CandidateCheck {
+    /// Checks which candidate signatures exist in a contract.
+    ///
+    /// The selector of each candidate is computed locally from its canonical form,
+    /// so spacing or type aliases in the candidates don't prevent a match. Without the `keccak` feature
+    /// no selector can be computed, and every candidate is unmatched.
+    ///
+    /// Arguments:
+    /// * `candidates`: The suspected signatures, e.g. `transfer(address,uint256)`.
+    /// * `selectors`: The selectors extracted from the contract.
+    ///
+    /// Returns:
+    /// The `CandidateCheck`, with the candidates in the given order.
+    pub fn new(candidates: &[String], selectors: &HashSet<String>) -> Self {
+        let mut matched = Vec::new();
+        let mut unmatched_candidates = Vec::new();
+
+        for candidate in candidates {
+            let text = signature::canonicalize(candidate);
+            let signature = signature::try_hash(&text).ok().map(|hash| Signature::new(text, hash));
+            match signature.filter(|signature| selectors.contains(&signature.selector)) {
+                Some(signature) => matched.push(signature),
+                None => unmatched_candidates.push(candidate.clone()),
+            }
+        }
+
+        let found: HashSet<&str> = matched.iter().map(|s| s.selector.as_str()).collect();
+        let mut unmatched_selectors: Vec<String> = selectors.iter().filter(|s| !found.contains(s.as_str())).cloned().collect();
+        unmatched_selectors.sort();
+
+        Self { matched, unmatched_candidates, unmatched_selectors }
+    }
+
+    /// Checks the candidate signatures of a file, one per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_file(path: &Path, selectors: &HashSet<String>) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let candidates: Vec<String> =
+            content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(str::to_string).collect();
+
+        Ok(Self::new(&candidates, selectors))
+    }
+}
+
+impl Display for CandidateCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "matched candidates ({}):", self.matched.len())?;
+        self.matched.iter().try_for_each(|signature| writeln!(f, "  {signature}"))?;
+
+        writeln!(f, "unmatched candidates ({}):", self.unmatched_candidates.len())?;
+        self.unmatched_candidates.iter().try_for_each(|candidate| writeln!(f, "  {candidate}"))?;
+
+        writeln!(f, "unmatched selectors ({}):", self.unmatched_selectors.len())?;
+        self.unmatched_selectors.iter().try_for_each(|selector| writeln!(f, "  {selector}"))
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Display};
+use thiserror::Error;
+
+/// The chains known without any configuration, with a public RPC.
+const PRESETS: [(&str, u64, &str); 7] = [
+    ("mainnet", 1, crate::DEFAULT_RPC_URL),
+    ("sepolia", 11155111, "https://ethereum-sepolia-rpc.publicnode.com"),
+    ("polygon", 137, "https://polygon-bor-rpc.publicnode.com"),
+    ("arbitrum", 42161, "https://arbitrum-one-rpc.publicnode.com"),
+    ("optimism", 10, "https://optimism-rpc.publicnode.com"),
+    ("base", 8453, "https://base-rpc.publicnode.com"),
+    ("bsc", 56, "https://bsc-rpc.publicnode.com"),
+];
+
+/// Error types for resolving chains.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ChainError {
+    #[error("ChainError: Unknown chain `{chain}`, expected a chain id or one of: {known}")]
+    Unknown { chain: String, known: String },
+    #[error("ChainError: The RPC of --chain {chain} is on chain id {actual}, expected {expected}")]
+    Mismatch { chain: String, expected: u64, actual: u64 },
+}
+
+/// A chain contracts can be analyzed on.
+///
+/// Fields:
+/// * `id`: The chain id, e.g. `42161`.
+/// * `name`: The name of the chain, e.g. `arbitrum`, or its id for chains that aren't presets.
+/// * `rpc_url`: The RPC the bytecode of the chain is fetched from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Chain {
+    pub id: u64,
+    pub name: String,
+    #[serde(skip)]
+    pub rpc_url: String,
+}
+
+impl Display for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The chains contracts can be analyzed on, keyed by chain id.
+///
+/// Holds the presets with their public RPC, whose URL can be overridden, and the chains
+/// given an RPC in the config file, e.g. `[rpc_urls]` with `arbitrum = "https://..."` or `"100" = "https://..."`.
+#[derive(Debug, Clone)]
+pub struct ChainRegistry {
+    chains: BTreeMap<u64, Chain>,
+}
+
+impl Default for ChainRegistry {
+    fn default() -> Self {
+        let chains = PRESETS.iter().map(|&(name, id, rpc_url)| (id, Chain { id, name: name.to_string(), rpc_url: rpc_url.to_string() })).collect();
+        Self { chains }
+    }
+}
+
+impl ChainRegistry {
+    /// Creates the registry of the presets, with the given RPC overrides.
+    ///
+    /// Arguments:
+    /// * `rpc_urls`: The RPC of each chain, keyed by the name of a preset or a chain id, which adds the chain if it isn't a preset.
+    ///
+    /// Returns:
+    /// A `Result` containing the registry, or a `ChainError` for a name that is neither a preset nor a chain id.
+    pub fn new(rpc_urls: &BTreeMap<String, String>) -> Result<Self, ChainError> {
+        let mut registry = Self::default();
+        for (chain, rpc_url) in rpc_urls {
+            let id = match registry.resolve(chain) {
+                Ok(chain) => chain.id,
+                Err(e) => chain.trim().parse().map_err(|_| e)?,
+            };
+            let chain = registry.chains.entry(id).or_insert_with(|| Chain { id, name: id.to_string(), rpc_url: String::new() });
+            chain.rpc_url = rpc_url.clone();
+        }
+        Ok(registry)
+    }
+
+    /// Finds a chain by its name, ignoring case, or by its id.
+    ///
+    /// Returns:
+    /// A `Result` containing the chain, or a `ChainError` listing the known chains if it isn't one of them.
+    pub fn resolve(&self, chain: &str) -> Result<&Chain, ChainError> {
+        let chain = chain.trim();
+        let found = match chain.parse::<u64>() {
+            Ok(id) => self.chains.get(&id),
+            Err(_) => self.chains.values().find(|known| known.name.eq_ignore_ascii_case(chain)),
+        };
+        found.ok_or_else(|| ChainError::Unknown {
+            chain: chain.to_string(),
+            known: self.chains.values().map(|known| known.name.as_str()).collect::<Vec<_>>().join(", "),
+        })
+    }
+}
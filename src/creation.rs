@@ -0,0 +1,240 @@
+use crate::{
+    abi::{self, AbiError, AbiValue, ParamType},
+    providers,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt::Display, path::PathBuf};
+
+/// Analysis of contract creation code, as found in deployment transactions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreationAnalysis {
+    /// The decoded constructor arguments, if any were appended to the creation code.
+    pub constructor_args: Option<ConstructorArgs>,
+}
+
+/// Constructor arguments decoded from the tail of the creation code.
+///
+/// Fields:
+/// * `heuristic`: Whether the types were guessed from the data instead of read from an ABI.
+/// * `raw`: The hex-encoded arguments blob.
+/// * `args`: The decoded arguments, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstructorArgs {
+    pub heuristic: bool,
+    pub raw: String,
+    pub args: Vec<ConstructorArg>,
+}
+
+/// A single decoded constructor argument.
+///
+/// Fields:
+/// * `name`: The parameter name, when known from an ABI.
+/// * `kind`: The ABI type, or the best guess when decoded heuristically.
+/// * `value`: The decoded value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstructorArg {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub kind: String,
+    pub value: AbiValue,
+}
+
+impl ConstructorArgs {
+    /// Decodes constructor arguments precisely, using the constructor inputs of an ABI.
+    ///
+    /// Arguments:
+    /// * `data`: The ABI encoded arguments blob.
+    /// * `inputs`: The `(name, type)` pairs of the constructor inputs.
+    ///
+    /// Returns:
+    /// The decoded `ConstructorArgs`, or an `AbiError` if the data doesn't match the inputs.
+    pub fn decode(data: &[u8], inputs: &[(String, ParamType)]) -> Result<Self, AbiError> {
+        let types: Vec<_> = inputs.iter().map(|(_, ty)| ty.clone()).collect();
+        let values = abi::decode(&types, data)?;
+
+        let args = inputs
+            .iter()
+            .zip(values)
+            .map(|((name, ty), value)| ConstructorArg {
+                name: (!name.is_empty()).then(|| name.clone()),
+                kind: ty.to_string(),
+                value,
+            })
+            .collect();
+
+        Ok(Self { heuristic: false, raw: format!("0x{}", hex::encode(data)), args })
+    }
+
+    /// Decodes constructor arguments without knowing their types.
+    ///
+    /// Every 32-byte word of the head is interpreted by its shape:
+    /// 1. Offsets pointing to a length-prefixed payload in the tail are decoded as `string`
+    ///    when the payload is printable UTF-8, and as `bytes` otherwise.
+    /// 2. Words with 12 leading zero bytes and a value that doesn't fit in 64 bits are addresses.
+    /// 3. Zero and one are reported as booleans, other values fitting in 64 bits as integers.
+    /// 4. Anything else is kept as a raw `bytes32` word.
+    ///
+    /// The result is a best guess and is labeled as such through the `heuristic` field.
+    pub fn decode_heuristic(data: &[u8]) -> Self {
+        let mut args = Vec::new();
+        // The head ends where the first dynamic payload starts
+        let mut head_end = data.len() / 32;
+        let mut idx = 0;
+
+        while idx < head_end {
+            // The loop bound guarantees that the word exists
+            let word = abi::read_word(data, idx * 32).unwrap();
+
+            let (kind, value) = match dynamic_payload(data, idx) {
+                Some((offset, payload)) => {
+                    head_end = head_end.min(offset / 32);
+                    match std::str::from_utf8(payload) {
+                        Ok(text) if !text.is_empty() && !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+                            ("string", AbiValue::String(text.to_string()))
+                        }
+                        _ => ("bytes", AbiValue::Bytes(format!("0x{}", hex::encode(payload)))),
+                    }
+                }
+                None if word[..12].iter().all(|b| *b == 0) && word[12..24].iter().any(|b| *b != 0) => {
+                    ("address", AbiValue::Address(format!("0x{}", hex::encode(&word[12..]))))
+                }
+                None if word[..31].iter().all(|b| *b == 0) && word[31] <= 1 => ("bool", AbiValue::Bool(word[31] == 1)),
+                None if word[..24].iter().all(|b| *b == 0) => ("uint256", AbiValue::Uint(abi::word_to_decimal(word))),
+                None => ("bytes32", AbiValue::FixedBytes(format!("0x{}", hex::encode(word)))),
+            };
+
+            args.push(ConstructorArg { name: None, kind: kind.to_string(), value });
+            idx += 1;
+        }
+
+        Self { heuristic: true, raw: format!("0x{}", hex::encode(data)), args }
+    }
+}
+
+impl Display for ConstructorArgs {
+    /// Formats the constructor arguments as one line per argument, labeling heuristic results.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.heuristic {
+            true => writeln!(f, "Constructor arguments (heuristic, best guess):")?,
+            false => writeln!(f, "Constructor arguments:")?,
+        }
+
+        for (idx, arg) in self.args.iter().enumerate() {
+            let name = arg.name.as_ref().map(|n| format!(" {n}")).unwrap_or_default();
+            writeln!(f, "  [{idx}] {}{name}: {}", arg.kind, arg.value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks whether the head word at `idx` is an offset to a length-prefixed payload.
+///
+/// Returns:
+/// The offset and the payload bytes if the word points past the current head word,
+/// is word aligned, and the payload (including its padding) fits in the data.
+fn dynamic_payload(data: &[u8], idx: usize) -> Option<(usize, &[u8])> {
+    let offset = abi::read_usize(data, idx * 32).ok()?;
+    if offset % 32 != 0 || offset <= idx * 32 {
+        return None;
+    }
+
+    let len = abi::read_usize(data, offset).ok()?;
+    let start = offset + 32;
+    let padded = len.checked_add(31)? / 32 * 32;
+    if start.checked_add(padded)? > data.len() {
+        return None;
+    }
+
+    Some((offset, &data[start..start + len]))
+}
+
+/// Splits the constructor arguments from the tail of Solidity or Vyper creation code.
+///
+/// The compiler appends a CBOR encoded metadata map to the runtime code, followed by
+/// its length as a 2-byte big-endian integer. Since the runtime code is placed at the end
+/// of the creation code, the constructor arguments start right after the last metadata trailer.
+///
+/// Returns:
+/// The arguments blob, or `None` if no metadata trailer was found or nothing follows it.
+pub fn split_constructor_args(code: &[u8]) -> Option<&[u8]> {
+    // Known first keys of the metadata map, including the CBOR map and text string headers
+    const KEYS: [&[u8]; 3] = [b"\x64ipfs", b"\x65bzzr0", b"\x65bzzr1"];
+
+    let mut end = None;
+
+    for start in 0..code.len() {
+        if !matches!(code[start], 0xa1..=0xa5) || !KEYS.iter().any(|key| code[start + 1..].starts_with(key)) {
+            continue;
+        }
+
+        // Look for the length trailer that points back at the start of the map
+        for len_pos in start + 1..code.len().saturating_sub(1).min(start + 256) {
+            let len = u16::from_be_bytes([code[len_pos], code[len_pos + 1]]) as usize;
+            if len == len_pos - start {
+                end = Some(len_pos + 2);
+                break;
+            }
+        }
+    }
+
+    end.map(|end| &code[end..]).filter(|args| !args.is_empty())
+}
+
+/// Reads the constructor inputs from a local ABI file or compiler artifact.
+///
+/// Both plain ABI arrays and artifacts containing an `abi` key (Hardhat, Foundry) are supported.
+///
+/// Returns:
+/// The `(name, type)` pairs of the constructor inputs, which are empty if the ABI has no constructor.
+pub fn constructor_inputs(path: &PathBuf) -> Result<Vec<(String, ParamType)>, Box<dyn std::error::Error>> {
+    let entries = abi_entries(path)?;
+    let Some(constructor) = entries.iter().find(|e| e.get("type").and_then(Value::as_str) == Some("constructor")) else {
+        return Ok(Vec::new());
+    };
+
+    let inputs = constructor.get("inputs").and_then(Value::as_array).cloned().unwrap_or_default();
+    let inputs = inputs
+        .iter()
+        .map(|input| {
+            let name = input.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+            Ok((name, ParamType::parse(&abi_type(input)?)?))
+        })
+        .collect::<Result<_, AbiError>>()?;
+
+    Ok(inputs)
+}
+
+/// Reads the functions of a local ABI file or compiler artifact, as canonical signatures such as `transfer(address,uint256)`.
+pub fn function_signatures(path: &PathBuf) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    Ok(providers::abi_functions(&abi_entries(path)?)?)
+}
+
+/// Reads the entries of a plain ABI array, or of the `abi` key of a compiler artifact.
+fn abi_entries(path: &PathBuf) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let json: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let abi = match json {
+        Value::Array(_) => json,
+        mut json => json.get_mut("abi").map(Value::take).ok_or_else(|| AbiError::Artifact("missing `abi` key".to_string()))?,
+    };
+
+    match abi {
+        Value::Array(entries) => Ok(entries),
+        _ => Err(AbiError::Artifact("`abi` is not an array".to_string()).into()),
+    }
+}
+
+/// Builds the canonical type string of an ABI input, expanding `tuple` into its components.
+pub(crate) fn abi_type(input: &Value) -> Result<String, AbiError> {
+    let ty = input.get("type").and_then(Value::as_str).ok_or_else(|| AbiError::Artifact("input without a `type`".to_string()))?;
+
+    match ty.strip_prefix("tuple") {
+        Some(suffix) => {
+            let components = input.get("components").and_then(Value::as_array).cloned().unwrap_or_default();
+            let components: Vec<_> = components.iter().map(abi_type).collect::<Result<_, _>>()?;
+            Ok(format!("({}){suffix}", components.join(",")))
+        }
+        None => Ok(ty.to_string()),
+    }
+}
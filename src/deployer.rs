@@ -0,0 +1,129 @@
+use crate::{
+    address::Address,
+    client::{Client, ClientError},
+    signature::{self, SignatureError},
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use thiserror::Error;
+
+/// Error types for finding the contracts created by a deployer.
+#[derive(Error, Debug)]
+pub enum DeployerError {
+    #[error("{0}")]
+    Client(#[from] ClientError),
+    #[error("{0}")]
+    Signature(#[from] SignatureError),
+    /// A transaction of the deployer has a nonce that isn't a hex quantity.
+    #[error("DeployerError: Invalid nonce `{nonce}` of transaction {hash}")]
+    Nonce { hash: String, nonce: String },
+    /// The transaction of `--tx` has no receipt.
+    #[error("DeployerError: The transaction {0} isn't mined yet, or is unknown to the RPC")]
+    NotMined(String),
+    /// The transaction of `--tx` didn't create a contract itself, e.g. it called a factory.
+    #[error("DeployerError: The transaction {hash} called {to} instead of creating a contract{hint}")]
+    NotCreation { hash: String, to: String, hint: String },
+}
+
+/// Derives the address of a contract created with `CREATE`, the last 20 bytes of `keccak256(rlp([sender, nonce]))`.
+///
+/// Arguments:
+/// * `sender`: The address of the creating account.
+/// * `nonce`: The nonce of the sender when the contract was created.
+///
+/// Returns:
+/// A `Result` containing the lowercase "0x" prefixed address, or `SignatureError::VerificationUnavailable`
+/// when sigmund is built without the `keccak` feature.
+pub fn create_address(sender: &Address, nonce: u64) -> Result<String, SignatureError> {
+    // The address is validated, so it is always 20 bytes of hex
+    let sender = hex::decode(&sender[2..]).unwrap_or_default();
+
+    // Integers are encoded as their big-endian bytes without leading zeros, zero as the empty string
+    let nonce_bytes: Vec<u8> = nonce.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    let mut nonce = match nonce_bytes.as_slice() {
+        [byte] if *byte < 0x80 => Vec::new(),
+        bytes => vec![0x80 + bytes.len() as u8],
+    };
+    nonce.extend(nonce_bytes);
+
+    // Both items are shorter than 56 bytes, so the list has a single byte prefix
+    let mut rlp = vec![0xc0 + (1 + sender.len() + nonce.len()) as u8, 0x80 + sender.len() as u8];
+    rlp.extend(sender);
+    rlp.extend(nonce);
+
+    let hash = signature::try_keccak(&rlp)?;
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+/// Finds the contract deployed by a transaction, from the `contractAddress` of its receipt.
+///
+/// A contract created by a factory, e.g. with `CREATE2`, isn't the `contractAddress` of the transaction calling
+/// the factory, so the error points to `--deployer` with the called factory on its block, since the factory is the
+/// creator `trace_filter` knows the contract by, and to the contracts that emitted its logs.
+///
+/// Arguments:
+/// * `client`: The client of the RPC.
+/// * `hash`: The hash of the transaction.
+///
+/// Returns:
+/// A `Result` containing the lowercase address of the contract, or a `DeployerError` if the transaction
+/// isn't mined or didn't create a contract.
+pub async fn deployed_by(client: &Client, hash: &str) -> Result<String, DeployerError> {
+    let receipt = client.get_transaction_receipt(hash).await?.ok_or_else(|| DeployerError::NotMined(hash.to_string()))?;
+    if let Some(address) = receipt.contract_address {
+        return Ok(address.to_lowercase());
+    }
+
+    let to = receipt.to.unwrap_or_default().to_lowercase();
+    let mut hint = match u64::from_str_radix(receipt.block_number.trim_start_matches("0x"), 16) {
+        Ok(block) => format!(", the contracts the factory created in it are found with --deployer {to} --from-block {block} --to-block {block}"),
+        Err(_) => String::new(),
+    };
+    let mut emitters: Vec<String> = receipt.logs.into_iter().map(|log| log.address.to_lowercase()).filter(|address| *address != to).collect();
+    emitters.dedup();
+    if !emitters.is_empty() {
+        hint.push_str(&format!(", one of them may have emitted its logs: {}", emitters.join(", ")));
+    }
+    Err(DeployerError::NotCreation { hash: hash.to_string(), to, hint })
+}
+
+/// Finds the contracts created by an account within a block range.
+///
+/// The `trace_filter` API is used when the RPC supports it, since it also finds the contracts a deployer that is
+/// itself a contract, e.g. a factory, created without a transaction of their own. The contracts a factory created
+/// for an account are only found with the factory as the deployer, since the account isn't their creator.
+/// Otherwise every block is fetched and the addresses of the deployer's creation transactions are derived from
+/// its nonce, which includes creations that reverted.
+///
+/// Arguments:
+/// * `client`: The client of the RPC.
+/// * `deployer`: The address of the account.
+/// * `from_block`, `to_block`: The inclusive range of blocks to search.
+///
+/// Returns:
+/// A `Result` containing the created addresses in the order of creation, without duplicates,
+/// or a `DeployerError` if a block couldn't be retrieved.
+pub async fn find_creations(client: &Client, deployer: &Address, from_block: u64, to_block: u64) -> Result<Vec<String>, DeployerError> {
+    let mut created = match client.trace_creations(deployer, from_block, to_block).await? {
+        Some(created) => created,
+        None => {
+            eprintln!("trace_filter isn't supported by the RPC, scanning {} blocks instead", to_block.saturating_sub(from_block) + 1);
+            let blocks: Vec<_> = stream::iter(from_block..=to_block)
+                .map(|number| client.get_block_transactions(number))
+                .buffered(client.concurrency())
+                .try_collect()
+                .await?;
+
+            let creations = blocks.into_iter().flatten().filter(|tx| tx.to.is_none() && tx.from.eq_ignore_ascii_case(deployer));
+            creations
+                .map(|tx| {
+                    let nonce = u64::from_str_radix(tx.nonce.trim_start_matches("0x"), 16).map_err(|_| DeployerError::Nonce { hash: tx.hash, nonce: tx.nonce })?;
+                    Ok(create_address(deployer, nonce)?)
+                })
+                .collect::<Result<Vec<_>, DeployerError>>()?
+        }
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    created.retain(|address| seen.insert(address.clone()));
+    Ok(created)
+}
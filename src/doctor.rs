@@ -0,0 +1,95 @@
+use crate::{providers::ProviderError, signature::Signature};
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// The selector every signature database is expected to know, `transfer(address,uint256)`.
+pub const PROBE_SELECTOR: &str = "a9059cbb";
+
+/// The outcome of checking a component sigmund depends on, e.g. the RPC or a signature database.
+///
+/// Fields:
+/// * `component`: The checked component, e.g. `rpc https://...` or `provider openchain`.
+/// * `passed`: Whether the component works.
+/// * `latency`: How long the check took.
+/// * `detail`: What the check found, e.g. the chain id of the RPC, or why it failed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub component: String,
+    pub passed: bool,
+    #[serde(serialize_with = "serialize_millis")]
+    pub latency: Duration,
+    pub detail: String,
+}
+
+impl Check {
+    /// Creates the check of a component from its outcome, timed from `start`.
+    ///
+    /// Arguments:
+    /// * `component`: The checked component.
+    /// * `start`: When the check started.
+    /// * `outcome`: The detail of a passed check, or the reason it failed.
+    pub fn timed(component: impl Into<String>, start: Instant, outcome: Result<String, String>) -> Self {
+        let (passed, detail) = match outcome {
+            Ok(detail) => (true, detail),
+            Err(reason) => (false, reason),
+        };
+        Self { component: component.into(), passed, latency: start.elapsed(), detail }
+    }
+
+    /// Creates the check of a signature database from its resolution of `selector`.
+    ///
+    /// A local database answering without a match still passes, since it may simply not hold the selector.
+    /// A remote one fails, since every public database knows `PROBE_SELECTOR`, and some providers turn
+    /// a missing page or an HTML error page into an empty answer.
+    ///
+    /// Arguments:
+    /// * `name`: The name of the database.
+    /// * `selector`: The resolved selector, `PROBE_SELECTOR` for `doctor`.
+    /// * `remote`: Whether the database is queried over the network, see `SignatureProvider::is_remote`.
+    /// * `start`: When the check started.
+    /// * `result`: The outcome of the resolution.
+    pub fn provider(name: &str, selector: &str, remote: bool, start: Instant, result: Result<Vec<Signature>, ProviderError>) -> Self {
+        let outcome = match result {
+            Ok(signatures) => match signatures.first() {
+                Some(signature) => Ok(format!("resolved {selector} to {}", signature.text)),
+                None if remote => Err(format!("responded without resolving {selector}, which it should know, check its URL")),
+                None => Ok(format!("responded, but doesn't know {selector}")),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        Self::timed(format!("provider {name}"), start, outcome)
+    }
+}
+
+impl Display for Check {
+    /// Formats the check as a single line, e.g. `pass  rpc https://...  (42ms)  chain id 1`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self.passed {
+            true => "pass",
+            false => "FAIL",
+        };
+        write!(f, "{status}  {}  ({}ms)  {}", self.component, self.latency.as_millis(), self.detail)
+    }
+}
+
+/// Serializes a latency as its whole milliseconds.
+fn serialize_millis<S: serde::Serializer>(latency: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(latency.as_millis() as u64)
+}
+
+/// Checks that a file can be created in the directory, creating the directory if it's missing like the cache does.
+///
+/// Returns:
+/// A `Result` containing the detail of the check, or the reason the directory isn't writable.
+pub fn check_writable(dir: &Path) -> Result<String, String> {
+    let probe = dir.join(".sigmund-doctor");
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| format!("{} isn't writable: {e}", dir.display()))?;
+    Ok(format!("{} is writable", dir.display()))
+}
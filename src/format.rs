@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+/// Formats a duration with the largest fitting unit, e.g. `850ms`, `2.1s`, `3m 05s` or `2h 03m`.
+pub fn human_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{}ms", duration.as_millis()),
+        1..=59 => format!("{:.1}s", duration.as_secs_f64()),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Formats a size in bytes with binary units, e.g. `512 B`, `1.5 KiB` or `3.2 MiB`.
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Formats a count with `,` separated thousands, e.g. `10,000,000`.
+pub fn human_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Formats a count followed by the singular or plural form of what is counted, e.g. `1 entry` or `42 entries`.
+pub fn plural(count: usize, singular: &str, plural: &str) -> String {
+    match count {
+        1 => format!("1 {singular}"),
+        _ => format!("{count} {plural}"),
+    }
+}
+
+/// Formats a ratio between 0 and 1 as a percentage with one decimal, e.g. `42.5%`.
+pub fn pct(ratio: f64) -> String {
+    format!("{:.1}%", ratio * 100.0)
+}
+
+/// Formats a rate of events per second, rounded to whole events and with separated thousands, e.g. `3,900,000/s`.
+pub fn human_rate(count: usize, elapsed: Duration) -> String {
+    let rate = count as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    format!("{}/s", human_count(rate.round() as usize))
+}
+
+/// The selector of a hex-encoded hash, i.e. its 4 first bytes, or the whole hash if it's shorter.
+pub fn selector(hash: &str) -> &str {
+    hash.get(..8).unwrap_or(hash)
+}
@@ -0,0 +1,283 @@
+use crate::{config::Config, format};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// The name of the run history file inside the cache directory.
+const HISTORY_FILE: &str = "history.jsonl";
+/// The number of runs kept by a compaction.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Error types for reading and writing the run history.
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("HistoryIoError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("HistorySerdeError: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("HistoryNotFoundError: no run #{0} in the history")]
+    NotFound(usize),
+    #[error("HistoryRerunError: {0}")]
+    Rerun(#[from] clap::Error),
+}
+
+/// A single analysis recorded in the run history.
+///
+/// Fields:
+/// * `id`: The number of the run, increasing and kept across compactions.
+/// * `timestamp`: When the run finished.
+/// * `args`: The command line of the run, without the secret options and with the secrets of its URLs redacted.
+/// * `cwd`: The directory the run was started from, which relative paths of `args` are relative to.
+/// * `input`: The analyzed address or file.
+/// * `chain_id`: The id of the chain of an analyzed address, if it was given or could be detected.
+/// * `code_hash`: The hex-encoded Keccak-256 hash of the analyzed code, missing without the `keccak` feature.
+/// * `selectors`: The number of selectors found.
+/// * `coverage`: The share of the selectors resolved to a signature, present with `--signatures`.
+/// * `output`: The JSON file the output was written to, if any.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: usize,
+    pub timestamp: DateTime<Utc>,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    pub input: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_hash: Option<String>,
+    pub selectors: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+}
+
+impl HistoryEntry {
+    /// Creates an entry for a run with the given command line, numbered when it's recorded.
+    ///
+    /// The secret options, such as `--etherscan-key` or `--rpc-header`, are left out with their values, so
+    /// a rerun takes them from the environment or its own command line instead, and the secrets of the URLs,
+    /// e.g. the path of an RPC URL holding its API key, are redacted like in `config show`.
+    pub fn new(args: &[String], input: impl Into<String>, selectors: usize) -> Self {
+        Self {
+            id: 0,
+            timestamp: std::time::SystemTime::now().into(),
+            args: crate::config::redact_args(args, true),
+            cwd: std::env::current_dir().unwrap_or_default(),
+            input: input.into(),
+            chain_id: None,
+            code_hash: None,
+            selectors,
+            coverage: None,
+            output: None,
+        }
+    }
+
+    /// Whether the input, code hash, chain id, output or command line of the run contain `query`, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim_start_matches("0x").to_lowercase();
+        let fields = [
+            Some(self.input.clone()),
+            self.code_hash.clone(),
+            self.chain_id.map(|id| id.to_string()),
+            self.output.as_ref().map(|output| output.display().to_string()),
+            Some(self.args.join(" ")),
+        ];
+        fields.into_iter().flatten().any(|field| field.to_lowercase().contains(&query))
+    }
+
+    /// Reconstructs the configuration of the run from its command line, to run it again.
+    ///
+    /// Relative paths are relative to the directory the run was started from, if it still exists.
+    ///
+    /// Returns:
+    /// A `Result` containing the `Config`, or a `HistoryError` if the command line is no longer valid, e.g. after an option was removed.
+    pub fn config(&self) -> Result<Config, HistoryError> {
+        let dir = Some(self.cwd.as_path()).filter(|cwd| cwd.is_dir());
+        Ok(Config::try_parse_in(&self.args, dir)?)
+    }
+}
+
+impl Display for HistoryEntry {
+    /// Formats the entry as a line of `history list`, or with every field on its own line in the alternate form of `history show`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let timestamp = self.timestamp.format("%Y-%m-%d %H:%M");
+        let coverage = self.coverage.map(|coverage| format!(", {} resolved", format::pct(coverage))).unwrap_or_default();
+        if !f.alternate() {
+            return write!(f, "{:>4}  {timestamp}  {}  {} selectors{coverage}", self.id, self.input, self.selectors);
+        }
+
+        writeln!(f, "run:        #{}", self.id)?;
+        writeln!(f, "date:       {timestamp} UTC")?;
+        writeln!(f, "input:      {}", self.input)?;
+        if let Some(chain_id) = self.chain_id {
+            writeln!(f, "chain id:   {chain_id}")?;
+        }
+        if let Some(code_hash) = &self.code_hash {
+            writeln!(f, "code hash:  0x{code_hash}")?;
+        }
+        writeln!(f, "selectors:  {}", self.selectors)?;
+        if let Some(coverage) = self.coverage {
+            writeln!(f, "coverage:   {}", format::pct(coverage))?;
+        }
+        if let Some(output) = &self.output {
+            writeln!(f, "output:     {}", output.display())?;
+        }
+        writeln!(f, "directory:  {}", self.cwd.display())?;
+        write!(f, "command:    {}", self.args.join(" "))
+    }
+}
+
+/// The local history of the analyses, stored in the cache directory and never sent anywhere.
+///
+/// Runs are appended to a JSON Lines file, one entry per line, so recording a run doesn't rewrite
+/// the history. Once the file holds twice as many lines as `max_entries`, it's compacted to the latest ones.
+/// Recording and compacting re-read the file under an exclusive lock, so concurrent runs number their runs
+/// after each other's and a compaction doesn't drop the runs recorded since the history was opened.
+#[derive(Debug)]
+pub struct History {
+    path: PathBuf,
+    max_entries: usize,
+    entries: Vec<HistoryEntry>,
+    /// The number of lines that couldn't be parsed, e.g. a line cut short by an interrupted run.
+    skipped: usize,
+}
+
+impl History {
+    /// Opens the history stored in the given directory.
+    ///
+    /// A missing history file results in an empty history, which is created when the first run is recorded.
+    /// Lines that can't be parsed are skipped, and dropped by the next compaction.
+    ///
+    /// Arguments:
+    /// * `dir`: The cache directory.
+    ///
+    /// Returns:
+    /// A `Result` containing the `History`, or a `HistoryError` if the file can't be read.
+    pub fn open(dir: &Path) -> Result<Self, HistoryError> {
+        let mut history = Self { path: dir.join(HISTORY_FILE), max_entries: DEFAULT_MAX_ENTRIES, entries: Vec::new(), skipped: 0 };
+        history.reload()?;
+        Ok(history)
+    }
+
+    /// Reads the history file again, picking up the runs recorded by other runs since it was opened.
+    fn reload(&mut self) -> Result<(), HistoryError> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        self.entries.clear();
+        self.skipped = 0;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(entry) => self.entries.push(entry),
+                Err(_) => self.skipped += 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes the exclusive lock of the history, released when the returned file is dropped.
+    ///
+    /// The lock is taken on a file of its own, since a compaction replaces the history file.
+    fn lock(&self) -> Result<File, HistoryError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let lock = OpenOptions::new().create(true).truncate(false).write(true).open(self.path.with_extension("jsonl.lock"))?;
+        lock.lock()?;
+        Ok(lock)
+    }
+
+    /// Sets the number of runs kept by a compaction, `DEFAULT_MAX_ENTRIES` by default.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// The path of the history file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The recorded runs, oldest first.
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// The run with the given number, if it's still in the history.
+    pub fn get(&self, id: usize) -> Result<&HistoryEntry, HistoryError> {
+        self.entries.iter().find(|entry| entry.id == id).ok_or(HistoryError::NotFound(id))
+    }
+
+    /// The runs matching `query` as documented on `HistoryEntry::matches`, oldest first.
+    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+        self.entries.iter().filter(|entry| entry.matches(query)).collect()
+    }
+
+    /// Appends a run to the history, numbering it after the last recorded run.
+    ///
+    /// Returns:
+    /// A `Result` containing the number of the run, or a `HistoryError` if the file can't be written.
+    pub fn record(&mut self, mut entry: HistoryEntry) -> Result<usize, HistoryError> {
+        let _lock = self.lock()?;
+        self.reload()?;
+        entry.id = self.entries.last().map_or(1, |last| last.id + 1);
+
+        let line = format!("{}\n", serde_json::to_string(&entry)?);
+        OpenOptions::new().create(true).append(true).open(&self.path)?.write_all(line.as_bytes())?;
+        let id = entry.id;
+        self.entries.push(entry);
+
+        if self.entries.len() + self.skipped > 2 * self.max_entries {
+            self.rewrite()?;
+        }
+        Ok(id)
+    }
+
+    /// Rewrites the history file with the latest `max_entries` runs, dropping the lines that couldn't be parsed.
+    ///
+    /// The content is written to a temporary file first and then renamed over the history file,
+    /// so an interrupted compaction doesn't lose the history.
+    pub fn compact(&mut self) -> Result<(), HistoryError> {
+        let _lock = self.lock()?;
+        self.reload()?;
+        self.rewrite()
+    }
+
+    /// Rewrites the history file with the latest `max_entries` of the loaded runs, under the lock taken by the caller.
+    fn rewrite(&mut self) -> Result<(), HistoryError> {
+        let dropped = self.entries.len().saturating_sub(self.max_entries);
+        self.entries.drain(..dropped);
+        self.skipped = 0;
+
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        let tmp = self.path.with_extension(format!("jsonl.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Removes all runs and deletes the history file.
+    pub fn clear(&mut self) -> Result<(), HistoryError> {
+        self.entries.clear();
+        self.skipped = 0;
+
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
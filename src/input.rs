@@ -0,0 +1,272 @@
+use crate::{
+    address::Address,
+    bytecode::Bytecode,
+    chain::{Chain, ChainError, ChainRegistry},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Keys holding the bytecode in the JSON files exported by explorers, RPC nodes and compilers.
+const JSON_KEYS: [&str; 4] = ["bytecode", "code", "deployedBytecode", "result"];
+
+/// Error types for reading bytecode from a file.
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("InputIoError: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content isn't valid in the requested format.
+    #[error("The input is not valid {0}: {1}")]
+    Invalid(InputFormat, String),
+
+    /// No format could be detected, the format can be given explicitly with `--input-format`.
+    #[error("Unrecognized input format, expected hex, binary, json or base64 bytecode")]
+    Unrecognized,
+
+    /// A `chain:address` line of an address file names a chain that isn't known.
+    #[error("line {line}: {source}")]
+    UnknownChain { line: usize, source: ChainError },
+}
+
+/// The encoding of a file containing bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    /// Hex-encoded bytecode, with or without the "0x" prefix
+    Hex,
+    /// Raw bytecode
+    Binary,
+    /// A JSON string or object with the hex-encoded bytecode under a common key
+    Json,
+    /// Base64-encoded bytecode
+    Base64,
+}
+
+impl Display for InputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Hex => write!(f, "hex"),
+            Self::Binary => write!(f, "binary"),
+            Self::Json => write!(f, "json"),
+            Self::Base64 => write!(f, "base64"),
+        }
+    }
+}
+
+/// A contract to analyze, given either by its address or by a file containing its bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// The address of a deployed contract, as it was given.
+    Address(String),
+    /// The path of a file containing the bytecode.
+    File(PathBuf),
+    /// The address of a contract deployed on the given chain, fetched from the RPC of the chain.
+    OnChain { chain: Chain, address: String },
+}
+
+impl Input {
+    /// Parses an entry of an address file, valid addresses are fetched and everything else is a file path.
+    pub fn parse(entry: &str) -> Self {
+        match Address::validate(entry) {
+            Ok(()) => Self::Address(entry.to_string()),
+            Err(_) => Self::File(PathBuf::from(entry)),
+        }
+    }
+
+    /// The address, if the contract is given by its address.
+    pub fn address(&self) -> Option<&str> {
+        match self {
+            Self::Address(address) | Self::OnChain { address, .. } => Some(address),
+            Self::File(_) => None,
+        }
+    }
+
+    /// The chain, if the contract is given along with its chain.
+    pub fn chain(&self) -> Option<&Chain> {
+        match self {
+            Self::OnChain { chain, .. } => Some(chain),
+            Self::Address(_) | Self::File(_) => None,
+        }
+    }
+
+    /// Identifies the contract regardless of how it was written: the lowercase address, or the canonical path
+    /// of the file, falling back to the path as given if it can't be resolved.
+    pub fn identity(&self) -> String {
+        match self {
+            Self::Address(address) => address.to_lowercase(),
+            // The same address on another chain is another contract
+            Self::OnChain { chain, address } => format!("{}:{}", chain.id, address.to_lowercase()),
+            Self::File(path) => std::fs::canonicalize(path).unwrap_or_else(|_| path.clone()).to_string_lossy().into_owned(),
+        }
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{address}"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::OnChain { chain, address } => write!(f, "{chain}:{address}"),
+        }
+    }
+}
+
+/// Reads the inputs of an address file, one address, `chain:address` or bytecode file path per line.
+///
+/// Empty lines and lines starting with `#` are ignored, relative paths are relative to the working directory.
+/// The chain of a `chain:address` line is a chain name or id of the registry, e.g. `arbitrum:0x...` or `10:0x...`.
+///
+/// Returns:
+/// A `Result` containing the inputs in order, or an `InputError` if the file can't be read or a line names an unknown chain.
+pub fn read_inputs(path: &Path, chains: &ChainRegistry) -> Result<Vec<Input>, InputError> {
+    let content = std::fs::read_to_string(path)?;
+    let mut inputs = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let input = match line.split_once(':') {
+            Some((chain, address)) if Address::validate(address.trim()).is_ok() => {
+                let chain = chains.resolve(chain).map_err(|source| InputError::UnknownChain { line: index + 1, source })?;
+                Input::OnChain { chain: chain.clone(), address: address.trim().to_string() }
+            }
+            _ => Input::parse(line),
+        };
+        inputs.push(input);
+    }
+    Ok(inputs)
+}
+
+/// Removes the inputs referring to the same contract as an earlier one, keeping the first occurrence in place.
+///
+/// Returns:
+/// The number of removed duplicates.
+pub fn dedupe(inputs: &mut Vec<Input>) -> usize {
+    let (len, mut seen) = (inputs.len(), HashSet::new());
+    inputs.retain(|input| seen.insert(input.identity()));
+    len - inputs.len()
+}
+
+/// Reads the bytecode from a file, in the given format or a detected one.
+///
+/// Arguments:
+/// * `path`: The path to the file containing the bytecode.
+/// * `format`: The format of the file, detected from its content if `None`.
+///
+/// Returns:
+/// A `Result` containing the `Bytecode` and the format it was read as, or an `InputError`.
+pub fn read(path: &Path, format: Option<InputFormat>) -> Result<(Bytecode, InputFormat), InputError> {
+    decode(&std::fs::read(path)?, format)
+}
+
+/// Decodes bytecode in the given format or a detected one.
+///
+/// The detection tries each format in order and keeps the first one that applies:
+/// 1. JSON, if the content parses as a JSON string or an object with one of the common bytecode keys.
+/// 2. Hex, if the content is a valid hex string. Hex strings are also valid base64,
+///    so hex always wins this ambiguity and base64 has to be requested explicitly.
+/// 3. Base64, if the content decodes to bytes starting like EVM code (see `plausible_code`).
+/// 4. Binary, if the content isn't text.
+///
+/// Arguments:
+/// * `content`: The raw file content.
+/// * `format`: The format of the content, detected if `None`.
+///
+/// Returns:
+/// A `Result` containing the `Bytecode` and the format it was decoded as, or an `InputError`.
+pub fn decode(content: &[u8], format: Option<InputFormat>) -> Result<(Bytecode, InputFormat), InputError> {
+    let (inner, format) = match format {
+        Some(format) => (decode_as(content, format)?, format),
+        None => detect(content)?,
+    };
+
+    Ok((Bytecode { inner }, format))
+}
+
+/// Decodes the content in the given format.
+fn decode_as(content: &[u8], format: InputFormat) -> Result<Vec<u8>, InputError> {
+    let invalid = |e: &dyn Display| InputError::Invalid(format, e.to_string());
+
+    if format == InputFormat::Binary {
+        return Ok(content.to_vec());
+    }
+
+    let text = std::str::from_utf8(content).map_err(|e| invalid(&e))?.trim();
+    match format {
+        InputFormat::Hex => decode_hex(text).map_err(|e| invalid(&e)),
+        InputFormat::Base64 => STANDARD.decode(text).map_err(|e| invalid(&e)),
+        InputFormat::Json => {
+            let value: serde_json::Value = serde_json::from_str(text).map_err(|e| invalid(&e))?;
+            let code = json_bytecode(&value).ok_or_else(|| invalid(&format!("no string under the keys {JSON_KEYS:?}")))?;
+            decode_hex(code).map_err(|e| invalid(&e))
+        }
+        InputFormat::Binary => unreachable!("binary content is returned as is"),
+    }
+}
+
+/// Detects the format of the content, see `decode` for the detection order.
+fn detect(content: &[u8]) -> Result<(Vec<u8>, InputFormat), InputError> {
+    let Ok(text) = std::str::from_utf8(content).map(str::trim) else {
+        return Ok((content.to_vec(), InputFormat::Binary));
+    };
+
+    if let Some(code) = serde_json::from_str(text).ok().as_ref().and_then(json_bytecode) {
+        return Ok((decode_hex(code).map_err(|e| InputError::Invalid(InputFormat::Json, e.to_string()))?, InputFormat::Json));
+    }
+
+    // A "0x" prefix is unambiguous, so report the hex error instead of trying other formats
+    if text.starts_with("0x") {
+        return Ok((decode_as(content, InputFormat::Hex)?, InputFormat::Hex));
+    }
+
+    if let Ok(code) = decode_hex(text) {
+        return Ok((code, InputFormat::Hex));
+    }
+
+    if let Some(code) = STANDARD.decode(text).ok().filter(|code| plausible_code(code)) {
+        return Ok((code, InputFormat::Base64));
+    }
+
+    // Text that is mostly control characters isn't meant to be read as text
+    let printable = text.chars().filter(|c| !c.is_control() || c.is_whitespace()).count();
+    match printable < text.chars().count() {
+        true => Ok((content.to_vec(), InputFormat::Binary)),
+        false => Err(InputError::Unrecognized),
+    }
+}
+
+/// Decodes a hex string, with or without the "0x" prefix.
+fn decode_hex(text: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    hex::decode(text.trim().trim_start_matches("0x"))
+}
+
+/// Finds the hex-encoded bytecode in a JSON value.
+///
+/// The value is either the bytecode string itself or an object with the bytecode under one of
+/// `JSON_KEYS`, which may also be nested under an `object` key like in Foundry artifacts.
+fn json_bytecode(value: &serde_json::Value) -> Option<&str> {
+    match value {
+        serde_json::Value::String(code) => Some(code),
+        serde_json::Value::Object(map) => JSON_KEYS.iter().filter_map(|key| map.get(*key)).find_map(|code| match code {
+            serde_json::Value::Object(nested) => nested.get("object")?.as_str(),
+            code => code.as_str(),
+        }),
+        _ => None,
+    }
+}
+
+/// Whether decoded bytes plausibly start with EVM code.
+///
+/// Compiled contracts start by pushing a value, usually the free memory pointer (`PUSH1 0x80`),
+/// while minimal proxies start with `CALLDATASIZE`.
+fn plausible_code(code: &[u8]) -> bool {
+    matches!(code.first(), Some(0x5f..=0x7f | 0x36))
+}
@@ -0,0 +1,153 @@
+use crate::{
+    jsonrpc::{self, RequestIds},
+    transport::{HttpResponse, TransportError},
+};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::atomic::AtomicU64,
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream,
+    sync::Mutex,
+};
+
+/// The connection to the IPC endpoint of a node, a Unix domain socket.
+///
+/// Another platform only needs its own `Stream` and `connect`, e.g. a named pipe on Windows.
+type Stream = UnixStream;
+
+/// A JSON-RPC transport over the IPC socket of a local node, e.g. `/path/to/geth.ipc`.
+///
+/// The socket is opened on the first request and reused by the following ones, one request at a time,
+/// each one sent with ids of its own (see `RequestIds`). The requests are written as newline-delimited JSON
+/// and the responses read as a stream of JSON values, whether the node delimits them with newlines or not.
+///
+/// Fields:
+/// * `timeout`: The maximum duration of a request, including connecting.
+/// * `max_message_size`: The maximum size of a response.
+/// * `deadline`: An optional instant the requests are cancelled at, with `TransportError::DeadlineExceeded`.
+/// * `connection`: The open connection, the path it was opened to, and the bytes read past the last response.
+/// * `next_id`: The id of the next request.
+pub struct Ipc {
+    timeout: Duration,
+    max_message_size: u64,
+    deadline: Option<Instant>,
+    connection: Mutex<Option<(String, Stream, Vec<u8>)>>,
+    next_id: AtomicU64,
+}
+
+impl Ipc {
+    /// Creates a transport that connects on its first request.
+    ///
+    /// Arguments:
+    /// * `timeout`: The maximum duration of a request.
+    /// * `max_message_size`: The maximum size of a response.
+    /// * `deadline`: An optional instant the requests are cancelled at.
+    pub fn new(timeout: Duration, max_message_size: u64, deadline: Option<Instant>) -> Self {
+        Self { timeout, max_message_size, deadline, connection: Mutex::new(None), next_id: AtomicU64::new(1) }
+    }
+
+    /// Sends a JSON-RPC request or batch and reads its response, like an HTTP `POST` answered with `200 OK`.
+    ///
+    /// Returns:
+    /// A `Result` containing the response, or a `TransportError::Ipc` if the connection failed or the request timed out.
+    pub async fn post(&self, path: &str, body: String) -> Result<HttpResponse, TransportError> {
+        jsonrpc::bounded(self.exchange(path, body), self.timeout, self.deadline, |message| error(path, message)).await
+    }
+
+    /// Sends a request over the open connection, opening it first if needed.
+    ///
+    /// A reused connection the node closed in the meantime, e.g. after a restart, is opened again once.
+    async fn exchange(&self, path: &str, body: String) -> Result<HttpResponse, TransportError> {
+        let mut request: Value = serde_json::from_str(&body).map_err(|e| error(path, e))?;
+        let ids = RequestIds::assign(&mut request, &self.next_id);
+
+        let mut connection = self.connection.lock().await;
+        let reused = connection.as_ref().is_some_and(|(connected, _, _)| connected == path);
+        if !reused {
+            *connection = Some((path.to_string(), connect(path).await?, Vec::new()));
+        }
+
+        let (_, stream, buffer) = connection.as_mut().unwrap();
+        let mut response = match self.round_trip(stream, buffer, &request, &ids).await {
+            Ok(response) => response,
+            Err(e) if e.kind() == io::ErrorKind::OutOfMemory => {
+                *connection = None;
+                return Err(TransportError::ResponseTooLarge { url: path.to_string(), limit: self.max_message_size });
+            }
+            Err(_) if reused => {
+                *connection = Some((path.to_string(), connect(path).await?, Vec::new()));
+                let (_, stream, buffer) = connection.as_mut().unwrap();
+                match self.round_trip(stream, buffer, &request, &ids).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        *connection = None;
+                        return Err(error(path, e));
+                    }
+                }
+            }
+            Err(e) => {
+                *connection = None;
+                return Err(error(path, e));
+            }
+        };
+
+        ids.restore(&mut response);
+        Ok(HttpResponse { url: path.to_string(), status: StatusCode::OK, headers: BTreeMap::new(), body: response.to_string().into_bytes() })
+    }
+
+    /// Writes a request and reads values until its response, skipping the late responses of the previous requests.
+    ///
+    /// Arguments:
+    /// * `buffer`: The bytes read past the previous response, the start of the next value.
+    ///
+    /// Returns:
+    /// The response, or an `io::Error` of kind `OutOfMemory` if it grew past `max_message_size`.
+    async fn round_trip(&self, stream: &mut Stream, buffer: &mut Vec<u8>, request: &Value, ids: &RequestIds) -> io::Result<Value> {
+        stream.write_all(format!("{request}\n").as_bytes()).await?;
+        let mut chunk = vec![0; 64 * 1024];
+        loop {
+            let mut values = serde_json::Deserializer::from_slice(buffer).into_iter::<Value>();
+            let mut answer = None;
+            for value in values.by_ref() {
+                match value {
+                    Ok(value) if ids.answered_by(&value) => {
+                        answer = Some(value);
+                        break;
+                    }
+                    Ok(_) => continue,
+                    Err(e) if e.is_eof() => break,
+                    Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+                }
+            }
+            let consumed = values.byte_offset();
+            buffer.drain(..consumed);
+            if let Some(answer) = answer {
+                return Ok(answer);
+            }
+
+            if buffer.len() as u64 > self.max_message_size {
+                return Err(io::ErrorKind::OutOfMemory.into());
+            }
+            match stream.read(&mut chunk).await? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                read => buffer.extend_from_slice(&chunk[..read]),
+            }
+        }
+    }
+}
+
+/// Opens the socket of the node.
+async fn connect(path: &str) -> Result<Stream, TransportError> {
+    UnixStream::connect(path).await.map_err(|e| error(path, e))
+}
+
+/// The error of an IPC request.
+fn error(path: &str, error: impl std::fmt::Display) -> TransportError {
+    TransportError::Ipc { path: path.to_string(), message: error.to_string() }
+}
@@ -0,0 +1,77 @@
+use crate::transport::TransportError;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The ids of a request sent over a connection shared by the requests of the run, e.g. a WebSocket or an IPC socket.
+///
+/// Every request is sent with ids of its own, so its response is told apart from the late response of a
+/// request that timed out, and the ids it was given are restored in the response.
+pub(crate) struct RequestIds(HashMap<u64, Value>);
+
+impl RequestIds {
+    /// Replaces the ids of a request, or of each request of a batch, with fresh ones taken from `next_id`.
+    pub(crate) fn assign(request: &mut Value, next_id: &AtomicU64) -> Self {
+        let mut ids = HashMap::new();
+        for request in requests(request) {
+            let id = next_id.fetch_add(1, Ordering::Relaxed);
+            ids.insert(id, request.insert("id".to_string(), id.into()).unwrap_or(Value::Null));
+        }
+        Self(ids)
+    }
+
+    /// Whether a response, or a batch of responses, answers the request.
+    ///
+    /// An error response without an id, e.g. for a request the node couldn't parse, is taken as the answer,
+    /// since a single request is in flight at once.
+    pub(crate) fn answered_by(&self, response: &Value) -> bool {
+        let answers = |response: &Value| match response.get("id") {
+            Some(Value::Null) | None => response.get("error").is_some(),
+            Some(id) => id.as_u64().is_some_and(|id| self.0.contains_key(&id)),
+        };
+        match response {
+            Value::Array(responses) => responses.iter().any(answers),
+            response => answers(response),
+        }
+    }
+
+    /// Restores the original ids of the requests in their responses.
+    pub(crate) fn restore(&self, response: &mut Value) {
+        for response in requests(response) {
+            if let Some(original) = response.get("id").and_then(Value::as_u64).and_then(|id| self.0.get(&id)) {
+                response.insert("id".to_string(), original.clone());
+            }
+        }
+    }
+}
+
+/// The objects of a request or response, or of each one of a batch.
+fn requests(value: &mut Value) -> Vec<&mut serde_json::Map<String, Value>> {
+    match value {
+        Value::Array(values) => values.iter_mut().filter_map(Value::as_object_mut).collect(),
+        value => value.as_object_mut().into_iter().collect(),
+    }
+}
+
+/// Runs a request until its timeout, or until the deadline of the run if it comes first.
+///
+/// Returns:
+/// The output of the request, `TransportError::DeadlineExceeded` once the deadline is reached, or the error of `timed_out` after the timeout.
+pub(crate) async fn bounded<T>(
+    request: impl Future<Output = Result<T, TransportError>>,
+    timeout: Duration,
+    deadline: Option<Instant>,
+    timed_out: impl FnOnce(String) -> TransportError,
+) -> Result<T, TransportError> {
+    let limit = Instant::now() + timeout;
+    let until = deadline.map_or(limit, |deadline| deadline.min(limit));
+    match tokio::time::timeout_at(until.into(), request).await {
+        Ok(output) => output,
+        Err(_) if deadline.is_some_and(|deadline| deadline <= Instant::now()) => Err(TransportError::DeadlineExceeded),
+        Err(_) => Err(timed_out(format!("no response within {}, the limit can be raised with --timeout", crate::format::human_duration(timeout)))),
+    }
+}
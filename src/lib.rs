@@ -1,32 +1,413 @@
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const DEFAULT_RPC_URL: &str = "https://ethereum-rpc.publicnode.com";
+const USER_AGENT: &str = concat!("sigmund/", env!("CARGO_PKG_VERSION"), " (+https://github.com/wavefnx/sigmund)");
+const ETHERFACE_URL: &str = "https://api.etherface.io";
+const OPENCHAIN_URL: &str = "https://api.openchain.xyz";
+const FOURBYTE_URL: &str = "https://www.4byte.directory";
+const DEFAULT_MAX_MATCHES: usize = 50;
+const ETHERSCAN_URL: &str = "https://api.etherscan.io/api";
+const SOURCIFY_URL: &str = "https://sourcify.dev/server";
+const FOURBYTE_LISTING_URL: &str = "https://www.4byte.directory/api/v1/signatures/?ordering=id";
+/// The version of the JSON output, increased when fields change meaning. Version 2 groups the matches by selector under `matches`.
+pub const SCHEMA_VERSION: u32 = 2;
+/// The exit code of a run that reached its `--deadline`, the one of `timeout(1)`, so scripts can tell it from a failed run.
+pub const DEADLINE_EXIT_CODE: i32 = 124;
+/// The exit code of a run whose address has no code, e.g. an EOA, so scripts can tell it from a failed analysis.
+pub const NO_CODE_EXIT_CODE: i32 = 3;
 
 pub mod config;
 
-use std::collections::HashSet;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
-use config::Config;
+use config::{CacheCommand, Command, Config, ConfigCommand, HistoryCommand, Origin};
 
 mod client;
-use client::Client;
+pub use client::{CallOutcome, Client, ClientError, ClientOptions, Failover, LookupFailure, Pace, RateLimits, ReceiptLog, Transaction, TransactionReceipt};
+
+pub mod providers;
+use providers::{Etherscan, JsonPath, LocalDb, ParseWarnings, ProviderChain, ProviderKind, ProviderError, SignatureProvider, Sourcify, UrlTemplate};
+
+mod cache;
+pub use cache::{CacheEntry, CacheError, CacheStats, SignatureCache};
+
+mod access;
+pub use access::{access_hints, AccessHint};
 
 mod bytecode;
-pub use bytecode::Bytecode;
+pub use bytecode::{Bytecode, Confidence, Dispatcher, DispatcherStyle, Instruction, Instructions, Mutability, OpcodeStats, Similarity};
+
+mod opcode;
+pub use opcode::Opcode;
 
 mod address;
 pub use address::Address;
 
-mod signature;
-pub use signature::Signature;
+mod block;
+pub use block::{BlockError, BlockId};
+
+mod chain;
+pub use chain::{Chain, ChainError, ChainRegistry};
+
+mod doctor;
+pub use doctor::{Check, PROBE_SELECTOR};
+
+pub mod selftest;
+
+/// Human readable rendering of durations, sizes, counts and percentages, used for everything shown on the console.
+/// The machine formats keep the raw numbers, so they don't depend on these helpers nor on the locale.
+pub mod format;
+
+pub mod signature;
+pub use signature::{EventSignature, HashKind, ParseError, ParsedSignature, RankHints, Signature, SignatureError, TextSelector, SignatureGroup, SignatureKind, UnresolvedSelectors};
+
+mod candidates;
+pub use candidates::CandidateCheck;
+
+mod bruteforce;
+pub use bruteforce::{Bruteforce, BruteforceReport};
+
+mod abi;
+pub use abi::{AbiError, AbiValue, ParamType};
+
+pub mod input;
+pub use input::{Input, InputError, InputFormat};
+
+mod warning;
+pub use warning::{Severity, Warning, WarningKind};
+
+mod proxy;
+pub use proxy::{Hop, HopKind, ResolutionChain, Target, TargetError, ADMIN_SLOT, BEACON_SLOT, IMPLEMENTATION_SLOT};
+
+mod creation;
+pub use creation::{split_constructor_args, ConstructorArg, ConstructorArgs, CreationAnalysis};
+
+mod deployer;
+pub use deployer::{create_address, deployed_by, find_creations, DeployerError};
+
+mod router;
+pub use router::{RouterError, RouterProbe};
+
+mod verify;
+pub use verify::{CallVerdict, CallVerification};
+
+mod scan;
+pub use scan::{Creation, ScanError};
+
+#[cfg(feature = "ws")]
+mod websocket;
+#[cfg(unix)]
+mod ipc;
+#[cfg(any(feature = "ws", unix))]
+mod jsonrpc;
+
+mod transport;
+pub use transport::{HttpResponse, Transport, TransportError, TransportMode};
+
+mod history;
+pub use history::{History, HistoryEntry, HistoryError};
+
+mod sink;
+pub use sink::{DeadLetter, DirectorySink, EvmoleSink, ExportUnknownSink, FileSink, HttpSink, JsonMinSink, JsonSink, MinimalOut, NdjsonSink, OutputFormat, OutputSink, SeenIn, SinkError, UnknownSelector};
+
+/// Asks a yes or no question on the console, no being the default.
+fn confirm(question: &str) -> std::io::Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+/// The provider of the bundled signature table used by `--offline`.
+#[cfg(feature = "offline")]
+fn offline_provider() -> Result<Box<dyn SignatureProvider>, Box<dyn std::error::Error>> {
+    Ok(Box::new(providers::Offline))
+}
+
+/// The provider of the bundled signature table used by `--offline`.
+#[cfg(not(feature = "offline"))]
+fn offline_provider() -> Result<Box<dyn SignatureProvider>, Box<dyn std::error::Error>> {
+    Err("sigmund was built without the bundled signature table, enable the `offline` feature to use --offline".into())
+}
+
+/// The selectors of the bundled signature table, probed on storage-mapped routers.
+#[cfg(feature = "offline")]
+fn common_selectors() -> Vec<String> {
+    providers::Offline.selectors()
+}
+
+/// The selectors of the bundled signature table, probed on storage-mapped routers.
+#[cfg(not(feature = "offline"))]
+fn common_selectors() -> Vec<String> {
+    Vec::new()
+}
+
+/// The provider of the SQLite signature database of `--sqlite-db`.
+///
+/// Its entries are looked up ahead of the cached results of the remote databases, see `SignatureProvider::shadows_cache`.
+#[cfg(feature = "sqlite")]
+fn sqlite_provider(path: &Path) -> Result<Box<dyn SignatureProvider>, Box<dyn std::error::Error>> {
+    let db = providers::SqliteProvider::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(Box::new(db))
+}
+
+/// The provider of the SQLite signature database of `--sqlite-db`.
+///
+/// Its entries are looked up ahead of the cached results of the remote databases, see `SignatureProvider::shadows_cache`.
+#[cfg(not(feature = "sqlite"))]
+fn sqlite_provider(_path: &Path) -> Result<Box<dyn SignatureProvider>, Box<dyn std::error::Error>> {
+    Err("sigmund was built without SQLite support, enable the `sqlite` feature to use --sqlite-db".into())
+}
+
+/// Creates the bruteforcer of the wordlist and/or the built-in function names, the built-in ones first.
+fn bruteforcer(wordlist: Option<&Path>, builtin: bool, max_params: usize, limit: usize, timeout: std::time::Duration) -> Result<Bruteforce, Box<dyn std::error::Error>> {
+    let mut bruteforce = Bruteforce::builtin(max_params, limit, timeout);
+    if !builtin {
+        bruteforce.stems.clear();
+    }
+    if let Some(path) = wordlist {
+        let words = Bruteforce::from_wordlist(path, max_params, limit, timeout).map_err(|e| format!("{}: {e}", path.display()))?;
+        let known: HashSet<String> = bruteforce.stems.iter().cloned().collect();
+        bruteforce.stems.extend(words.stems.into_iter().filter(|stem| !known.contains(stem)));
+    }
+    Ok(bruteforce)
+}
+
+/// Bulk-loads a CSV dump into the SQLite signature database of `import-db`, reporting the imported entries.
+#[cfg(feature = "sqlite")]
+fn import_db(dump: &Path, db: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let summary = providers::SqliteProvider::import(dump, db).map_err(|e| format!("{}: {e}", dump.display()))?;
+    println!(
+        "Imported {} signatures into {} ({} already present, {} invalid rows skipped)",
+        summary.imported,
+        db.display(),
+        summary.duplicates,
+        summary.skipped
+    );
+    Ok(())
+}
+
+/// Bulk-loads a CSV dump into the SQLite signature database of `import-db`, reporting the imported entries.
+#[cfg(not(feature = "sqlite"))]
+fn import_db(_dump: &Path, _db: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    Err("sigmund was built without SQLite support, enable the `sqlite` feature to use import-db".into())
+}
+
+/// Prints the effective configuration of `config show`, with the origin of each option and the problems that would fail a run.
+///
+/// Unlike a run, the configuration isn't validated first, so that its problems are reported instead of stopping at the first one.
+pub fn show_config(config: &Config, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let report = config.report();
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(&report)?),
+        false => println!("{report}"),
+    }
+    Ok(())
+}
+
+/// Computes the selectors of signature texts locally and prints them, for the `selector` command.
+///
+/// Nothing is looked up, so no client is needed: the texts are canonicalized and hashed, see `TextSelector`.
+///
+/// Arguments:
+/// * `texts`: The signature texts, `-` reads them from stdin, one per line, skipping blank lines and `#` comments.
+/// * `full`: Whether to print the full 32-byte hash as well.
+/// * `json`: Whether to print a JSON list instead of a line per text.
+///
+/// Returns:
+/// A `Result` containing the selector of each text in order, or an error for the first text that can't be parsed.
+pub fn selector(texts: &[String], full: bool, json: bool) -> Result<Vec<TextSelector>, Box<dyn std::error::Error>> {
+    let mut selectors = Vec::new();
+    for text in texts {
+        let texts = match text.as_str() {
+            "-" => std::io::stdin().lines().collect::<Result<Vec<_>, _>>()?,
+            _ => vec![text.clone()],
+        };
+        for text in texts.iter().map(|text| text.trim()).filter(|text| !text.is_empty() && !text.starts_with('#')) {
+            let selector = TextSelector::compute(text).map_err(|e| e.to_string())?;
+            selectors.push(TextSelector { hash: selector.hash.filter(|_| full), ..selector });
+        }
+    }
+
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(&selectors)?),
+        false => selectors.iter().for_each(|selector| println!("{selector}")),
+    }
+    Ok(selectors)
+}
+
+/// Metadata describing how an output was produced.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Meta {
+    /// The `SCHEMA_VERSION` of the output.
+    pub schema_version: u32,
+    /// The hop of the resolution chain whose bytecode was analyzed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<AnalyzedTarget>,
+    /// The page of the selectors included in the output, present when `--page` is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<Page>,
+    /// The address exactly as it was given, while every other field uses the normalized lowercase address.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_as_given: Option<String>,
+    /// The chain of a `chain:address` input or of an address analyzed on `--chain`, absent for the inputs analyzed through `--rpc-url`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<Chain>,
+    /// The block the code was read at, present when `--block` is given for an address input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<BlockId>,
+    /// The contracts the calls to the input address were followed to, from the input to the last one, present when it was followed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolution: Vec<ResolvedHop>,
+    /// The admin of an EIP-1967 proxy, present when its admin slot is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_admin: Option<String>,
+    /// The hex-encoded Keccak-256 hash of the analyzed code, missing without the `keccak` feature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_hash: Option<String>,
+}
+
+/// The position of a paginated output within the sorted selectors of the contract.
+///
+/// Fields:
+/// * `page`: The 1-based number of the page.
+/// * `page_size`: The maximum number of selectors on each page.
+/// * `total_pages`: The number of pages, `0` when the contract has no selectors.
+/// * `total_selectors`: The number of selectors across all pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Page {
+    pub page: usize,
+    pub page_size: usize,
+    pub total_pages: usize,
+    pub total_selectors: usize,
+}
+
+/// The hop of the resolution chain selected with `--target`.
+///
+/// Fields:
+/// * `target`: The requested target.
+/// * `kind`: The role of the selected contract in the chain.
+/// * `address`: The address of the selected contract, unknown for local files.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct AnalyzedTarget {
+    pub target: Target,
+    pub kind: HopKind,
+    pub address: Option<String>,
+}
+
+/// A contract of the resolution chain, as recorded in the output.
+///
+/// Fields:
+/// * `kind`: The role of the contract in the chain, e.g. `input` for a proxy and `implementation` for its implementation.
+/// * `address`: The address of the contract, unknown for local files.
+/// * `selectors`: The selectors found in the code of the contract itself, sorted, e.g. the administrative functions of a proxy.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ResolvedHop {
+    pub kind: HopKind,
+    pub address: Option<String>,
+    #[serde(default)]
+    pub selectors: BTreeSet<String>,
+}
+
+/// The event signatures of the topics found in the bytecode.
+///
+/// Fields:
+/// * `signatures`: The most likely event signature of each resolved topic, or every match with `--all-matches`.
+/// * `unresolved`: The topics no provider knows, which may also be other 32-byte constants than topics.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Events {
+    pub signatures: Vec<EventSignature>,
+    pub unresolved: Vec<String>,
+}
+
+/// The matches of a hash given to `lookup`.
+///
+/// Fields:
+/// * `hash`: The hex-encoded hash, without the "0x" prefix.
+/// * `kind`: Whether the hash is a 4-byte selector or a full 32-byte hash, detected by its length.
+/// * `signatures`: The function and error signatures of the hash, full hashes only keep the ones hashing to the whole hash.
+/// * `events`: The event signatures of a full hash, which selectors don't have.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct HashLookup {
+    pub hash: String,
+    pub kind: HashKind,
+    pub signatures: Vec<Signature>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventSignature>,
+}
 
 /// Represents the output of Sigmund's operations, including both function selectors
 /// and optionally decoded signatures.
-#[derive(Debug, serde::Serialize)]
+///
+/// Outputs are read back for `--retry-failed`, the fields missing from older outputs are left empty.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct SigmundOut {
+    /// Metadata describing how the output was produced.
+    pub meta: Meta,
     /// Decoded signatures, present if the `decode` operation is executed.
+    /// Kept flat for backward compatibility, `matches` groups them by selector.
     pub signatures: Vec<Signature>,
-    /// Extracted function selectors from contract bytecode.
-    pub selectors: HashSet<String>,
+    /// The texts competing for each selector, in rank order, with the grouped variants after the match they were grouped under.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub matches: BTreeMap<String, Vec<String>>,
+    /// The sorted selectors no signature was found for, present with `--signatures`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unresolved: Vec<String>,
+    /// The signatures of the custom errors the contract reverts with, present with `--errors`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Signature>,
+    /// The event signatures of the topics found in the bytecode, present with `--events`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Events>,
+    /// Extracted function selectors from contract bytecode, sorted so outputs are stable across runs.
+    pub selectors: BTreeSet<String>,
+    /// How likely each extracted selector is to be a function selector, keyed by selector.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub confidence: BTreeMap<String, Confidence>,
+    /// Who can call each function with a dispatcher branch, inferred from the checks following its entry, keyed by selector.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub access: BTreeMap<String, AccessHint>,
+    /// The selectors the contract calls on the contracts it deploys, e.g. their initializer, which belong to their interface.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub child_init_selectors: BTreeSet<String>,
+    /// How the contract dispatches calls to its functions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dispatcher: Option<Dispatcher>,
+    /// The selectors a storage-mapped router accepts, present when the router was probed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub router: Option<RouterProbe>,
+    /// How the contract answered the call of each selector, present with `--verify-calls`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<CallVerification>,
+    /// The provider that resolved each selector, keyed by selector.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, String>,
+    /// Suspected signatures checked against the selectors, present when candidates are given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidates: Option<CandidateCheck>,
+    /// The functions of the verified ABI checked against the selectors, present with `--etherscan` or `--sourcify` for verified contracts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_abi: Option<CandidateCheck>,
+    /// Indicators that the code can be removed or replaced.
+    pub mutability: Mutability,
+    /// Creation code analysis, present when constructor arguments are decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creation: Option<CreationAnalysis>,
+    /// Conditions detected during the analysis, also printed to the console.
+    pub warnings: Vec<Warning>,
+    /// The selectors and topics whose lookup errored, left unresolved unless `--strict` is given.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<LookupFailure>,
+    /// Whether the run reached its `--deadline`, the lookups still in flight were cancelled and their selectors left unresolved.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
 }
 
 impl SigmundOut {
@@ -44,7 +425,77 @@ impl SigmundOut {
     /// Returns:
     /// A `SigmundOut` instance containing the processed data.
     pub fn new(selectors: HashSet<String>, signatures: Vec<Signature>) -> Self {
-        Self { selectors, signatures }
+        let mut out = Self {
+            meta: Meta { schema_version: SCHEMA_VERSION, ..Meta::default() },
+            selectors: selectors.into_iter().collect(),
+            ..Self::default()
+        };
+        out.add_signatures(signatures);
+        out
+    }
+
+    /// Adds signatures to the output, grouping their texts under `matches`.
+    fn add_signatures(&mut self, signatures: Vec<Signature>) {
+        for signature in &signatures {
+            self.matches.entry(signature.selector.clone()).or_default().extend(std::iter::once(&signature.text).chain(&signature.variants).cloned());
+        }
+        self.signatures.extend(signatures);
+        // Signatures are resolved concurrently, the sort is stable so the ranking within a selector is kept
+        self.signatures.sort_by(|a, b| a.selector.cmp(&b.selector));
+    }
+
+    /// The selectors to look up again with `--retry-failed`: the unresolved ones and the ones whose lookup failed.
+    pub fn retry_selectors(&self) -> BTreeSet<String> {
+        let failed = self.failures.iter().map(|failure| &failure.selector).filter(|selector| self.selectors.contains(*selector));
+        self.unresolved.iter().chain(failed).cloned().collect()
+    }
+
+    /// Merges the results of looking the `retry_selectors` up again, leaving the signatures resolved before untouched.
+    ///
+    /// Arguments:
+    /// * `retried`: The selectors that were looked up again.
+    /// * `signatures`: The signatures found for them, the ones of other selectors are ignored.
+    /// * `sources`: The provider that resolved each of them, keyed by selector.
+    /// * `failures`: The lookups that failed again.
+    pub fn merge_retried(&mut self, retried: &BTreeSet<String>, signatures: Vec<Signature>, sources: BTreeMap<String, String>, failures: Vec<LookupFailure>) {
+        self.add_signatures(signatures.into_iter().filter(|s| retried.contains(&s.selector) && !self.matches.contains_key(&s.selector)).collect());
+        self.sources.extend(sources.into_iter().filter(|(selector, _)| retried.contains(selector)));
+        self.unresolved.retain(|selector| !self.matches.contains_key(selector));
+        self.failures.retain(|failure| !retried.contains(&failure.selector));
+        self.failures.extend(failures);
+    }
+
+    /// Restricts the output to a single page of its sorted selectors.
+    ///
+    /// The signatures, sources and candidate matches are restricted to the selectors of the page,
+    /// and the position of the page is recorded in `meta.page`. A page beyond the last one is empty,
+    /// so scripts can iterate until `page > total_pages`.
+    ///
+    /// Arguments:
+    /// * `page`: The 1-based number of the page, `0` is treated as `1`.
+    /// * `page_size`: The maximum number of selectors on each page, `0` is treated as `1`.
+    pub fn paginate(&mut self, page: usize, page_size: usize) {
+        let (page, page_size) = (page.max(1), page_size.max(1));
+        let total_selectors = self.selectors.len();
+
+        self.selectors = std::mem::take(&mut self.selectors).into_iter().skip((page - 1).saturating_mul(page_size)).take(page_size).collect();
+        self.signatures.retain(|s| self.selectors.contains(&s.selector));
+        self.matches.retain(|selector, _| self.selectors.contains(selector));
+        self.unresolved.retain(|selector| self.selectors.contains(selector));
+        self.sources.retain(|selector, _| self.selectors.contains(selector));
+        self.confidence.retain(|selector, _| self.selectors.contains(selector));
+        self.access.retain(|selector, _| self.selectors.contains(selector));
+        for check in self.candidates.iter_mut().chain(self.verified_abi.iter_mut()) {
+            check.matched.retain(|s| self.selectors.contains(&s.selector));
+            check.unmatched_selectors.retain(|selector| self.selectors.contains(selector));
+        }
+
+        self.meta.page = Some(Page {
+            page,
+            page_size,
+            total_pages: total_selectors.div_ceil(page_size),
+            total_selectors,
+        });
     }
 }
 
@@ -56,7 +507,32 @@ impl SigmundOut {
 /// and/or decoding signatures).
 pub struct Sigmund {
     client: Client,
+    provider: Box<dyn SignatureProvider>,
     config: Config,
+    /// Warnings raised while setting up, e.g. when loading the local signature database.
+    warnings: Vec<Warning>,
+    /// The warning about the deprecated flags of the run, printed once and added to every output.
+    deprecation: Option<Warning>,
+    /// The signature database responses that didn't have the expected shape, reported with the other warnings.
+    parse_warnings: ParseWarnings,
+    /// The destinations the JSON output is delivered to.
+    sinks: Vec<Box<dyn OutputSink>>,
+    /// The source of verified ABIs, present with `--etherscan`.
+    etherscan: Option<Etherscan>,
+    /// The source of verified ABIs consulted after Etherscan, present with `--sourcify`.
+    sourcify: Option<Sourcify>,
+    /// The chains of the `chain:address` inputs, with their RPC.
+    chains: ChainRegistry,
+    /// The chain of `--chain`, whose RPC replaces `--rpc-url`.
+    chain: Option<Chain>,
+    /// The warning raised when the RPC of `--chain` is on another chain, see `check_chain`.
+    chain_mismatch: Mutex<Option<Warning>>,
+    /// The destination of the human-readable results, stdout unless replaced.
+    console: Mutex<Box<dyn Write + Send>>,
+    /// The code of the next contracts of a batch, fetched ahead in JSON-RPC batches and keyed by the identity of their input.
+    prefetched: Mutex<HashMap<String, Result<client::GetCodeResponse, ClientError>>>,
+    /// The clients of the RPCs of the `chain:address` inputs, keyed by chain id and created once per run.
+    chain_clients: Mutex<HashMap<u64, Arc<Client>>>,
 }
 
 impl Sigmund {
@@ -69,12 +545,225 @@ impl Sigmund {
     /// * `config`: The `Config` struct containing settings like RPC URL, input source, and operation flags.
     ///
     /// Returns:
-    /// A `Sigmund` instance ready to perform operations based on the provided configuration.
-    pub fn from_config(config: Config) -> Self {
-        Self {
-            client: Client::new(&config.rpc_url),
-            config,
+    /// A `Result` containing a `Sigmund` instance ready to perform operations based on the
+    /// provided configuration, or an error if an output destination can't be written, the client options
+    /// are invalid or the local database can't be loaded.
+    pub fn from_config(mut config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+        // Fail early instead of after the analysis when an output can't be written
+        config.validate()?;
+        // --chain stands for the RPC of the chain, so the RPC checks and requests go through it like a given --rpc-url
+        // Mainnet is the chain of --rpc-url unless the config file gives it an RPC of its own
+        let mut rpc_urls = config.rpc_urls.clone();
+        if !rpc_urls.keys().any(|chain| chain.trim() == "1" || chain.trim().eq_ignore_ascii_case("mainnet")) {
+            rpc_urls.insert("mainnet".to_string(), config.rpc_url[0].clone());
+        }
+        let chains = ChainRegistry::new(&rpc_urls)?;
+        let chain = config.chain.as_deref().map(|chain| chains.resolve(chain).cloned()).transpose()?;
+        if let Some(chain) = &chain {
+            config.rpc_url = vec![chain.rpc_url.clone()];
+        }
+        // A scan streams a JSON line per contract, unless another format is asked for
+        if matches!(config.command, Some(Command::Scan { .. })) && config.format == OutputFormat::Text {
+            config.ndjson = true;
+        }
+        if config.verbose > 0 {
+            for (option, origin) in config.origins.iter().filter(|(_, origin)| **origin != Origin::Default) {
+                eprintln!("config: {option} set from the {origin}");
+            }
+        }
+
+        let options = ClientOptions {
+            user_agent: config.user_agent.clone().unwrap_or_else(|| USER_AGENT.to_string()),
+            contact: config.contact.clone(),
+            // The standard `SSL_CERT_FILE` is honored when no bundle is given explicitly
+            ca_bundle: config.ca_bundle.clone().or_else(|| std::env::var_os("SSL_CERT_FILE").filter(|f| !f.is_empty()).map(PathBuf::from)),
+            timeout: config.timeout,
+            connect_timeout: config.connect_timeout,
+            headers: config::header_map(&config.header),
+            rpc_headers: config.rpc_headers(),
+            provider_headers: config::header_map(&config.provider_header),
+            max_response_size: config.max_response_size,
+            proxy: config.proxy.clone(),
+            // The deadline bounds the whole run, so it's counted from its start
+            deadline: config.deadline.map(|deadline| std::time::Instant::now() + deadline),
+            transport: config.transport(),
+        };
+
+        // The functions of a local ABI belong to the contract, so their matches are ranked first
+        let hints = match &config.abi {
+            Some(path) => {
+                let functions = creation::function_signatures(path).map_err(|e| format!("{}: {e}", path.display()))?;
+                RankHints::with_abi(functions.iter().map(String::as_str))
+            }
+            None => RankHints::default(),
+        };
+        let client = Client::with_options(&config.rpc_url[0], &options)?
+            .with_fallback_urls(&config.rpc_url[1..])
+            .with_max_matches(config.max_matches)
+            .with_concurrency(config.concurrency)
+            .with_rank_hints(hints)
+            .with_retries(config.retries, std::time::Duration::from_millis(config.retry_backoff_ms))
+            .with_rate_limit_wait(config.max_rate_limit_wait)
+            .with_cache_only(config.cache_only)
+            .with_block(config.block.clone().unwrap_or_default());
+        let kinds = match config.providers.is_empty() {
+            true => vec![config.provider],
+            false => config.providers.clone(),
+        };
+        // The first page holds the most likely match, the following ones are only needed for more matches
+        let max_pages = match config.top_matches() > 1 {
+            true => config.max_pages,
+            false => 1,
+        };
+        let remote = !config.cache_only && (config.provider_url.is_some() || kinds.iter().any(|kind| !matches!(kind, ProviderKind::Local | ProviderKind::None)));
+        let parse_warnings = ParseWarnings::new(config.verbose);
+        let mut providers: Vec<Box<dyn SignatureProvider>> = match config.offline {
+            true => vec![offline_provider()?],
+            false => kinds.iter().filter_map(|kind| kind.build(client.http(), max_pages, &parse_warnings)).collect(),
+        };
+
+        // A custom database is queried before the public ones, e.g. an internal one holding private signatures
+        if let Some(template) = &config.provider_url {
+            let path = JsonPath::parse(config.provider_jsonpath.as_deref().unwrap_or_default())?;
+            providers.insert(0, Box::new(UrlTemplate::new(client.http().clone(), template, path)));
+        }
+
+        // Signatures are looked up in the persistent cache first, unless it's disabled or not needed
+        let mut warnings = Vec::new();
+        let cache = match config.uses_cache() {
+            true => Some(SignatureCache::open(&config.cache_dir())?.with_max_matches(config.max_matches)),
+            false => None,
+        };
+        warnings.extend(cache.iter().flat_map(|cache| cache.warnings()).map(|warning| Warning::new(WarningKind::CacheReset, warning.as_str())));
+
+        // The imported database is queried before the remote ones, so that known selectors don't need any request
+        if let Some(path) = &config.sqlite_db {
+            providers.insert(0, sqlite_provider(path)?);
+        }
+
+        // Local entries take precedence over the remote databases
+        if let Some(path) = &config.local_db {
+            let db = LocalDb::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            warnings.extend(db.warnings().iter().map(|warning| Warning::new(WarningKind::InvalidLocalDbEntry, warning.as_str())));
+            providers.insert(0, Box::new(db));
+        }
+
+        // Only the local databases are consulted without requests, the selectors missing from the cache are left unresolved
+        if config.cache_only {
+            providers.retain(|provider| !provider.is_remote());
+        }
+
+        // Even a single provider goes through a chain with a circuit breaker, so a failing database isn't queried for every selector
+        let provider: Box<dyn SignatureProvider> = match config.max_consecutive_failures {
+            0 => ProviderChain::from_providers(providers),
+            max => Box::new(ProviderChain::new(providers).with_circuit_breaker(max)),
+        };
+        if config.events && !provider.resolves_events() {
+            eprintln!("--events: none of the signature databases knows events, the topics are left unresolved, add e.g. --providers openchain");
+        }
+        let client = match cache {
+            Some(cache) => client
+                .with_cache(cache)
+                .with_negative_ttl(config.negative_ttl)
+                .with_negative_refresh(config.refresh_unresolved)
+                // Without a signature database a selector being unknown says nothing about it
+                .with_negative_writes(remote)
+                .with_cache_refresh(config.refresh_cache),
+            None => client,
+        };
+
+        let mut sinks: Vec<Box<dyn OutputSink>> = Vec::new();
+        if let Some(output) = &config.output {
+            sinks.push(Box::new(FileSink::new(output)));
         }
+        if let Some(dir) = &config.output_dir {
+            sinks.push(Box::new(DirectorySink::new(dir)));
+        }
+        if config.ndjson {
+            sinks.push(Box::new(NdjsonSink));
+        }
+        match config.format {
+            OutputFormat::Text => {}
+            OutputFormat::Json => sinks.push(Box::new(JsonSink)),
+            OutputFormat::Evmole => sinks.push(Box::new(EvmoleSink::new(config.confidence_sweep))),
+            OutputFormat::JsonMin => sinks.push(Box::new(JsonMinSink)),
+        }
+        if let Some(path) = &config.export_unknown {
+            sinks.push(Box::new(ExportUnknownSink::new(path)));
+        }
+        if let Some(url) = &config.post_results {
+            sinks.push(Box::new(HttpSink::new(client.http().inner().clone(), url)));
+        }
+        if let Some(path) = &config.dead_letter {
+            sinks = sinks.into_iter().map(|sink| Box::new(DeadLetter::new(sink, path)) as Box<dyn OutputSink>).collect();
+        }
+
+        let etherscan = match config.etherscan {
+            true => Some(Etherscan::new(client.http().clone(), config.etherscan_key.clone())),
+            false => None,
+        };
+
+        let sourcify = match config.sourcify {
+            true => Some(Sourcify::new(client.http().clone())),
+            false => None,
+        };
+
+        let deprecation = config.deprecation_warning();
+        deprecation.iter().for_each(|warning| eprintln!("{warning}"));
+        let console: Mutex<Box<dyn Write + Send>> = Mutex::new(Box::new(std::io::stdout()));
+        let prefetched = Mutex::new(HashMap::new());
+        let chain_clients = Mutex::new(HashMap::new());
+        let chain_mismatch = Mutex::new(None);
+        Ok(Self { client, provider, config, warnings, deprecation, parse_warnings, sinks, etherscan, sourcify, chains, chain, chain_mismatch, console, prefetched, chain_clients })
+    }
+
+    /// Replaces the signature provider selected in the configuration.
+    ///
+    /// Allows library users to resolve signatures through their own `SignatureProvider`.
+    pub fn with_provider(mut self, provider: Box<dyn SignatureProvider>) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// Adds a destination for the JSON output, in addition to the ones selected in the configuration.
+    ///
+    /// Allows library users to deliver results through their own `OutputSink`.
+    pub fn with_sink(mut self, sink: Box<dyn OutputSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Replaces the destination of the human-readable results, which is stdout by default.
+    ///
+    /// Allows library users to capture the console output as it is streamed.
+    pub fn with_console(mut self, console: Box<dyn Write + Send>) -> Self {
+        self.console = Mutex::new(console);
+        self
+    }
+
+    /// Writes to the console and flushes it, so the results show up while the analysis continues.
+    fn print(&self, text: impl std::fmt::Display) {
+        let mut console = self.console.lock().unwrap();
+        if let Err(e) = write!(console, "{text}").and_then(|_| console.flush()) {
+            eprintln!("unable to write to the console: {e}");
+        }
+    }
+
+    /// Writes a signature as a line to the console, including its metadata when verbose.
+    fn print_signature(&self, signature: &Signature) {
+        match self.config.verbose {
+            0 => self.print(format_args!("{signature}\n")),
+            _ => self.print(format_args!("{signature:#}\n")),
+        }
+    }
+
+    /// Summarizes the failed lookups in a line, with the reason of the first one.
+    fn failures_summary(&self, failures: &[LookupFailure], first: &LookupFailure) -> String {
+        let hint = match self.config.strict {
+            true => "",
+            false => " (use --strict to make this fatal)",
+        };
+        format!("{} lookups failed{hint}, e.g. {}: {}", failures.len(), first.selector, first.reason)
     }
 
     /// Asynchronously retrieves bytecode from the specified source.
@@ -83,64 +772,1376 @@ impl Sigmund {
     /// an Ethereum contract address based on the configuration. It then attempts to
     /// parse and return the bytecode in a structured format.
     ///
+    /// Arguments:
+    /// * `input`: The contract to retrieve the bytecode of.
+    /// * `rpc`: The client of the RPC the addresses are fetched from, the one of the input chain for `chain:address` inputs.
+    ///
     /// Returns:
     /// A `Result` containing `Bytecode` on success, or an error if the retrieval or parsing fails.
-    async fn get_bytecode(&self) -> Result<Bytecode, Box<dyn std::error::Error>> {
-        match &self.config.file {
-            // Try generating bytecode from the file
-            Some(file) => Bytecode::try_from(file),
-            None => {
-                // #![INFO]: Address will always be set since it's required in the CLI
-                let address = self.config.address.to_owned().unwrap();
-                // Try creating an address from the string, after verifying it's a valid EVM address
-                let address = Address::try_from(address)?;
-                // Get the bytecode from the RPC url using the`eth_getCode` method
-                let code = self.client.get_code(&address).await?;
-                // Try generating bytecode from the result
-                Bytecode::try_from(code.result)
+    async fn get_bytecode(&self, input: &Input, rpc: &Client) -> Result<Bytecode, Box<dyn std::error::Error>> {
+        match input {
+            Input::Address(address) | Input::OnChain { address, .. } => {
+                // The code may have been fetched along with the next contracts of a batch
+                let prefetched = self.prefetched.lock().unwrap().remove(&input.identity());
+                let code = match prefetched {
+                    Some(code) => code,
+                    None => {
+                        // Try creating an address from the string, after verifying it's a valid EVM address
+                        let address = Address::try_from(address.to_string())?;
+                        // Get the bytecode from the RPC url using the`eth_getCode` method
+                        rpc.get_code(&address).await
+                    }
+                };
+                self.deployed_code(address, code)
             }
+            // Try generating bytecode from the file
+            Input::File(file) => self.read_file(file),
         }
     }
 
-    /// Asynchronously retrieves bytecode and processes it to extract function selectors and/or decode signatures.
+    /// Decodes the code of an address, refusing the empty code of an EOA or of a contract not deployed yet.
     ///
-    /// Depending on the configuration settings, this method fetches bytecode from either a local file
-    /// or an Ethereum contract address. It then extracts 4-byte selectors and optionally decodes
-    /// the corresponding signatures.
+    /// Nodes tell an address without code by `0x`, a lone `0x00` or no result at all, which would otherwise be
+    /// analyzed as a contract without any selector.
     ///
-    /// The results are either printed to the console or saved to a file specified in the configuration.
+    /// Returns:
+    /// A `Result` containing the bytecode, or a `ClientError::NoCode` if the address has no code.
+    fn deployed_code(&self, address: &str, code: Result<client::GetCodeResponse, ClientError>) -> Result<Bytecode, Box<dyn std::error::Error>> {
+        let no_code = || ClientError::NoCode { address: address.to_string(), block: self.config.block.clone().unwrap_or_default() };
+        let code = match code {
+            Err(ClientError::MissingResult(_)) => return Err(no_code().into()),
+            code => code?,
+        };
+        let bytecode = Bytecode::try_from(code.result)?;
+        match bytecode.inner.iter().all(|byte| *byte == 0) {
+            true => Err(no_code().into()),
+            false => Ok(bytecode),
+        }
+    }
+
+    /// Builds the resolution chain of the input contract.
+    ///
+    /// The chain starts with the input contract, further hops are appended
+    /// as proxies are resolved to the contracts serving their calls.
+    ///
+    /// The EIP-7702 delegation of an EOA is followed to its delegate, and an EIP-1167 minimal proxy, an EIP-1967 proxy
+    /// or a beacon proxy to its implementation, unless `--no-follow` is given. Files are analyzed as they are, without any request.
+    ///
+    /// Arguments:
+    /// * `address`: The address of the input, `None` for files.
+    /// * `bytecode`: The code of the input.
+    /// * `rpc`: The client of the RPC the code of the followed contracts is fetched from.
     ///
     /// Returns:
-    /// A `Result` indicating the success or failure of the operations.
-    pub async fn execute(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Get the bytecode from the specified source
-        let bytecode = self.get_bytecode().await.map_err(|e| e.to_string())?;
-        // Extract function selectors from the bytecode
-        let selectors = bytecode.find_function_selectors(self.config.deep);
+    /// A `Result` containing the chain and the warnings about the proxies that couldn't be resolved, or an error if
+    /// the delegate of an EOA couldn't be fetched.
+    async fn resolve_chain(&self, address: Option<&str>, bytecode: Bytecode, rpc: &Client) -> Result<(ResolutionChain, Vec<Warning>), Box<dyn std::error::Error>> {
+        // The address is normalized once here, so every later use of it agrees regardless of the input casing
+        let address = address.map(|address| Address::try_from(address.to_string())).transpose()?;
+        let mut chain = ResolutionChain::new(address.as_ref().map(|address| address.as_str().to_string()), bytecode);
+        let mut warnings = Vec::new();
+        let Some(address) = address.filter(|_| !self.config.no_follow) else {
+            return Ok((chain, warnings));
+        };
 
-        let signatures = if self.config.signatures {
-            // Collect all signatures that exist in the database
-            let signatures = self.client.get_signatures(&selectors, self.config.all_matches).await;
-            let signatures = signatures.map_err(|e| e.to_string())?;
-            // Print the formatted signatures to the console
-            signatures.iter().for_each(|s| println!("{}", s));
+        if let Some(delegate) = chain.hops[0].bytecode.delegation_target() {
+            eprintln!("EIP-7702 delegation to {delegate}");
+            let code = rpc.get_code(&Address::try_from(delegate.clone())?).await;
+            let bytecode = self.deployed_code(&delegate, code).map_err(|e| format!("delegate {delegate}: {e}"))?;
+            chain.hops.push(Hop { kind: HopKind::Delegate, address: Some(delegate), bytecode });
+        }
+
+        // A minimal proxy forwards every call to the target fixed in its code
+        if let Some(target) = chain.hops.last().and_then(|hop| hop.bytecode.minimal_proxy_target()) {
+            eprintln!("minimal proxy -> {target}");
+            match self.followed_code("implementation", &target, rpc).await {
+                Ok(bytecode) => chain.hops.push(Hop { kind: HopKind::Implementation, address: Some(target), bytecode }),
+                Err(reason) => warnings.push(Warning::new(WarningKind::ProxyUnresolved, format!("{reason}, the minimal proxy itself is analyzed"))),
+            }
+        }
 
-            Some(signatures)
+        // The proxy code runs on the storage of the input, be it the proxy itself or an EOA delegating to it
+        let code = &chain.hops[chain.hops.len() - 1].bytecode;
+        let (eip1967, beacon_slot) = (code.pushes(IMPLEMENTATION_SLOT), code.pushes(BEACON_SLOT));
+        // A beacon proxy loads its beacon from the beacon slot, so code that only calls `implementation()`, unlike a beacon
+        // answering it itself, has that slot checked too. A beacon held in an immutable isn't read from the code.
+        let calls_beacon = code.pushes(proxy::IMPLEMENTATION_SELECTOR) && !code.find_function_selectors(false).contains(&proxy::IMPLEMENTATION_SELECTOR[2..]);
+        // Other contracts push the implementation slot too, e.g. a UUPS implementation, whose own slots are empty
+        let proxy_like = eip1967 && code.looks_like_proxy();
+        let followed = if eip1967 {
+            match self.follow_eip1967(&mut chain, address.as_str(), proxy_like, rpc).await {
+                Ok(false) if proxy_like => Err(format!("the EIP-1967 implementation and beacon slots of {address} are empty, the proxy isn't initialized")),
+                followed => followed.map(drop),
+            }
+        } else if beacon_slot || calls_beacon {
+            self.follow_beacon(&mut chain, address.as_str(), beacon_slot, rpc).await.map(drop)
         } else {
-            // Otherwise print the selectors
-            println!("{selectors:?}");
-            None
+            Ok(())
         };
+        if let Err(reason) = followed {
+            warnings.push(Warning::new(WarningKind::ProxyUnresolved, format!("{reason}, the proxy itself is analyzed")));
+        }
+        Ok((chain, warnings))
+    }
 
-        // Use a Default when no signatures exist to provide a more safe
-        // and consistent output format when accessed by users
-        let out = SigmundOut::new(selectors, signatures.unwrap_or_default());
+    /// Follows an EIP-1967 proxy to the implementation held in its implementation slot, and reads its admin slot.
+    ///
+    /// A proxy that delegates to a beacon, e.g. one sharing its code with UUPS proxies, leaves the implementation slot
+    /// empty and is followed through the beacon slot instead.
+    ///
+    /// Arguments:
+    /// * `chain`: The resolution chain, whose last hop is the proxy.
+    /// * `storage`: The address whose storage the proxy runs on.
+    /// * `proxy_like`: Whether the code looks like a proxy, see `Bytecode::looks_like_proxy`. Otherwise an empty
+    ///   implementation slot means it isn't a proxy, e.g. a UUPS implementation, and the beacon slot isn't read.
+    /// * `rpc`: The client of the RPC the slots and the code of the implementation are read from.
+    ///
+    /// Returns:
+    /// A `Result` containing whether the implementation was added to the chain, `false` if both slots are empty,
+    /// e.g. for a proxy that wasn't initialized yet, or the reason it couldn't be.
+    async fn follow_eip1967(&self, chain: &mut ResolutionChain, storage: &str, proxy_like: bool, rpc: &Client) -> Result<bool, String> {
+        let slot = rpc.get_storage_at(storage, IMPLEMENTATION_SLOT).await.map_err(|e| format!("the EIP-1967 implementation slot of {storage} couldn't be read ({e})"))?;
+        let Some(implementation) = proxy::word_address(&slot) else {
+            return match proxy_like {
+                true => self.follow_beacon(chain, storage, false, rpc).await,
+                false => Ok(false),
+            };
+        };
+        eprintln!("EIP-1967 proxy, implementation {implementation}");
+
+        let bytecode = self.followed_code("implementation", &implementation, rpc).await?;
+        chain.hops.push(Hop { kind: HopKind::Implementation, address: Some(implementation), bytecode });
+        // The admin is informative only, a proxy without one is common
+        chain.admin = rpc.get_storage_at(storage, ADMIN_SLOT).await.ok().and_then(|slot| proxy::word_address(&slot));
+        Ok(true)
+    }
+
+    /// Follows a beacon proxy to the implementation its beacon returns from `implementation()`.
+    ///
+    /// Arguments:
+    /// * `chain`: The resolution chain, whose last hop is the proxy.
+    /// * `storage`: The address whose storage the proxy runs on.
+    /// * `required`: Whether the proxy loads the beacon slot, so an empty one means it isn't initialized. Otherwise the
+    ///   code only calls `implementation()` and an empty slot means it isn't a beacon proxy after all.
+    /// * `rpc`: The client of the RPC the slot, the call and the code of the contracts are read from.
+    ///
+    /// Returns:
+    /// A `Result` containing whether the chain was resolved, `false` for an empty slot that isn't required,
+    /// or the reason it couldn't be, e.g. a reverted call.
+    async fn follow_beacon(&self, chain: &mut ResolutionChain, storage: &str, required: bool, rpc: &Client) -> Result<bool, String> {
+        let slot = rpc.get_storage_at(storage, BEACON_SLOT).await.map_err(|e| format!("the EIP-1967 beacon slot of {storage} couldn't be read ({e})"))?;
+        let beacon = match proxy::word_address(&slot) {
+            Some(beacon) => beacon,
+            None if required => return Err(format!("the EIP-1967 beacon slot of {storage} is empty, the proxy isn't initialized")),
+            None => return Ok(false),
+        };
+        let bytecode = self.followed_code("beacon", &beacon, rpc).await?;
+        chain.hops.push(Hop { kind: HopKind::Beacon, address: Some(beacon.clone()), bytecode });
+
+        let implementation = match rpc.call(&beacon, proxy::IMPLEMENTATION_SELECTOR).await {
+            Ok(CallOutcome::Returned(word)) => proxy::word_address(&word).ok_or_else(|| format!("the beacon {beacon} returned no implementation"))?,
+            Ok(CallOutcome::Reverted { message, .. }) => return Err(format!("implementation() of the beacon {beacon} reverted ({message})")),
+            Err(e) => return Err(format!("implementation() of the beacon {beacon} couldn't be called ({e})")),
+        };
+        eprintln!("beacon proxy, beacon {beacon}, implementation {implementation}");
+
+        let bytecode = self.followed_code("implementation", &implementation, rpc).await?;
+        chain.hops.push(Hop { kind: HopKind::Implementation, address: Some(implementation), bytecode });
+        Ok(true)
+    }
+
+    /// Fetches the code of a contract a proxy forwards its calls to, or of its beacon.
+    ///
+    /// Arguments:
+    /// * `role`: The role of the contract in the errors, e.g. "implementation".
+    ///
+    /// Returns:
+    /// A `Result` containing the code, or the reason it couldn't be fetched, e.g. a target without code.
+    async fn followed_code(&self, role: &str, address: &str, rpc: &Client) -> Result<Bytecode, String> {
+        let code = match Address::try_from(address.to_string()) {
+            Ok(target) => rpc.get_code(&target).await,
+            Err(e) => return Err(format!("the {role} {address} is invalid ({e})")),
+        };
+        self.deployed_code(address, code).map_err(|e| format!("the code of the {role} {address} couldn't be fetched ({e})"))
+    }
+
+    /// Asynchronously retrieves bytecode from an input that is either an address or a file path.
+    ///
+    /// Inputs that are valid EVM addresses are fetched through the RPC, everything else
+    /// is treated as the path to a file containing the bytecode.
+    async fn get_bytecode_from(&self, input: &str) -> Result<Bytecode, Box<dyn std::error::Error>> {
+        match Address::try_from(input.to_string()) {
+            Ok(address) => self.deployed_code(input, self.client.get_code(&address).await),
+            Err(_) => self.read_file(&PathBuf::from(input)),
+        }
+    }
+
+    /// Reads bytecode from a file in the configured input format, or the detected one.
+    ///
+    /// The format the file was read as is reported when verbose.
+    fn read_file(&self, path: &std::path::Path) -> Result<Bytecode, Box<dyn std::error::Error>> {
+        let (bytecode, format) = input::read(path, self.config.input_format)?;
+        if self.config.verbose > 0 {
+            eprintln!("{}: read as {format}", path.display());
+        }
+        Ok(bytecode)
+    }
+
+    /// Compares two contracts and reports their similarity.
+    ///
+    /// The result is printed to the console and written to the output file if specified.
+    async fn similarity(&self, a: &str, b: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let a = self.get_bytecode_from(a).await.map_err(|e| format!("{a}: {e}"))?;
+        let b = self.get_bytecode_from(b).await.map_err(|e| format!("{b}: {e}"))?;
+
+        let similarity = Similarity::between(&a, &b);
+        println!("{similarity}");
 
-        // Write the output to a file if specified
         if let Some(output) = &self.config.output {
-            std::fs::write(output, serde_json::to_string_pretty(&out)?)?;
+            std::fs::write(output, serde_json::to_string_pretty(&similarity)?)?;
         };
 
         Ok(())
     }
+
+    /// Checks every component a run depends on, printing whether each works with its latency.
+    ///
+    /// The RPC and the RPCs of the config file are asked for their chain id, each signature database resolves
+    /// `PROBE_SELECTOR` directly, bypassing the cache, and a file is created in the cache directory.
+    ///
+    /// Arguments:
+    /// * `json`: Whether to print the checks as JSON instead of one line each.
+    ///
+    /// Returns:
+    /// The checks, in the order they were printed.
+    pub async fn doctor(&self, json: bool) -> Vec<Check> {
+        let mut checks = Vec::new();
+        // Each fallback RPC is checked on its own, so an unhealthy primary doesn't go unnoticed
+        let rpcs: Vec<(&String, Client)> = self.config.rpc_url.iter().chain(self.config.rpc_urls.values()).map(|url| (url, self.client.for_rpc(url))).collect();
+        for (url, client) in rpcs {
+            let start = std::time::Instant::now();
+            let outcome = client.chain_id().await.map(|id| format!("chain id {id}")).map_err(|e| e.to_string());
+            checks.push(Check::timed(format!("rpc {}", transport::redact_rpc(url)), start, outcome));
+        }
+
+        checks.extend(self.provider.check(PROBE_SELECTOR).await);
+
+        // Without the cache, sigmund doesn't write to its directory, unless recording the history
+        if !self.config.no_cache || self.config.history {
+            let start = std::time::Instant::now();
+            checks.push(Check::timed("cache directory", start, doctor::check_writable(&self.config.cache_dir())));
+        }
+
+        match json {
+            true => self.print(format_args!("{}\n", serde_json::to_string_pretty(&checks).unwrap_or_default())),
+            false => checks.iter().for_each(|check| self.print(format_args!("{check}\n"))),
+        }
+        checks
+    }
+
+    /// Runs the built-in known-answer checks of `selftest::run`, without any network, printing whether each passed.
+    ///
+    /// Arguments:
+    /// * `json`: Whether to print the checks as JSON instead of one line each.
+    ///
+    /// Returns:
+    /// The checks, in the order they were printed.
+    pub fn selftest(&self, json: bool) -> Vec<Check> {
+        let checks = selftest::run();
+        match json {
+            true => self.print(format_args!("{}\n", serde_json::to_string_pretty(&checks).unwrap_or_default())),
+            false => checks.iter().for_each(|check| self.print(format_args!("{check}\n"))),
+        }
+        checks
+    }
+
+    /// Looks the given selectors and full hashes up in the configured providers, printing their matches.
+    ///
+    /// Selectors are resolved like the extracted ones, through the cache. Full hashes are looked up both as
+    /// event topics and as function hashes, keeping only the functions whose text hashes to the whole hash,
+    /// so that their matches can't be collisions of the selector.
+    ///
+    /// Arguments:
+    /// * `hashes`: The hex-encoded 4-byte selectors or 32-byte hashes, with or without the "0x" prefix.
+    ///
+    /// Returns:
+    /// A `Result` containing the matches of each hash, in the given order.
+    pub async fn lookup(&self, hashes: &[String]) -> Result<Vec<HashLookup>, Box<dyn std::error::Error>> {
+        let mut lookups = Vec::new();
+        for hash in hashes {
+            let (hash, kind) = HashKind::detect(hash).ok_or_else(|| format!("invalid hash `{hash}`, expected 4 or 32 hex-encoded bytes"))?;
+            let lookup = match kind {
+                HashKind::Selector => {
+                    let selectors = HashSet::from([hash.clone()]);
+                    let signatures = self.client.get_signatures(self.provider.as_ref(), &selectors, self.config.top_matches() > 1).await?;
+                    let signatures = signature::top_matches(signatures, self.config.top_matches());
+                    HashLookup { hash, kind, signatures, events: Vec::new() }
+                }
+                HashKind::Full => {
+                    // A failed lookup is recorded like the selector ones, so it doesn't fail the lookup of the other hashes
+                    let failed = |e: &ProviderError| self.client.record_failed(std::slice::from_ref(&hash), e);
+                    let signatures = self.provider.resolve_hash(&hash).await.inspect_err(failed).unwrap_or_default();
+                    let signatures = signatures.into_iter().map(|s| s.with_source(self.provider.name())).collect();
+                    let events = self.provider.resolve_event(&hash).await.inspect_err(failed).unwrap_or_default();
+                    HashLookup { hash, kind, signatures, events }
+                }
+            };
+
+            if lookup.signatures.is_empty() && lookup.events.is_empty() && !self.client.failed_lookups().contains_key(&lookup.hash) {
+                self.print(format_args!("no match found for 0x{}\n", lookup.hash));
+            }
+            lookup.signatures.iter().for_each(|s| self.print_signature(s));
+            lookup.events.iter().for_each(|event| self.print(format_args!("{event}\n")));
+            lookups.push(lookup);
+        }
+
+        let failures = self.client.take_failures();
+        if let Some(failure) = failures.first() {
+            eprintln!("{}", self.failures_summary(&failures, failure));
+        }
+        if let Some(output) = &self.config.output {
+            std::fs::write(output, serde_json::to_string_pretty(&lookups)?)?;
+        }
+        if let Some(failure) = failures.first().filter(|_| self.config.strict) {
+            return Err(format!("{} lookups failed with --strict, e.g. {}: {}", failures.len(), failure.selector, failure.reason).into());
+        }
+
+        Ok(lookups)
+    }
+
+    /// Executes a cache maintenance command.
+    ///
+    /// Warming and clearing operate on the cache file in the configured cache directory,
+    /// clearing asks for a confirmation unless it was given on the command line.
+    async fn cache(&self, command: &CacheCommand) -> Result<(), Box<dyn std::error::Error>> {
+        let mut cache = SignatureCache::open(&self.config.cache_dir())?.with_max_matches(self.config.max_matches);
+
+        match command {
+            CacheCommand::Warm { top, listing_url, delay_ms } => {
+                let delay = std::time::Duration::from_millis(*delay_ms);
+                let collected = cache.warm(&self.client, listing_url, &cache::listing_source(listing_url), *top, delay).await?;
+                println!("Cached {collected} signatures in {}", cache.path().display());
+            }
+            CacheCommand::Stats => println!("{}", cache.stats()),
+            CacheCommand::Invalidate { selectors } => {
+                let selectors: Vec<String> = selectors.iter().map(|selector| selector.trim_start_matches("0x").to_lowercase()).collect();
+                let invalidated = selectors.iter().filter(|selector| cache.invalidate(selector)).count();
+                cache.save()?;
+                println!("Invalidated {invalidated} of {} selectors in {}", selectors.len(), cache.path().display());
+            }
+            CacheCommand::Clear { yes } => {
+                if *yes || confirm(&format!("Clear {} cached entries in {}?", cache.entries.len() + cache.negative.len(), cache.path().display()))? {
+                    cache.clear()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes a run history command.
+    ///
+    /// The history is read from the configured cache directory whether or not `--history` is given,
+    /// clearing asks for a confirmation unless it was given on the command line.
+    async fn history(&self, command: &HistoryCommand) -> Result<(), Box<dyn std::error::Error>> {
+        let mut history = History::open(&self.config.cache_dir())?;
+        if history.entries().is_empty() && !matches!(command, HistoryCommand::Clear { .. }) {
+            eprintln!("no runs recorded in {}, pass --history to record them", history.path().display());
+        }
+
+        match command {
+            HistoryCommand::List { limit } => {
+                let entries = history.entries();
+                entries[entries.len().saturating_sub(*limit)..].iter().for_each(|entry| println!("{entry}"));
+            }
+            HistoryCommand::Search { query } => history.search(query).iter().for_each(|entry| println!("{entry}")),
+            HistoryCommand::Show { id } => println!("{:#}", history.get(*id)?),
+            HistoryCommand::Rerun { id } => {
+                let entry = history.get(*id)?;
+                let mut config = entry.config()?;
+                // The secrets left out of the history are given again on the command line of the rerun
+                let given = |id: &str| self.config.origins.get(id).is_some_and(|origin| *origin == Origin::CommandLine);
+                if given("rpc_url") {
+                    config.rpc_url = self.config.rpc_url.clone();
+                }
+                if given("rpc_auth") {
+                    config.rpc_auth = self.config.rpc_auth.clone();
+                }
+                if given("proxy") {
+                    config.proxy = self.config.proxy.clone();
+                }
+                if given("header") {
+                    config.header = self.config.header.clone();
+                }
+                if given("rpc_header") {
+                    config.rpc_header = self.config.rpc_header.clone();
+                }
+                if given("provider_header") {
+                    config.provider_header = self.config.provider_header.clone();
+                }
+                eprintln!("rerunning #{id}: {}", entry.args.join(" "));
+                return Box::pin(Sigmund::from_config(config)?.execute()).await;
+            }
+            HistoryCommand::Clear { yes } => {
+                if *yes || confirm(&format!("Clear {} recorded runs in {}?", history.entries().len(), history.path().display()))? {
+                    history.clear()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds the run history entry of an analysis, whose coverage is the share of the selectors resolved to a signature.
+    fn history_entry(&self, input: &Input, out: &SigmundOut) -> HistoryEntry {
+        let mut entry = HistoryEntry::new(&self.config.args, input.to_string(), out.selectors.len());
+        entry.chain_id = input.chain().or(input.address().and(self.chain.as_ref())).map(|chain| chain.id);
+        entry.code_hash = out.meta.code_hash.clone();
+        if self.config.signatures && !out.selectors.is_empty() {
+            entry.coverage = Some(1.0 - out.unresolved.len() as f64 / out.selectors.len() as f64);
+        }
+        entry
+    }
+
+    /// Records an analysis in the run history, with the chain of the analyzed address if it was given or can be detected.
+    ///
+    /// Failing to record the run doesn't fail the analysis, the error is only reported.
+    async fn record_run(&self, mut entry: HistoryEntry, address: Option<&str>, output: Option<PathBuf>) {
+        entry.chain_id = match (entry.chain_id.or(self.config.chain_id), address) {
+            (Some(chain_id), _) => Some(chain_id),
+            (None, Some(_)) => self.client.chain_id().await.ok(),
+            (None, None) => None,
+        };
+        entry.output = output;
+
+        let recorded = History::open(&self.config.cache_dir()).and_then(|mut history| history.record(entry));
+        if let Err(e) = recorded {
+            eprintln!("unable to record the run in the history: {e}");
+        }
+    }
+
+    /// Guesses the signatures of the selectors without a resolved signature, if a wordlist or the built-in names are configured.
+    fn bruteforce(&self, selectors: &HashSet<String>, resolved: &[Signature]) -> Result<Vec<Signature>, Box<dyn std::error::Error>> {
+        if self.config.bruteforce.is_none() && !self.config.bruteforce_unresolved {
+            return Ok(Vec::new());
+        }
+
+        let timeout = std::time::Duration::from_secs(self.config.bruteforce_timeout);
+        let bruteforce = bruteforcer(self.config.bruteforce.as_deref(), self.config.bruteforce_unresolved, self.config.max_params, self.config.bruteforce_limit, timeout)?;
+
+        let resolved: HashSet<&str> = resolved.iter().map(|s| s.selector.as_str()).collect();
+        let unresolved: HashSet<String> = selectors.iter().filter(|s| !resolved.contains(s.as_str())).cloned().collect();
+        if unresolved.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let report = bruteforce.search(&unresolved, 1);
+        eprintln!("{report}");
+        Ok(report.guessed)
+    }
+
+    /// Guesses the signatures of a single selector for the `bruteforce` command, printing the matches and the hash rate.
+    fn bruteforce_selector(&self, selector: &str, matches: usize, wordlist: Option<&Path>, max_params: usize, limit: usize, timeout: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let selector = selector.trim_start_matches("0x").to_lowercase();
+        if selector.len() != 8 || !selector.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("invalid selector `0x{selector}`, expected 4 hex-encoded bytes").into());
+        }
+
+        let bruteforce = bruteforcer(wordlist, true, max_params, limit, std::time::Duration::from_secs(timeout))?;
+        let report = bruteforce.search(&HashSet::from([selector.clone()]), matches.max(1));
+        for signature in &report.guessed {
+            self.print_signature(signature);
+        }
+        if report.guessed.is_empty() {
+            self.print(format_args!("no match found for 0x{selector}\n"));
+        } else {
+            eprintln!("guessed signatures match the selector but may not be the original, e.g. a colliding name");
+        }
+        eprintln!("{report}");
+        Ok(())
+    }
+
+    /// Resolves the selectors of the custom errors of the bytecode, through the same lookups as the function selectors.
+    ///
+    /// The matches of selectors only found in revert paths are ranked with `rank_errors`, so that a function
+    /// colliding with the error doesn't come first. Selectors also found in the dispatcher keep the provider ranking.
+    ///
+    /// Arguments:
+    /// * `bytecode`: The analyzed bytecode.
+    /// * `functions`: The function selectors extracted from the bytecode.
+    ///
+    /// Returns:
+    /// A `Result` containing the error signatures sorted by selector, or an error if a lookup failed.
+    async fn resolve_errors(&self, bytecode: &Bytecode, functions: &HashSet<String>) -> Result<Vec<Signature>, Box<dyn std::error::Error>> {
+        let selectors: HashSet<String> = bytecode.find_error_selectors().into_iter().collect();
+        let resolved = self.client.get_signatures(self.provider.as_ref(), &selectors, true).await.map_err(|e| e.to_string())?;
+
+        // All matches are requested to rank them, the matches of a selector are contiguous
+        let mut matches: BTreeMap<String, Vec<Signature>> = BTreeMap::new();
+        for signature in resolved {
+            matches.entry(signature.selector.clone()).or_default().push(Signature { kind: SignatureKind::Error, ..signature });
+        }
+
+        let mut errors = Vec::new();
+        for (selector, mut matches) in matches {
+            if !functions.contains(&selector) {
+                signature::rank_errors(&mut matches);
+            }
+            errors.extend(matches.into_iter().take(self.config.top_matches()));
+        }
+
+        Ok(errors)
+    }
+
+    /// Resolves the candidate event topics of the bytecode to event signatures.
+    ///
+    /// Returns:
+    /// A `Result` containing the resolved signatures sorted by topic and the unresolved topics,
+    /// or an error if a lookup failed.
+    async fn resolve_events(&self, bytecode: &Bytecode) -> Result<Events, Box<dyn std::error::Error>> {
+        let topics = bytecode.find_event_topics();
+        let mut resolved = self.client.get_event_signatures(self.provider.as_ref(), &topics).await.map_err(|e| e.to_string())?;
+
+        let mut events = Events::default();
+        // The topics cancelled at the deadline are missing from the lookups, and unresolved like the unknown ones
+        for topic in topics {
+            let matches = resolved.remove(&topic).unwrap_or_default();
+            match matches.is_empty() {
+                true => events.unresolved.push(topic),
+                // Providers return their matches ranked, so the first ones are the most likely
+                false => events.signatures.extend(matches.into_iter().take(self.config.top_matches())),
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Probes a storage-mapped router for the selectors of the bundled table and the `--known` file.
+    async fn probe_router(&self, rpc: &Client, address: &str) -> Result<RouterProbe, Box<dyn std::error::Error>> {
+        let mut selectors = common_selectors();
+        if let Some(path) = &self.config.known {
+            selectors.extend(router::read_known(path).map_err(|e| format!("{}: {e}", path.display()))?);
+        }
+        selectors.sort();
+        selectors.dedup();
+
+        Ok(RouterProbe::run(rpc, address, &selectors).await?)
+    }
+
+    /// Checks the selectors against the verified ABI of a contract, from Etherscan or else Sourcify if enabled.
+    ///
+    /// The chain id Sourcify needs is the one of the input chain, or else taken from `--chain-id` or detected through the RPC.
+    /// Etherscan only knows mainnet contracts, so it isn't consulted for the other chains.
+    ///
+    /// Returns:
+    /// A `Result` containing the name of the source and the `CandidateCheck` of the ABI functions,
+    /// or `None` if neither source is enabled or knows the contract.
+    async fn verified_abi(&self, rpc: &Client, chain: Option<&Chain>, address: &str, selectors: &HashSet<String>) -> Result<Option<(&'static str, CandidateCheck)>, Box<dyn std::error::Error>> {
+        if let Some(etherscan) = self.etherscan.as_ref().filter(|_| chain.is_none_or(|chain| chain.id == 1)) {
+            match etherscan.functions(address).await.map_err(|e| format!("etherscan: {e}"))? {
+                Some(functions) => return Ok(Some(("etherscan", CandidateCheck::new(&functions, selectors)))),
+                None => {
+                    let address = Address::try_from(address.to_string()).map_or_else(|_| address.to_string(), |address| address.to_string());
+                    eprintln!("{address} isn't verified on Etherscan");
+                }
+            }
+        }
+
+        if let Some(sourcify) = &self.sourcify {
+            let chain_id = match chain.map(|chain| chain.id).or(self.config.chain_id) {
+                Some(chain_id) => chain_id,
+                None => rpc.chain_id().await?,
+            };
+            // Contracts Sourcify doesn't know fall through to the databases silently
+            if let Some(functions) = sourcify.functions(chain_id, address).await.map_err(|e| format!("sourcify: {e}"))? {
+                return Ok(Some(("sourcify", CandidateCheck::new(&functions, selectors))));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Decodes the constructor arguments of the creation code, if requested.
+    ///
+    /// The arguments are either provided explicitly through the configuration or split from
+    /// the tail of the creation code. When an ABI is provided they are decoded precisely,
+    /// otherwise a heuristic decoding is used.
+    ///
+    /// Returns:
+    /// A `Result` containing the `CreationAnalysis`, or `None` if no creation analysis was requested.
+    fn analyze_creation(&self, bytecode: &Bytecode) -> Result<Option<CreationAnalysis>, Box<dyn std::error::Error>> {
+        let explicit = match &self.config.constructor_args {
+            Some(args) => Some(hex::decode(args.trim_start_matches("0x"))?),
+            None if self.config.creation => None,
+            None => return Ok(None),
+        };
+
+        let args = match &explicit {
+            Some(args) => Some(args.as_slice()),
+            None => creation::split_constructor_args(bytecode),
+        };
+
+        let inputs = match &self.config.abi {
+            Some(path) => Some(creation::constructor_inputs(path)?),
+            None => None,
+        };
+
+        let constructor_args = match (args, inputs) {
+            (Some(args), Some(inputs)) => Some(ConstructorArgs::decode(args, &inputs)?),
+            (Some(args), None) => Some(ConstructorArgs::decode_heuristic(args)),
+            (None, _) => None,
+        };
+
+        Ok(Some(CreationAnalysis { constructor_args }))
+    }
+
+    /// Whether the run reached its `--deadline`, after which it exits with `DEADLINE_EXIT_CODE`.
+    pub fn deadline_exceeded(&self) -> bool {
+        self.client.deadline_exceeded()
+    }
+
+    /// The error of a run cut off by its deadline.
+    fn deadline_error(&self) -> String {
+        let deadline = format::human_duration(self.config.deadline.unwrap_or_default());
+        format!("the run reached its --deadline of {deadline}, the lookups in flight were cancelled and the output is partial")
+    }
+
+    /// Asynchronously retrieves bytecode and processes it to extract function selectors and/or decode signatures.
+    ///
+    /// Depending on the configuration settings, this method fetches bytecode from either a local file
+    /// or an Ethereum contract address. It then extracts 4-byte selectors and optionally decodes
+    /// the corresponding signatures.
+    ///
+    /// The results are either printed to the console or saved to a file specified in the configuration.
+    ///
+    /// Returns:
+    /// A `Result` indicating the success or failure of the operations.
+    pub async fn execute(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match &self.config.command {
+            Some(Command::Similarity { a, b }) => return self.similarity(a, b).await,
+            Some(Command::Cache { command }) => return self.cache(command).await,
+            Some(Command::History { command }) => return self.history(command).await,
+            Some(Command::ImportDb { dump, db }) => return import_db(dump, db),
+            Some(Command::Config { command: ConfigCommand::Show { json } }) => return show_config(&self.config, *json),
+            Some(Command::Lookup { hashes }) => return self.lookup(hashes).await.map(|_| ()),
+            Some(Command::Selector { texts, full, json }) => return selector(texts, *full, *json).map(|_| ()),
+            Some(Command::Doctor { json }) => {
+                let checks = self.doctor(*json).await;
+                let failed = checks.iter().filter(|check| !check.passed).count();
+                return match failed {
+                    0 => Ok(()),
+                    _ => Err(format!("{failed} of {} checks failed", checks.len()).into()),
+                };
+            }
+            Some(Command::Selftest { json }) => {
+                let checks = self.selftest(*json);
+                let failed = checks.iter().filter(|check| !check.passed).count();
+                return match failed {
+                    0 => Ok(()),
+                    _ => Err(format!("{failed} of {} self-test checks failed", checks.len()).into()),
+                };
+            }
+            Some(Command::Scan { from_block, to_block, blocks_per_page, rate, checkpoint }) => {
+                return self.scan(*from_block, *to_block, *blocks_per_page, *rate, checkpoint.as_deref()).await;
+            }
+            Some(Command::Bruteforce { selector, matches, wordlist, max_params, bruteforce_limit, bruteforce_timeout }) => {
+                return self.bruteforce_selector(selector, *matches, wordlist.as_deref(), *max_params, *bruteforce_limit, *bruteforce_timeout);
+            }
+            None => {}
+        }
+
+        if let Some(path) = &self.config.retry_failed {
+            return self.retry_failed(path).await;
+        }
+        self.check_chain().await?;
+        if let Some(deployer) = &self.config.deployer {
+            return self.analyze_deployments(deployer).await;
+        }
+
+        let mut inputs: Vec<Input> = self.config.address.iter().map(|address| Input::Address(address.clone())).collect();
+        // A contract that was just deployed is analyzed from the hash of its deployment
+        if let Some(hash) = &self.config.tx {
+            let address = deployer::deployed_by(&self.client, hash).await?;
+            eprintln!("transaction {hash} deployed {address}");
+            inputs.push(Input::Address(address));
+        }
+        inputs.extend(self.config.file.iter().map(|file| Input::File(file.clone())));
+        if let Some(path) = &self.config.address_file {
+            let lines = input::read_inputs(path, &self.chains).map_err(|e| format!("{}: {e}", path.display()))?;
+            // The bytecode files of a rerun are relative to the directory it was recorded in
+            inputs.extend(lines.into_iter().map(|input| match (input, &self.config.dir) {
+                (Input::File(file), Some(dir)) => Input::File(dir.join(file)),
+                (input, _) => input,
+            }));
+        }
+        // The same contract given twice, e.g. in different casings or through a flag and the file, is analyzed once
+        let duplicates = match self.config.allow_duplicates {
+            true => 0,
+            false => input::dedupe(&mut inputs),
+        };
+
+        match inputs.as_slice() {
+            [input] if duplicates == 0 => self.analyze(input).await,
+            _ => self.analyze_all(&inputs, duplicates).await,
+        }
+    }
+
+    /// Asks the RPC of `--chain` for its chain id, so that an RPC of another chain, e.g. a misconfigured `[rpc_urls]`
+    /// entry, doesn't silently analyze the contracts of the wrong chain.
+    ///
+    /// A mismatch is warned about in every output, or fails the run with `--strict`. An RPC that can't tell its
+    /// chain id is left to fail the analysis itself.
+    ///
+    /// Returns:
+    /// A `Result` indicating whether the run can go on, or a `ChainError::Mismatch` with `--strict`.
+    async fn check_chain(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(chain) = &self.chain else { return Ok(()) };
+        let Ok(actual) = self.client.chain_id().await else { return Ok(()) };
+        if actual == chain.id {
+            return Ok(());
+        }
+
+        let mismatch = ChainError::Mismatch { chain: chain.name.clone(), expected: chain.id, actual };
+        if self.config.strict {
+            return Err(mismatch.into());
+        }
+        let message = format!("the RPC of --chain {} is on chain id {actual}, not {}, the contracts may be read from the wrong chain", chain.name, chain.id);
+        *self.chain_mismatch.lock().unwrap() = Some(Warning::new(WarningKind::ChainMismatch, message));
+        Ok(())
+    }
+
+    /// Looks the unresolved and failed selectors of a previous JSON output up again, and writes the merged output.
+    ///
+    /// The signatures resolved before are left untouched. The output is written to `--output` if given,
+    /// otherwise the previous output is updated in place, through a temporary file so it isn't lost on failure.
+    ///
+    /// Arguments:
+    /// * `path`: The previous JSON output.
+    ///
+    /// Returns:
+    /// A `Result` indicating the success or failure of the operations, failing with `--strict` if lookups failed again.
+    async fn retry_failed(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut out: SigmundOut = serde_json::from_str(&content).map_err(|e| format!("{}: not a sigmund JSON output: {e}", path.display()))?;
+        let retried = out.retry_selectors();
+        if retried.is_empty() {
+            eprintln!("{}: every selector is already resolved", path.display());
+            return Ok(());
+        }
+
+        let top = self.config.top_matches();
+        let selectors: HashSet<String> = retried.iter().cloned().collect();
+        let signatures = self.client.get_signatures(self.provider.as_ref(), &selectors, top > 1).await?;
+        let mut signatures = signature::top_matches(signatures, top);
+        signatures.extend(self.bruteforce(&selectors, &signatures)?);
+        let mut signatures = match self.config.expand_matches {
+            true => signatures,
+            false => signature::group_variants(signatures),
+        };
+        signatures.sort_by(|a, b| a.selector.cmp(&b.selector));
+        let cache_hits = self.client.cache_hits();
+        let sources = signatures
+            .iter()
+            .filter_map(|s| match s.guessed {
+                true => Some((s.selector.clone(), "bruteforce".to_string())),
+                false if cache_hits.contains(&s.selector) => Some((s.selector.clone(), "cache".to_string())),
+                false => Some((s.selector.clone(), self.provider.source_of(&s.selector)?)),
+            })
+            .collect();
+
+        let resolved: BTreeSet<&str> = signatures.iter().map(|s| s.selector.as_str()).collect();
+        self.print(format_args!("resolved {} of {} retried selectors\n", resolved.len(), retried.len()));
+        for group in signature::group_by_selector(&signatures) {
+            match self.config.verbose {
+                0 => self.print(format_args!("{group}\n")),
+                _ => self.print(format_args!("{group:#}\n")),
+            }
+        }
+
+        out.merge_retried(&retried, signatures, sources, self.client.take_failures());
+        if !out.unresolved.is_empty() {
+            self.print(format_args!("{}\n", UnresolvedSelectors(&out.unresolved)));
+        }
+        // The previous summary of the failed lookups is replaced by one for the lookups that are still failing
+        out.warnings.retain(|warning| warning.kind != WarningKind::LookupFailed);
+        if let Some(failure) = out.failures.first() {
+            let warning = Warning::new(WarningKind::LookupFailed, self.failures_summary(&out.failures, failure));
+            eprintln!("{warning}");
+            out.warnings.push(warning);
+        }
+
+        out.partial = self.deadline_exceeded();
+        let json = serde_json::to_string_pretty(&out)?;
+        match &self.config.output {
+            Some(output) => std::fs::write(output, json)?,
+            None => {
+                let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+                std::fs::write(&tmp, json)?;
+                std::fs::rename(&tmp, path)?;
+            }
+        }
+
+        if out.partial {
+            return Err(self.deadline_error().into());
+        }
+        if let Some(failure) = out.failures.first().filter(|_| self.config.strict) {
+            return Err(format!("{} lookups failed with --strict, e.g. {}: {}", out.failures.len(), failure.selector, failure.reason).into());
+        }
+        Ok(())
+    }
+
+    /// Analyzes every contract created by a deployer within the configured block range.
+    async fn analyze_deployments(&self, deployer: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let deployer = Address::try_from(deployer.to_string())?;
+        // #![INFO]: The block range is always set since it's required with --deployer in the CLI
+        let (from_block, to_block) = (self.config.from_block.unwrap_or_default(), self.config.to_block.unwrap_or_default());
+
+        let created = deployer::find_creations(&self.client, &deployer, from_block, to_block).await?;
+        eprintln!("{} contracts created by {deployer} in blocks {from_block} to {to_block}", created.len());
+
+        let inputs: Vec<Input> = created.into_iter().map(Input::Address).collect();
+        self.analyze_all(&inputs, 0).await
+    }
+
+    /// Analyzes the contracts created in a range of blocks, a page of blocks at a time.
+    ///
+    /// The contracts of a page are analyzed once all its blocks are fetched, then the checkpoint records its last
+    /// block, so a scan that was interrupted resumes after the last complete page. A contract that fails to be
+    /// analyzed is reported and counted, and the checkpoint doesn't advance past the block before it anymore, so a
+    /// resumed scan retries it, analyzing the contracts created after it again. The scan stops at the first block
+    /// that isn't mined yet, so a later run with the same checkpoint picks up from there.
+    ///
+    /// Arguments:
+    /// * `from_block`, `to_block`: The inclusive range of blocks to scan.
+    /// * `blocks_per_page`: The number of blocks of a page.
+    /// * `rate`: The maximum number of block and receipt requests per second.
+    /// * `checkpoint`: The file recording the last block whose contracts were all analyzed.
+    async fn scan(&self, from_block: u64, to_block: u64, blocks_per_page: u64, rate: u32, checkpoint: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_chain().await?;
+        let mut start = from_block;
+        if let Some(last) = checkpoint.map(scan::read_checkpoint).transpose()?.flatten().filter(|last| *last >= from_block) {
+            eprintln!("resuming after block {last}");
+            start = last.saturating_add(1);
+        }
+
+        let (first, pace) = (start, client::Pace::new(rate));
+        let (mut created, mut failed, mut without_code) = (0, 0, 0);
+        let (mut scanned, mut first_failed) = (None, None);
+        while start <= to_block && !self.deadline_exceeded() {
+            let end = to_block.min(start.saturating_add(blocks_per_page - 1));
+            let (creations, mined) = scan::page_creations(&self.client, start..=end, &pace).await?;
+
+            let inputs: Vec<Input> = creations.iter().map(|creation| Input::Address(creation.address.clone())).collect();
+            let inputs: Vec<&Input> = inputs.iter().collect();
+            for (position, (creation, input)) in creations.iter().zip(&inputs).enumerate() {
+                if self.deadline_exceeded() {
+                    break;
+                }
+                if position % client::MAX_BATCH_SIZE == 0 {
+                    self.prefetch_codes(&inputs[position..inputs.len().min(position + client::MAX_BATCH_SIZE)]).await;
+                }
+                created += 1;
+                eprintln!("block {}: contract {} created by {}", creation.block, creation.address, creation.transaction);
+                match self.analyze(input).await {
+                    Ok(()) => {}
+                    // A contract that destroyed itself in its constructor has no code left
+                    Err(error) if matches!(error.downcast_ref(), Some(ClientError::NoCode { .. })) => {
+                        eprintln!("{input}: {error}");
+                        without_code += 1;
+                    }
+                    Err(error) => {
+                        eprintln!("{input}: {error}");
+                        failed += 1;
+                        first_failed.get_or_insert(creation.block);
+                    }
+                }
+            }
+            // A page cut short by the deadline is scanned again on resume
+            if self.deadline_exceeded() {
+                break;
+            }
+
+            let last = start + mined;
+            if mined > 0 {
+                scanned = Some(last - 1);
+                // The blocks from the first failed contract on are scanned again on resume
+                let complete = match first_failed {
+                    Some(block) => block.checked_sub(1).filter(|block| *block >= first),
+                    None => Some(last - 1),
+                };
+                if let Some((path, complete)) = checkpoint.zip(complete) {
+                    scan::write_checkpoint(path, complete)?;
+                }
+            }
+            if last <= end {
+                eprintln!("block {last} isn't mined yet, the scan stops before it");
+                break;
+            }
+            start = end.saturating_add(1);
+        }
+
+        let scanned = match scanned {
+            Some(last) => format!("scanned blocks {first} to {last}"),
+            None => "scanned no block".to_string(),
+        };
+        let without_code = match without_code {
+            0 => String::new(),
+            without_code => format!(", {without_code} without code skipped"),
+        };
+        eprintln!("{scanned}: {created} contracts created, {failed} failed{without_code}");
+        if self.deadline_exceeded() {
+            return Err(self.deadline_error().into());
+        }
+        match failed {
+            0 => Ok(()),
+            _ => Err(format!("{failed} of {created} created contracts failed to be analyzed").into()),
+        }
+    }
+
+    /// Analyzes several contracts in order.
+    ///
+    /// A contract that fails to be analyzed is reported and the others are still analyzed,
+    /// the run fails afterwards if any did.
+    ///
+    /// Arguments:
+    /// * `inputs`: The contracts to analyze.
+    /// * `duplicates`: The number of duplicate inputs that were skipped, reported in the summary.
+    async fn analyze_all(&self, inputs: &[Input], duplicates: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let inputs: Vec<&Input> = inputs.iter().collect();
+        let (mut failed, mut analyzed, mut without_code) = (0, 0, 0);
+        for (position, input) in inputs.iter().copied().enumerate() {
+            // The contracts left at the deadline aren't analyzed at all
+            if self.deadline_exceeded() {
+                break;
+            }
+            // The code of the next contracts is fetched in a single request, instead of a round trip per contract
+            if position % client::MAX_BATCH_SIZE == 0 {
+                self.prefetch_codes(&inputs[position..inputs.len().min(position + client::MAX_BATCH_SIZE)]).await;
+            }
+            analyzed += 1;
+            eprintln!("contract {input}");
+            match self.analyze(input).await {
+                Ok(()) => {}
+                // An address without code is skipped, it isn't a contract to analyze
+                Err(error) if matches!(error.downcast_ref(), Some(ClientError::NoCode { .. })) => {
+                    eprintln!("{input}: {error}");
+                    without_code += 1;
+                }
+                Err(error) => {
+                    eprintln!("{input}: {error}");
+                    failed += 1;
+                }
+            }
+        }
+
+        let tripped = match self.provider.tripped_providers() {
+            tripped if tripped.is_empty() => String::new(),
+            tripped => format!(", {} skipped after failing repeatedly", tripped.join(", ")),
+        };
+        let left = match inputs.len() - analyzed {
+            0 => String::new(),
+            left => format!(", {left} left at the deadline"),
+        };
+        let failovers = match self.client.failover_count() {
+            0 => String::new(),
+            failovers => format!(", {failovers} RPC failovers"),
+        };
+        let without_code = match without_code {
+            0 => String::new(),
+            without_code => format!(", {without_code} addresses without code skipped"),
+        };
+        eprintln!("analyzed {analyzed} contracts, {failed} failed, {duplicates} duplicate inputs skipped{without_code}{tripped}{failovers}{left}");
+        if self.deadline_exceeded() {
+            return Err(self.deadline_error().into());
+        }
+        match failed {
+            0 => Ok(()),
+            _ => Err(format!("{failed} of {} contracts failed to be analyzed", inputs.len()).into()),
+        }
+    }
+
+    /// Fetches the code of the address inputs of a batch ahead of their analysis, with a JSON-RPC batch per chain.
+    ///
+    /// The inputs of a chain are batched together wherever they are in the batch, so a mixed address file
+    /// keeps its order. The results are taken by `get_bytecode`, errors included, so a contract whose code
+    /// couldn't be fetched fails like it would have on its own.
+    async fn prefetch_codes(&self, inputs: &[&Input]) {
+        let mut chains: BTreeMap<Option<u64>, Vec<&Input>> = BTreeMap::new();
+        for input in inputs {
+            chains.entry(input.chain().map(|chain| chain.id)).or_default().push(input);
+        }
+
+        for group in chains.values() {
+            // A contract given several times with --allow-duplicates is only fetched ahead once
+            let mut seen = HashSet::new();
+            let (identities, addresses): (Vec<String>, Vec<Address>) = group
+                .iter()
+                .filter(|input| seen.insert(input.identity()))
+                .filter_map(|input| Some((input.identity(), Address::try_from(input.address()?.to_string()).ok()?)))
+                .unzip();
+            // A single contract gains nothing from a batch
+            if addresses.len() < 2 {
+                continue;
+            }
+
+            let chain_client = group[0].chain().and_then(|chain| self.chain_client(chain));
+            let rpc = chain_client.as_deref().unwrap_or(&self.client);
+            let codes = rpc.get_codes(&addresses).await;
+            self.prefetched.lock().unwrap().extend(identities.into_iter().zip(codes));
+        }
+    }
+
+    /// The client of the RPC of a chain, created on its first input and reused for the next ones.
+    ///
+    /// Returns:
+    /// The client, or `None` for a chain reached through `--rpc-url` itself, whose client, fallbacks included, is used instead.
+    fn chain_client(&self, chain: &Chain) -> Option<Arc<Client>> {
+        if chain.rpc_url == self.config.rpc_url[0] {
+            return None;
+        }
+        let mut clients = self.chain_clients.lock().unwrap();
+        Some(clients.entry(chain.id).or_insert_with(|| Arc::new(self.client.for_rpc(&chain.rpc_url))).clone())
+    }
+
+    /// Retrieves the bytecode of a single contract and processes it to extract function selectors and/or decode signatures.
+    ///
+    /// Arguments:
+    /// * `input`: The address of the contract or the file containing its bytecode.
+    async fn analyze(&self, input: &Input) -> Result<(), Box<dyn std::error::Error>> {
+        let address = input.address();
+        // The addresses of a `chain:address` input are fetched from the RPC of its chain
+        let chain_client = input.chain().and_then(|chain| self.chain_client(chain));
+        let rpc = chain_client.as_deref().unwrap_or(&self.client);
+        // The addresses of the other inputs are on the chain of --chain, if given
+        let network = input.chain().or(address.and(self.chain.as_ref()));
+        // Get the bytecode from the specified source
+        let bytecode = self.get_bytecode(input, rpc).await?;
+        // Select the contract of the resolution chain the rest of the run operates on
+        let (chain, resolution_warnings) = self.resolve_chain(address, bytecode, rpc).await?;
+        let hop = chain.select(&self.config.target).map_err(|e| e.to_string())?;
+        let bytecode = &hop.bytecode;
+        // Extract function selectors from the bytecode, a confidence sweep needs every confidence level
+        let deadline = std::time::Instant::now() + self.config.extraction_budget;
+        let (mut selectors, extracted) = bytecode.find_function_selectors_until(self.config.deep || self.config.confidence_sweep, Some(deadline));
+        // The selectors a factory calls on the contracts it deploys belong to them, a deep scan lists them apart
+        let (child_init_selectors, searched) = bytecode.find_child_init_selectors_until(Some(deadline));
+        let extracted = extracted && searched;
+        selectors.retain(|selector| !child_init_selectors.contains(selector));
+        if self.config.verbose > 0 && !child_init_selectors.is_empty() {
+            eprintln!("child init selectors: {}", child_init_selectors.iter().cloned().collect::<Vec<_>>().join(", "));
+        }
+        // The human-readable results are replaced by a JSON line with --ndjson, or by the selected format
+        let console = !self.config.ndjson && self.config.format == OutputFormat::Text;
+        // Show the selectors right away, the signatures are streamed below as their lookups complete
+        if console {
+            self.print(format_args!("{selectors:?}\n"));
+        }
+        // Report how the dispatcher was understood
+        let dispatcher = bytecode.dispatcher();
+        if self.config.verbose > 0 {
+            eprintln!("dispatcher: {dispatcher}");
+            eprintln!("opcodes: {}", bytecode.opcode_stats());
+        }
+
+        // The functions of a storage-mapped router can only be discovered by calling it
+        let router = match (dispatcher.style, &hop.address) {
+            (DispatcherStyle::StorageMapped, Some(address)) if self.config.probe_storage_router => Some(self.probe_router(rpc, address).await?),
+            _ => None,
+        };
+        if let Some(router) = &router {
+            eprintln!("{router}");
+            let accepted: BTreeSet<&String> = router.accepted.iter().filter(|selector| !selectors.contains(*selector)).collect();
+            if console && !accepted.is_empty() {
+                self.print(format_args!("{accepted:?}\n"));
+            }
+            selectors.extend(router.accepted.iter().cloned());
+        }
+        // False positives of the PUSH4 heuristic reach no function, which calling the contract tells apart
+        let verification = match &hop.address {
+            Some(address) if self.config.verify_calls => {
+                let candidates: Vec<String> = selectors.iter().cloned().collect::<BTreeSet<_>>().into_iter().collect();
+                let verification = CallVerification::run(rpc, address, &candidates, self.config.verify_calls_rate).await?;
+                eprintln!("{verification}");
+                Some(verification)
+            }
+            _ => None,
+        };
+
+        // The verified ABI of the contract needs its address, so it doesn't apply to files
+        let (abi_source, verified_abi) = match &hop.address {
+            Some(address) => self.verified_abi(rpc, network, address, &selectors).await?.unzip(),
+            None => (None, None),
+        };
+        if let Some(abi) = &verified_abi {
+            eprintln!("verified ABI: {} of {} functions found in the bytecode", abi.matched.len(), abi.matched.len() + abi.unmatched_candidates.len());
+        }
+        // Tag the functions checking their caller near their entry
+        let access: BTreeMap<String, AccessHint> = access::access_hints(bytecode).into_iter().filter(|(selector, _)| selectors.contains(selector)).collect();
+
+        let (signatures, unresolved) = if self.config.signatures {
+            // The functions of the verified ABI are exact, only the remaining selectors are looked up in the databases
+            let abi_matches = verified_abi.iter().flat_map(|abi| abi.matched.iter().cloned());
+            let mut signatures: Vec<Signature> = abi_matches.map(|s| s.with_source(abi_source.unwrap_or_default())).collect();
+            let exact: HashSet<String> = signatures.iter().map(|s| s.selector.clone()).collect();
+            let remaining = selectors.difference(&exact).cloned().collect();
+            // Print every signature as soon as it is known, in the order the lookups complete.
+            // The matches of a selector are resolved at once, so the `--top` ones can be picked from each chunk
+            let top = self.config.top_matches();
+            let stream = |signatures: &[Signature]| {
+                if console {
+                    signature::top_matches(signatures.to_vec(), top).iter().for_each(|s| self.print_signature(s));
+                }
+            };
+            stream(&signatures);
+            // Collect all signatures that exist in the database
+            let resolved = self.client.stream_signatures(self.provider.as_ref(), &remaining, top > 1, stream).await;
+            signatures.extend(signature::top_matches(resolved.map_err(|e| e.to_string())?, top));
+            if self.config.verbose > 0 {
+                eprintln!("{} of {} selectors resolved from the cache", self.client.cache_hits().len(), selectors.len());
+            }
+            let rate_limits = self.client.rate_limits();
+            if rate_limits.events > 0 {
+                eprintln!("{} signature lookups were rate limited, waited {} in total", rate_limits.events, format::human_duration(rate_limits.waited));
+            }
+            // Guess the signatures of the selectors no provider resolved
+            let guessed = self.bruteforce(&selectors, &signatures)?;
+            stream(&guessed);
+            signatures.extend(guessed);
+            // Show the highest ranked match per function name, unless every match was requested
+            let mut signatures = match self.config.expand_matches {
+                true => signatures,
+                false => signature::group_variants(signatures),
+            };
+            // Close the stream with the final results sorted by selector, the sort is stable so the ranking is kept
+            signatures.sort_by(|a, b| a.selector.cmp(&b.selector));
+            let resolved: HashSet<&str> = signatures.iter().map(|s| s.selector.as_str()).collect();
+            let unresolved: Vec<String> = selectors.iter().filter(|selector| !resolved.contains(selector.as_str())).cloned().collect::<BTreeSet<_>>().into_iter().collect();
+            if console {
+                self.print(format_args!("resolved {} of {} selectors\n", resolved.len(), selectors.len()));
+                // Matches of the same selector are listed under a single header
+                for mut group in signature::group_by_selector(&signatures) {
+                    group.gated = access.get(group.selector).is_some_and(AccessHint::is_gated);
+                    match self.config.verbose {
+                        0 => self.print(format_args!("{group}\n")),
+                        _ => self.print(format_args!("{group:#}\n")),
+                    }
+                }
+                if !unresolved.is_empty() {
+                    self.print(format_args!("{}\n", UnresolvedSelectors(&unresolved)));
+                }
+            }
+
+            (Some(signatures), unresolved)
+        } else {
+            (None, Vec::new())
+        };
+
+        // Resolve the custom errors the contract reverts with
+        let errors = match self.config.errors {
+            true => self.resolve_errors(bytecode, &selectors).await?,
+            false => Vec::new(),
+        };
+        if console {
+            errors.iter().for_each(|s| self.print_signature(s));
+        }
+
+        // Resolve the topics of the events the contract emits
+        let events = match self.config.events {
+            true => Some(self.resolve_events(bytecode).await?),
+            false => None,
+        };
+        if let Some(events) = events.as_ref().filter(|_| console) {
+            events.signatures.iter().for_each(|event| self.print(format_args!("{event}\n")));
+            if !events.unresolved.is_empty() {
+                eprintln!("{} event topics unresolved", events.unresolved.len());
+            }
+        }
+
+        // Check which of the suspected signatures exist in the contract
+        let candidates = match &self.config.candidates {
+            Some(path) => Some(CandidateCheck::from_file(path, &selectors).map_err(|e| format!("{}: {e}", path.display()))?),
+            None => None,
+        };
+        if let Some(candidates) = candidates.as_ref().filter(|_| console) {
+            self.print(candidates);
+        }
+
+        // Collect the warnings of every detector, which are both printed and part of the output
+        let mutability = bytecode.mutability();
+        let mut warnings = self.warnings.clone();
+        warnings.extend(resolution_warnings);
+        match dispatcher.style {
+            DispatcherStyle::None => {
+                warnings.push(Warning::new(WarningKind::NoDispatcherFound, "no function dispatcher found, selectors can only be found by a deep scan"));
+            }
+            DispatcherStyle::StorageMapped => {
+                let message = "storage-mapped dispatcher detected — static extraction cannot enumerate functions";
+                warnings.push(Warning::new(WarningKind::StorageMappedDispatcher, message));
+            }
+            _ => {}
+        }
+        for signature in signatures.iter().flatten() {
+            if let Some(dropped) = signature.truncated {
+                let message = format!("{} has more matches than --max-matches, {dropped} lower ranked matches were dropped", signature.selector);
+                warnings.push(Warning::new(WarningKind::MatchesTruncated, message));
+            }
+        }
+        if !extracted {
+            let message = format!("the selector extraction exceeded --extraction-budget, {} selectors were found before it stopped", selectors.len());
+            warnings.push(Warning::new(WarningKind::ExtractionIncomplete, message));
+        }
+        let failures = self.client.take_failures();
+        if let Some(failure) = failures.first() {
+            warnings.push(Warning::new(WarningKind::LookupFailed, self.failures_summary(&failures, failure)));
+        }
+        for failover in self.client.take_failovers() {
+            warnings.push(Warning::new(WarningKind::RpcFailover, format!("the RPC {failover}, the primary --rpc-url may be unhealthy")));
+        }
+        for provider in self.provider.tripped_providers() {
+            let message = format!("{provider} failed {} requests in a row and is skipped for the rest of the run", self.config.max_consecutive_failures);
+            warnings.push(Warning::new(WarningKind::ProviderTripped, message));
+        }
+        for parse in self.parse_warnings.take() {
+            let hint = match self.config.verbose {
+                0 | 1 => ", use -vv to print its shape",
+                _ => "",
+            };
+            let message = format!("{} sent {} responses or items that didn't have the expected shape, e.g. {}{hint}", parse.provider, parse.count, parse.first);
+            warnings.push(Warning::new(WarningKind::UnexpectedResponse, message));
+        }
+        if let Some(abi) = &verified_abi {
+            if !abi.unmatched_selectors.is_empty() {
+                let message = format!(
+                    "{} extracted selectors aren't in the verified ABI, e.g. hidden functions or the functions of a proxy: {}",
+                    abi.unmatched_selectors.len(),
+                    abi.unmatched_selectors.join(", ")
+                );
+                warnings.push(Warning::new(WarningKind::AbiMismatch, message));
+            }
+            if !abi.unmatched_candidates.is_empty() {
+                let message = format!(
+                    "{} functions of the verified ABI weren't found in the bytecode: {}",
+                    abi.unmatched_candidates.len(),
+                    abi.unmatched_candidates.join(", ")
+                );
+                warnings.push(Warning::new(WarningKind::AbiMismatch, message));
+            }
+        }
+        let guessed = signatures.iter().flatten().filter(|s| s.guessed).count();
+        if guessed > 0 {
+            let message = format!("{guessed} signatures were guessed by hashing candidate names, they match the selectors but may not be the original functions");
+            warnings.push(Warning::new(WarningKind::GuessedSignatures, message));
+        }
+        warnings.extend(mutability.warnings());
+        warnings.extend(self.chain_mismatch.lock().unwrap().clone());
+        warnings.iter().for_each(|warning| eprintln!("{warning}"));
+        warnings.extend(self.deprecation.clone());
+
+        // Decode the constructor arguments when analyzing creation code
+        let creation = self.analyze_creation(bytecode)?;
+        if let Some(args) = creation.as_ref().and_then(|c| c.constructor_args.as_ref()).filter(|_| console) {
+            self.print(args);
+        }
+
+        // Use a Default when no signatures exist to provide a more safe
+        // and consistent output format when accessed by users
+        let mut out = SigmundOut::new(selectors, signatures.unwrap_or_default());
+        out.meta.input_as_given = address.map(str::to_string);
+        out.meta.chain = network.cloned();
+        out.meta.block = address.and(self.config.block.clone());
+        out.meta.code_hash = signature::try_keccak(&bytecode.inner).ok().map(hex::encode);
+        if chain.hops.len() > 1 {
+            let deep = self.config.deep;
+            out.meta.resolution = chain.hops.iter().map(|hop| ResolvedHop { kind: hop.kind, address: hop.address.clone(), selectors: hop.bytecode.find_function_selectors(deep).into_iter().collect() }).collect();
+        }
+        out.meta.proxy_admin = chain.admin.clone();
+        out.meta.target = Some(AnalyzedTarget {
+            target: self.config.target.clone(),
+            kind: hop.kind,
+            address: hop.address.clone(),
+        });
+        out.unresolved = unresolved;
+        out.dispatcher = Some(dispatcher);
+        out.router = router;
+        out.verification = verification;
+        out.access = access;
+        out.child_init_selectors = child_init_selectors;
+        out.confidence = bytecode.selector_confidence().into_iter().filter(|(selector, _)| out.selectors.contains(selector)).collect();
+        // Selectors a storage-mapped router accepted were confirmed by calling it
+        out.confidence.extend(out.router.iter().flat_map(|router| router.accepted.iter().map(|selector| (selector.clone(), Confidence::High))));
+        // Calling the contract confirms the selectors that reached a function, and demotes the ones answered like an unknown selector
+        for (selector, verdict) in out.verification.iter().flat_map(|verification| &verification.verdicts) {
+            match verdict {
+                CallVerdict::Executes | CallVerdict::RevertsWithData => out.confidence.insert(selector.clone(), Confidence::High),
+                CallVerdict::LikeUnknown => out.confidence.insert(selector.clone(), Confidence::Low),
+                CallVerdict::RevertsEmpty => None,
+            };
+        }
+        let cache_hits = self.client.cache_hits();
+        let exact: HashSet<&str> = verified_abi.iter().flat_map(|abi| abi.matched.iter().map(|s| s.selector.as_str())).collect();
+        out.sources = out
+            .signatures
+            .iter()
+            .filter_map(|s| match s.guessed {
+                true => Some((s.selector.clone(), "bruteforce".to_string())),
+                false if exact.contains(s.selector.as_str()) => Some((s.selector.clone(), abi_source?.to_string())),
+                false if cache_hits.contains(&s.selector) => Some((s.selector.clone(), "cache".to_string())),
+                false => Some((s.selector.clone(), self.provider.source_of(&s.selector)?)),
+            })
+            .collect();
+        out.errors = errors;
+        out.events = events;
+        out.candidates = candidates;
+        out.verified_abi = verified_abi;
+        out.mutability = mutability;
+        out.creation = creation;
+        out.warnings = warnings;
+        out.failures = failures;
+        out.partial = self.deadline_exceeded();
+
+        // Build the history entry before the pagination, so every selector is counted
+        let run = self.config.history.then(|| self.history_entry(input, &out));
+
+        // Paginate the structured outputs last, so the pages are taken from the selectors left by every other flag
+        if let Some(page) = self.config.page {
+            if console {
+                eprintln!("--page only applies to the JSON outputs, the console lists every selector");
+            }
+            out.paginate(page as usize, self.config.page_size as usize);
+        }
+
+        // Deliver the output to every selected sink, identifying the contract by its address or file name,
+        // prefixed with its chain so the same address on several chains doesn't overwrite itself
+        let file = match input {
+            Input::File(file) => Some(file),
+            Input::Address(_) | Input::OnChain { .. } => None,
+        };
+        let key = hop.address.clone().or_else(|| Some(file?.file_stem()?.to_string_lossy().into_owned()));
+        let key = key.unwrap_or_else(|| "contract".to_string());
+        let key = match input.chain() {
+            Some(chain) => format!("{}_{key}", chain.name),
+            None => key,
+        };
+        for sink in &self.sinks {
+            sink.write(&key, &out).await.map_err(|e| format!("{} sink: {e}", sink.name()))?;
+        }
+        if let Some(run) = run {
+            let output = self.config.output.clone().or_else(|| Some(DirectorySink::new(self.config.output_dir.clone()?).path_of(&key)));
+            self.record_run(run, hop.address.as_deref(), output).await;
+        }
+
+        // A run cut off by its deadline fails once what was resolved so far is written
+        if out.partial {
+            return Err(self.deadline_error().into());
+        }
+        // Fail on the warnings requested to be fatal, after the output is written
+        if let Some(warning) = out.warnings.iter().find(|w| self.config.fail_on_warning.contains(&w.kind)) {
+            return Err(format!("fatal warning `{}`: {}", warning.kind, warning.message).into());
+        }
+        if let Some(failure) = out.failures.first().filter(|_| self.config.strict) {
+            return Err(format!("{} lookups failed with --strict, e.g. {}: {}", out.failures.len(), failure.selector, failure.reason).into());
+        }
+
+        Ok(())
+    }
 }
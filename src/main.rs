@@ -1,14 +1,36 @@
-use clap::Parser;
-use sigmund::{config::Config, Sigmund};
+use sigmund::{
+    config::{Command, ConfigCommand, Config},
+    ClientError, Sigmund,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Build and parse the command-line interface arguments.
-    let cli = Config::parse();
+    let cli = Config::parse_with_origins();
+    // The configuration is shown as it is, including the problems that would fail its validation
+    if let Some(Command::Config { command: ConfigCommand::Show { json } }) = &cli.command {
+        return sigmund::show_config(&cli, *json);
+    }
+    // Selectors are computed locally, without a client or any other setup
+    if let Some(Command::Selector { texts, full, json }) = &cli.command {
+        return sigmund::selector(texts, *full, *json).map(|_| ());
+    }
     // Sigmund 🗿
-    let sigmund = Sigmund::from_config(cli);
+    let sigmund = Sigmund::from_config(cli)?;
     // Sigmund 🗿
-    sigmund.execute().await?;
+    if let Err(error) = sigmund.execute().await {
+        // A run cut off by --deadline exits with a code of its own, after writing what was resolved so far
+        if sigmund.deadline_exceeded() {
+            eprintln!("Error: {error}");
+            std::process::exit(sigmund::DEADLINE_EXIT_CODE);
+        }
+        // So does a run whose address has no code, which was likely given on the wrong chain or block
+        if matches!(error.downcast_ref(), Some(ClientError::NoCode { .. })) {
+            eprintln!("Error: {error}");
+            std::process::exit(sigmund::NO_CODE_EXIT_CODE);
+        }
+        return Err(error);
+    }
 
     Ok(())
 }
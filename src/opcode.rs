@@ -0,0 +1,136 @@
+use std::fmt::Display;
+
+/// The mnemonic of every opcode, indexed by opcode, empty for the undefined ones.
+///
+/// Opcodes are listed under their current name, see `Opcode::aliases` for the historical ones.
+const NAMES: [&str; 256] = [
+    // 0x00..=0x0f
+    "STOP", "ADD", "MUL", "SUB", "DIV", "SDIV", "MOD", "SMOD", "ADDMOD", "MULMOD", "EXP", "SIGNEXTEND", "", "", "", "",
+    // 0x10..=0x1f
+    "LT", "GT", "SLT", "SGT", "EQ", "ISZERO", "AND", "OR", "XOR", "NOT", "BYTE", "SHL", "SHR", "SAR", "", "",
+    // 0x20..=0x2f
+    "KECCAK256", "", "", "", "", "", "", "", "", "", "", "", "", "", "", "",
+    // 0x30..=0x3f
+    "ADDRESS", "BALANCE", "ORIGIN", "CALLER", "CALLVALUE", "CALLDATALOAD", "CALLDATASIZE", "CALLDATACOPY", "CODESIZE", "CODECOPY", "GASPRICE", "EXTCODESIZE", "EXTCODECOPY", "RETURNDATASIZE", "RETURNDATACOPY", "EXTCODEHASH",
+    // 0x40..=0x4f
+    "BLOCKHASH", "COINBASE", "TIMESTAMP", "NUMBER", "PREVRANDAO", "GASLIMIT", "CHAINID", "SELFBALANCE", "BASEFEE", "BLOBHASH", "BLOBBASEFEE", "", "", "", "", "",
+    // 0x50..=0x5f
+    "POP", "MLOAD", "MSTORE", "MSTORE8", "SLOAD", "SSTORE", "JUMP", "JUMPI", "PC", "MSIZE", "GAS", "JUMPDEST", "TLOAD", "TSTORE", "MCOPY", "PUSH0",
+    // 0x60..=0x6f
+    "PUSH1", "PUSH2", "PUSH3", "PUSH4", "PUSH5", "PUSH6", "PUSH7", "PUSH8", "PUSH9", "PUSH10", "PUSH11", "PUSH12", "PUSH13", "PUSH14", "PUSH15", "PUSH16",
+    // 0x70..=0x7f
+    "PUSH17", "PUSH18", "PUSH19", "PUSH20", "PUSH21", "PUSH22", "PUSH23", "PUSH24", "PUSH25", "PUSH26", "PUSH27", "PUSH28", "PUSH29", "PUSH30", "PUSH31", "PUSH32",
+    // 0x80..=0x8f
+    "DUP1", "DUP2", "DUP3", "DUP4", "DUP5", "DUP6", "DUP7", "DUP8", "DUP9", "DUP10", "DUP11", "DUP12", "DUP13", "DUP14", "DUP15", "DUP16",
+    // 0x90..=0x9f
+    "SWAP1", "SWAP2", "SWAP3", "SWAP4", "SWAP5", "SWAP6", "SWAP7", "SWAP8", "SWAP9", "SWAP10", "SWAP11", "SWAP12", "SWAP13", "SWAP14", "SWAP15", "SWAP16",
+    // 0xa0..=0xaf
+    "LOG0", "LOG1", "LOG2", "LOG3", "LOG4", "", "", "", "", "", "", "", "", "", "", "",
+    // 0xb0..=0xbf
+    "", "", "", "", "", "", "", "", "", "", "", "", "", "", "", "",
+    // 0xc0..=0xcf
+    "", "", "", "", "", "", "", "", "", "", "", "", "", "", "", "",
+    // 0xd0..=0xdf
+    "", "", "", "", "", "", "", "", "", "", "", "", "", "", "", "",
+    // 0xe0..=0xef
+    "", "", "", "", "", "", "", "", "", "", "", "", "", "", "", "",
+    // 0xf0..=0xff
+    "CREATE", "CALL", "CALLCODE", "RETURN", "DELEGATECALL", "CREATE2", "", "", "", "", "STATICCALL", "", "", "REVERT", "INVALID", "SELFDESTRUCT",
+];
+
+/// An EVM opcode, defined by any hard fork or not.
+///
+/// The walker of `Bytecode::instructions` decodes every byte, including the ones no hard fork defines,
+/// which appear in the code of ancient or adversarial contracts and in data appended to the code:
+/// they are a single byte without any immediate, and they don't end the scan since the code following
+/// them may still be reached through a jump. They are counted as `undefined` in the `OpcodeStats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Opcode(pub u8);
+
+impl Opcode {
+    /// The current mnemonic of the opcode, e.g. `PREVRANDAO` for `0x44`, or `None` if it's undefined.
+    pub fn name(&self) -> Option<&'static str> {
+        Some(NAMES[usize::from(self.0)]).filter(|name| !name.is_empty())
+    }
+
+    /// The former mnemonics of the opcode, e.g. `DIFFICULTY` before the merge and `SUICIDE` before Homestead.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self.0 {
+            0x20 => &["SHA3"],
+            0x44 => &["DIFFICULTY"],
+            0xff => &["SUICIDE"],
+            _ => &[],
+        }
+    }
+
+    /// Whether a hard fork defines the opcode, `INVALID` (`0xfe`) being the designated invalid instruction.
+    pub fn is_defined(&self) -> bool {
+        self.name().is_some()
+    }
+
+    /// Whether the use of the opcode is discouraged, i.e. `CALLCODE` superseded by `DELEGATECALL`
+    /// and `SELFDESTRUCT` deprecated by EIP-6049.
+    pub fn is_deprecated(&self) -> bool {
+        matches!(self.0, 0xf2 | 0xff)
+    }
+
+    /// The number of stack items the opcode pops and pushes, or `None` if it's undefined.
+    ///
+    /// `DUPn` pops and pushes back its `n` items plus the duplicate, `SWAPn` pops and pushes back its `n + 1` items.
+    pub fn stack_effect(&self) -> Option<(usize, usize)> {
+        let effect = match self.0 {
+            // STOP, JUMPDEST, INVALID
+            0x00 | 0x5b | 0xfe => (0, 0),
+            // ADDMOD, MULMOD
+            0x08 | 0x09 => (3, 1),
+            // ISZERO, NOT
+            0x15 | 0x19 => (1, 1),
+            // Binary arithmetic, comparison, bitwise and KECCAK256
+            0x01..=0x0b | 0x10..=0x1d | 0x20 => (2, 1),
+            // BALANCE, CALLDATALOAD, EXTCODESIZE, EXTCODEHASH, BLOCKHASH, BLOBHASH, MLOAD, SLOAD, TLOAD
+            0x31 | 0x35 | 0x3b | 0x3f | 0x40 | 0x49 | 0x51 | 0x54 | 0x5c => (1, 1),
+            // CALLDATACOPY, CODECOPY, RETURNDATACOPY, MCOPY
+            0x37 | 0x39 | 0x3e | 0x5e => (3, 0),
+            // EXTCODECOPY
+            0x3c => (4, 0),
+            // The environment and block values, PC, MSIZE, GAS, PUSH0..PUSH32
+            0x30..=0x48 | 0x4a | 0x58..=0x5a | 0x5f..=0x7f => (0, 1),
+            // POP, JUMP, SELFDESTRUCT
+            0x50 | 0x56 | 0xff => (1, 0),
+            // MSTORE, MSTORE8, SSTORE, JUMPI, TSTORE, RETURN, REVERT
+            0x52 | 0x53 | 0x55 | 0x57 | 0x5d | 0xf3 | 0xfd => (2, 0),
+            0x80..=0x8f => (usize::from(self.0 - 0x7f), usize::from(self.0 - 0x7f) + 1),
+            0x90..=0x9f => (usize::from(self.0 - 0x8e), usize::from(self.0 - 0x8e)),
+            // LOG0..LOG4
+            0xa0..=0xa4 => (usize::from(self.0 - 0xa0) + 2, 0),
+            // CREATE
+            0xf0 => (3, 1),
+            // CALL, CALLCODE
+            0xf1 | 0xf2 => (7, 1),
+            // DELEGATECALL, STATICCALL
+            0xf4 | 0xfa => (6, 1),
+            // CREATE2
+            0xf5 => (4, 1),
+            _ => return None,
+        };
+        Some(effect)
+    }
+
+    /// The number of bytes of data following the opcode, 1 to 32 for `PUSH1..PUSH32` and none otherwise.
+    pub fn immediate_size(&self) -> usize {
+        match self.0 {
+            0x60..=0x7f => usize::from(self.0 - 0x5f),
+            _ => 0,
+        }
+    }
+}
+
+impl Display for Opcode {
+    /// Formats the mnemonic of the opcode, or its hex value for undefined ones, e.g. `UNDEFINED(0x0c)`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "UNDEFINED(0x{:02x})", self.0),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use super::{ProviderError, SignatureProvider};
+use crate::{
+    doctor::Check,
+    signature::{EventSignature, Signature},
+};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Mutex};
+
+/// The default number of consecutive failed requests after which a provider is skipped.
+pub const DEFAULT_MAX_CONSECUTIVE_FAILURES: usize = 5;
+
+/// A provider that falls back through several providers in order.
+///
+/// Each selector is resolved by the first provider returning a non-empty result,
+/// and the name of that provider is recorded to attribute the source of the signatures.
+///
+/// With a circuit breaker, a provider failing `max_consecutive_failures` requests in a row is tripped:
+/// it isn't queried anymore for the lifetime of the chain, and its selectors fall through to the next
+/// providers. Rate limited requests don't count, since the client waits for them.
+///
+/// Fields:
+/// - `providers`: The providers to query, in order.
+/// - `sources`: The name of the provider that resolved each selector.
+/// - `max_consecutive_failures`: The number of failed requests in a row that trips a provider, `None` never trips them.
+/// - `failures`: The number of consecutive failed requests of each provider, `None` once it's tripped.
+pub struct ProviderChain {
+    providers: Vec<Box<dyn SignatureProvider>>,
+    sources: Mutex<HashMap<String, String>>,
+    max_consecutive_failures: Option<usize>,
+    failures: Mutex<Vec<Option<usize>>>,
+}
+
+impl ProviderChain {
+    /// Creates a chain querying the given providers in order.
+    pub fn new(providers: Vec<Box<dyn SignatureProvider>>) -> Self {
+        Self {
+            failures: Mutex::new(vec![Some(0); providers.len()]),
+            providers,
+            sources: Mutex::new(HashMap::new()),
+            max_consecutive_failures: None,
+        }
+    }
+
+    /// Trips the providers failing the given number of requests in a row, `0` disables the circuit breaker.
+    pub fn with_circuit_breaker(mut self, max_consecutive_failures: usize) -> Self {
+        self.max_consecutive_failures = Some(max_consecutive_failures).filter(|max| *max > 0);
+        self
+    }
+
+    /// Creates a chain of the given providers, or returns the provider itself if only one is given.
+    pub fn from_providers(mut providers: Vec<Box<dyn SignatureProvider>>) -> Box<dyn SignatureProvider> {
+        match providers.len() {
+            1 => providers.remove(0),
+            _ => Box::new(Self::new(providers)),
+        }
+    }
+
+    /// Records the provider that resolved a selector.
+    fn attribute(&self, selector: &str, provider: &dyn SignatureProvider) {
+        self.sources.lock().unwrap().insert(selector.to_string(), provider.name().to_string());
+    }
+
+    /// The error of a request that wasn't sent since the provider at `index` is tripped, if it is.
+    fn circuit_open(&self, index: usize) -> Option<ProviderError> {
+        let max = self.max_consecutive_failures?;
+        match self.failures.lock().unwrap()[index] {
+            Some(_) => None,
+            None => Some(ProviderError::CircuitOpen(self.providers[index].name().to_string(), max)),
+        }
+    }
+
+    /// Counts the outcome of a request to the provider at `index`, tripping it once it failed too many requests in a row.
+    fn record<T>(&self, index: usize, result: &Result<T, ProviderError>) {
+        let Some(max) = self.max_consecutive_failures else { return };
+        let mut failures = self.failures.lock().unwrap();
+        failures[index] = match (result, failures[index]) {
+            (_, None) => None,
+            (Ok(_), Some(_)) => Some(0),
+            (Err(e), Some(count)) if e.is_rate_limited() => Some(count),
+            (Err(_), Some(count)) => Some(count + 1).filter(|count| *count < max),
+        };
+    }
+}
+
+#[async_trait]
+impl SignatureProvider for ProviderChain {
+    fn name(&self) -> &str {
+        "chain"
+    }
+
+    /// The largest batch size of the chained providers, chunks are split further for each provider.
+    fn batch_size(&self) -> usize {
+        self.providers.iter().map(|p| p.batch_size()).max().unwrap_or(1)
+    }
+
+    fn is_remote(&self) -> bool {
+        self.providers.iter().any(|p| p.is_remote())
+    }
+
+    fn resolves_events(&self) -> bool {
+        self.providers.iter().any(|p| p.resolves_events())
+    }
+
+    fn shadows_cache(&self, selector: &str) -> bool {
+        self.providers.iter().any(|p| p.shadows_cache(selector))
+    }
+
+    fn source_of(&self, selector: &str) -> Option<String> {
+        self.sources.lock().unwrap().get(selector).cloned()
+    }
+
+    fn tripped_providers(&self) -> Vec<String> {
+        let failures = self.failures.lock().unwrap();
+        let tripped = self.providers.iter().zip(failures.iter()).filter(|(_, failures)| failures.is_none());
+        tripped.map(|(provider, _)| provider.name().to_string()).collect()
+    }
+
+    async fn check(&self, selector: &str) -> Vec<Check> {
+        let mut checks = Vec::new();
+        for provider in &self.providers {
+            checks.extend(provider.check(selector).await);
+        }
+        checks
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let mut results = self.resolve_batch(&[selector.to_string()]).await?;
+        Ok(results.remove(selector).unwrap_or_default())
+    }
+
+    /// Resolves the selectors through each provider in order, passing only the
+    /// selectors that are still unresolved to the next one.
+    ///
+    /// A failing or tripped provider doesn't stop the chain, its error is only returned
+    /// if no later provider resolved the affected selectors.
+    async fn resolve_batch(&self, selectors: &[String]) -> Result<HashMap<String, Vec<Signature>>, ProviderError> {
+        let mut resolved = HashMap::new();
+        let mut remaining = selectors.to_vec();
+        let mut error = None;
+
+        for (index, provider) in self.providers.iter().enumerate() {
+            if remaining.is_empty() {
+                break;
+            }
+
+            // Resolve the remaining selectors in chunks of the provider's batch size, one after the other,
+            // since the callers already resolve several batches concurrently within their concurrency limit
+            for chunk in remaining.chunks(provider.batch_size().max(1)) {
+                if let Some(e) = self.circuit_open(index) {
+                    error = Some(e);
+                    break;
+                }
+                let result = provider.resolve_batch(chunk).await;
+                self.record(index, &result);
+                match result {
+                    Ok(matches) => {
+                        for (selector, signatures) in matches.into_iter().filter(|(_, s)| !s.is_empty()) {
+                            self.attribute(&selector, provider.as_ref());
+                            resolved.insert(selector, signatures);
+                        }
+                    }
+                    Err(e) => error = Some(e),
+                }
+            }
+
+            remaining.retain(|selector| !resolved.contains_key(selector));
+        }
+
+        match error {
+            Some(error) if !remaining.is_empty() => Err(error),
+            _ => Ok(resolved),
+        }
+    }
+
+    /// Resolves the full hash through each provider in order, until one of them knows it.
+    ///
+    /// A failing provider doesn't stop the chain, its error is only returned if no later provider knows the hash.
+    async fn resolve_hash(&self, hash: &str) -> Result<Vec<Signature>, ProviderError> {
+        let mut error = None;
+
+        for (index, provider) in self.providers.iter().enumerate() {
+            if let Some(e) = self.circuit_open(index) {
+                error = Some(e);
+                continue;
+            }
+            let result = provider.resolve_hash(hash).await;
+            self.record(index, &result);
+            match result {
+                Ok(signatures) if !signatures.is_empty() => return Ok(signatures.into_iter().map(|s| s.with_source(provider.name())).collect()),
+                Ok(_) => {}
+                Err(e) => error = Some(e),
+            }
+        }
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Resolves the topic through each provider in order, until one of them knows it.
+    ///
+    /// A failing provider doesn't stop the chain, its error is only returned if no later provider knows the topic.
+    async fn resolve_event(&self, topic: &str) -> Result<Vec<EventSignature>, ProviderError> {
+        let mut error = None;
+
+        for (index, provider) in self.providers.iter().enumerate() {
+            if let Some(e) = self.circuit_open(index) {
+                error = Some(e);
+                continue;
+            }
+            let result = provider.resolve_event(topic).await;
+            self.record(index, &result);
+            match result {
+                Ok(events) if !events.is_empty() => return Ok(events),
+                Ok(_) => {}
+                Err(e) => error = Some(e),
+            }
+        }
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(Vec::new()),
+        }
+    }
+}
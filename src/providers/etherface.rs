@@ -0,0 +1,171 @@
+use super::{ParseWarnings, ProviderError, SignatureItem, SignatureProvider};
+use crate::{
+    signature::{self, EventSignature, RankHints, Signature},
+    transport::Transport,
+};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Etherface API response for a page of the matches of a signature hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureResponse {
+    /// The number of result pages of the hash, missing in single page responses.
+    #[serde(default)]
+    pub total_pages: usize,
+    pub items: Vec<SignatureItem>,
+}
+
+/// A page of matches as sent by the API, whose items are parsed one by one into `SignatureItem`s.
+#[derive(Deserialize)]
+struct RawPage {
+    #[serde(default)]
+    total_pages: usize,
+    items: Vec<Value>,
+}
+
+/// Signature provider backed by the Etherface API.
+///
+/// Fields:
+/// - `url`: The base URL of the Etherface API.
+/// - `http`: The shared HTTP transport used for making requests.
+/// - `max_pages`: The maximum number of result pages fetched per selector.
+/// - `parse_warnings`: Where the responses that don't have the expected shape are counted.
+pub struct Etherface {
+    url: String,
+    http: Transport,
+    max_pages: usize,
+    parse_warnings: ParseWarnings,
+}
+
+impl Etherface {
+    /// Creates an Etherface provider using the public API.
+    pub fn new(http: Transport) -> Self {
+        Self::with_url(http, crate::ETHERFACE_URL)
+    }
+
+    /// Creates an Etherface provider using a different base URL, e.g. a mirror or a mock server.
+    pub fn with_url(http: Transport, url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http,
+            max_pages: 1,
+            parse_warnings: ParseWarnings::default(),
+        }
+    }
+
+    /// Counts the responses that don't have the expected shape in the given `ParseWarnings`, shared with the other providers.
+    pub fn with_parse_warnings(mut self, parse_warnings: ParseWarnings) -> Self {
+        self.parse_warnings = parse_warnings;
+        self
+    }
+
+    /// Sets the maximum number of result pages fetched per selector, `1` by default.
+    ///
+    /// Heavily collided selectors have more matches than fit in a page, which are only
+    /// needed when all matches are requested.
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    /// Retrieves a page of the matches of a selector or hash, or `None` if the page doesn't exist.
+    ///
+    /// Etherface indexes functions, events and errors together, `kind` selects them: `all`, `function`, `event` or `error`.
+    ///
+    /// Unknown selectors and pages beyond the last result in a `404`, or another client error, which means there are no matches.
+    /// Server errors and rate limited responses are returned, so that they can be retried.
+    ///
+    /// A successful response that isn't a page of matches, e.g. after a change of the API, is counted in the
+    /// `ParseWarnings` and treated as no matches, as are the items missing their hash or text. A page wrapped
+    /// in an envelope such as `{"data": {...}}` is unwrapped, and counted as well.
+    async fn page(&self, kind: &str, selector: &str, page: usize) -> Result<Option<SignatureResponse>, ProviderError> {
+        let url = format!("{}/v1/signatures/hash/{}/{}/{}", self.url, kind, selector, page);
+        let response = self.http.get(&url).await?;
+        let response = match response.status.is_server_error() || response.status == StatusCode::TOO_MANY_REQUESTS {
+            true => response.error_for_status()?,
+            false => response,
+        };
+        if !response.status.is_success() {
+            return Ok(None);
+        }
+
+        let Ok(body) = serde_json::from_slice::<Value>(&response.body) else {
+            self.parse_warnings.record(self.name(), "the body isn't JSON", &Value::Null);
+            return Ok(None);
+        };
+        let wrapped = match &body {
+            Value::Object(fields) if !fields.contains_key("items") => fields.iter().find(|(_, value)| value.get("items").is_some()),
+            _ => None,
+        };
+        let page = match wrapped {
+            Some((key, page)) => {
+                self.parse_warnings.record(self.name(), format!("the page is wrapped in `{key}`"), &body);
+                page
+            }
+            None => &body,
+        };
+        match RawPage::deserialize(page) {
+            Ok(raw) => Ok(Some(SignatureResponse { total_pages: raw.total_pages, items: self.parse_warnings.parse_items(self.name(), raw.items) })),
+            Err(e) => {
+                self.parse_warnings.record(self.name(), e.to_string(), &body);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Retrieves the ranked matches of a selector or hash among the signatures of `kind`, from the first page up to `max_pages`.
+    async fn items(&self, kind: &str, selector: &str) -> Result<Vec<SignatureItem>, ProviderError> {
+        let Some(first) = self.page(kind, selector, 1).await? else {
+            return Ok(Vec::new());
+        };
+
+        let mut items = first.items;
+        for page in 2..=first.total_pages.min(self.max_pages) {
+            match self.page(kind, selector, page).await? {
+                Some(response) => items.extend(response.items),
+                None => break,
+            }
+        }
+
+        // The provider knows nothing about the contract, the client ranks the matches again with its hints
+        signature::rank(&mut items, &RankHints::default());
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl SignatureProvider for Etherface {
+    fn name(&self) -> &str {
+        "etherface"
+    }
+
+    fn resolves_events(&self) -> bool {
+        true
+    }
+
+    /// Retrieves the signatures associated with a selector from the Etherface API.
+    ///
+    /// The API returns signatures ordered by the ones that are encountered the most, and they are ranked
+    /// with `signature::rank`, which falls back to that order when the heuristics are tied.
+    /// Unknown selectors are treated as an empty result. The pages following the first one
+    /// are fetched up to `max_pages`, and their matches are concatenated in page order.
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let items = self.items("all", selector).await?;
+        Ok(items.into_iter().map(|item| Signature::from_item(item, selector)).collect())
+    }
+
+    /// Etherface keys its signatures by their full hash, of which selectors are a prefix, so the whole hash is looked up,
+    /// among the functions only since the full hash of an event is its topic.
+    async fn resolve_hash(&self, hash: &str) -> Result<Vec<Signature>, ProviderError> {
+        let items = self.items("function", hash).await?;
+        Ok(items.into_iter().filter_map(|item| Signature::from_item(item, hash).check_full_hash(hash)).collect())
+    }
+
+    /// Retrieves the event signatures associated with a topic, among the events Etherface indexes along with the functions.
+    async fn resolve_event(&self, topic: &str) -> Result<Vec<EventSignature>, ProviderError> {
+        let items = self.items("event", topic).await?;
+        Ok(items.into_iter().filter_map(|item| EventSignature::verified(item.text, topic)).collect())
+    }
+}
@@ -0,0 +1,96 @@
+use super::ProviderError;
+use crate::{
+    creation::abi_type,
+    transport::{KeyRing, Transport},
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The result Etherscan returns for contracts without verified source code.
+const NOT_VERIFIED: &str = "Contract source code not verified";
+
+/// Etherscan API response, whose `result` is the ABI as a JSON string on success and an error message otherwise.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EtherscanResponse {
+    pub status: String,
+    pub message: String,
+    pub result: String,
+}
+
+/// Retrieves the verified ABI of contracts from the Etherscan API.
+///
+/// Unlike the signature databases, the ABI belongs to a single contract, so its functions are
+/// exact signatures of that contract rather than every known text of a selector.
+///
+/// Fields:
+/// - `url`: The URL of the Etherscan API endpoint.
+/// - `http`: The shared HTTP transport used for making requests.
+/// - `keys`: The Etherscan API keys used in turn, requests without one are rate limited more strictly.
+pub struct Etherscan {
+    url: String,
+    http: Transport,
+    keys: KeyRing,
+}
+
+impl Etherscan {
+    /// Creates an Etherscan provider using the public API, with an optional API key or several keys to rotate.
+    pub fn new(http: Transport, keys: impl Into<KeyRing>) -> Self {
+        Self::with_url(http, crate::ETHERSCAN_URL, keys)
+    }
+
+    /// Creates an Etherscan provider using a different API endpoint, e.g. a compatible explorer or a mock server.
+    pub fn with_url(http: Transport, url: &str, keys: impl Into<KeyRing>) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http,
+            keys: keys.into(),
+        }
+    }
+
+    /// Retrieves the function signatures of the verified ABI of a contract.
+    ///
+    /// Arguments:
+    /// * `address`: The "0x" prefixed address of the contract.
+    ///
+    /// Returns:
+    /// A `Result` containing the canonical signatures of the ABI functions, `None` if the contract isn't verified,
+    /// or a `ProviderError` if the request failed or Etherscan rejected it, e.g. because of an invalid API key.
+    /// A rate limited key is skipped for a while, and the request is sent again with the next one.
+    pub async fn functions(&self, address: &str) -> Result<Option<Vec<String>>, ProviderError> {
+        let url = |key: Option<&str>| match key {
+            Some(key) => format!("{}?module=contract&action=getabi&address={address}&apikey={key}", self.url),
+            None => format!("{}?module=contract&action=getabi&address={address}", self.url),
+        };
+
+        let body = self.http.get_with_key(&self.keys, url).await?.error_for_status()?.body;
+        let response: EtherscanResponse = serde_json::from_slice(&body)?;
+        match response.status.as_str() {
+            "1" => Ok(Some(parse_functions(&response.result)?)),
+            _ if response.result.starts_with(NOT_VERIFIED) => Ok(None),
+            _ => Err(ProviderError::Api(format!("{} ({})", response.message, response.result))),
+        }
+    }
+}
+
+/// Builds the canonical signatures of the functions of a JSON ABI, e.g. `transfer(address,uint256)`.
+///
+/// Entries without a `type` are functions, as in the ABI specification. Events, errors and the
+/// constructor are skipped since they aren't dispatched by selector.
+pub fn parse_functions(abi: &str) -> Result<Vec<String>, ProviderError> {
+    let entries: Vec<Value> = serde_json::from_str(abi)?;
+    abi_functions(&entries)
+}
+
+/// Builds the canonical signatures of the functions of parsed ABI entries, like `parse_functions`.
+pub(crate) fn abi_functions(entries: &[Value]) -> Result<Vec<String>, ProviderError> {
+    let mut functions = Vec::new();
+
+    for entry in entries.iter().filter(|e| e.get("type").and_then(Value::as_str).unwrap_or("function") == "function") {
+        let Some(name) = entry.get("name").and_then(Value::as_str) else { continue };
+        let inputs = entry.get("inputs").and_then(Value::as_array).cloned().unwrap_or_default();
+        let types: Vec<String> = inputs.iter().map(abi_type).collect::<Result<_, _>>().map_err(|e| ProviderError::Api(e.to_string()))?;
+        functions.push(format!("{name}({})", types.join(",")));
+    }
+
+    Ok(functions)
+}
@@ -0,0 +1,139 @@
+use super::{ParseWarnings, ProviderError, SignatureProvider};
+use crate::{
+    signature::{self, EventSignature, Signature},
+    transport::Transport,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The maximum number of result pages followed for a single selector.
+const MAX_PAGES: usize = 10;
+
+/// A page of 4byte.directory signature results, used by both lookups and listings.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SignaturePage {
+    /// The URL of the next page, if any.
+    pub next: Option<String>,
+    pub results: Vec<FourbyteSignature>,
+}
+
+/// A page of results as sent by the API, whose results are parsed one by one into `FourbyteSignature`s.
+#[derive(Deserialize)]
+struct RawPage {
+    next: Option<String>,
+    results: Vec<Value>,
+}
+
+/// Item values of the 4byte.directory API responses.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FourbyteSignature {
+    #[serde(default)]
+    pub id: u64,
+    pub text_signature: String,
+    pub hex_signature: String,
+    #[serde(default, deserialize_with = "signature::deserialize_timestamp")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<FourbyteSignature> for Signature {
+    /// Converts a 4byte.directory result into a `Signature`.
+    ///
+    /// 4byte.directory only stores the selector, the full hash is computed from the text.
+    fn from(item: FourbyteSignature) -> Self {
+        Self {
+            first_seen: item.created_at,
+            ..Self::verified(item.text_signature, &item.hex_signature)
+        }
+    }
+}
+
+/// Signature provider backed by the 4byte.directory API.
+///
+/// Fields:
+/// - `url`: The base URL of the 4byte.directory API.
+/// - `http`: The shared HTTP transport used for making requests.
+/// - `parse_warnings`: Where the responses that don't have the expected shape are counted.
+pub struct Fourbyte {
+    url: String,
+    http: Transport,
+    parse_warnings: ParseWarnings,
+}
+
+impl Fourbyte {
+    /// Creates a 4byte.directory provider using the public API.
+    pub fn new(http: Transport) -> Self {
+        Self::with_url(http, crate::FOURBYTE_URL)
+    }
+
+    /// Creates a 4byte.directory provider using a different base URL, e.g. a mirror or a mock server.
+    pub fn with_url(http: Transport, url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http,
+            parse_warnings: ParseWarnings::default(),
+        }
+    }
+
+    /// Counts the responses that don't have the expected shape in the given `ParseWarnings`, shared with the other providers.
+    pub fn with_parse_warnings(mut self, parse_warnings: ParseWarnings) -> Self {
+        self.parse_warnings = parse_warnings;
+        self
+    }
+
+    /// Collects the results of a lookup, following the `next` links up to `MAX_PAGES` pages.
+    ///
+    /// A page that isn't a list of results fails the lookup, the results missing their text or selector are skipped.
+    /// Both are counted in the `ParseWarnings`.
+    async fn pages(&self, url: String) -> Result<Vec<FourbyteSignature>, ProviderError> {
+        let mut next = Some(url);
+        let mut results = Vec::new();
+
+        for _ in 0..MAX_PAGES {
+            let Some(url) = next.take() else { break };
+            let body = self.http.get(&url).await?.error_for_status()?.body;
+            let body: Value = serde_json::from_slice(&body)?;
+            let page = RawPage::deserialize(&body).inspect_err(|e| self.parse_warnings.record(self.name(), e.to_string(), &body))?;
+
+            results.extend(self.parse_warnings.parse_items(self.name(), page.results));
+            next = page.next;
+        }
+
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl SignatureProvider for Fourbyte {
+    fn name(&self) -> &str {
+        "fourbyte"
+    }
+
+    fn resolves_events(&self) -> bool {
+        true
+    }
+
+    /// Retrieves the signatures associated with a selector from 4byte.directory.
+    ///
+    /// Results are paginated through `next` links, which are followed up to `MAX_PAGES` pages.
+    /// Collided selectors return several texts, which are ordered by id since the oldest
+    /// submission is usually the correct one and later ones are often crafted collisions.
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let url = format!("{}/api/v1/signatures/?hex_signature=0x{selector}", self.url);
+        let mut results = self.pages(url).await?;
+
+        results.sort_by_key(|item| item.id);
+        Ok(results.into_iter().map(Signature::from).collect())
+    }
+
+    /// Retrieves the event signatures associated with a topic from the event endpoint of 4byte.directory,
+    /// ordered by id like the function signatures.
+    async fn resolve_event(&self, topic: &str) -> Result<Vec<EventSignature>, ProviderError> {
+        let url = format!("{}/api/v1/event-signatures/?hex_signature=0x{topic}", self.url);
+        let mut results = self.pages(url).await?;
+
+        results.sort_by_key(|item| item.id);
+        Ok(results.into_iter().filter_map(|item| EventSignature::verified(item.text_signature, topic)).collect())
+    }
+}
@@ -0,0 +1,162 @@
+use super::{ProviderError, SignatureProvider};
+use crate::{
+    signature::{self, Signature},
+    UnknownSelector,
+};
+use async_trait::async_trait;
+use std::{collections::HashMap, path::Path};
+
+/// Signature provider backed by a local file, resolving selectors without any network calls.
+///
+/// The file is either a JSON object mapping selectors to a signature text or a list of texts,
+/// e.g. `{"a9059cbb": "transfer(address,uint256)"}`, a file written by `--export-unknown` whose entries
+/// without a `text` are skipped, or a CSV file with `selector,text` rows.
+/// Files with a `.csv` extension are read as CSV, everything else as JSON.
+///
+/// Fields:
+/// - `entries`: The signature texts of each selector, in file order.
+/// - `warnings`: The entries that were skipped while loading the file, and why.
+#[derive(Debug, Default)]
+pub struct LocalDb {
+    entries: HashMap<String, Vec<String>>,
+    warnings: Vec<String>,
+}
+
+impl LocalDb {
+    /// Loads a local signature database.
+    ///
+    /// Entries whose selector isn't 4 bytes long, or doesn't match the keccak-256 hash of
+    /// the signature text, are skipped and reported in `warnings` instead of failing the load.
+    ///
+    /// Arguments:
+    /// * `path`: The path of the JSON or CSV file.
+    ///
+    /// Returns:
+    /// A `Result` containing the `LocalDb`, or a `ProviderError` if the file can't be read or parsed.
+    pub fn open(path: &Path) -> Result<Self, ProviderError> {
+        let content = std::fs::read_to_string(path)?;
+
+        let rows = match path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+            true => parse_csv(&content),
+            false => parse_json(&content)?,
+        };
+
+        let mut db = Self::default();
+        for (selector, text) in rows {
+            db.insert(&selector, text);
+        }
+
+        Ok(db)
+    }
+
+    /// Whether the database has an entry for a selector.
+    pub fn contains(&self, selector: &str) -> bool {
+        self.entries.contains_key(selector)
+    }
+
+    /// The entries that were skipped while loading the file.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Adds an entry after validating it with `validate_entry`.
+    fn insert(&mut self, selector: &str, text: String) {
+        let (selector, text) = match validate_entry(selector, &text) {
+            Ok(entry) => entry,
+            Err(warning) => return self.warnings.push(format!("local db: {warning}")),
+        };
+
+        let texts = self.entries.entry(selector).or_default();
+        if !texts.contains(&text) {
+            texts.push(text);
+        }
+    }
+}
+
+/// Normalizes a `selector,text` entry and validates it against the keccak-256 hash of its text,
+/// which is skipped when sigmund is built without the `keccak` feature.
+///
+/// Returns:
+/// A `Result` containing the lowercase selector without the "0x" prefix and the trimmed text,
+/// or the reason the entry is invalid.
+pub(crate) fn validate_entry(selector: &str, text: &str) -> Result<(String, String), String> {
+    let selector = selector.trim().trim_start_matches("0x").to_lowercase();
+    let text = text.trim().to_string();
+
+    if selector.len() != 8 || hex::decode(&selector).is_err() {
+        return Err(format!("skipping `{selector}`, expected a 4-byte hex selector"));
+    }
+
+    if signature::try_hash(&signature::canonicalize(&text)).is_ok_and(|hash| !hash.starts_with(&selector)) {
+        return Err(format!("skipping `{text}`, its selector isn't {selector}"));
+    }
+
+    Ok((selector, text))
+}
+
+/// Parses a JSON object whose values are either a signature text or a list of texts,
+/// or the list of unknown selectors written by `--export-unknown`.
+fn parse_json(content: &str) -> Result<Vec<(String, String)>, ProviderError> {
+    // The entries that weren't filled in are still unknown, rather than invalid
+    if let Ok(unknown) = serde_json::from_str::<Vec<UnknownSelector>>(content) {
+        return Ok(unknown.into_iter().filter(|entry| !entry.text.trim().is_empty()).map(|entry| (entry.selector, entry.text)).collect());
+    }
+
+    let map: HashMap<String, serde_json::Value> = serde_json::from_str(content)?;
+    let mut rows = Vec::new();
+
+    for (selector, value) in map {
+        match value {
+            serde_json::Value::String(text) => rows.push((selector, text)),
+            serde_json::Value::Array(texts) => {
+                rows.extend(texts.into_iter().filter_map(|t| t.as_str().map(str::to_string)).map(|t| (selector.clone(), t)))
+            }
+            // Keep the invalid entry so that it is reported with the other skipped entries
+            _ => rows.push((selector, String::new())),
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Parses `selector,text` rows, skipping empty lines, `#` comments and a `selector` header.
+fn parse_csv(content: &str) -> Vec<(String, String)> {
+    content.lines().filter_map(parse_csv_row).collect()
+}
+
+/// Parses a `selector,text` row, returning `None` for empty lines, `#` comments and a `selector` header.
+///
+/// Signature texts contain commas themselves, so each row is only split at its first comma
+/// and quotes around the text are removed.
+pub(crate) fn parse_csv_row(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (selector, text) = line.split_once(',')?;
+    match selector.trim().eq_ignore_ascii_case("selector") {
+        true => None,
+        false => Some((selector.to_string(), text.trim().trim_matches('"').to_string())),
+    }
+}
+
+#[async_trait]
+impl SignatureProvider for LocalDb {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    fn shadows_cache(&self, selector: &str) -> bool {
+        self.contains(selector)
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let texts = self.entries.get(selector).into_iter().flatten();
+        Ok(texts.map(|text| Signature::verified(text.clone(), selector)).collect())
+    }
+}
@@ -0,0 +1,287 @@
+use crate::{
+    doctor::Check,
+    signature::{self, EventSignature, Signature},
+    transport::{Transport, TransportError},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use reqwest::Error as ReqwestError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+mod chain;
+pub use chain::{ProviderChain, DEFAULT_MAX_CONSECUTIVE_FAILURES};
+
+mod etherface;
+pub use etherface::Etherface;
+
+mod etherscan;
+pub(crate) use etherscan::abi_functions;
+pub use etherscan::{parse_functions, Etherscan, EtherscanResponse};
+
+mod fourbyte;
+pub use fourbyte::{Fourbyte, FourbyteSignature, SignaturePage};
+
+mod local;
+pub use local::LocalDb;
+
+#[cfg(feature = "offline")]
+mod offline;
+#[cfg(feature = "offline")]
+pub use offline::Offline;
+
+mod openchain;
+pub use openchain::Openchain;
+
+mod parse;
+pub use parse::{ParseWarning, ParseWarnings};
+
+mod sourcify;
+pub use sourcify::{Sourcify, SourcifyFile, SourcifyFiles};
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::{ImportSummary, SqliteProvider, IMPORT_BATCH_SIZE};
+
+mod template;
+pub use template::{JsonPath, UrlTemplate, SELECTOR_PLACEHOLDER};
+
+/// Error types for signature providers.
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    #[error("ProviderRequestError: {0}{hint}", hint = crate::client::certificate_hint(.0))]
+    Request(#[from] ReqwestError),
+    #[error("ProviderSerdeError: Unexpected provider response. ({0})")]
+    Serde(#[from] serde_json::Error),
+    #[error("ProviderIoError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Provider{0}")]
+    Transport(TransportError),
+    #[error("ProviderApiError: {0}")]
+    Api(String),
+    #[error("ProviderCircuitOpenError: {0} is skipped for the rest of the run after failing {1} requests in a row")]
+    CircuitOpen(String, usize),
+    #[cfg(feature = "sqlite")]
+    #[error("ProviderSqliteError: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+impl From<TransportError> for ProviderError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Request(error) => Self::Request(error),
+            error => Self::Transport(error),
+        }
+    }
+}
+
+impl ProviderError {
+    /// Whether the lookup may succeed when retried, i.e. it failed with a timeout,
+    /// a connection error, a server error or was rate limited. Client errors such as a `404` are final.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Request(error) => match error.status() {
+                Some(status) => status.is_server_error(),
+                None => !error.is_builder(),
+            },
+            Self::Transport(TransportError::Status { status, .. }) => status.is_server_error(),
+            Self::Transport(TransportError::RateLimited { .. }) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the provider rejected the lookup with `429 Too Many Requests`.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Self::Transport(TransportError::RateLimited { .. }))
+    }
+
+    /// The delay the provider requested before retrying a rate limited lookup, if any.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Transport(TransportError::RateLimited { retry_after, .. }) => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// A backend that resolves function selectors to their known signatures.
+///
+/// Implementations are responsible for ranking the results, since every API
+/// orders and weighs its matches differently. The first returned signature is
+/// considered the most likely one.
+#[async_trait]
+pub trait SignatureProvider: Send + Sync {
+    /// A short name identifying the provider, e.g. in the cache provenance.
+    fn name(&self) -> &str;
+
+    /// Resolves a selector to its known signatures, ordered from most to least likely.
+    ///
+    /// Arguments:
+    /// * `selector`: The hex-encoded 4-byte selector, without the "0x" prefix.
+    ///
+    /// Returns:
+    /// A `Result` containing the matching signatures, which is empty if the selector is unknown,
+    /// or a `ProviderError` if the lookup failed.
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError>;
+
+    /// Resolves a full 32-byte function hash to the signatures that hash to it exactly.
+    ///
+    /// The default implementation resolves the selector of the hash and drops the matches whose text
+    /// doesn't hash to the whole of it, providers keyed by the full hash look it up directly.
+    ///
+    /// Arguments:
+    /// * `hash`: The hex-encoded 32-byte hash, without the "0x" prefix.
+    ///
+    /// Returns:
+    /// A `Result` containing the signatures flagged as `full_hash_verified`, or a `ProviderError` if the lookup failed.
+    async fn resolve_hash(&self, hash: &str) -> Result<Vec<Signature>, ProviderError> {
+        let signatures = self.resolve(&hash[..8]).await?;
+        Ok(signatures.into_iter().filter_map(|s| s.check_full_hash(hash)).collect())
+    }
+
+    /// The name of the provider that resolved a selector, to attribute the source of its signatures.
+    ///
+    /// Providers aggregating other providers report the one that actually resolved the selector.
+    fn source_of(&self, _selector: &str) -> Option<String> {
+        Some(self.name().to_string())
+    }
+
+    /// The names of the providers that are skipped after failing too many requests in a row.
+    ///
+    /// Only providers aggregating other providers with a circuit breaker trip them, see `ProviderChain`.
+    fn tripped_providers(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Checks that the provider responds by resolving a selector, timing the request.
+    ///
+    /// Providers aggregating other providers check each of them, regardless of their circuit breaker.
+    async fn check(&self, selector: &str) -> Vec<Check> {
+        let start = Instant::now();
+        vec![Check::provider(self.name(), selector, self.is_remote(), start, self.resolve(selector).await)]
+    }
+
+    /// Whether resolving a selector sends a request, which `--cache-only` forbids.
+    ///
+    /// Providers reading a local table or file keep it from being consulted without network access.
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    /// Whether the provider has an event database that `resolve_event` consults.
+    fn resolves_events(&self) -> bool {
+        false
+    }
+
+    /// Whether the provider has a local entry for a selector, which takes precedence over its cached results.
+    ///
+    /// Local signature databases report their entries, so the results of the remote databases cached by
+    /// other runs don't shadow them, while the cache itself is left untouched.
+    fn shadows_cache(&self, _selector: &str) -> bool {
+        false
+    }
+
+    /// The maximum number of selectors a single `resolve_batch` call accepts.
+    ///
+    /// Providers without a batch endpoint keep the default of 1.
+    fn batch_size(&self) -> usize {
+        1
+    }
+
+    /// Resolves several selectors at once, for providers that support batched lookups.
+    ///
+    /// The default implementation resolves the selectors one after the other.
+    ///
+    /// Returns:
+    /// A `Result` containing the matching signatures keyed by selector. Unknown selectors
+    /// may be missing from the map or map to an empty list.
+    async fn resolve_batch(&self, selectors: &[String]) -> Result<HashMap<String, Vec<Signature>>, ProviderError> {
+        let mut results = HashMap::new();
+        for selector in selectors {
+            results.insert(selector.clone(), self.resolve(selector).await?);
+        }
+        Ok(results)
+    }
+
+    /// Resolves an event topic to its known event signatures, ordered from most to least likely.
+    ///
+    /// Providers without an event database keep the default implementation, which knows no event.
+    ///
+    /// Arguments:
+    /// * `topic`: The hex-encoded 32-byte topic, without the "0x" prefix.
+    ///
+    /// Returns:
+    /// A `Result` containing the matching event signatures, which is empty if the topic is unknown,
+    /// or a `ProviderError` if the lookup failed.
+    async fn resolve_event(&self, _topic: &str) -> Result<Vec<EventSignature>, ProviderError> {
+        Ok(Vec::new())
+    }
+}
+
+/// The signature providers that can be selected from the command line or the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// The Etherface signature database (https://etherface.io)
+    Etherface,
+    /// The 4byte.directory signature database (https://www.4byte.directory)
+    Fourbyte,
+    /// The openchain.xyz signature database (https://openchain.xyz)
+    Openchain,
+    /// The bundled table of common signatures, attributed to `offline`
+    Local,
+    /// No signature database, only the cache and the local databases are consulted
+    None,
+}
+
+impl ProviderKind {
+    /// Creates the provider, sharing the given HTTP transport.
+    ///
+    /// Arguments:
+    /// * `http`: The shared HTTP transport.
+    /// * `max_pages`: The maximum number of Etherface result pages fetched per selector.
+    /// * `parse_warnings`: Where the responses that don't have the expected shape are counted.
+    ///
+    /// Returns:
+    /// The provider, or `None` for `none`, and for `local` in builds without the bundled table, which the configuration checks refuse.
+    pub fn build(&self, http: &Transport, max_pages: usize, parse_warnings: &ParseWarnings) -> Option<Box<dyn SignatureProvider>> {
+        match self {
+            Self::Etherface => Some(Box::new(Etherface::new(http.clone()).with_max_pages(max_pages).with_parse_warnings(parse_warnings.clone()))),
+            Self::Fourbyte => Some(Box::new(Fourbyte::new(http.clone()).with_parse_warnings(parse_warnings.clone()))),
+            Self::Openchain => Some(Box::new(Openchain::new(http.clone()).with_parse_warnings(parse_warnings.clone()))),
+            #[cfg(feature = "offline")]
+            Self::Local => Some(Box::new(Offline)),
+            #[cfg(not(feature = "offline"))]
+            Self::Local => None,
+            Self::None => None,
+        }
+    }
+
+    /// The names of every provider, e.g. to list the valid ones.
+    pub fn names() -> Vec<String> {
+        Self::value_variants().iter().filter_map(|kind| kind.to_possible_value()).map(|value| value.get_name().to_string()).collect()
+    }
+}
+
+/// A signature match as returned by a provider, before it is converted into a `Signature`.
+///
+/// Temporal metadata and popularity are optional, since not every provider supplies them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct SignatureItem {
+    pub hash: String,
+    pub text: String,
+    /// When the signature was first indexed by the provider.
+    #[serde(default, alias = "added_at", deserialize_with = "signature::deserialize_timestamp")]
+    pub first_seen: Option<DateTime<Utc>>,
+    /// When the signature was last seen by the provider.
+    #[serde(default, deserialize_with = "signature::deserialize_timestamp")]
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Provider reported popularity, higher is more common.
+    #[serde(default)]
+    pub popularity: Option<u64>,
+}
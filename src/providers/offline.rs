@@ -0,0 +1,66 @@
+use super::{ProviderError, SignatureProvider};
+use crate::signature::{self, Signature};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::OnceLock};
+
+/// Signature provider backed by a table of common signatures embedded in the binary.
+///
+/// It covers the usual token standards (ERC-20, ERC-721, ERC-1155, ERC-4626), access control,
+/// proxies and popular protocols, and resolves selectors without any network access.
+#[derive(Debug, Default)]
+pub struct Offline;
+
+impl Offline {
+    /// The bundled signatures keyed by selector, built on first use.
+    ///
+    /// Only the texts are stored, gzip-compressed, the selectors are derived from them when the table is first used.
+    fn table() -> &'static HashMap<String, Vec<Signature>> {
+        static TABLE: OnceLock<HashMap<String, Vec<Signature>>> = OnceLock::new();
+
+        TABLE.get_or_init(|| {
+            let mut table: HashMap<String, Vec<Signature>> = HashMap::new();
+            for text in signature::common_signatures() {
+                let signature = Signature::new(text.to_string(), signature::hash(text));
+                table.entry(signature.selector.clone()).or_default().push(signature);
+            }
+            table
+        })
+    }
+
+    /// The number of selectors in the bundled table.
+    pub fn len(&self) -> usize {
+        Self::table().len()
+    }
+
+    /// The selectors of the bundled table, sorted.
+    pub fn selectors(&self) -> Vec<String> {
+        let mut selectors: Vec<String> = Self::table().keys().cloned().collect();
+        selectors.sort();
+        selectors
+    }
+
+    /// Whether the bundled table is empty.
+    pub fn is_empty(&self) -> bool {
+        Self::table().is_empty()
+    }
+}
+
+#[async_trait]
+impl SignatureProvider for Offline {
+    fn name(&self) -> &str {
+        "offline"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    /// The whole table is available locally, so any number of selectors is resolved at once.
+    fn batch_size(&self) -> usize {
+        usize::MAX
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        Ok(Self::table().get(selector).cloned().unwrap_or_default())
+    }
+}
@@ -0,0 +1,137 @@
+use super::{ParseWarnings, ProviderError, SignatureProvider};
+use crate::{
+    signature::{EventSignature, Signature},
+    transport::Transport,
+};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The number of selectors sent in a single lookup request.
+const BATCH_SIZE: usize = 50;
+
+/// Openchain API response for a signature lookup.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LookupResponse {
+    pub result: LookupResult,
+}
+
+/// Results of an openchain lookup, keyed by the "0x" prefixed selector or event topic.
+/// Unknown selectors and topics map to `null` or an empty list. The items are parsed one by one
+/// into `OpenchainSignature`s, so an unexpected item only skips itself.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LookupResult {
+    #[serde(default)]
+    pub function: HashMap<String, Option<Vec<Value>>>,
+    #[serde(default)]
+    pub event: HashMap<String, Option<Vec<Value>>>,
+}
+
+/// Item values of the openchain lookup results.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OpenchainSignature {
+    pub name: String,
+    #[serde(default)]
+    pub filtered: bool,
+}
+
+/// Signature provider backed by the openchain.xyz signature database.
+///
+/// Fields:
+/// - `url`: The base URL of the openchain API.
+/// - `http`: The shared HTTP transport used for making requests.
+/// - `parse_warnings`: Where the responses that don't have the expected shape are counted.
+pub struct Openchain {
+    url: String,
+    http: Transport,
+    parse_warnings: ParseWarnings,
+}
+
+impl Openchain {
+    /// Creates an openchain provider using the public API.
+    pub fn new(http: Transport) -> Self {
+        Self::with_url(http, crate::OPENCHAIN_URL)
+    }
+
+    /// Creates an openchain provider using a different base URL, e.g. a mirror or a mock server.
+    pub fn with_url(http: Transport, url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http,
+            parse_warnings: ParseWarnings::default(),
+        }
+    }
+
+    /// Counts the responses that don't have the expected shape in the given `ParseWarnings`, shared with the other providers.
+    pub fn with_parse_warnings(mut self, parse_warnings: ParseWarnings) -> Self {
+        self.parse_warnings = parse_warnings;
+        self
+    }
+
+    /// Sends a lookup request, counting a response that isn't a lookup result in the `ParseWarnings` before failing.
+    async fn lookup(&self, url: &str) -> Result<LookupResult, ProviderError> {
+        let body = self.http.get(url).await?.error_for_status()?.body;
+        let body: Value = serde_json::from_slice(&body)?;
+        let response = LookupResponse::deserialize(&body).inspect_err(|e| self.parse_warnings.record(self.name(), e.to_string(), &body))?;
+        Ok(response.result)
+    }
+
+    /// Parses the items of a selector or topic, skipping the unexpected ones and the entries flagged as spam.
+    fn names(&self, items: Option<Vec<Value>>) -> impl Iterator<Item = String> {
+        let items: Vec<OpenchainSignature> = self.parse_warnings.parse_items(self.name(), items.unwrap_or_default());
+        items.into_iter().filter(|item| !item.filtered).map(|item| item.name)
+    }
+}
+
+#[async_trait]
+impl SignatureProvider for Openchain {
+    fn name(&self) -> &str {
+        "openchain"
+    }
+
+    fn batch_size(&self) -> usize {
+        BATCH_SIZE
+    }
+
+    fn resolves_events(&self) -> bool {
+        true
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let mut results = self.resolve_batch(&[selector.to_string()]).await?;
+        Ok(results.remove(selector).unwrap_or_default())
+    }
+
+    /// Resolves up to `BATCH_SIZE` selectors with a single lookup request.
+    ///
+    /// The `filter` option excludes entries openchain flagged as spam, the remaining
+    /// entries are kept in the order the API returns them.
+    async fn resolve_batch(&self, selectors: &[String]) -> Result<HashMap<String, Vec<Signature>>, ProviderError> {
+        let functions: Vec<_> = selectors.iter().map(|s| format!("0x{s}")).collect();
+        let url = format!("{}/signature-database/v1/lookup?function={}&filter=true", self.url, functions.join(","));
+
+        let results = self
+            .lookup(&url)
+            .await?
+            .function
+            .into_iter()
+            .map(|(selector, items)| {
+                let selector = selector.trim_start_matches("0x").to_lowercase();
+                let signatures = self.names(items).map(|name| Signature::verified(name, &selector)).collect();
+                (selector, signatures)
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Retrieves the event signatures associated with a topic, excluding the entries flagged as spam.
+    async fn resolve_event(&self, topic: &str) -> Result<Vec<EventSignature>, ProviderError> {
+        let url = format!("{}/signature-database/v1/lookup?event=0x{topic}&filter=true", self.url);
+        let events = self.lookup(&url).await?.event;
+
+        let names: Vec<String> = events.into_values().flat_map(|items| self.names(items)).collect();
+        Ok(names.into_iter().filter_map(|name| EventSignature::verified(name, topic)).collect())
+    }
+}
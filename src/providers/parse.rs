@@ -0,0 +1,98 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+/// The depth up to which the shape of an unexpected response is described.
+const SHAPE_DEPTH: usize = 3;
+
+/// The responses of a provider that didn't have the expected shape since they were last taken.
+///
+/// Fields:
+/// - `provider`: The name of the provider.
+/// - `count`: The number of responses and items that didn't have the expected shape.
+/// - `first`: What was wrong with the first of them, e.g. a missing field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    pub provider: String,
+    pub count: usize,
+    pub first: String,
+}
+
+/// Tracks the provider responses that didn't have the expected shape, e.g. after an API change.
+///
+/// The response types ignore unknown fields on purpose, so fields added by a provider don't break the
+/// lookups. A missing expected field only skips the response or the item it's in, and is counted here
+/// instead of being read as no match. The first unexpected response of each provider is described with
+/// its shape at `-vv`, to update the response types from. Clones share the same counts.
+#[derive(Debug, Clone, Default)]
+pub struct ParseWarnings {
+    verbose: u8,
+    counts: Arc<Mutex<BTreeMap<String, ParseWarning>>>,
+    probed: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ParseWarnings {
+    /// Creates the tracker, describing the first unexpected response of each provider when `verbose` is at least 2.
+    pub fn new(verbose: u8) -> Self {
+        Self { verbose, ..Self::default() }
+    }
+
+    /// Counts an unexpected response or item of a provider.
+    ///
+    /// Arguments:
+    /// * `provider`: The name of the provider.
+    /// * `problem`: What was wrong with it, e.g. a missing field.
+    /// * `value`: The unexpected response or item, whose shape is described at `-vv`.
+    pub fn record(&self, provider: &str, problem: impl Into<String>, value: &Value) {
+        let problem = problem.into();
+        if self.verbose > 1 && self.probed.lock().unwrap().insert(provider.to_string()) {
+            eprintln!("{provider}: unexpected response ({problem}), shape: {}", shape(value, SHAPE_DEPTH));
+        }
+
+        let mut counts = self.counts.lock().unwrap();
+        let warning = counts.entry(provider.to_string()).or_insert_with(|| ParseWarning { provider: provider.to_string(), count: 0, first: problem });
+        warning.count += 1;
+    }
+
+    /// Parses the items of a response one by one, so an unexpected item is skipped and counted
+    /// instead of failing the whole response.
+    pub fn parse_items<T: DeserializeOwned>(&self, provider: &str, items: Vec<Value>) -> Vec<T> {
+        items
+            .into_iter()
+            .filter_map(|item| match T::deserialize(&item) {
+                Ok(parsed) => Some(parsed),
+                Err(e) => {
+                    self.record(provider, format!("item skipped, {e}"), &item);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Takes the warnings recorded since the last call, by provider name, so each contract of a batch only reports its own.
+    pub fn take(&self) -> Vec<ParseWarning> {
+        std::mem::take(&mut *self.counts.lock().unwrap()).into_values().collect()
+    }
+}
+
+/// Describes the shape of a JSON value without its data, e.g. `{items: [{hash: string, text: string}]}`.
+fn shape(value: &Value, depth: usize) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Array(_) | Value::Object(_) if depth == 0 => "...".to_string(),
+        Value::Array(items) => match items.first() {
+            Some(item) => format!("[{}]", shape(item, depth - 1)),
+            None => "[]".to_string(),
+        },
+        Value::Object(fields) => {
+            let fields: Vec<String> = fields.iter().map(|(key, value)| format!("{key}: {}", shape(value, depth - 1))).collect();
+            format!("{{{}}}", fields.join(", "))
+        }
+    }
+}
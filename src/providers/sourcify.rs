@@ -0,0 +1,72 @@
+use super::{etherscan::abi_functions, ProviderError};
+use crate::transport::Transport;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Sourcify API response listing the files of a verified contract.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SourcifyFiles {
+    #[serde(default)]
+    pub status: String,
+    pub files: Vec<SourcifyFile>,
+}
+
+/// A file of a verified contract, e.g. its `metadata.json` or a source file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SourcifyFile {
+    pub name: String,
+    pub content: String,
+}
+
+/// Retrieves the ABI of verified contracts from the metadata hosted by Sourcify, which doesn't need an API key.
+///
+/// Like Etherscan, the ABI belongs to a single contract on a single chain, so its functions are exact signatures.
+///
+/// Fields:
+/// - `url`: The base URL of the Sourcify server API.
+/// - `http`: The shared HTTP transport used for making requests.
+pub struct Sourcify {
+    url: String,
+    http: Transport,
+}
+
+impl Sourcify {
+    /// Creates a Sourcify provider using the public server.
+    pub fn new(http: Transport) -> Self {
+        Self::with_url(http, crate::SOURCIFY_URL)
+    }
+
+    /// Creates a Sourcify provider using a different server, e.g. a self-hosted instance or a mock server.
+    pub fn with_url(http: Transport, url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http,
+        }
+    }
+
+    /// Retrieves the function signatures of the ABI in the metadata of a verified contract.
+    ///
+    /// Fully and partially matched contracts are both accepted, since their ABI is the same.
+    ///
+    /// Arguments:
+    /// * `chain_id`: The id of the chain the contract is deployed on.
+    /// * `address`: The "0x" prefixed address of the contract.
+    ///
+    /// Returns:
+    /// A `Result` containing the canonical signatures of the ABI functions, `None` if Sourcify doesn't know the contract,
+    /// or a `ProviderError` if the request failed or the metadata is malformed.
+    pub async fn functions(&self, chain_id: u64, address: &str) -> Result<Option<Vec<String>>, ProviderError> {
+        let response = self.http.get(&format!("{}/files/any/{chain_id}/{address}", self.url)).await?;
+        if response.status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let files: SourcifyFiles = serde_json::from_slice(&response.error_for_status()?.body)?;
+        let metadata = files.files.iter().find(|file| file.name == "metadata.json").ok_or_else(|| ProviderError::Api("no metadata.json".to_string()))?;
+        let metadata: Value = serde_json::from_str(&metadata.content)?;
+        let abi = metadata.pointer("/output/abi").and_then(Value::as_array).ok_or_else(|| ProviderError::Api("no ABI in metadata.json".to_string()))?;
+
+        Ok(Some(abi_functions(abi)?))
+    }
+}
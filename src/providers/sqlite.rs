@@ -0,0 +1,162 @@
+use super::{
+    local::{parse_csv_row, validate_entry},
+    ProviderError, SignatureProvider,
+};
+use crate::signature::Signature;
+use async_trait::async_trait;
+use rusqlite::{Connection, OpenFlags};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::Mutex,
+};
+
+/// The number of rows inserted per transaction while importing a dump.
+pub const IMPORT_BATCH_SIZE: usize = 100_000;
+
+/// The schema of the signature database. The unique constraint indexes the lookups by selector,
+/// and the row ids keep the texts of a selector in import order.
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS signatures (selector TEXT NOT NULL, text TEXT NOT NULL, UNIQUE (selector, text))";
+
+const SELECT: &str = "SELECT text FROM signatures WHERE selector = ?1 ORDER BY rowid";
+
+const INSERT: &str = "INSERT OR IGNORE INTO signatures (selector, text) VALUES (?1, ?2)";
+
+/// The outcome of importing a dump into a signature database.
+///
+/// Fields:
+/// - `imported`: The number of entries added to the database.
+/// - `duplicates`: The number of entries the database already had.
+/// - `skipped`: The number of invalid rows, e.g. whose selector doesn't match the hash of their text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+}
+
+/// Signature provider backed by a local SQLite database, resolving selectors without any network calls.
+///
+/// The database is created from a dump of a public signature database with `import`, and can hold
+/// millions of signatures since lookups go through the selector index instead of loading the whole file.
+///
+/// Fields:
+/// - `connection`: The read-only connection to the database, shared by the concurrent lookups.
+pub struct SqliteProvider {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteProvider {
+    /// Opens a signature database created with `import`, read-only.
+    ///
+    /// Arguments:
+    /// * `path`: The path of the database file.
+    ///
+    /// Returns:
+    /// A `Result` containing the `SqliteProvider`, or a `ProviderError` if the file doesn't exist or isn't a signature database.
+    pub fn open(path: &Path) -> Result<Self, ProviderError> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+        // Fail on a missing table now rather than on the first lookup
+        connection.prepare_cached(SELECT)?;
+        Ok(Self { connection: Mutex::new(connection) })
+    }
+
+    /// Whether the database has an entry for a selector.
+    pub fn contains(&self, selector: &str) -> bool {
+        let connection = self.connection.lock().unwrap();
+        let exists = connection.prepare_cached(SELECT).and_then(|mut statement| statement.exists([selector]));
+        exists.unwrap_or(false)
+    }
+
+    /// Bulk-loads the `selector,text` rows of a CSV dump into a signature database, creating it if it doesn't exist.
+    ///
+    /// The dump is streamed and inserted in transactions of `IMPORT_BATCH_SIZE` rows, so dumps of millions
+    /// of rows neither need to fit in memory nor pay for a transaction per row. Rows are validated like the
+    /// entries of `--local-db`, and entries the database already has are left as they are.
+    ///
+    /// Arguments:
+    /// * `dump`: The path of the CSV dump, in the format of `--local-db`.
+    /// * `db`: The path of the database file.
+    ///
+    /// Returns:
+    /// A `Result` containing the `ImportSummary`, or a `ProviderError` if a file can't be read or written.
+    pub fn import(dump: &Path, db: &Path) -> Result<ImportSummary, ProviderError> {
+        let reader = BufReader::new(std::fs::File::open(dump)?);
+        let mut connection = Connection::open(db)?;
+        connection.execute(SCHEMA, [])?;
+
+        let mut summary = ImportSummary::default();
+        let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+        for line in reader.lines() {
+            let Some((selector, text)) = parse_csv_row(&line?) else { continue };
+            match validate_entry(&selector, &text) {
+                Ok(entry) => batch.push(entry),
+                Err(_) => summary.skipped += 1,
+            }
+
+            if batch.len() == IMPORT_BATCH_SIZE {
+                insert_batch(&mut connection, &batch, &mut summary)?;
+                batch.clear();
+            }
+        }
+        insert_batch(&mut connection, &batch, &mut summary)?;
+
+        Ok(summary)
+    }
+}
+
+/// Inserts the entries in a single transaction, counting the ones the database already had as duplicates.
+fn insert_batch(connection: &mut Connection, entries: &[(String, String)], summary: &mut ImportSummary) -> Result<(), ProviderError> {
+    let transaction = connection.transaction()?;
+    {
+        let mut statement = transaction.prepare_cached(INSERT)?;
+        for (selector, text) in entries {
+            match statement.execute([selector, text])? {
+                0 => summary.duplicates += 1,
+                _ => summary.imported += 1,
+            }
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+#[async_trait]
+impl SignatureProvider for SqliteProvider {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    fn shadows_cache(&self, selector: &str) -> bool {
+        self.contains(selector)
+    }
+
+    /// Lookups are local, so large batches only save the locking of the connection.
+    fn batch_size(&self) -> usize {
+        256
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let mut results = self.resolve_batch(&[selector.to_string()]).await?;
+        Ok(results.remove(selector).unwrap_or_default())
+    }
+
+    async fn resolve_batch(&self, selectors: &[String]) -> Result<HashMap<String, Vec<Signature>>, ProviderError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare_cached(SELECT)?;
+        let mut results = HashMap::new();
+
+        for selector in selectors {
+            let texts = statement.query_map([selector], |row| row.get::<_, String>(0))?;
+            let signatures = texts.map(|text| Ok(Signature::verified(text?, selector))).collect::<Result<_, ProviderError>>()?;
+            results.insert(selector.clone(), signatures);
+        }
+
+        Ok(results)
+    }
+}
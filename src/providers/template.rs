@@ -0,0 +1,134 @@
+use super::{ProviderError, SignatureProvider};
+use crate::{signature::Signature, transport::Transport};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+/// The placeholder replaced by the looked up selector in a URL template.
+pub const SELECTOR_PLACEHOLDER: &str = "{selector}";
+
+/// A step of a `JsonPath`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    /// The value of an object field.
+    Field(String),
+    /// Every element of an array.
+    Each,
+    /// A single element of an array.
+    Index(usize),
+}
+
+/// A path selecting the signature texts in a JSON response, e.g. `results[].signature`.
+///
+/// The path is made of `.` separated object fields, each optionally followed by `[]` to select every
+/// element of an array or `[<n>]` to select a single one. An empty path selects the whole response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonPath {
+    steps: Vec<Step>,
+}
+
+impl JsonPath {
+    /// Parses a path, failing on empty fields and invalid indices.
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let mut steps = Vec::new();
+        if path.is_empty() {
+            return Ok(Self { steps });
+        }
+
+        for segment in path.split('.') {
+            let (field, indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+            if field.is_empty() && (indices.is_empty() || !steps.is_empty()) {
+                return Err(format!("invalid path `{path}`, expected e.g. results[].signature"));
+            }
+            if !field.is_empty() {
+                steps.push(Step::Field(field.to_string()));
+            }
+
+            for index in indices.split_inclusive(']') {
+                let index = index.strip_prefix('[').and_then(|index| index.strip_suffix(']'));
+                match index {
+                    Some("") => steps.push(Step::Each),
+                    Some(index) => steps.push(Step::Index(index.parse().map_err(|_| format!("invalid index `{index}` in path `{path}`"))?)),
+                    None => return Err(format!("invalid path `{path}`, expected e.g. results[].signature")),
+                }
+            }
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Selects the values at the path, skipping the branches where a field or an element is missing.
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut selected = vec![value];
+
+        for step in &self.steps {
+            selected = selected
+                .into_iter()
+                .flat_map(|value| -> Vec<&Value> {
+                    match step {
+                        Step::Field(field) => value.get(field).into_iter().collect(),
+                        Step::Each => value.as_array().map(|values| values.iter().collect()).unwrap_or_default(),
+                        Step::Index(index) => value.get(index).into_iter().collect(),
+                    }
+                })
+                .collect();
+        }
+
+        selected
+    }
+}
+
+/// Signature provider backed by a custom signature database, e.g. an internal one, queried through a URL template.
+///
+/// Each lookup requests the template with `{selector}` replaced by the selector, without the "0x" prefix.
+/// The signature texts are selected from the JSON response with a `JsonPath`, texts may be strings or lists
+/// of strings. Selectors the database responds to with `404` are unknown.
+///
+/// Fields:
+/// - `template`: The URL template, containing `{selector}`.
+/// - `path`: The path of the signature texts in the responses.
+/// - `http`: The shared HTTP transport used for making requests.
+pub struct UrlTemplate {
+    template: String,
+    path: JsonPath,
+    http: Transport,
+}
+
+impl UrlTemplate {
+    /// Creates a provider for a URL template, which must contain `{selector}`.
+    pub fn new(http: Transport, template: &str, path: JsonPath) -> Self {
+        Self { template: template.to_string(), path, http }
+    }
+}
+
+#[async_trait]
+impl SignatureProvider for UrlTemplate {
+    fn name(&self) -> &str {
+        "custom"
+    }
+
+    /// Retrieves the signatures associated with a selector, in the order of the response.
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let response = self.http.get(&self.template.replace(SELECTOR_PLACEHOLDER, selector)).await?;
+        if response.status == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+
+        let body: Value = serde_json::from_slice(&response.error_for_status()?.body)?;
+        let mut texts: Vec<&str> = Vec::new();
+        for value in self.path.select(&body) {
+            match value {
+                Value::Array(values) => texts.extend(values.iter().filter_map(Value::as_str)),
+                value => texts.extend(value.as_str()),
+            }
+        }
+
+        let mut signatures: Vec<Signature> = Vec::new();
+        for text in texts {
+            if !signatures.iter().any(|s| s.text == text) {
+                signatures.push(Signature::verified(text.to_string(), selector));
+            }
+        }
+        Ok(signatures)
+    }
+}
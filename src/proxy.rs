@@ -0,0 +1,173 @@
+use crate::bytecode::Bytecode;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+
+/// The EIP-1967 slot holding the implementation of a proxy, `keccak256("eip1967.proxy.implementation") - 1`.
+pub const IMPLEMENTATION_SLOT: &str = "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+/// The EIP-1967 slot holding the admin of a proxy, `keccak256("eip1967.proxy.admin") - 1`.
+pub const ADMIN_SLOT: &str = "0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103";
+/// The EIP-1967 slot holding the beacon of a beacon proxy, `keccak256("eip1967.proxy.beacon") - 1`.
+pub const BEACON_SLOT: &str = "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+/// The selector of `implementation()`, which a beacon answers with the implementation of its proxies.
+pub(crate) const IMPLEMENTATION_SELECTOR: &str = "0x5c60da1b";
+
+/// Error types for selecting a hop of the resolution chain.
+#[derive(Error, Debug, PartialEq)]
+pub enum TargetError {
+    /// The target string is not one of the supported forms.
+    #[error("Invalid target `{0}`, expected implementation, proxy, beacon or facet:<address>")]
+    Invalid(String),
+
+    /// The resolution chain has no hop of the requested kind.
+    #[error("The resolution chain has no {0} to analyze")]
+    Missing(String),
+
+    /// A facet was requested, but the facets of the contract were not enumerated.
+    #[error("Selecting a facet requires the diamond facets to be enumerated first")]
+    FacetsNotEnumerated,
+}
+
+/// The role of a contract in the resolution chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HopKind {
+    /// The contract given as input, which is the proxy when the chain has further hops.
+    Input,
+    Beacon,
+    Implementation,
+    Facet,
+    /// The delegate of an EOA, whose code the calls to the EOA run per EIP-7702.
+    Delegate,
+}
+
+/// A contract encountered while resolving proxies.
+///
+/// Fields:
+/// * `kind`: The role of the contract in the chain.
+/// * `address`: The address of the contract, unknown when the input is a local file.
+/// * `bytecode`: The runtime code of the contract.
+pub struct Hop {
+    pub kind: HopKind,
+    pub address: Option<String>,
+    pub bytecode: Bytecode,
+}
+
+/// The contracts involved in serving calls to the input contract, in resolution order.
+///
+/// The first hop is always the input contract. Proxy resolution appends the beacon,
+/// implementation and facet contracts it discovers.
+///
+/// Fields:
+/// * `hops`: The contracts in resolution order.
+/// * `facets_enumerated`: Whether the facets of a diamond were enumerated.
+/// * `admin`: The admin of an EIP-1967 proxy of the chain, read from its admin slot.
+pub struct ResolutionChain {
+    pub hops: Vec<Hop>,
+    pub facets_enumerated: bool,
+    pub admin: Option<String>,
+}
+
+impl ResolutionChain {
+    /// Creates a chain containing only the input contract.
+    pub fn new(address: Option<String>, bytecode: Bytecode) -> Self {
+        Self {
+            hops: vec![Hop { kind: HopKind::Input, address, bytecode }],
+            facets_enumerated: false,
+            admin: None,
+        }
+    }
+
+    /// Selects the hop whose bytecode the rest of the run operates on.
+    ///
+    /// * `implementation`: The final implementation or the delegate of an EOA, which is the input itself when it isn't a proxy.
+    /// * `proxy`: The input contract.
+    /// * `beacon`: The beacon of a beacon proxy.
+    /// * `facet:<address>`: A facet of a diamond, which requires the facets to be enumerated.
+    ///
+    /// Returns:
+    /// A `Result` containing the selected `Hop`, or a `TargetError` if the chain has no such hop.
+    pub fn select(&self, target: &Target) -> Result<&Hop, TargetError> {
+        let find = |kind: HopKind| self.hops.iter().rev().find(|hop| hop.kind == kind);
+
+        match target {
+            // The chain always contains the input, so there is at least one hop
+            Target::Implementation => Ok(self.hops.iter().rev().find(|hop| matches!(hop.kind, HopKind::Implementation | HopKind::Delegate)).unwrap_or(&self.hops[0])),
+            Target::Proxy => Ok(&self.hops[0]),
+            Target::Beacon => find(HopKind::Beacon).ok_or_else(|| TargetError::Missing("beacon".to_string())),
+            Target::Facet(address) => {
+                if !self.facets_enumerated {
+                    return Err(TargetError::FacetsNotEnumerated);
+                }
+
+                self.hops
+                    .iter()
+                    .find(|hop| hop.kind == HopKind::Facet && hop.address.as_ref().is_some_and(|a| a.eq_ignore_ascii_case(address)))
+                    .ok_or_else(|| TargetError::Missing(format!("facet {address}")))
+            }
+        }
+    }
+}
+
+/// The hop of the resolution chain to analyze, selected with `--target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Implementation,
+    Proxy,
+    Beacon,
+    Facet(String),
+}
+
+impl FromStr for Target {
+    type Err = TargetError;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        match target {
+            "implementation" => Ok(Self::Implementation),
+            "proxy" => Ok(Self::Proxy),
+            "beacon" => Ok(Self::Beacon),
+            _ => match target.strip_prefix("facet:") {
+                Some(address) if crate::Address::validate(address).is_ok() => Ok(Self::Facet(address.to_string())),
+                _ => Err(TargetError::Invalid(target.to_string())),
+            },
+        }
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Implementation => write!(f, "implementation"),
+            Self::Proxy => write!(f, "proxy"),
+            Self::Beacon => write!(f, "beacon"),
+            Self::Facet(address) => write!(f, "facet:{address}"),
+        }
+    }
+}
+
+impl Serialize for Target {
+    /// Serializes the target in the same form it is given on the command line.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Target {
+    /// Deserializes the target from the form it is given on the command line.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The address held by a storage word, e.g. the result of `eth_getStorageAt` on an EIP-1967 slot.
+///
+/// Returns:
+/// The lowercase, 0x-prefixed address of the lower 20 bytes of the word, or `None` for an empty slot or a word that isn't hex.
+pub(crate) fn word_address(word: &str) -> Option<String> {
+    let word = word.trim_start_matches("0x");
+    let word = hex::decode(format!("{word:0>64}")).ok().filter(|word| word.len() == 32)?;
+    match word[12..].iter().all(|byte| *byte == 0) {
+        true => None,
+        false => Some(format!("0x{}", hex::encode(&word[12..]))),
+    }
+}
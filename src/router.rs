@@ -0,0 +1,96 @@
+use crate::{
+    client::{CallOutcome, Client, ClientError},
+    signature,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::Path};
+use thiserror::Error;
+
+/// A selector no contract is expected to route, whose outcome is the router's rejection.
+const BASELINE_SELECTOR: &str = "ffffffff";
+/// The number of zero words appended to each probed selector, so functions taking a few static arguments can decode them.
+const ARGUMENT_WORDS: usize = 4;
+
+/// Error types for probing a storage-mapped router.
+#[derive(Error, Debug)]
+pub enum RouterError {
+    #[error("{0}")]
+    Client(#[from] ClientError),
+    /// The baseline call succeeded, so a rejected selector can't be told apart from an accepted one.
+    #[error("RouterError: The router accepts any selector, e.g. through a fallback, so probing is inconclusive")]
+    AcceptsEverything,
+}
+
+/// The selectors a storage-mapped router accepts, discovered by calling it.
+///
+/// Fields:
+/// * `probed`: The number of selectors probed.
+/// * `accepted`: The selectors whose call had a different outcome than an unknown selector, sorted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouterProbe {
+    pub probed: usize,
+    pub accepted: Vec<String>,
+}
+
+impl RouterProbe {
+    /// Probes which of the given selectors a storage-mapped router accepts.
+    ///
+    /// A router rejects unknown selectors the same way every time, e.g. with a "function does not exist"
+    /// revert. The rejection of a selector no contract routes is used as the baseline, every selector whose
+    /// `eth_call` has a different outcome, be it success or another revert, is accepted by the router.
+    ///
+    /// Arguments:
+    /// * `client`: The client used for the calls, which are sent within its concurrency limit.
+    /// * `address`: The address of the router.
+    /// * `selectors`: The hex-encoded selectors to probe, without the "0x" prefix.
+    ///
+    /// Returns:
+    /// A `Result` containing the `RouterProbe`, or a `RouterError` if a call failed or the probe is inconclusive.
+    pub async fn run(client: &Client, address: &str, selectors: &[String]) -> Result<Self, RouterError> {
+        let baseline = client.call(address, &calldata(BASELINE_SELECTOR)).await?;
+        if let CallOutcome::Returned(_) = baseline {
+            return Err(RouterError::AcceptsEverything);
+        }
+
+        let calls = selectors.iter().map(|selector| async move { Ok::<_, ClientError>((selector, client.call(address, &calldata(selector)).await?)) });
+        let outcomes: Vec<_> = stream::iter(calls).buffer_unordered(client.concurrency()).try_collect().await?;
+
+        let mut accepted: Vec<String> = outcomes.into_iter().filter(|(_, outcome)| *outcome != baseline).map(|(selector, _)| selector.clone()).collect();
+        accepted.sort();
+
+        Ok(Self { probed: selectors.len(), accepted })
+    }
+}
+
+impl Display for RouterProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "storage router accepts {} of {} probed selectors", self.accepted.len(), self.probed)
+    }
+}
+
+/// The calldata of a probe, the selector followed by zeroed arguments.
+fn calldata(selector: &str) -> String {
+    format!("0x{selector}{}", "0".repeat(ARGUMENT_WORDS * 64))
+}
+
+/// Reads the selectors to probe from a file, one 4-byte selector or signature per line.
+///
+/// Signatures are hashed to their selector, empty lines and lines starting with `#` are skipped.
+/// Without the `keccak` feature signatures can't be hashed, and are skipped as well.
+pub fn read_known(path: &Path) -> std::io::Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let selector = line.trim_start_matches("0x").to_lowercase();
+            match selector.len() == 8 && hex::decode(&selector).is_ok() {
+                true => Some(selector),
+                false => Some(signature::try_hash(&signature::canonicalize(line)).ok()?[..8].to_string()),
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,96 @@
+use crate::client::{Client, ClientError, Pace};
+use futures::{stream, StreamExt, TryStreamExt};
+use std::{ops::RangeInclusive, path::Path};
+use thiserror::Error;
+
+/// Error types for scanning blocks for the contracts they created.
+#[derive(Error, Debug)]
+pub enum ScanError {
+    #[error("{0}")]
+    Client(#[from] ClientError),
+    /// The checkpoint file couldn't be read or written, or doesn't hold a block number.
+    #[error("ScanError: The checkpoint {path} {message}")]
+    Checkpoint { path: String, message: String },
+}
+
+/// A contract created by a transaction of a scanned block.
+///
+/// Fields:
+/// * `block`: The number of the block.
+/// * `transaction`: The hash of the creating transaction.
+/// * `address`: The lowercase address of the created contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Creation {
+    pub block: u64,
+    pub transaction: String,
+    pub address: String,
+}
+
+/// Finds the contracts created in a page of blocks, whose blocks are fetched concurrently.
+///
+/// Returns:
+/// A `Result` containing the creations in block order and the number of blocks of the page that are mined,
+/// the ones after the first block that isn't mined yet being left out, or a `ClientError` if a request failed.
+pub(crate) async fn page_creations(client: &Client, blocks: RangeInclusive<u64>, pace: &Pace) -> Result<(Vec<Creation>, u64), ClientError> {
+    let blocks: Vec<_> = stream::iter(blocks).map(|number| block_creations(client, number, pace)).buffered(client.concurrency()).try_collect().await?;
+    let mined = blocks.iter().take_while(|block| block.is_some()).count() as u64;
+    Ok((blocks.into_iter().map_while(|block| block).flatten().collect(), mined))
+}
+
+/// Finds the contracts created by the transactions of a block, those without a recipient, from their receipts.
+///
+/// Only the contracts the transactions create themselves are found, not the ones they create through a factory.
+/// A creation that reverted created nothing and is skipped.
+///
+/// Arguments:
+/// * `client`: The client of the RPC.
+/// * `number`: The number of the block.
+/// * `pace`: The pace the block and its receipts are requested at.
+///
+/// Returns:
+/// A `Result` containing the creations in transaction order, `None` if the block isn't mined yet,
+/// or a `ClientError` if a request failed.
+async fn block_creations(client: &Client, number: u64, pace: &Pace) -> Result<Option<Vec<Creation>>, ClientError> {
+    pace.wait().await;
+    let Some(transactions) = client.get_mined_block_transactions(number).await? else {
+        return Ok(None);
+    };
+
+    let receipts = transactions.into_iter().filter(|tx| tx.to.is_none()).map(|tx| async move {
+        pace.wait().await;
+        Ok::<_, ClientError>((client.get_transaction_receipt(&tx.hash).await?, tx.hash))
+    });
+    let receipts: Vec<_> = stream::iter(receipts).buffered(client.concurrency()).try_collect().await?;
+
+    let creations = receipts
+        .into_iter()
+        .filter_map(|(receipt, transaction)| {
+            let receipt = receipt.filter(|receipt| receipt.status.as_deref() != Some("0x0"))?;
+            Some(Creation { block: number, transaction, address: receipt.contract_address?.to_lowercase() })
+        })
+        .collect();
+    Ok(Some(creations))
+}
+
+/// Reads the last block a previous scan completed from its checkpoint file.
+///
+/// Returns:
+/// A `Result` containing the block number, `None` if the file doesn't exist yet, or a `ScanError` if it can't be read.
+pub(crate) fn read_checkpoint(path: &Path) -> Result<Option<u64>, ScanError> {
+    let error = |message: String| ScanError::Checkpoint { path: path.display().to_string(), message };
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(error(format!("couldn't be read ({e})"))),
+    };
+    content.trim().parse().map(Some).map_err(|_| error(format!("doesn't hold a block number: `{}`", content.trim())))
+}
+
+/// Records the last block whose contracts were all analyzed, replacing the file at once so a killed scan
+/// never leaves it half written.
+pub(crate) fn write_checkpoint(path: &Path, block: u64) -> Result<(), ScanError> {
+    let error = |e: std::io::Error| ScanError::Checkpoint { path: path.display().to_string(), message: format!("couldn't be written ({e})") };
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, format!("{block}\n")).map_err(error)?;
+    std::fs::rename(&temporary, path).map_err(error)
+}
@@ -0,0 +1,177 @@
+use crate::{doctor::Check, format, Bytecode, DispatcherStyle, EvmoleSink, MinimalOut, Signature, SigmundOut};
+use std::{
+    collections::{BTreeSet, HashSet},
+    time::{Duration, Instant},
+};
+
+/// A contract whose selectors are known, assembled after the dispatcher its compiler emits.
+///
+/// Fields:
+/// * `name`: The name of the fixture.
+/// * `code`: The hex-encoded runtime bytecode.
+/// * `deep`: Whether the selectors are only found by a deep scan, e.g. behind the XOR comparisons of Vyper.
+/// * `style`: The dispatcher `Bytecode::dispatcher` must recognize.
+/// * `expected`: The functions of the contract, one `<selector> <signature>` per line.
+struct Fixture {
+    name: &'static str,
+    code: &'static str,
+    deep: bool,
+    style: DispatcherStyle,
+    expected: &'static str,
+}
+
+const FIXTURES: [Fixture; 4] = [
+    Fixture {
+        name: "weth9",
+        code: include_str!("../data/selftest/weth9.hex"),
+        deep: false,
+        style: DispatcherStyle::LinearEqChain,
+        expected: include_str!("../data/selftest/weth9.txt"),
+    },
+    Fixture {
+        name: "erc721",
+        code: include_str!("../data/selftest/erc721.hex"),
+        deep: false,
+        style: DispatcherStyle::BinarySearch,
+        expected: include_str!("../data/selftest/erc721.txt"),
+    },
+    Fixture {
+        name: "vyper",
+        code: include_str!("../data/selftest/vyper.hex"),
+        deep: true,
+        style: DispatcherStyle::VyperTable,
+        expected: include_str!("../data/selftest/vyper.txt"),
+    },
+    Fixture {
+        name: "via-ir",
+        code: include_str!("../data/selftest/via_ir.hex"),
+        deep: false,
+        style: DispatcherStyle::LinearEqChain,
+        expected: include_str!("../data/selftest/via_ir.txt"),
+    },
+];
+
+/// Keccak-256 vectors, the hash of the empty input and of well-known signatures.
+const KECCAK_VECTORS: [(&str, &str); 3] = [
+    ("", "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+    ("transfer(address,uint256)", "a9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b"),
+    ("Transfer(address,address,uint256)", "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+];
+
+/// The EIP-55 test vectors, all caps, all lowercase and mixed case addresses.
+const CHECKSUM_VECTORS: [&str; 8] = [
+    "0x52908400098527886E0F7030069857D2E4169EE7",
+    "0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+    "0xde709f2102306220921060314715629080e2fb77",
+    "0x27b1fdb04752bbc536007a920d24acb045561c26",
+    "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+    "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+    "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+    "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+];
+
+/// The golden output of `--format json-min` for `golden_out`.
+const GOLDEN_JSON_MIN: &str = r#"{"selectors":["0x70a08231","0xa9059cbb","0xdeadbeef"],"signatures":{"0x70a08231":"balanceOf(address)","0xa9059cbb":"transfer(address,uint256)"}}"#;
+
+/// The golden output of `--format evmole` for `golden_out`, keyed by `token`.
+const GOLDEN_EVMOLE: &str = r#"{"token":["70a08231","a9059cbb","deadbeef"]}"#;
+
+/// Runs the built-in known-answer checks, without any network.
+///
+/// The selectors extracted from the embedded fixtures are compared against their expected functions, the keccak
+/// and EIP-55 checksum vectors are verified, and the output formatters are compared against golden strings.
+/// The keccak and checksum checks, including whether the expected signatures hash to their selectors,
+/// need sigmund built with the `keccak` feature and are left out otherwise.
+///
+/// Returns:
+/// The checks, one per fixture, vector and formatter.
+pub fn run() -> Vec<Check> {
+    let mut checks: Vec<Check> = FIXTURES.iter().map(check_fixture).collect();
+
+    if cfg!(feature = "keccak") {
+        checks.extend(FIXTURES.iter().map(check_signatures));
+        checks.extend(KECCAK_VECTORS.iter().map(|&(text, hash)| {
+            let start = Instant::now();
+            let outcome = crate::signature::try_keccak(text.as_bytes()).map_err(|e| e.to_string()).and_then(|actual| match hex::encode(actual) {
+                actual if actual == hash => Ok(actual),
+                actual => Err(format!("expected {hash}, got {actual}")),
+            });
+            Check::timed(format!("keccak {text:?}"), start, outcome)
+        }));
+        checks.extend(CHECKSUM_VECTORS.iter().map(|&vector| {
+            let start = Instant::now();
+            let outcome = crate::Address::try_from(vector.to_lowercase()).map_err(|e| e.to_string()).and_then(|address| match address.checksummed() {
+                actual if actual == vector => Ok(actual),
+                actual => Err(format!("expected {vector}, got {actual}")),
+            });
+            Check::timed(format!("checksum {vector}"), start, outcome)
+        }));
+    }
+
+    let out = golden_out();
+    checks.push(golden("format json-min", || serde_json::to_string(&MinimalOut::from(&out)).unwrap_or_default(), GOLDEN_JSON_MIN));
+    checks.push(golden("format evmole", || EvmoleSink::new(false).render("token", &out).to_string(), GOLDEN_EVMOLE));
+    checks.push(golden(
+        "format human",
+        || format!("{} {} {} {}", format::human_duration(Duration::from_millis(2100)), format::human_bytes(1536), format::human_count(10_000_000), format::pct(0.425)),
+        "2.1s 1.5 KiB 10,000,000 42.5%",
+    ));
+    checks
+}
+
+/// Checks that the selectors extracted from a fixture are exactly its expected ones, and that its dispatcher is recognized.
+fn check_fixture(fixture: &Fixture) -> Check {
+    let start = Instant::now();
+    let outcome = Bytecode::try_from(fixture.code.trim().to_string()).map_err(|e| e.to_string()).and_then(|bytecode| {
+        let found: BTreeSet<String> = bytecode.find_function_selectors(fixture.deep).into_iter().collect();
+        let expected: BTreeSet<String> = expected(fixture).map(|(selector, _)| selector.to_string()).collect();
+        let dispatcher = bytecode.dispatcher();
+
+        let missing: Vec<&String> = expected.difference(&found).collect();
+        let unexpected: Vec<&String> = found.difference(&expected).collect();
+        match (missing.is_empty() && unexpected.is_empty(), dispatcher.style == fixture.style) {
+            (true, true) => Ok(format!("{} selectors, {dispatcher}", found.len())),
+            (false, _) => Err(format!("missing {missing:?}, unexpected {unexpected:?}")),
+            (true, false) => Err(format!("expected a {:?} dispatcher, recognized {dispatcher}", fixture.style)),
+        }
+    });
+    Check::timed(format!("selectors {}", fixture.name), start, outcome)
+}
+
+/// Checks that each expected signature of a fixture hashes to its selector, so the expected sets can't drift.
+fn check_signatures(fixture: &Fixture) -> Check {
+    let start = Instant::now();
+    let mismatched: Vec<&str> = expected(fixture).filter(|(selector, text)| !Signature::new(text.to_string(), selector.to_string()).verified).map(|(_, text)| text).collect();
+    let outcome = match mismatched.is_empty() {
+        true => Ok(format!("{} signatures hash to their selector", expected(fixture).count())),
+        false => Err(format!("{} don't hash to their selector", mismatched.join(", "))),
+    };
+    Check::timed(format!("signatures {}", fixture.name), start, outcome)
+}
+
+/// The expected `(selector, signature)` pairs of a fixture, skipping comments and blank lines.
+fn expected(fixture: &Fixture) -> impl Iterator<Item = (&'static str, &'static str)> {
+    fixture.expected.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).filter_map(|line| line.split_once(' '))
+}
+
+/// Checks the output of a formatter against its golden string.
+fn golden(component: &str, render: impl FnOnce() -> String, expected: &str) -> Check {
+    let start = Instant::now();
+    let outcome = match render() {
+        actual if actual == expected => Ok(actual),
+        actual => Err(format!("expected {expected}, got {actual}")),
+    };
+    Check::timed(component, start, outcome)
+}
+
+/// The output the formatters are checked with: two resolved selectors and an unresolved one.
+fn golden_out() -> SigmundOut {
+    let signatures = [
+        ("transfer(address,uint256)", "a9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b"),
+        ("balanceOf(address)", "70a08231b98ef4ca268c9cc3f6b4590e4bfec28280db06bb5d45e689f2a360be"),
+    ]
+    .map(|(text, hash)| Signature::new(text.to_string(), hash.to_string()));
+    let mut out = SigmundOut::new(HashSet::from(["a9059cbb", "70a08231", "deadbeef"].map(str::to_string)), signatures.to_vec());
+    out.unresolved = vec!["deadbeef".to_string()];
+    out
+}
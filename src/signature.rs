@@ -1,6 +1,106 @@
-use crate::client::SignatureItem;
-use serde::{Deserialize, Serialize};
-use std::fmt::Display;
+use crate::{
+    abi::{self, ParamType},
+    format,
+    providers::SignatureItem,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    io::Read,
+    sync::OnceLock,
+};
+use thiserror::Error;
+#[cfg(feature = "keccak")]
+use tiny_keccak::{Hasher, Keccak};
+
+/// The bundled table of common signatures, gzip-compressed from `data/common_signatures.txt`.
+const COMMON_SIGNATURES_GZ: &[u8] = include_bytes!("../data/common_signatures.txt.gz");
+
+/// The parameter types most functions take, any other type such as `bytes1` or `int40` makes a match less likely.
+const COMMON_TYPES: [&str; 17] = [
+    "address", "uint256", "bool", "bytes32", "bytes", "string", "uint8", "uint16", "uint24", "uint32", "uint64", "uint96", "uint128", "uint160",
+    "int24", "int256", "bytes4",
+];
+
+/// The weights of the ranking heuristics, see `score`.
+const ABI_WEIGHT: i64 = 1000;
+const COMMON_WEIGHT: i64 = 100;
+const PENALTY_WEIGHT: i64 = 10;
+
+/// Error types for operations on signatures.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("VerificationUnavailable: sigmund was built without keccak support, enable the `keccak` feature")]
+    VerificationUnavailable,
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+}
+
+/// Error types for parsing a signature text into a `ParsedSignature`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("ParseError: `{0}` has no parameter list")]
+    MissingParameters(String),
+    #[error("ParseError: `{0}` doesn't start with a valid function name")]
+    InvalidName(String),
+    #[error("ParseError: `{0}` has unbalanced parentheses")]
+    UnbalancedParentheses(String),
+    #[error("ParseError: `{param}` isn't a valid parameter of `{text}`")]
+    InvalidParameter { text: String, param: String },
+}
+
+/// The selector of a signature text, computed locally by the `selector` command.
+///
+/// Fields:
+/// * `text`: The text as it was given.
+/// * `canonical`: The canonical text that was hashed, see `canonicalize`.
+/// * `selector`: The "0x" prefixed 4-byte selector.
+/// * `hash`: The "0x" prefixed 32-byte hash, e.g. the topic of an event, present when requested.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextSelector {
+    pub text: String,
+    pub canonical: String,
+    pub selector: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl TextSelector {
+    /// Computes the selector and the full hash of a signature text, once canonicalized.
+    ///
+    /// Returns:
+    /// The `TextSelector`, or a `SignatureError` if the text can't be parsed or sigmund is built without the `keccak` feature.
+    pub fn compute(text: &str) -> Result<Self, SignatureError> {
+        let canonical = ParsedSignature::parse(text)?.to_string();
+        let hash = try_hash(&canonical)?;
+        Ok(Self { text: text.to_string(), canonical, selector: format!("0x{}", &hash[..8]), hash: Some(format!("0x{hash}")) })
+    }
+}
+
+impl Display for TextSelector {
+    /// Formats the selector followed by the canonical text, with the full hash in between when it's kept.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.hash {
+            Some(hash) => write!(f, "{} {hash} {}", self.selector, self.canonical),
+            None => write!(f, "{} {}", self.selector, self.canonical),
+        }
+    }
+}
+
+/// A signature text split into its parts, e.g. to generate an ABI or an interface.
+///
+/// Fields:
+/// * `name`: The function, error or event name.
+/// * `inputs`: The types of the parameters, in order, without their names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedSignature {
+    pub name: String,
+    pub inputs: Vec<ParamType>,
+}
 
 /// A representation of a signature extracted from a smart contract.
 ///
@@ -11,11 +111,56 @@ use std::fmt::Display;
 /// * `text`: The function signature.
 /// * `hash`: The Keccak-256 hash of the signature.
 /// * `selector`: The 4-byte function selector derived from the hash.
+/// * `verified`: Whether the keccak-256 hash of the text, computed locally, starts with the selector.
+///   Always `false` when sigmund is built without the `keccak` feature.
+/// * `first_seen`: When the signature was first indexed, if the provider supplies it.
+/// * `last_seen`: When the signature was last seen, if the provider supplies it.
+/// * `variants`: Lower ranked matches of the selector with the same function name, when grouped.
+/// * `truncated`: The number of lower ranked matches of the selector dropped by the matches cap.
+/// * `guessed`: Whether the signature was guessed locally instead of being found in a database.
+/// * `kind`: Whether the selector was found as a function or as a custom error.
+/// * `full_hash_verified`: Whether the text was looked up by, and hashes to, a full 32-byte hash rather than only its selector.
+/// * `source`: The provider that resolved the signature, e.g. `etherscan`, `openchain`, `local`, `offline` or `bruteforce`.
+///   Signatures resolved from the cache keep the provider they were cached from.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Signature {
     pub text: String,
     pub hash: String,
     pub selector: String,
+    #[serde(default)]
+    pub verified: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<usize>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub guessed: bool,
+    #[serde(default, skip_serializing_if = "SignatureKind::is_function")]
+    pub kind: SignatureKind,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub full_hash_verified: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source: String,
+}
+
+/// What a selector is used for, functions are called through the dispatcher while custom errors are reverted with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureKind {
+    #[default]
+    Function,
+    Error,
+}
+
+impl SignatureKind {
+    /// Whether the kind is `Function`, the default which is left out of the JSON output.
+    pub fn is_function(&self) -> bool {
+        *self == Self::Function
+    }
 }
 
 impl Signature {
@@ -29,23 +174,103 @@ impl Signature {
     /// Returns:
     /// A new `Signature` instance.
     pub fn new(text: String, hash: String) -> Self {
+        let selector = format::selector(&hash).to_string();
         Self {
-            selector: format!("{:.8}", hash),
+            verified: try_hash(&canonicalize(&text)).is_ok_and(|hash| hash.starts_with(&selector)),
+            selector,
             hash,
             text,
+            first_seen: None,
+            last_seen: None,
+            variants: Vec::new(),
+            truncated: None,
+            guessed: false,
+            kind: SignatureKind::Function,
+            full_hash_verified: false,
+            source: String::new(),
+        }
+    }
+
+    /// Creates a `Signature` for a text returned for the given selector, verifying the match locally.
+    ///
+    /// The hash is computed from the canonical text (see `canonicalize`) instead of trusting the one reported by a provider,
+    /// and the signature is flagged as unverified if it doesn't start with the selector.
+    /// Without the `keccak` feature the hash is only the selector, and the signature is unverified.
+    ///
+    /// Arguments:
+    /// * `text`: The full text of the signature.
+    /// * `selector`: The hex-encoded selector the text was returned for.
+    ///
+    /// Returns:
+    /// A new `Signature` instance.
+    pub fn verified(text: String, selector: &str) -> Self {
+        let selector = selector.trim_start_matches("0x").to_lowercase();
+        let hash = try_hash(&canonicalize(&text)).unwrap_or_else(|_| selector.clone());
+        Self {
+            verified: hash.starts_with(&selector),
+            selector,
+            ..Self::new(text, hash)
+        }
+    }
+
+    /// Checks whether the keccak-256 hash of the canonical text starts with the selector.
+    ///
+    /// Returns:
+    /// A `Result` containing whether the text matches the selector, or `SignatureError::VerificationUnavailable`
+    /// when sigmund is built without the `keccak` feature.
+    pub fn verify(&self) -> Result<bool, SignatureError> {
+        Ok(try_hash(&canonicalize(&self.text))?.starts_with(&self.selector))
+    }
+
+    /// Checks the signature against the full 32-byte hash it was looked up by, which leaves no room for collisions.
+    ///
+    /// Without the `keccak` feature the text can't be checked, and the signature is kept as it is.
+    ///
+    /// Arguments:
+    /// * `hash`: The hex-encoded 32-byte hash, without the "0x" prefix.
+    ///
+    /// Returns:
+    /// The signature flagged as `full_hash_verified`, or `None` if its text doesn't hash to `hash`.
+    pub fn check_full_hash(self, hash: &str) -> Option<Self> {
+        match try_hash(&canonicalize(&self.text)) {
+            Ok(computed) if computed != hash => None,
+            Ok(_) => Some(Self { full_hash_verified: true, ..self }),
+            Err(_) => Some(self),
         }
     }
+
+    /// Attributes the signature to the provider that resolved it, unless it already is.
+    pub fn with_source(self, source: &str) -> Self {
+        match self.source.is_empty() {
+            true => Self { source: source.to_string(), ..self },
+            false => self,
+        }
+    }
+
+    /// The function name of the signature, i.e. the text before the parameters.
+    pub fn name(&self) -> &str {
+        self.text.split('(').next().unwrap_or_default().trim()
+    }
+
+    /// Parses the text into its name and parameter types, see `ParsedSignature::parse`.
+    pub fn parse(&self) -> Result<ParsedSignature, ParseError> {
+        ParsedSignature::parse(&self.text)
+    }
 }
 
 // Constants for terminal coloring
 const BLUE: &str = "\x1b[38;5;39m";
 const GRAY: &str = "\x1b[38;5;248m";
+const DIM: &str = "\x1b[2m";
+const UNDIM: &str = "\x1b[22m";
 
 impl Display for Signature {
     /// Formats a `Signature` for display.
     ///
     /// Represents the `Signature` in a formatted string, suitable for console output.
-    /// The hash is displayed in blue, and the signature text in gray.
+    /// The selector is displayed in blue, and the signature text in gray, followed by a mark if the text
+    /// doesn't hash to the selector, the number of grouped variants and the dimmed source.
+    /// The alternate form (`{:#}`) additionally shows when the signature was first and last seen.
     ///
     /// Arguments:
     /// * `f`: The formatter.
@@ -53,38 +278,543 @@ impl Display for Signature {
     /// Returns:
     /// A `Result` as per the `std::fmt::Display` trait.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let out = format!("{BLUE}[{:.8}]: {GRAY}{}", self.hash, self.text);
-        write!(f, "{}", out)
+        write!(f, "{BLUE}[{}]: ", self.selector)?;
+        self.fmt_text(f)
+    }
+}
+
+impl Signature {
+    /// Formats the text of the signature in gray along with its marks, as shown after the selector.
+    fn fmt_text(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{GRAY}{}", self.text)?;
+
+        // Without keccak support no signature is verified, so marking all of them is only noise
+        if !self.verified && cfg!(feature = "keccak") {
+            write!(f, " (unverified)")?;
+        }
+        if self.guessed {
+            write!(f, " (guessed)")?;
+        }
+        if self.kind == SignatureKind::Error {
+            write!(f, " (error)")?;
+        }
+        if self.full_hash_verified {
+            write!(f, " (full hash)")?;
+        }
+
+        match self.variants.len() {
+            0 => {}
+            1 => write!(f, " +1 variant")?,
+            n => write!(f, " +{n} variants")?,
+        }
+        if !self.source.is_empty() {
+            write!(f, " {DIM}via {}{UNDIM}", self.source)?;
+        }
+
+        if f.alternate() {
+            let date = |d: &Option<DateTime<Utc>>| d.map(|d| d.format("%Y-%m-%d").to_string());
+            if let Some(first_seen) = date(&self.first_seen) {
+                write!(f, " (first seen {first_seen})")?;
+            }
+            if let Some(last_seen) = date(&self.last_seen) {
+                write!(f, " (last seen {last_seen})")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The matches of a single selector, displayed under a selector header instead of repeating it on every line.
+///
+/// Fields:
+/// * `selector`: The selector the signatures share.
+/// * `signatures`: The matches of the selector, in rank order.
+/// * `gated`: Whether the function only accepts some callers, marked with a dim `[admin]`.
+pub struct SignatureGroup<'a> {
+    pub selector: &'a str,
+    pub signatures: &'a [Signature],
+    pub gated: bool,
+}
+
+impl Display for SignatureGroup<'_> {
+    /// Formats a single match like a `Signature`, and several matches as an indented list under the selector,
+    /// followed by a dim `[admin]` if the function is gated.
+    /// The alternate form (`{:#}`) is passed on to the matches.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let admin = match self.gated {
+            true => format!(" {DIM}[admin]{UNDIM}"),
+            false => String::new(),
+        };
+        match self.signatures {
+            [signature] => {
+                signature.fmt(f)?;
+                write!(f, "{admin}")
+            }
+            signatures => {
+                write!(f, "{BLUE}[{}]:{admin}", self.selector)?;
+                for signature in signatures {
+                    write!(f, "\n  ")?;
+                    signature.fmt_text(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The selectors no signature was found for, displayed dimmed after the resolved ones.
+pub struct UnresolvedSelectors<'a>(pub &'a [String]);
+
+impl Display for UnresolvedSelectors<'_> {
+    /// Formats the selectors one per line under an `unresolved` header.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{DIM}unresolved:")?;
+        for selector in self.0 {
+            write!(f, "\n  [{selector}]")?;
+        }
+        write!(f, "{UNDIM}")
+    }
+}
+
+/// Groups signatures sorted by selector into the matches of each selector, keeping their order.
+pub fn group_by_selector(signatures: &[Signature]) -> Vec<SignatureGroup<'_>> {
+    signatures.chunk_by(|a, b| a.selector == b.selector).map(|signatures| SignatureGroup { selector: &signatures[0].selector, signatures, gated: false }).collect()
+}
+
+/// The kind of a hash given to `lookup`, detected by its length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashKind {
+    /// A 4-byte selector of a function or custom error.
+    Selector,
+    /// A 32-byte hash, either an event topic or the full hash of a function.
+    Full,
+}
+
+impl HashKind {
+    /// Detects the kind of a hex-encoded hash, with or without the "0x" prefix.
+    ///
+    /// Returns:
+    /// The lowercase hash without the prefix along with its kind, or `None` if it isn't 4 or 32 hex-encoded bytes.
+    pub fn detect(hash: &str) -> Option<(String, Self)> {
+        let hash = hash.trim().trim_start_matches("0x").to_lowercase();
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        match hash.len() {
+            8 => Some((hash, Self::Selector)),
+            64 => Some((hash, Self::Full)),
+            _ => None,
+        }
+    }
+}
+
+/// An event signature resolved from a log topic.
+///
+/// Fields:
+/// * `text`: The event signature, e.g. `Transfer(address,address,uint256)`.
+/// * `topic`: The 32-byte Keccak-256 hash of the text, hex-encoded without the "0x" prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSignature {
+    pub text: String,
+    pub topic: String,
+}
+
+impl EventSignature {
+    /// Creates an `EventSignature` for a text returned for the given topic, verifying the match locally.
+    ///
+    /// Unlike selectors, topics are the whole hash, so a text that doesn't hash to the topic is wrong
+    /// rather than a collision. Without the `keccak` feature the texts can't be checked and are kept.
+    ///
+    /// Returns:
+    /// The `EventSignature`, or `None` if the text doesn't hash to the topic.
+    pub fn verified(text: String, topic: &str) -> Option<Self> {
+        let topic = topic.trim_start_matches("0x").to_lowercase();
+        match try_hash(&canonicalize(&text)) {
+            Ok(hash) if hash != topic => None,
+            _ => Some(Self { text, topic }),
+        }
+    }
+}
+
+impl Display for EventSignature {
+    /// Formats an `EventSignature` for console output, like a `Signature` with the topic in place of the selector.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{BLUE}[{}]: {GRAY}{}", self.topic, self.text)
     }
 }
 
-impl From<SignatureItem> for Signature {
-    /// Converts a `SignatureItem` into a `Signature`.
+impl Signature {
+    /// Converts a `SignatureItem` a provider returned for a selector into a `Signature`.
     ///
-    /// Creates a `Signature` from a given `SignatureItem`, typically used
-    /// for converting raw signature data into a more structured format.
+    /// The hash is recomputed locally and the text verified against the requested selector, not the hash
+    /// the provider reported, which a wrong or malicious entry can get wrong as well.
     ///
     /// Arguments:
     /// * `item`: The `SignatureItem` to convert.
+    /// * `selector`: The selector or full hash the item was returned for, of which only the selector is verified.
     ///
     /// Returns:
     /// A `Signature` instance derived from the `SignatureItem`.
-    fn from(item: SignatureItem) -> Self {
-        Self::new(item.text, item.hash)
+    pub fn from_item(item: SignatureItem, selector: &str) -> Self {
+        Self {
+            first_seen: item.first_seen,
+            last_seen: item.last_seen,
+            ..Self::verified(item.text, format::selector(selector.trim_start_matches("0x")))
+        }
     }
 }
 
-impl From<&SignatureItem> for Signature {
-    /// Converts a `&SignatureItem` into a `Signature`.
-    /// It clones the `SignatureItem` before converting it in order to provide a clean conversion.
+/// Computes the hex-encoded Keccak-256 hash of a signature text, whose first 4 bytes are its selector.
+#[cfg(feature = "keccak")]
+pub fn hash(text: &str) -> String {
+    hex::encode(keccak(text.as_bytes()))
+}
+
+/// Computes the Keccak-256 hash of arbitrary bytes, e.g. to derive the address of a created contract.
+#[cfg(feature = "keccak")]
+pub fn keccak(bytes: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(bytes);
+    keccak.finalize(&mut hash);
+    hash
+}
+
+/// Computes the Keccak-256 hash of arbitrary bytes like `keccak`, available whether or not sigmund is built with keccak support.
+#[cfg(feature = "keccak")]
+pub fn try_keccak(bytes: &[u8]) -> Result<[u8; 32], SignatureError> {
+    Ok(keccak(bytes))
+}
+
+/// Computes the Keccak-256 hash of arbitrary bytes like `keccak`, available whether or not sigmund is built with keccak support.
+#[cfg(not(feature = "keccak"))]
+pub fn try_keccak(_bytes: &[u8]) -> Result<[u8; 32], SignatureError> {
+    Err(SignatureError::VerificationUnavailable)
+}
+
+/// Computes the hash of a signature text like `hash`, available whether or not sigmund is built with keccak support.
+///
+/// Returns:
+/// A `Result` containing the hex-encoded hash, or `SignatureError::VerificationUnavailable`
+/// when sigmund is built without the `keccak` feature.
+#[cfg(feature = "keccak")]
+pub fn try_hash(text: &str) -> Result<String, SignatureError> {
+    Ok(hash(text))
+}
+
+/// Computes the hash of a signature text like `hash`, available whether or not sigmund is built with keccak support.
+///
+/// Returns:
+/// A `Result` containing the hex-encoded hash, or `SignatureError::VerificationUnavailable`
+/// when sigmund is built without the `keccak` feature.
+#[cfg(not(feature = "keccak"))]
+pub fn try_hash(_text: &str) -> Result<String, SignatureError> {
+    Err(SignatureError::VerificationUnavailable)
+}
+
+/// Normalizes a signature text to the form its selector is the hash of, for hashing and comparison
+/// (e.g. `transfer(address to, uint amount)` to `transfer(address,uint256)`).
+///
+/// Whitespace, parameter names and keywords such as `memory` or `indexed` are removed, type aliases are expanded
+/// (`uint` to `uint256`, `int` to `int256`, `byte` to `bytes1`) and `tuple(...)` is written as `(...)`, in nested
+/// tuples and arrays alike. Texts that can't be parsed (see `ParsedSignature::parse`) are only stripped of whitespace.
+pub fn canonicalize(text: &str) -> String {
+    match ParsedSignature::parse(text) {
+        Ok(parsed) => parsed.to_string(),
+        Err(_) => text.chars().filter(|c| !c.is_whitespace()).collect(),
+    }
+}
+
+impl ParsedSignature {
+    /// Parses a signature text into its name and parameter types.
     ///
-    /// Arguments:
-    /// * `item`: The `&SignatureItem` to convert after cloning.
+    /// Parameter names and keywords such as `memory` or `indexed` are skipped, type aliases are expanded
+    /// and `tuple(...)` is read as `(...)`, so the parsed signature is the same for every spelling of a function.
     ///
     /// Returns:
-    /// A `Signature` instance derived from the `&SignatureItem`.
-    fn from(item: &SignatureItem) -> Self {
-        let item = item.clone();
-        Self::new(item.text, item.hash)
+    /// The `ParsedSignature`, or a `ParseError` if the text isn't a name followed by a list of ABI types,
+    /// e.g. a malformed database entry.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let (name, params) = text
+            .split_once('(')
+            .and_then(|(name, rest)| Some((name.trim(), rest.trim_end().strip_suffix(')')?)))
+            .ok_or_else(|| ParseError::MissingParameters(text.to_string()))?;
+        let identifier = name.chars().next().is_some_and(|c| !c.is_ascii_digit()) && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+        if !identifier {
+            return Err(ParseError::InvalidName(text.to_string()));
+        }
+
+        let inputs = match params.trim().is_empty() {
+            true => Vec::new(),
+            false => abi::split_top_level(params)
+                .ok_or_else(|| ParseError::UnbalancedParentheses(text.to_string()))?
+                .into_iter()
+                .map(|param| parse_param(param).ok_or_else(|| ParseError::InvalidParameter { text: text.to_string(), param: param.trim().to_string() }))
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Self { name: name.to_string(), inputs })
+    }
+}
+
+impl Display for ParsedSignature {
+    /// Formats the signature in its canonical form, e.g. `transfer(address,uint256)`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let inputs: Vec<String> = self.inputs.iter().map(ToString::to_string).collect();
+        write!(f, "{}({})", self.name, inputs.join(","))
+    }
+}
+
+/// Parses a single parameter, e.g. `tuple(address to, uint amount)[] memory orders` to `(address,uint256)[]`.
+///
+/// Returns:
+/// The parameter type, or `None` if the parameter isn't an ABI type followed by a name or keywords.
+fn parse_param(param: &str) -> Option<ParamType> {
+    let param = param.trim();
+    let param = match param.strip_prefix("tuple") {
+        Some(tuple) if tuple.trim_start().starts_with('(') => tuple.trim_start(),
+        _ => param,
+    };
+
+    // The type is either a tuple or the first word, followed by its array dimensions
+    let (mut ty, mut rest) = match param.strip_prefix('(') {
+        Some(tuple) => {
+            let close = closing_paren(tuple)?;
+            let components = match tuple[..close].trim().is_empty() {
+                true => Vec::new(),
+                false => abi::split_top_level(&tuple[..close])?.into_iter().map(parse_param).collect::<Option<Vec<_>>>()?,
+            };
+            (ParamType::Tuple(components), &tuple[close + 1..])
+        }
+        None => {
+            let (ty, rest) = param.split_at(param.find(|c: char| c.is_whitespace() || c == '[').unwrap_or(param.len()));
+            (ParamType::parse(ty).ok()?, rest)
+        }
+    };
+    rest = rest.trim_start();
+    while let Some(dimension) = rest.strip_prefix('[') {
+        let (size, after) = dimension.split_once(']')?;
+        ty = match size.trim() {
+            "" => ParamType::Array(Box::new(ty)),
+            size => ParamType::FixedArray(Box::new(ty), size.parse().ok()?),
+        };
+        rest = after.trim_start();
+    }
+
+    // Anything after the type is its name and keywords, e.g. `calldata`, `indexed` or `payable`
+    match rest.split_whitespace().all(|word| word.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$')) {
+        true => Some(ty),
+        false => None,
+    }
+}
+
+/// The index of the parenthesis closing a tuple, given the text following its opening parenthesis.
+fn closing_paren(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(idx),
+            ')' => depth -= 1,
+            _ => {}
+        }
     }
+    None
+}
+
+/// Removes matches whose canonical text is identical to a higher ranked match.
+pub fn dedup(mut matches: Vec<Signature>) -> Vec<Signature> {
+    let mut seen = HashSet::new();
+    matches.retain(|signature| seen.insert(canonicalize(&signature.text)));
+    matches
+}
+
+/// Keeps the `n` highest ranked matches of each selector.
+///
+/// The matches of a selector with more than `n` matches aren't reported as `truncated`, since they were
+/// limited on request rather than by the maximum number of matches kept.
+///
+/// Arguments:
+/// * `signatures`: The matches of one or more selectors, ranked within each selector.
+/// * `n`: The number of matches kept per selector, selectors with fewer matches keep all of them.
+///
+/// Returns:
+/// The kept signatures, in the given order.
+pub fn top_matches(signatures: Vec<Signature>, n: usize) -> Vec<Signature> {
+    let mut totals: HashMap<String, usize> = HashMap::new();
+    signatures.iter().for_each(|signature| *totals.entry(signature.selector.clone()).or_default() += 1);
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    signatures
+        .into_iter()
+        .filter(|signature| {
+            let count = counts.entry(signature.selector.clone()).or_default();
+            *count += 1;
+            *count <= n
+        })
+        .map(|signature| match totals[&signature.selector] > n {
+            true => Signature { truncated: None, ..signature },
+            false => signature,
+        })
+        .collect()
+}
+
+/// Groups the matches of each selector by function name.
+///
+/// The highest ranked match of each name is kept and the texts of the other matches with the same
+/// name are listed in its `variants`. Matches with different names, such as collisions, are kept apart.
+///
+/// Arguments:
+/// * `signatures`: The matches of one or more selectors, ranked within each selector.
+///
+/// Returns:
+/// The grouped signatures, in the order of their highest ranked match.
+pub fn group_variants(signatures: Vec<Signature>) -> Vec<Signature> {
+    let mut grouped: Vec<Signature> = Vec::new();
+    let mut groups: HashMap<(String, String), usize> = HashMap::new();
+
+    for signature in signatures {
+        let key = (signature.selector.clone(), signature.name().to_string());
+        match groups.get(&key) {
+            Some(&idx) => grouped[idx].variants.push(signature.text),
+            None => {
+                groups.insert(key, grouped.len());
+                grouped.push(signature);
+            }
+        }
+    }
+
+    grouped
+}
+
+/// Ranks the matches of a selector only found in revert paths, so that error-like names come first.
+///
+/// Custom errors are conventionally named in CapWords while functions are in mixedCase, so a match such as
+/// `transfer(address,uint256)` is likely a collision. Matches that don't hash to the selector stay last.
+pub fn rank_errors(matches: &mut [Signature]) {
+    matches.sort_by_key(|signature| (!signature.verified, !signature.name().starts_with(|c: char| c.is_ascii_uppercase())));
+}
+
+/// What the ranking of collided matches knows about a contract besides the matches themselves.
+///
+/// Fields:
+/// * `abi`: The texts of the verified ABI of the contract, if known. Matches found in it are exact.
+#[derive(Debug, Clone, Default)]
+pub struct RankHints {
+    pub abi: HashSet<String>,
+}
+
+impl RankHints {
+    /// Creates hints from the functions of a verified ABI.
+    pub fn with_abi<'a>(texts: impl IntoIterator<Item = &'a str>) -> Self {
+        Self { abi: texts.into_iter().map(canonicalize).collect() }
+    }
+}
+
+/// Scores how likely a match is to be the original function of a collided selector, higher is more likely.
+///
+/// A match found in the verified ABI comes first, then the ones found in the bundled common signatures.
+/// Each of the following lowers the score: a name in snake_case that isn't a constant (e.g. `many_msg_babbage`),
+/// a name with a run of 4 or more digits or longer than 40 characters, which are typical of spam submitted to
+/// collide with popular selectors, and every parameter of an uncommon type such as `bytes1`.
+///
+/// Arguments:
+/// * `text`: The text of the match.
+/// * `hints`: What is known about the contract.
+///
+/// Returns:
+/// The score of the match, only meaningful relative to the other matches of the selector.
+pub fn score(text: &str, hints: &RankHints) -> i64 {
+    let text = canonicalize(text);
+    let (name, params) = text.split_once('(').unwrap_or((&text, ""));
+    let params = params.strip_suffix(')').unwrap_or(params);
+
+    let mut score = 0;
+    if hints.abi.contains(&text) {
+        score += ABI_WEIGHT;
+    }
+    if common_texts().contains(&text) {
+        score += COMMON_WEIGHT;
+    }
+
+    let name = name.trim_start_matches('_');
+    let snake_case = name.contains('_') && name.chars().any(|c| c.is_ascii_lowercase());
+    let digits = name.split(|c: char| !c.is_ascii_digit()).any(|run| run.len() >= 4);
+    let penalties = [snake_case, digits, name.len() > 40].into_iter().filter(|&penalty| penalty).count() + uncommon_types(params);
+
+    score - PENALTY_WEIGHT * penalties as i64
+}
+
+/// Orders the matches of a selector from the most to the least likely, the first one being the default answer.
+///
+/// Matches are ordered by their `score`, then by the popularity reported by the provider and by recency,
+/// using the last seen timestamp and falling back to the first seen one. Popularity is only compared if the
+/// provider reports it for every match, otherwise the provider's order is kept between matches with equal scores,
+/// since providers usually return them ordered already.
+///
+/// Arguments:
+/// * `items`: The matches of a selector, as returned by the provider.
+/// * `hints`: What is known about the contract, e.g. its verified ABI.
+pub fn rank(items: &mut [SignatureItem], hints: &RankHints) {
+    let popularity = items.iter().all(|item| item.popularity.is_some());
+    items.sort_by_cached_key(|item| {
+        let recency = item.last_seen.or(item.first_seen);
+        (Reverse(score(&item.text, hints)), Reverse(item.popularity.filter(|_| popularity)), Reverse(recency.filter(|_| popularity)))
+    });
+}
+
+/// The bundled table of common signatures, one canonical signature per line, decompressed on first use.
+///
+/// It's served by the `offline` provider and consulted when ranking collided matches.
+pub(crate) fn common_signatures() -> impl Iterator<Item = &'static str> {
+    static TABLE: OnceLock<String> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = String::new();
+        GzDecoder::new(COMMON_SIGNATURES_GZ).read_to_string(&mut table).expect("the bundled signature table is valid gzip");
+        table
+    });
+    table.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// The canonical texts of the bundled common signatures, built on first use.
+fn common_texts() -> &'static HashSet<String> {
+    static TEXTS: OnceLock<HashSet<String>> = OnceLock::new();
+    TEXTS.get_or_init(|| common_signatures().map(canonicalize).collect())
+}
+
+/// The number of parameters whose type isn't in `COMMON_TYPES`, arrays and tuples counting by their components.
+fn uncommon_types(params: &str) -> usize {
+    let Some(params) = abi::split_top_level(params).filter(|_| !params.is_empty()) else {
+        return 0;
+    };
+    params
+        .into_iter()
+        .map(|param| {
+            // Arrays count as their element type, e.g. `uint256[2][]` as `uint256`
+            let mut base = param;
+            while let Some((element, _)) = base.strip_suffix(']').and_then(|base| base.rsplit_once('[')) {
+                base = element;
+            }
+            match base.strip_prefix('(').and_then(|tuple| tuple.strip_suffix(')')) {
+                Some(tuple) => uncommon_types(tuple),
+                None => usize::from(!COMMON_TYPES.contains(&base)),
+            }
+        })
+        .sum()
+}
+
+/// Deserializes an optional timestamp leniently.
+///
+/// Accepts RFC 3339 timestamps and timestamps without an offset, which are assumed to be UTC.
+/// Values that can't be parsed result in `None` rather than failing the whole response.
+pub(crate) fn deserialize_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+
+    Ok(value.as_ref().and_then(serde_json::Value::as_str).and_then(|value| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|d| d.with_timezone(&Utc))
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").map(|d| d.and_utc()))
+            .ok()
+    }))
 }
@@ -0,0 +1,420 @@
+use crate::{bytecode::Confidence, SigmundOut};
+use async_trait::async_trait;
+use clap::ValueEnum;
+use reqwest::{header::CONTENT_TYPE, Client as ReqwestClient, Error as ReqwestError, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::sync::Semaphore;
+
+/// The number of delivery attempts of the HTTP sink.
+const POST_ATTEMPTS: u32 = 3;
+/// The maximum number of concurrent deliveries of the HTTP sink.
+const POST_CONCURRENCY: usize = 4;
+/// The delay before the first retry of the HTTP sink, doubled on every further retry.
+const POST_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Error types for delivering an output to a sink.
+#[derive(Error, Debug)]
+pub enum SinkError {
+    #[error("SinkIoError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("SinkSerdeError: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("SinkRequestError: {0}")]
+    Request(#[from] ReqwestError),
+    #[error("SinkStatusError: The endpoint responded with {0}")]
+    Status(StatusCode),
+    #[error("SinkError: {0}")]
+    Other(String),
+}
+
+/// The format of the results printed to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable results
+    #[default]
+    Text,
+    /// The JSON output, pretty-printed
+    Json,
+    /// The selectors keyed by contract, as consumed by the evmole benchmark
+    Evmole,
+    /// A minimal JSON line of the selectors and their best signature, whose format never changes
+    #[serde(rename = "json-min")]
+    JsonMin,
+}
+
+/// A destination for the JSON output of an analyzed contract.
+///
+/// Implement this trait to deliver results elsewhere, e.g. to a message queue or an object store,
+/// and register the sink with `Sigmund::with_sink`.
+#[async_trait]
+pub trait OutputSink: Send + Sync {
+    /// The name of the sink, used in error messages and dead-letter records.
+    fn name(&self) -> &str;
+
+    /// Delivers the output of a single contract.
+    ///
+    /// Arguments:
+    /// * `key`: Identifies the contract, its address or the name of the file it was read from.
+    /// * `out`: The output of the analysis.
+    ///
+    /// Returns:
+    /// A `Result` indicating whether the output was delivered.
+    async fn write(&self, key: &str, out: &SigmundOut) -> Result<(), SinkError>;
+}
+
+/// Writes the output to a single JSON file, which is the behavior of `--output`.
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl OutputSink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn write(&self, _key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        Ok(std::fs::write(&self.path, serde_json::to_string_pretty(out)?)?)
+    }
+}
+
+/// Writes the output of each contract to its own `<key>.json` file in a directory.
+pub struct DirectorySink {
+    dir: PathBuf,
+}
+
+impl DirectorySink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The path of the output file of a contract, with the characters that aren't safe in file names replaced.
+    pub fn path_of(&self, key: &str) -> PathBuf {
+        let name: String = key.chars().map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' }).collect();
+        self.dir.join(format!("{name}.json"))
+    }
+}
+
+#[async_trait]
+impl OutputSink for DirectorySink {
+    fn name(&self) -> &str {
+        "directory"
+    }
+
+    async fn write(&self, key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        std::fs::create_dir_all(&self.dir)?;
+        Ok(std::fs::write(self.path_of(key), serde_json::to_string_pretty(out)?)?)
+    }
+}
+
+/// Prints the output of each contract to stdout as a single JSON line.
+pub struct NdjsonSink;
+
+#[async_trait]
+impl OutputSink for NdjsonSink {
+    fn name(&self) -> &str {
+        "ndjson"
+    }
+
+    async fn write(&self, _key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        let line = serde_json::to_string(out)?;
+        Ok(writeln!(std::io::stdout().lock(), "{line}")?)
+    }
+}
+
+/// Prints the output of each contract to stdout as pretty-printed JSON, which is the behavior of `--format json`.
+pub struct JsonSink;
+
+#[async_trait]
+impl OutputSink for JsonSink {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    async fn write(&self, _key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        let json = serde_json::to_string_pretty(out)?;
+        Ok(writeln!(std::io::stdout().lock(), "{json}")?)
+    }
+}
+
+/// Prints the selectors of each contract to stdout in the format of the evmole benchmark, `{"<key>": ["<selector>", ...]}`.
+///
+/// With a confidence sweep, a result set is printed for each confidence threshold instead, keyed by the threshold:
+/// `{"high": {...}, "medium": {...}, "low": {...}}`. Each set holds the selectors of at least that confidence,
+/// so every set contains the stricter ones and a single extraction yields the whole precision/recall curve.
+pub struct EvmoleSink {
+    sweep: bool,
+}
+
+impl EvmoleSink {
+    /// Creates a sink printing a single result set, or one per confidence threshold if `sweep` is set.
+    pub fn new(sweep: bool) -> Self {
+        Self { sweep }
+    }
+
+    /// The evmole JSON of an output, with the selectors sorted.
+    pub fn render(&self, key: &str, out: &SigmundOut) -> Value {
+        if !self.sweep {
+            return json!({ key: out.selectors });
+        }
+
+        let sets = [Confidence::High, Confidence::Medium, Confidence::Low].map(|threshold| {
+            let selectors: Vec<&String> = out.confidence.iter().filter(|(_, confidence)| **confidence >= threshold).map(|(selector, _)| selector).collect();
+            (threshold.to_string(), json!({ key: selectors }))
+        });
+        Value::Object(Map::from_iter(sets))
+    }
+}
+
+#[async_trait]
+impl OutputSink for EvmoleSink {
+    fn name(&self) -> &str {
+        "evmole"
+    }
+
+    async fn write(&self, key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        let line = serde_json::to_string(&self.render(key, out))?;
+        Ok(writeln!(std::io::stdout().lock(), "{line}")?)
+    }
+}
+
+/// The output of `--format json-min`: `{"selectors": ["0x...", ...], "signatures": {"0x...": "transfer(address,uint256)", ...}}`.
+///
+/// This is the stable format recommended to tools wrapping sigmund, and it never changes: it has no metadata
+/// or schema version, holds the highest ranked match of each resolved selector only, and its keys are sorted.
+/// New fields go into the full `SigmundOut` instead, which remains free to evolve.
+///
+/// Fields:
+/// * `selectors`: The "0x" prefixed selectors, sorted.
+/// * `signatures`: The highest ranked signature of each resolved selector, keyed by the "0x" prefixed selector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinimalOut {
+    pub selectors: Vec<String>,
+    pub signatures: BTreeMap<String, String>,
+}
+
+impl From<&SigmundOut> for MinimalOut {
+    fn from(out: &SigmundOut) -> Self {
+        Self {
+            selectors: out.selectors.iter().map(|selector| format!("0x{selector}")).collect(),
+            signatures: out.matches.iter().filter_map(|(selector, texts)| Some((format!("0x{selector}"), texts.first()?.clone()))).collect(),
+        }
+    }
+}
+
+/// Prints the `MinimalOut` of each contract to stdout as a single JSON line, which is the behavior of `--format json-min`.
+pub struct JsonMinSink;
+
+#[async_trait]
+impl OutputSink for JsonMinSink {
+    fn name(&self) -> &str {
+        "json-min"
+    }
+
+    async fn write(&self, _key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        let line = serde_json::to_string(&MinimalOut::from(out))?;
+        Ok(writeln!(std::io::stdout().lock(), "{line}")?)
+    }
+}
+
+/// An unresolved selector written by `--export-unknown`, with a `text` to fill in.
+///
+/// Fields:
+/// * `selector`: The "0x" prefixed selector.
+/// * `text`: The signature of the selector, left empty to be filled in before the file is fed back with `--local-db`.
+/// * `seen_in`: The contracts the selector was found in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnknownSelector {
+    pub selector: String,
+    pub text: String,
+    #[serde(default)]
+    pub seen_in: Vec<SeenIn>,
+}
+
+/// A contract an unknown selector was found in.
+///
+/// Fields:
+/// * `contract`: The contract as its outputs are keyed, its address or the name of its file, prefixed with its chain for `chain:address` inputs.
+/// * `code_hash`: The "0x" prefixed Keccak-256 hash of its code, missing without the `keccak` feature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeenIn {
+    pub contract: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_hash: Option<String>,
+}
+
+/// Writes the unresolved selectors of every contract of the run to a single JSON file, which is the behavior of `--export-unknown`.
+///
+/// The file is a list of `UnknownSelector` sorted by selector, rewritten after each contract so it's complete
+/// even if the run fails. Once their `text` is filled in, the entries are read back by `--local-db`.
+pub struct ExportUnknownSink {
+    path: PathBuf,
+    unknown: Mutex<BTreeMap<String, UnknownSelector>>,
+}
+
+impl ExportUnknownSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), unknown: Mutex::default() }
+    }
+}
+
+#[async_trait]
+impl OutputSink for ExportUnknownSink {
+    fn name(&self) -> &str {
+        "export-unknown"
+    }
+
+    async fn write(&self, key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        let mut unknown = self.unknown.lock().unwrap();
+        let seen_in = SeenIn { contract: key.to_string(), code_hash: out.meta.code_hash.as_ref().map(|hash| format!("0x{hash}")) };
+        for selector in &out.unresolved {
+            let entry = unknown.entry(selector.clone()).or_insert_with(|| UnknownSelector { selector: format!("0x{selector}"), text: String::new(), seen_in: Vec::new() });
+            if !entry.seen_in.contains(&seen_in) {
+                entry.seen_in.push(seen_in.clone());
+            }
+        }
+        let entries: Vec<&UnknownSelector> = unknown.values().collect();
+        Ok(std::fs::write(&self.path, serde_json::to_string_pretty(&entries)?)?)
+    }
+}
+
+/// POSTs the output of each contract as JSON to an HTTP endpoint.
+///
+/// The contract is identified by the `X-Sigmund-Contract` header. Connection errors, rate limited (`429`)
+/// and server error responses are retried with an exponential backoff, other error responses fail immediately.
+/// Concurrent deliveries are bounded, so a large scan doesn't overwhelm the endpoint.
+pub struct HttpSink {
+    client: ReqwestClient,
+    url: String,
+    attempts: u32,
+    permits: Semaphore,
+}
+
+impl HttpSink {
+    /// Creates a sink posting to the given URL.
+    ///
+    /// Arguments:
+    /// * `client`: The HTTP client used for the requests, sharing the options of the `Client`.
+    /// * `url`: The URL of the ingestion endpoint.
+    pub fn new(client: ReqwestClient, url: &str) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            attempts: POST_ATTEMPTS,
+            permits: Semaphore::new(POST_CONCURRENCY),
+        }
+    }
+
+    /// Sets the number of delivery attempts of each output.
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets the maximum number of concurrent deliveries.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.permits = Semaphore::new(concurrency.max(1));
+        self
+    }
+
+    async fn post(&self, key: &str, body: &[u8]) -> Result<(), SinkError> {
+        let request = self.client.post(&self.url).header(CONTENT_TYPE, "application/json").header("x-sigmund-contract", key);
+        let status = request.body(body.to_vec()).send().await?.status();
+
+        match status.is_success() {
+            true => Ok(()),
+            false => Err(SinkError::Status(status)),
+        }
+    }
+}
+
+#[async_trait]
+impl OutputSink for HttpSink {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn write(&self, key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        // The semaphore is never closed, so acquiring a permit can't fail
+        let _permit = self.permits.acquire().await.map_err(|e| SinkError::Other(e.to_string()))?;
+        let body = serde_json::to_vec(out)?;
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.post(key, &body).await {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+
+            let retryable = match &error {
+                SinkError::Status(status) => *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+                _ => true,
+            };
+            attempt += 1;
+            if !retryable || attempt >= self.attempts {
+                return Err(error);
+            }
+
+            tokio::time::sleep(POST_BACKOFF * (1 << (attempt - 1))).await;
+        }
+    }
+}
+
+/// Wraps a sink, appending the outputs it fails to deliver to a local file instead of failing.
+///
+/// Each line of the file is a JSON object with the `sink`, the contract `key`, the `error`
+/// and the undelivered `output`, so the deliveries can be replayed later.
+pub struct DeadLetter {
+    inner: Box<dyn OutputSink>,
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl DeadLetter {
+    pub fn new(inner: Box<dyn OutputSink>, path: &Path) -> Self {
+        Self {
+            inner,
+            path: path.to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait]
+impl OutputSink for DeadLetter {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn write(&self, key: &str, out: &SigmundOut) -> Result<(), SinkError> {
+        let error = match self.inner.write(key, out).await {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+
+        let record = serde_json::json!({ "sink": self.name(), "key": key, "error": error.to_string(), "output": out });
+        let line = format!("{}\n", serde_json::to_string(&record)?);
+        // Appends are serialized so concurrent failures don't interleave their lines
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?.write_all(line.as_bytes())?;
+
+        eprintln!("{} sink failed for {key} ({error}), the output was appended to {}", self.name(), self.path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,533 @@
+use reqwest::{header::HeaderMap, Client as ReqwestClient, Error as ReqwestError, Method, Request, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+use thiserror::Error;
+
+/// The default maximum size of a response body, far above any contract code or signature lookup.
+pub const DEFAULT_MAX_RESPONSE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Headers that can carry credentials or personal information, which are never written to a recording.
+const SENSITIVE_HEADERS: [&str; 7] = ["authorization", "proxy-authorization", "cookie", "set-cookie", "from", "x-api-key", "api-key"];
+/// The value replacing secrets in recorded URLs.
+pub(crate) const REDACTED: &str = "REDACTED";
+/// The time a rate limited API key is skipped for, when the response doesn't say how long to wait.
+pub const DEFAULT_KEY_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Error types for sending requests through a `Transport`.
+#[derive(Error, Debug)]
+pub enum TransportError {
+    #[error("RequestError: {0}{hint}", hint = crate::client::certificate_hint(.0))]
+    Request(#[from] ReqwestError),
+    #[error("StatusError: {url} responded with {status}")]
+    Status { url: String, status: StatusCode },
+    #[error("RateLimitedError: {url} responded with 429 Too Many Requests")]
+    RateLimited { url: String, retry_after: Option<Duration> },
+    #[error("ReplayError: No recorded response for {method} {url} in {dir}, record it again with --record")]
+    ReplayMiss { method: String, url: String, dir: PathBuf },
+    #[error("RecordingError: {path}: {message}")]
+    Recording { path: PathBuf, message: String },
+    #[error("ResponseTooLargeError: {url} responded with more than {}, the limit can be raised with --max-response-size", crate::format::human_bytes(*.limit))]
+    ResponseTooLarge { url: String, limit: u64 },
+    #[error("DeadlineError: The request was cancelled at the --deadline of the run")]
+    DeadlineExceeded,
+    #[error("WebSocketError: {url}: {message}")]
+    WebSocket { url: String, message: String },
+    #[error("IpcError: {path}: {message}")]
+    Ipc { path: String, message: String },
+}
+
+/// Where the responses of a `Transport` come from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TransportMode {
+    /// Requests are sent over the network.
+    #[default]
+    Live,
+    /// Requests are sent over the network, and every interaction is saved to the given directory.
+    Record(PathBuf),
+    /// Requests are answered from the interactions recorded in the given directory, without any network access.
+    Replay(PathBuf),
+}
+
+/// An HTTP response read in full, either from the network or from a recording.
+///
+/// Fields:
+/// * `url`: The URL of the request, with secrets redacted.
+/// * `status`: The status code of the response.
+/// * `headers`: The response headers, without the sensitive ones.
+/// * `body`: The response body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub url: String,
+    pub status: StatusCode,
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// The value of a response header, by its lowercase name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    /// Turns a client or server error status into a `TransportError::Status`, like `reqwest::Response::error_for_status`.
+    ///
+    /// Rate limited responses (`429`) are turned into a `TransportError::RateLimited` instead,
+    /// with the delay requested by their `Retry-After` header.
+    pub fn error_for_status(self) -> Result<Self, TransportError> {
+        if self.status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = self.retry_after();
+            return Err(TransportError::RateLimited { url: self.url, retry_after });
+        }
+
+        match self.status.is_client_error() || self.status.is_server_error() {
+            true => Err(TransportError::Status { url: self.url, status: self.status }),
+            false => Ok(self),
+        }
+    }
+
+    /// The delay requested by the `Retry-After` header, given either in seconds or as an HTTP date.
+    pub fn retry_after(&self) -> Option<Duration> {
+        let value = self.header("retry-after")?.trim();
+        if let Ok(seconds) = value.parse() {
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        let at = SystemTime::UNIX_EPOCH + Duration::from_secs(u64::try_from(date.timestamp()).ok()?);
+        Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+}
+
+/// The HTTP client of sigmund's RPC and signature provider requests.
+///
+/// Wraps the shared `reqwest::Client`, and can record every interaction to a directory or
+/// replay a recording instead of using the network, so that a run can be reproduced exactly
+/// or turned into test fixtures. Each interaction is saved as a numbered JSON file holding the
+/// method, URL and body of the request with the status, headers and body of the response.
+/// Credentials are stripped from recordings: sensitive headers are dropped, the values of
+/// URL query parameters named like a key, token or secret are redacted, and so is the path of the URL
+/// of a `POST` request, a JSON-RPC endpoint whose path is often its API key, e.g. Alchemy's `/v2/<key>`.
+#[derive(Clone)]
+pub struct Transport {
+    inner: ReqwestClient,
+    mode: Arc<Mode>,
+    headers: HeaderMap,
+    max_response_size: u64,
+    deadline: Option<Instant>,
+}
+
+enum Mode {
+    Live,
+    Record { dir: PathBuf, next: AtomicUsize },
+    Replay { dir: PathBuf, responses: Mutex<HashMap<RequestKey, VecDeque<RecordedResponse>>> },
+}
+
+impl Transport {
+    /// Creates a transport sending every request over the network.
+    pub fn new(inner: ReqwestClient) -> Self {
+        Self { inner, mode: Arc::new(Mode::Live), headers: HeaderMap::new(), max_response_size: DEFAULT_MAX_RESPONSE_SIZE, deadline: None }
+    }
+
+    /// Creates a transport in the given mode.
+    ///
+    /// Arguments:
+    /// * `inner`: The HTTP client used for live and recorded requests.
+    /// * `mode`: The `TransportMode`, recording creates its directory and replaying loads the recorded interactions.
+    ///
+    /// Returns:
+    /// A `Result` containing the `Transport`, or a `TransportError` if the recording can't be created or read.
+    pub fn with_mode(inner: ReqwestClient, mode: &TransportMode) -> Result<Self, TransportError> {
+        let mode = match mode {
+            TransportMode::Live => Mode::Live,
+            TransportMode::Record(dir) => {
+                std::fs::create_dir_all(dir).map_err(|e| recording_error(dir, e))?;
+                // Continue the numbering of an existing recording instead of overwriting it
+                let next = recordings(dir)?.len();
+                Mode::Record { dir: dir.clone(), next: AtomicUsize::new(next) }
+            }
+            TransportMode::Replay(dir) => {
+                let mut responses: HashMap<RequestKey, VecDeque<RecordedResponse>> = HashMap::new();
+                for path in recordings(dir)? {
+                    let content = std::fs::read(&path).map_err(|e| recording_error(&path, e))?;
+                    let interaction: Interaction = serde_json::from_slice(&content).map_err(|e| recording_error(&path, e))?;
+                    responses.entry(interaction.request.key()).or_default().push_back(interaction.response);
+                }
+                Mode::Replay { dir: dir.clone(), responses: Mutex::new(responses) }
+            }
+        };
+
+        Ok(Self { inner, mode: Arc::new(mode), headers: HeaderMap::new(), max_response_size: DEFAULT_MAX_RESPONSE_SIZE, deadline: None })
+    }
+
+    /// Creates a transport sharing the client and the recording of this one, which adds the given headers to every request,
+    /// e.g. the credentials of the RPC. Like the other credentials, the headers are left out of the recordings.
+    pub fn with_headers(&self, headers: HeaderMap) -> Self {
+        Self { inner: self.inner.clone(), mode: self.mode.clone(), headers, max_response_size: self.max_response_size, deadline: self.deadline }
+    }
+
+    /// Sets the maximum size of a response body, decompressed, beyond which the response is refused
+    /// with `TransportError::ResponseTooLarge` instead of being read, e.g. an endpoint answering with a whole web page.
+    pub fn with_max_response_size(mut self, max_response_size: u64) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    /// Sets the instant every request is cancelled at with `TransportError::DeadlineExceeded`, so a slow endpoint
+    /// can't hold the run past its `--deadline`.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// The underlying HTTP client, e.g. for requests that must not be recorded.
+    pub fn inner(&self) -> &ReqwestClient {
+        &self.inner
+    }
+
+    /// Sends a `GET` request.
+    pub async fn get(&self, url: &str) -> Result<HttpResponse, TransportError> {
+        self.send(self.inner.get(url)).await
+    }
+
+    /// Sends a `GET` request authenticated with a key of the ring, moving on to the next key while the response is rate limited.
+    ///
+    /// Arguments:
+    /// * `keys`: The API keys to use in turn, the request is sent once without a key if there are none.
+    /// * `url`: Builds the URL of the request with the given key.
+    ///
+    /// Returns:
+    /// A `Result` containing the first response that isn't rate limited, or the last one once every key was rate limited.
+    pub async fn get_with_key(&self, keys: &KeyRing, url: impl Fn(Option<&str>) -> String) -> Result<HttpResponse, TransportError> {
+        let mut attempts = keys.len().max(1);
+        loop {
+            let index = keys.next();
+            let response = self.get(&url(index.map(|index| keys.keys[index].as_str()))).await?;
+            attempts -= 1;
+            if response.status != StatusCode::TOO_MANY_REQUESTS || attempts == 0 {
+                return Ok(response);
+            }
+            if let Some(index) = index {
+                keys.cool_down(index, response.retry_after());
+            }
+        }
+    }
+
+    /// Sends a `POST` request with the given body.
+    pub async fn post(&self, url: &str, body: String) -> Result<HttpResponse, TransportError> {
+        self.send(self.inner.post(url).body(body)).await
+    }
+
+    /// Sends a request and reads its response in full, or answers it from the recording when replaying.
+    ///
+    /// The request is cancelled with `TransportError::DeadlineExceeded` once the deadline of the transport is reached.
+    pub async fn send(&self, request: RequestBuilder) -> Result<HttpResponse, TransportError> {
+        match self.deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline.into(), self.exchange(request)).await.map_err(|_| TransportError::DeadlineExceeded)?,
+            None => self.exchange(request).await,
+        }
+    }
+
+    /// Sends a request and reads its response in full like `send`, without a deadline.
+    async fn exchange(&self, request: RequestBuilder) -> Result<HttpResponse, TransportError> {
+        let mut request = request.build()?;
+        let recorded = RecordedRequest::from(&request);
+        let rpc = request.method() == Method::POST;
+        request.headers_mut().extend(self.headers.clone());
+
+        let (dir, next) = match &*self.mode {
+            Mode::Live => return read(self.inner.execute(request).await.map_err(|e| redact_error(e, rpc))?, self.max_response_size, rpc).await,
+            Mode::Replay { dir, responses } => return replay(dir, responses, &recorded),
+            Mode::Record { dir, next } => (dir, next),
+        };
+
+        let response = read(self.inner.execute(request).await.map_err(|e| redact_error(e, rpc))?, self.max_response_size, rpc).await?;
+        let interaction = Interaction {
+            request: recorded,
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                headers: response.headers.clone(),
+                body: String::from_utf8_lossy(&response.body).into_owned(),
+            },
+        };
+        let path = dir.join(format!("{:05}.json", next.fetch_add(1, Ordering::SeqCst)));
+        let json = serde_json::to_string_pretty(&interaction).map_err(|e| recording_error(&path, e))?;
+        std::fs::write(&path, json).map_err(|e| recording_error(&path, e))?;
+
+        Ok(response)
+    }
+}
+
+/// API keys used in turn, e.g. to spread the requests over the per-key rate limits of Etherscan.
+///
+/// Keys are handed out round-robin, and a key that was rate limited is skipped until its cooldown is over,
+/// the `Retry-After` of the response or `DEFAULT_KEY_COOLDOWN`. When every key is cooling down, the one available
+/// the soonest is used. The keys never appear in the `Debug` output.
+pub struct KeyRing {
+    keys: Vec<String>,
+    next: AtomicUsize,
+    cooling: Mutex<Vec<Option<Instant>>>,
+    cooldown: Duration,
+}
+
+impl KeyRing {
+    /// Creates a ring of the given keys, without the empty ones.
+    pub fn new(keys: Vec<String>) -> Self {
+        let keys: Vec<String> = keys.into_iter().map(|key| key.trim().to_string()).filter(|key| !key.is_empty()).collect();
+        Self { cooling: Mutex::new(vec![None; keys.len()]), keys, next: AtomicUsize::new(0), cooldown: DEFAULT_KEY_COOLDOWN }
+    }
+
+    /// Sets the time a rate limited key is skipped for, when the response doesn't say how long to wait.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// The number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The index of the next key that isn't cooling down, or of the one available the soonest if they all are.
+    fn next(&self) -> Option<usize> {
+        let len = self.keys.len();
+        if len == 0 {
+            return None;
+        }
+        let start = self.next.fetch_add(1, Ordering::SeqCst);
+        let cooling = self.cooling.lock().unwrap();
+        let now = Instant::now();
+        (0..len)
+            .map(|offset| (start + offset) % len)
+            .find(|index| cooling[*index].is_none_or(|until| until <= now))
+            .or_else(|| (0..len).min_by_key(|index| cooling[*index]))
+    }
+
+    /// Skips a rate limited key for the requested delay, or the default cooldown.
+    fn cool_down(&self, index: usize, retry_after: Option<Duration>) {
+        self.cooling.lock().unwrap()[index] = Some(Instant::now() + retry_after.unwrap_or(self.cooldown));
+    }
+}
+
+impl From<Option<String>> for KeyRing {
+    fn from(key: Option<String>) -> Self {
+        Self::new(key.into_iter().collect())
+    }
+}
+
+impl From<Vec<String>> for KeyRing {
+    fn from(keys: Vec<String>) -> Self {
+        Self::new(keys)
+    }
+}
+
+impl std::fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyRing").field("keys", &self.keys.len()).field("cooldown", &self.cooldown).finish()
+    }
+}
+
+/// A recorded request and its response.
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+impl RecordedRequest {
+    /// Identifies the request in a recording, ignoring its headers.
+    fn key(&self) -> RequestKey {
+        (self.method.clone(), self.url.clone(), self.body.clone())
+    }
+}
+
+impl From<&Request> for RecordedRequest {
+    fn from(request: &Request) -> Self {
+        Self {
+            method: request.method().to_string(),
+            url: redact_url(request.url(), request.method() == Method::POST),
+            headers: headers(request.headers()),
+            body: request.body().and_then(|body| body.as_bytes()).map(|body| String::from_utf8_lossy(body).into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+/// The method, redacted URL and body of a request.
+type RequestKey = (String, String, Option<String>);
+
+/// Reads a live response in full, the path of its URL is redacted for the response of an RPC.
+async fn read(mut response: reqwest::Response, limit: u64, rpc: bool) -> Result<HttpResponse, TransportError> {
+    let url = redact_url(response.url(), rpc);
+    let status = response.status();
+    let headers = headers(response.headers());
+
+    // The announced length is checked first, the body is still counted since it may be missing, wrong or compressed
+    let too_large = || TransportError::ResponseTooLarge { url: url.clone(), limit };
+    if response.content_length().is_some_and(|length| length > limit) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| redact_error(e, rpc))? {
+        if (body.len() + chunk.len()) as u64 > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(HttpResponse { url, status, headers, body })
+}
+
+/// Answers a request from a recording.
+///
+/// Repeated requests are answered with their recorded responses in order, the last one
+/// being reused once they are exhausted, so that e.g. a retried request replays its retries.
+fn replay(dir: &Path, responses: &Mutex<HashMap<RequestKey, VecDeque<RecordedResponse>>>, request: &RecordedRequest) -> Result<HttpResponse, TransportError> {
+    let mut responses = responses.lock().unwrap();
+    let miss = || TransportError::ReplayMiss { method: request.method.clone(), url: request.url.clone(), dir: dir.to_path_buf() };
+
+    let queue = responses.get_mut(&request.key()).ok_or_else(miss)?;
+    let response = match queue.len() {
+        1 => queue.front().cloned(),
+        _ => queue.pop_front(),
+    };
+    let response = response.ok_or_else(miss)?;
+
+    Ok(HttpResponse {
+        url: request.url.clone(),
+        status: StatusCode::from_u16(response.status).map_err(|e| recording_error(dir, e))?,
+        headers: response.headers,
+        body: response.body.into_bytes(),
+    })
+}
+
+/// The interaction files of a recording, in the order they were recorded.
+fn recordings(dir: &Path) -> Result<Vec<PathBuf>, TransportError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| recording_error(dir, e))?;
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).filter(|path| path.extension().is_some_and(|ext| ext == "json")).collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// The headers worth recording, without the ones that can carry credentials.
+fn headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !SENSITIVE_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+/// Redacts the secret query parameters of a URL given as text, e.g. an option, text that isn't a URL is kept as it is.
+pub(crate) fn redact(url: &str) -> String {
+    reqwest::Url::parse(url).map(|url| redact_url(&url, false)).unwrap_or_else(|_| url.to_string())
+}
+
+/// Redacts the secrets of an RPC URL given as text like `redact`, and its path, where providers put the API key,
+/// e.g. `https://eth-mainnet.g.alchemy.com/v2/<key>` or the token of a QuickNode endpoint. The path of an IPC socket is kept.
+pub(crate) fn redact_rpc(url: &str) -> String {
+    reqwest::Url::parse(url).map(|url| redact_url(&url, true)).unwrap_or_else(|_| url.to_string())
+}
+
+/// Whether an RPC URL is reached over a WebSocket, `ws://` or `wss://`, rather than HTTP.
+pub(crate) fn is_websocket(url: &str) -> bool {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme.to_lowercase()).unwrap_or_default();
+    scheme == "ws" || scheme == "wss"
+}
+
+/// The proxy environment variable that would apply to a WebSocket URL, which the WebSocket connection doesn't go through.
+///
+/// `HTTPS_PROXY` applies to `wss://` and `HTTP_PROXY` to `ws://`, `ALL_PROXY` to both, unless the host is listed in
+/// `NO_PROXY`, either exactly or as one of its subdomains.
+pub(crate) fn websocket_proxy_var(url: &str) -> Option<&'static str> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    let vars = match parsed.scheme() {
+        "wss" => ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"],
+        "ws" => ["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"],
+        _ => return None,
+    };
+    let var = vars.into_iter().find(|var| std::env::var(var).is_ok_and(|value| !value.trim().is_empty()))?;
+
+    let no_proxy = std::env::var("NO_PROXY").or_else(|_| std::env::var("no_proxy")).unwrap_or_default();
+    let mut excluded = no_proxy.split(',').map(|entry| entry.trim().trim_start_matches('.').to_lowercase()).filter(|entry| !entry.is_empty());
+    match excluded.any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{entry}"))) {
+        true => None,
+        false => Some(var),
+    }
+}
+
+/// Whether an RPC URL is the path of the IPC socket of a local node, e.g. `/path/to/geth.ipc`, rather than a URL.
+pub(crate) fn is_ipc(url: &str) -> bool {
+    !url.contains("://") && url.ends_with(".ipc")
+}
+
+/// Redacts the values of query parameters named like a key, token or secret, e.g. `apikey`, and the password of the URL.
+///
+/// The path is replaced with a single `REDACTED` segment as well when `path` is set, for the URL of an RPC.
+fn redact_url(url: &reqwest::Url, path: bool) -> String {
+    let sensitive = |name: &str| ["key", "token", "secret", "password"].iter().any(|word| name.to_lowercase().contains(word));
+    let mut url = url.clone();
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    }
+    if path && !url.cannot_be_a_base() && !url.path().trim_matches('/').is_empty() {
+        url.set_path(&format!("/{REDACTED}"));
+    }
+    if !url.query_pairs().any(|(name, _)| sensitive(&name)) {
+        return url.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| match sensitive(&name) {
+            true => (name.into_owned(), REDACTED.to_string()),
+            false => (name.into_owned(), value.into_owned()),
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+    url.to_string()
+}
+
+/// Redacts the secrets of the URL of a request error, which is part of its message, and its path for an RPC.
+fn redact_error(mut error: ReqwestError, rpc: bool) -> ReqwestError {
+    if let Some(url) = error.url_mut() {
+        if let Ok(redacted) = reqwest::Url::parse(&redact_url(url, rpc)) {
+            *url = redacted;
+        }
+    }
+    error
+}
+
+fn recording_error(path: &Path, error: impl std::fmt::Display) -> TransportError {
+    TransportError::Recording { path: path.to_path_buf(), message: error.to_string() }
+}
@@ -0,0 +1,109 @@
+use crate::client::{CallOutcome, Client, ClientError, Pace};
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::RandomState, BTreeMap},
+    fmt::Display,
+    hash::{BuildHasher, Hasher},
+};
+
+/// How a contract answered the `eth_call` of a bare selector, compared with an unknown selector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallVerdict {
+    /// The call returned, unlike the unknown selector, so a function ran.
+    Executes,
+    /// The call reverted with data the unknown selector didn't, e.g. a custom error of the function.
+    RevertsWithData,
+    /// The call reverted without data, e.g. a function rejecting its missing arguments. It's ambiguous even when the
+    /// unknown selector reverted without data too, since many functions and fallbacks revert alike.
+    RevertsEmpty,
+    /// The call had the very outcome of the unknown selector, returned or reverted with data, so it likely reached no function.
+    LikeUnknown,
+}
+
+impl CallVerdict {
+    /// Classifies the outcome of the call of a selector against the one of the unknown selector.
+    ///
+    /// Only a distinctive outcome of the unknown selector, i.e. not an empty revert, tells the selectors that reached
+    /// no function apart, so an empty revert like it stays `RevertsEmpty`.
+    fn of(outcome: &CallOutcome, baseline: &CallOutcome) -> Self {
+        match outcome {
+            outcome if outcome == baseline && !reverts_empty(baseline) => Self::LikeUnknown,
+            CallOutcome::Returned(_) => Self::Executes,
+            outcome if !reverts_empty(outcome) => Self::RevertsWithData,
+            CallOutcome::Reverted { .. } => Self::RevertsEmpty,
+        }
+    }
+}
+
+/// The selectors checked by calling the contract, with `--verify-calls`.
+///
+/// Fields:
+/// * `baseline`: The random selector whose outcome is the one of a selector reaching no function.
+/// * `verdicts`: How the contract answered each selector, keyed by selector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallVerification {
+    pub baseline: String,
+    pub verdicts: BTreeMap<String, CallVerdict>,
+}
+
+impl CallVerification {
+    /// Calls the contract with each selector as the whole calldata, and with a random selector as the baseline.
+    ///
+    /// A false positive of the `PUSH4` heuristic reaches no function, so the contract answers it like any unknown
+    /// selector, e.g. with the revert of its fallback. The calls are started at `rate` per second at most, within
+    /// the concurrency limit of the client.
+    ///
+    /// Arguments:
+    /// * `client`: The client used for the calls.
+    /// * `address`: The address of the contract.
+    /// * `selectors`: The hex-encoded selectors to verify, without the "0x" prefix.
+    /// * `rate`: The maximum number of calls started per second.
+    ///
+    /// Returns:
+    /// A `Result` containing the `CallVerification`, or a `ClientError` if a call failed.
+    pub async fn run(client: &Client, address: &str, selectors: &[String], rate: u32) -> Result<Self, ClientError> {
+        let baseline = unknown_selector(selectors);
+        let baseline_outcome = client.call(address, &format!("0x{baseline}")).await?;
+
+        let pace = &Pace::new(rate);
+        let calls = selectors.iter().map(|selector| async move {
+            pace.wait().await;
+            Ok::<_, ClientError>((selector.clone(), client.call(address, &format!("0x{selector}")).await?))
+        });
+        let outcomes: Vec<_> = stream::iter(calls).buffer_unordered(client.concurrency()).try_collect().await?;
+
+        let verdicts = outcomes.into_iter().map(|(selector, outcome)| (selector, CallVerdict::of(&outcome, &baseline_outcome))).collect();
+        Ok(Self { baseline, verdicts })
+    }
+
+    /// The selectors answered like the unknown selector.
+    pub fn like_unknown(&self) -> impl Iterator<Item = &String> {
+        self.verdicts.iter().filter(|(_, verdict)| **verdict == CallVerdict::LikeUnknown).map(|(selector, _)| selector)
+    }
+}
+
+impl Display for CallVerification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "call verification: {} of {} selectors answered like an unknown selector", self.like_unknown().count(), self.verdicts.len())
+    }
+}
+
+/// Whether a call reverted without data.
+fn reverts_empty(outcome: &CallOutcome) -> bool {
+    match outcome {
+        CallOutcome::Returned(_) => false,
+        CallOutcome::Reverted { data, .. } => data.as_deref().is_none_or(|data| data.trim_start_matches("0x").is_empty()),
+    }
+}
+
+/// A random selector that isn't one of the verified ones.
+fn unknown_selector(selectors: &[String]) -> String {
+    loop {
+        let selector = format!("{:08x}", RandomState::new().build_hasher().finish() as u32);
+        if !selectors.contains(&selector) {
+            return selector;
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The conditions reported as warnings, selectable with `--fail-on-warning`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// No function dispatcher was found, so selectors can only be found by a deep scan.
+    NoDispatcherFound,
+    /// The code contains a `SELFDESTRUCT` instruction and can be removed.
+    Selfdestruct,
+    /// The code could be redeployed with different code at the same address.
+    MetamorphicSuspect,
+    /// An entry of the local signature database was skipped.
+    InvalidLocalDbEntry,
+    /// A selector had more matches than `--max-matches`, the lowest ranked ones were dropped.
+    MatchesTruncated,
+    /// Selectors were left unresolved because their lookup errored, after all retries for transient errors.
+    LookupFailed,
+    /// The signature cache file was corrupted and was reset.
+    CacheReset,
+    /// The functions are routed through a storage mapping, so they can't be enumerated from the code.
+    StorageMappedDispatcher,
+    /// The extracted selectors and the functions of the verified ABI differ, e.g. because of hidden functions or a proxy.
+    AbiMismatch,
+    /// The selector extraction ran out of time, so the selectors are partial.
+    ExtractionIncomplete,
+    /// Signatures were guessed by `--bruteforce`, they match the selectors but may not be the original functions.
+    GuessedSignatures,
+    /// A signature database sent responses that didn't have the expected shape, e.g. after a change of its API.
+    UnexpectedResponse,
+    /// A signature database failed too many requests in a row and was skipped for the rest of the run.
+    ProviderTripped,
+    /// A renamed flag was given under its deprecated spelling, which keeps working until it is removed.
+    DeprecatedFlag,
+    /// The RPC failed and the contract code was fetched from a fallback `--rpc-url` instead.
+    RpcFailover,
+    /// The RPC of `--chain` answered with the id of another chain.
+    ChainMismatch,
+    /// The implementation of a proxy couldn't be resolved, e.g. an uninitialized proxy, so the proxy itself was analyzed.
+    ProxyUnresolved,
+}
+
+impl Display for WarningKind {
+    /// Formats the kind as it is given to `--fail-on-warning` and serialized.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => write!(f, "{}", value.get_name()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How serious a warning is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A condition detected during the analysis, reported on the console and in the JSON output.
+///
+/// Fields:
+/// * `kind`: The detected condition.
+/// * `message`: A human readable description of the occurrence.
+/// * `severity`: How serious the condition is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl Warning {
+    /// Creates a warning of the given kind.
+    pub fn new(kind: WarningKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            severity: Severity::Warning,
+        }
+    }
+}
+
+// Constants for terminal coloring
+const YELLOW: &str = "\x1b[38;5;220m";
+const RESET: &str = "\x1b[0m";
+
+impl Display for Warning {
+    /// Formats the warning for the console, e.g. `warning: <message>`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{YELLOW}{}: {}{RESET}", self.severity, self.message)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    jsonrpc::{self, RequestIds},
+    transport::{self, HttpResponse, TransportError},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{SinkExt, StreamExt};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    StatusCode,
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    sync::atomic::AtomicU64,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpStream, sync::Mutex};
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, error::CapacityError, protocol::WebSocketConfig, Error as WsError, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+
+type Stream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// A JSON-RPC transport over a WebSocket, for the RPCs that are only reachable through `ws://` or `wss://`.
+///
+/// The connection is opened on the first request and reused by the following ones, one request at a time,
+/// each one sent with ids of its own (see `RequestIds`).
+///
+/// Fields:
+/// * `headers`: The headers of the handshake, e.g. the `Authorization` of `--rpc-auth`.
+/// * `timeout`: The maximum duration of a request, including connecting.
+/// * `max_message_size`: The maximum size of a response message.
+/// * `deadline`: An optional instant the requests are cancelled at, with `TransportError::DeadlineExceeded`.
+/// * `connection`: The open connection and the URL it was opened to.
+/// * `next_id`: The id of the next request.
+pub struct WebSocket {
+    headers: HeaderMap,
+    timeout: Duration,
+    max_message_size: u64,
+    deadline: Option<Instant>,
+    connection: Mutex<Option<(String, Stream)>>,
+    next_id: AtomicU64,
+}
+
+impl WebSocket {
+    /// Creates a transport that connects on its first request.
+    ///
+    /// Arguments:
+    /// * `headers`: The headers of the handshake.
+    /// * `timeout`: The maximum duration of a request.
+    /// * `max_message_size`: The maximum size of a response message.
+    /// * `deadline`: An optional instant the requests are cancelled at.
+    pub fn new(headers: HeaderMap, timeout: Duration, max_message_size: u64, deadline: Option<Instant>) -> Self {
+        Self { headers, timeout, max_message_size, deadline, connection: Mutex::new(None), next_id: AtomicU64::new(1) }
+    }
+
+    /// Sends a JSON-RPC request or batch and reads its response, like an HTTP `POST` answered with `200 OK`.
+    ///
+    /// Returns:
+    /// A `Result` containing the response, or a `TransportError::WebSocket` if the connection failed or the request timed out.
+    pub async fn post(&self, url: &str, body: String) -> Result<HttpResponse, TransportError> {
+        jsonrpc::bounded(self.exchange(url, body), self.timeout, self.deadline, |message| error(url, message)).await
+    }
+
+    /// Sends a request over the open connection, opening it first if needed.
+    ///
+    /// A reused connection the node closed in the meantime, e.g. after being idle, is opened again once.
+    async fn exchange(&self, url: &str, body: String) -> Result<HttpResponse, TransportError> {
+        let mut request: Value = serde_json::from_str(&body).map_err(|e| error(url, e))?;
+        let ids = RequestIds::assign(&mut request, &self.next_id);
+
+        let mut connection = self.connection.lock().await;
+        let reused = connection.as_ref().is_some_and(|(connected, _)| connected == url);
+        if !reused {
+            *connection = Some((url.to_string(), self.connect(url).await?));
+        }
+
+        let mut response = match round_trip(&mut connection.as_mut().unwrap().1, &request, &ids).await {
+            Ok(response) => response,
+            Err(WsError::Capacity(CapacityError::MessageTooLong { .. })) => {
+                *connection = None;
+                return Err(TransportError::ResponseTooLarge { url: transport::redact_rpc(url), limit: self.max_message_size });
+            }
+            Err(_) if reused => {
+                *connection = Some((url.to_string(), self.connect(url).await?));
+                round_trip(&mut connection.as_mut().unwrap().1, &request, &ids).await.map_err(|e| {
+                    *connection = None;
+                    error(url, e)
+                })?
+            }
+            Err(e) => {
+                *connection = None;
+                return Err(error(url, e));
+            }
+        };
+
+        ids.restore(&mut response);
+        Ok(HttpResponse { url: transport::redact_rpc(url), status: StatusCode::OK, headers: BTreeMap::new(), body: response.to_string().into_bytes() })
+    }
+
+    /// Opens a connection to the RPC, sending the credentials of the URL with basic auth like the HTTP requests.
+    async fn connect(&self, url: &str) -> Result<Stream, TransportError> {
+        let mut parsed = reqwest::Url::parse(url).map_err(|e| error(url, e))?;
+        let mut headers = self.headers.clone();
+        if !parsed.username().is_empty() {
+            let credentials = format!("{}:{}", parsed.username(), parsed.password().unwrap_or_default());
+            if let Ok(mut auth) = HeaderValue::from_str(&format!("Basic {}", STANDARD.encode(credentials))) {
+                auth.set_sensitive(true);
+                headers.entry(AUTHORIZATION).or_insert(auth);
+            }
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+        }
+
+        let mut request = parsed.as_str().into_client_request().map_err(|e| error(url, e))?;
+        request.headers_mut().extend(headers);
+        let limit = usize::try_from(self.max_message_size).unwrap_or(usize::MAX);
+        let config = WebSocketConfig { max_message_size: Some(limit), max_frame_size: Some(limit), ..WebSocketConfig::default() };
+        let (stream, _) = tokio_tungstenite::connect_async_with_config(request, Some(config), true).await.map_err(|e| error(url, e))?;
+        Ok(stream)
+    }
+}
+
+/// Sends a request and reads messages until its response, skipping the late responses of the previous requests.
+async fn round_trip(stream: &mut Stream, request: &Value, ids: &RequestIds) -> Result<Value, WsError> {
+    stream.send(Message::Text(request.to_string())).await?;
+    loop {
+        let message = match stream.next().await {
+            Some(message) => message?,
+            None => return Err(WsError::ConnectionClosed),
+        };
+        let response: Value = match message {
+            Message::Text(text) => serde_json::from_str(&text).unwrap_or_default(),
+            Message::Binary(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Message::Close(_) => return Err(WsError::ConnectionClosed),
+            _ => continue,
+        };
+
+        if ids.answered_by(&response) {
+            return Ok(response);
+        }
+    }
+}
+
+/// The error of a WebSocket request, with the secrets of the URL redacted.
+fn error(url: &str, error: impl std::fmt::Display) -> TransportError {
+    TransportError::WebSocket { url: transport::redact_rpc(url), message: error.to_string() }
+}
@@ -0,0 +1,33 @@
+use sigmund::{access_hints, AccessHint, Bytecode};
+
+fn bytecode(hex: &str) -> Bytecode {
+    Bytecode::try_from(hex.trim().to_string()).unwrap()
+}
+
+#[test]
+fn it_tags_the_functions_of_an_ownable_contract() {
+    let hints = access_hints(&bytecode(include_str!("fixtures/ownable.hex")));
+
+    // renounceOwnership() and transferOwnership(address) check the owner through an internal call, owner() doesn't
+    assert_eq!(hints.get("715018a6"), Some(&AccessHint::OwnerGated));
+    assert_eq!(hints.get("f2fde38b"), Some(&AccessHint::OwnerGated));
+    assert_eq!(hints.get("8da5cb5b"), Some(&AccessHint::Open));
+}
+
+#[test]
+fn it_tags_the_functions_of_an_access_control_contract() {
+    let hints = access_hints(&bytecode(include_str!("fixtures/access_control.hex")));
+
+    // grantRole(bytes32,address) checks the admin role and mint(address,uint256) a constant role, hasRole(bytes32,address) doesn't
+    assert_eq!(hints.get("2f2ff15d"), Some(&AccessHint::RoleGated));
+    assert_eq!(hints.get("40c10f19"), Some(&AccessHint::RoleGated));
+    assert_eq!(hints.get("91d14854"), Some(&AccessHint::Open));
+}
+
+#[test]
+fn it_does_not_tag_a_function_reverting_for_the_owner_as_gated() {
+    // A deny list reverts when the caller matches, every other caller succeeds
+    let hints = access_hints(&bytecode("0x60806040526004361061001d575f3560e01c80631234567814610021575b5f80fd5b5f546001600160a01b0316331461003457005b5f80fd"));
+
+    assert_eq!(hints.get("12345678"), Some(&AccessHint::Open));
+}
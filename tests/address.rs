@@ -1,4 +1,11 @@
-use sigmund::Address;
+use clap::Parser;
+use sigmund::{config::Config, Address, Sigmund};
+#[cfg(feature = "keccak")]
+use std::collections::HashSet;
+use wiremock::{
+    matchers::{body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
 
 #[test]
 fn it_creates_address_from_valid_input() {
@@ -30,3 +37,52 @@ fn it_fails_on_invalid_address_hex() {
     let addr = "0x123456789012345678901234567890123456789z".to_string();
     assert!(Address::validate(&addr).is_err())
 }
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_normalizes_the_casing_of_addresses() {
+    let casings = ["0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED", "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"];
+    let addresses: Vec<Address> = casings.iter().map(|casing| Address::try_from(casing.to_string()).unwrap()).collect();
+
+    assert!(addresses.iter().all(|address| *address == addresses[0]));
+    assert_eq!(addresses.iter().collect::<HashSet<_>>().len(), 1);
+    assert_eq!(addresses[1].as_str(), "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    // The EIP-55 test vector
+    assert_eq!(addresses[1].to_string(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+}
+
+#[tokio::test]
+async fn it_treats_every_casing_of_an_address_as_the_same_contract() {
+    let server = MockServer::start().await;
+    // Every casing requests the code of the lowercase address, so recordings and replays match as well.
+    // A dispatcher comparing the selector of `transfer(address,uint256)`
+    Mock::given(method("POST"))
+        .and(body_string_contains("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x60003560e01c8063a9059cbb14601057" })))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db.json");
+    std::fs::write(&db, r#"{"a9059cbb": "transfer(address,uint256)"}"#).unwrap();
+    let (outputs, cache) = (dir.path().join("outputs"), dir.path().join("cache"));
+
+    let casings = ["0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED", "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"];
+    let mut metas = Vec::new();
+    for casing in casings {
+        let args = ["sigmund", "--signatures", "--address", casing, "--rpc-url", &server.uri(), "--local-db", db.to_str().unwrap()];
+        let args = args.into_iter().chain(["--output-dir", outputs.to_str().unwrap(), "--cache-dir", cache.to_str().unwrap()]);
+        Sigmund::from_config(Config::parse_from(args)).unwrap().execute().await.unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&outputs).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(files[0].as_ref().unwrap().path()).unwrap()).unwrap();
+        let mut meta = out["meta"].clone();
+        assert_eq!(meta.as_object_mut().unwrap().remove("input_as_given").unwrap(), casing);
+        metas.push(meta);
+    }
+
+    assert!(metas.iter().all(|meta| *meta == metas[0]), "{metas:?}");
+    assert_eq!(metas[0]["target"]["address"], "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "keccak")]
+use clap::Parser;
+use sigmund::{
+    config::{Command, Config},
+    signature, Bruteforce,
+};
+use std::{collections::HashSet, time::Duration};
+
+fn selector(text: &str) -> String {
+    signature::hash(text)[..8].to_string()
+}
+
+fn bruteforce(max_params: usize, max_iterations: usize) -> Bruteforce {
+    let stems = ["pause", "setFee", "setLimits"].map(str::to_string).to_vec();
+    Bruteforce { stems, max_params, max_iterations, timeout: Duration::from_secs(10) }
+}
+
+#[test]
+fn it_guesses_selectors_from_a_wordlist() {
+    let unresolved = HashSet::from([selector("pause()"), selector("setFee(uint256)"), selector("setLimits(uint256,address)"), "deadbeef".to_string()]);
+    let mut guessed = bruteforce(2, usize::MAX).run(&unresolved);
+    guessed.sort_by(|a, b| a.text.cmp(&b.text));
+
+    let texts: Vec<_> = guessed.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["pause()", "setFee(uint256)", "setLimits(uint256,address)"]);
+    assert!(guessed.iter().all(|s| s.guessed && s.verified));
+    assert!(guessed[0].to_string().contains("(guessed)"));
+}
+
+#[test]
+fn it_stops_at_the_iteration_cap() {
+    let unresolved = HashSet::from([selector("pause()"), selector("setFee(uint256)")]);
+    // The parameterless candidates are tried first
+    let guessed = bruteforce(2, 3).run(&unresolved);
+    assert_eq!(guessed.len(), 1);
+    assert_eq!(guessed[0].text, "pause()");
+
+    // Parameters beyond the maximum aren't tried
+    assert!(bruteforce(0, usize::MAX).run(&HashSet::from([selector("setFee(uint256)")])).is_empty());
+}
+
+#[test]
+fn it_reads_stems_from_a_wordlist_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("words.txt");
+    std::fs::write(&path, "# admin functions\npause\n\nsetFee\n").unwrap();
+
+    let bruteforce = Bruteforce::from_wordlist(&path, 1, 100, Duration::from_secs(1)).unwrap();
+    assert_eq!(bruteforce.stems, vec!["pause", "setFee"]);
+}
+
+#[test]
+fn it_guesses_selectors_from_the_builtin_names() {
+    let unresolved = HashSet::from([selector("claimRewards(address)"), selector("setMaxWallet(uint256)")]);
+    let report = Bruteforce::builtin(2, usize::MAX, Duration::from_secs(60)).search(&unresolved, 1);
+
+    let mut texts: Vec<_> = report.guessed.iter().map(|s| s.text.as_str()).collect();
+    texts.sort();
+    assert_eq!(texts, vec!["claimRewards(address)", "setMaxWallet(uint256)"]);
+    assert!(report.guessed.iter().all(|s| s.guessed));
+    assert!(report.hashed > 0 && report.rate() > 0.0);
+}
+
+#[test]
+fn it_parses_the_bruteforce_command() {
+    let config = Config::try_parse_from(["sigmund", "bruteforce", "0xa9059cbb", "--matches", "3"]).unwrap();
+    assert!(matches!(config.command, Some(Command::Bruteforce { matches: 3, max_params: 3, .. })));
+
+    // The guessing options need a wordlist or the built-in names
+    assert!(Config::try_parse_from(["sigmund", "--signatures", "--file", "code.hex", "--max-params", "3"]).is_err());
+    assert!(Config::try_parse_from(["sigmund", "--signatures", "--file", "code.hex", "--bruteforce-unresolved", "--max-params", "3"]).is_ok());
+
+    // The combinations of too many parameters couldn't be exhausted
+    assert!(Config::try_parse_from(["sigmund", "bruteforce", "0xa9059cbb", "--max-params", "9"]).is_err());
+}
+
+#[test]
+fn it_generates_the_candidates_of_many_parameters_lazily() {
+    // The 9^8 lists of eight parameters aren't built upfront, so the search ends at the iteration cap right away
+    let report = bruteforce(8, 1_000).search(&HashSet::from(["deadbeef".to_string()]), 1);
+    assert_eq!(report.hashed, 1_000);
+    assert!(report.elapsed < Duration::from_secs(5));
+}
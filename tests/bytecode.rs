@@ -1,4 +1,8 @@
-use sigmund::Bytecode;
+use sigmund::{Bytecode, Confidence, DispatcherStyle, Opcode, Similarity};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
 
 #[test]
 fn it_creates_bytecode_from_valid_input() {
@@ -30,3 +34,395 @@ fn it_does_not_find_signatures_when_none_exist() {
     let signatures = bytecode.find_function_selectors(false);
     assert!(signatures.is_empty());
 }
+
+/// A dispatcher over the given selectors followed by a function body, as a hex string.
+fn contract(selectors: &[&str], body: &str) -> String {
+    let dispatch: String = selectors.iter().map(|s| format!("8063{s}14610{}57", &s[..3])).collect();
+    format!("0x6080604052600436106100{dispatch}{body}")
+}
+
+const BODY: &str = "5b6000805460018101825590600052602060002001555b3480156100575760008081fd5b50600435602435808201905092915050565b";
+
+#[test]
+fn it_scores_identical_bytecode_as_identical() {
+    let code = contract(&["a9059cbb", "095ea7b3", "70a08231"], BODY);
+    let a = Bytecode::try_from(code.clone()).unwrap();
+    let b = Bytecode::try_from(code).unwrap();
+    let similarity = Similarity::between(&a, &b);
+    assert_eq!(similarity.code, 1.0);
+    assert_eq!(similarity.selectors, 1.0);
+}
+
+#[test]
+fn it_ignores_push_immediates_in_lightly_patched_bytecode() {
+    let a = Bytecode::try_from(contract(&["a9059cbb", "095ea7b3", "70a08231"], BODY)).unwrap();
+    // Same code with a patched selector and a different stored constant
+    let patched = BODY.replace("6000805460018101", "6000805460078101");
+    let b = Bytecode::try_from(contract(&["a9059cbb", "095ea7b3", "18160ddd"], &patched)).unwrap();
+    let similarity = Similarity::between(&a, &b);
+    assert_eq!(similarity.code, 1.0);
+    assert!(similarity.selectors > 0.4 && similarity.selectors < 1.0);
+    assert!(similarity.combined >= 0.7);
+}
+
+#[test]
+fn it_scores_unrelated_bytecode_low() {
+    let a = Bytecode::try_from(contract(&["a9059cbb", "095ea7b3", "70a08231"], BODY)).unwrap();
+    let b = Bytecode::try_from("0x363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3".to_string()).unwrap();
+    let similarity = Similarity::between(&a, &b);
+    assert!(similarity.code < 0.1);
+    assert_eq!(similarity.selectors, 0.0);
+    assert_eq!(similarity.verdict(), "unrelated");
+}
+
+#[test]
+fn it_keeps_push_data_inside_instructions() {
+    let bytecode = Bytecode::try_from("0x6300010203145b".to_string()).unwrap();
+    let opcodes: Vec<u8> = bytecode.instructions().map(|i| i.opcode).collect();
+    assert_eq!(opcodes, vec![0x63, 0x14, 0x5b]);
+}
+
+#[test]
+fn it_flags_selfdestruct_and_metamorphic_code() {
+    // CREATE2 at offset 6, CALLER SELFDESTRUCT at offset 8
+    let bytecode = Bytecode::try_from("0x600035808080f55033ff".to_string()).unwrap();
+    let mutability = bytecode.mutability();
+    assert!(mutability.selfdestructable);
+    assert!(mutability.metamorphic_suspect);
+    assert_eq!(mutability.selfdestruct_offsets, vec![9]);
+    assert_eq!(mutability.create2_offsets, vec![6]);
+}
+
+#[test]
+fn it_detects_the_deployer_callback_of_metamorphic_init_code() {
+    let bytecode = Bytecode::try_from("0x5860208158601c335a63aaf10f428752fa158151803b80938091923cf3".to_string()).unwrap();
+    let mutability = bytecode.mutability();
+    assert_eq!(mutability.deployer_callback_offsets, vec![7]);
+    assert!(!mutability.selfdestructable);
+}
+
+#[test]
+fn it_ignores_selfdestruct_bytes_in_push_data_and_metadata() {
+    let push_data = format!("0x7f{}60ff00", "ff".repeat(32));
+    assert!(!Bytecode::try_from(push_data).unwrap().mutability().selfdestructable);
+
+    let metadata = "0x6001a16469706673ff0007".to_string();
+    assert!(!Bytecode::try_from(metadata).unwrap().mutability().selfdestructable);
+}
+
+#[test]
+fn it_classifies_a_linear_solidity_dispatcher() {
+    let bytecode = Bytecode::try_from(contract(&["a9059cbb", "095ea7b3", "70a08231"], BODY)).unwrap();
+    let dispatcher = bytecode.dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::LinearEqChain);
+    assert_eq!(dispatcher.entries, 3);
+    assert_eq!(dispatcher.to_string(), "linear EQ chain (Solidity), 3 entries");
+}
+
+#[test]
+fn it_labels_a_via_ir_dispatcher() {
+    // The last comparison consumes the selector, without the DUP1 of the legacy pipeline
+    let dispatcher = Bytecode::try_from(include_str!("../data/selftest/via_ir.hex").trim().to_string()).unwrap().dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::LinearEqChain);
+    assert!(dispatcher.via_ir);
+    assert_eq!(dispatcher.to_string(), "linear EQ chain (via-IR), 3 entries");
+    assert!(!Bytecode::try_from(contract(&["a9059cbb"], BODY)).unwrap().dispatcher().via_ir);
+}
+
+#[test]
+fn it_classifies_a_split_binary_search_dispatcher() {
+    // DUP1 PUSH4 <pivot> GT PUSH2 JUMPI, followed by the chains of both halves
+    let pivot = "80637ecebe0011610100578063";
+    let code = contract(&["a9059cbb", "dd62ed3e"], &format!("{pivot}06fdde031461011057{BODY}"));
+    let dispatcher = Bytecode::try_from(code).unwrap().dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::BinarySearch);
+    assert_eq!((dispatcher.pivots, dispatcher.entries), (1, 3));
+    assert_eq!(dispatcher.to_string(), "split binary search (Solidity), 1 pivot, 3 entries");
+}
+
+#[test]
+fn it_classifies_a_vyper_selector_table() {
+    // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR, PUSH1 2 DUP2 MOD, then XOR comparisons within the bucket
+    let buckets = "60003560e01c600281068063a9059cbb18610100578063095ea7b31861011057";
+    let dispatcher = Bytecode::try_from(format!("0x{buckets}{BODY}")).unwrap().dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::VyperTable);
+    assert_eq!(dispatcher.entries, 2);
+}
+
+#[test]
+fn it_reports_a_missing_dispatcher() {
+    let dispatcher = Bytecode::try_from(format!("0x{BODY}")).unwrap().dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::None);
+    assert_eq!(dispatcher.to_string(), "no dispatcher found, deep scan only");
+}
+
+#[test]
+fn it_detects_a_storage_mapped_router() {
+    // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR, PUSH1 0 MSTORE, PUSH1 1 PUSH1 0x20 MSTORE, PUSH1 0x40 PUSH1 0 KECCAK256 SLOAD
+    let lookup = "60003560e01c60005260016020526040600020 54".replace(' ', "");
+    // PUSH1 0 DUP1 CALLDATASIZE DUP2 DUP1 DUP6 GAS DELEGATECALL
+    let delegate = "600080368180855af4";
+
+    let dispatcher = Bytecode::try_from(contract(&["1f931c1c"], &format!("{lookup}{delegate}{BODY}"))).unwrap().dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::StorageMapped);
+    assert_eq!(dispatcher.to_string(), "storage-mapped router, 1 entry");
+
+    // A require on the route in between: DUP1 PUSH2 <tag> JUMPI PUSH1 0 DUP1 REVERT JUMPDEST
+    let checked = format!("{lookup}8061012357600080fd5b{delegate}{BODY}");
+    assert_eq!(Bytecode::try_from(contract(&["1f931c1c"], &checked)).unwrap().dispatcher().style, DispatcherStyle::StorageMapped);
+
+    // A mapping lookup that isn't delegated to is a regular function
+    let dispatcher = Bytecode::try_from(contract(&["1f931c1c"], &format!("{lookup}{BODY}"))).unwrap().dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::LinearEqChain);
+}
+
+#[test]
+fn it_keeps_a_solidity_dispatcher_reading_a_mapping_and_delegating() {
+    // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR, then DUP1 PUSH4 <selector> EQ PUSH2 <tag> JUMPI for each function
+    let dispatch = "60003560e01c 8063a9059cbb14610100 57 806370a0823114610120 57".replace(' ', "");
+    // JUMPDEST, a balances[address] lookup keyed on the calldata argument: PUSH1 4 CALLDATALOAD PUSH1 0 MSTORE,
+    // PUSH1 1 PUSH1 0x20 MSTORE, PUSH1 0x40 PUSH1 0 KECCAK256 SLOAD
+    let lookup = "5b 600435600052 6001602052 6040600020 54".replace(' ', "");
+    // A library call: PUSH1 0 DUP1 CALLDATASIZE DUP2 DUP1 PUSH20 <library> GAS DELEGATECALL
+    let library = format!("60008036818073{}5af4", "11".repeat(20));
+
+    let dispatcher = Bytecode::try_from(format!("0x{dispatch}{lookup}{library}00")).unwrap().dispatcher();
+    assert_eq!(dispatcher.style, DispatcherStyle::LinearEqChain);
+    assert_eq!(dispatcher.entries, 2);
+}
+
+#[test]
+fn it_grades_the_confidence_of_each_selector() {
+    // PUSH4 a9059cbb EQ PUSH2 0123 JUMPI, PUSH4 70a08231 EQ ISZERO, PUSH4 deadbeef POP, PUSH4 a9059cbb POP
+    let bytecode = Bytecode::try_from("0x63a9059cbb1461012357 6370a082311415 63deadbeef50 63a9059cbb50 00".replace(' ', "")).unwrap();
+    let confidence = bytecode.selector_confidence();
+
+    assert_eq!(confidence["a9059cbb"], Confidence::High);
+    assert_eq!(confidence["70a08231"], Confidence::Medium);
+    assert_eq!(confidence["deadbeef"], Confidence::Low);
+
+    let at_least = |threshold| confidence.iter().filter(|(_, c)| **c >= threshold).map(|(s, _)| s.clone()).collect();
+    assert_eq!(bytecode.find_function_selectors(false), at_least(Confidence::Medium));
+    assert_eq!(bytecode.find_function_selectors(true), at_least(Confidence::Low));
+}
+
+/// Runs every analysis of adversarial code, which must stay far below the bound even on slow CI machines.
+fn assert_linear(code: Vec<u8>) {
+    let bytecode = Bytecode { inner: code };
+    let start = Instant::now();
+
+    bytecode.find_function_selectors(true);
+    bytecode.selector_confidence();
+    bytecode.dispatcher();
+    bytecode.mutability();
+    Similarity::between(&bytecode, &bytecode);
+
+    assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+}
+
+#[test]
+fn it_analyzes_adversarial_code_in_linear_time() {
+    // PUSH32 opcodes with one trailing byte, each truncating the push data of the previous one
+    let mut push32 = vec![0x7f; 24 * 1024];
+    push32.push(0x00);
+    assert_linear(push32);
+
+    // Selector extractions followed by a storage lookup, without the DELEGATECALL that would end the scan early
+    let extraction = [0x60, 0xe0, 0x1c, 0x52, 0x20, 0x54];
+    assert_linear(extraction.repeat(40_000));
+
+    // Compared PUSH4 values and SELFDESTRUCTs repeated through a large input
+    let dispatcher = [0x63, 0xaa, 0xbb, 0xcc, 0xdd, 0x14, 0x60, 0x10, 0x57, 0xff];
+    let code = dispatcher.repeat(100_000);
+    assert_linear(code.clone());
+    assert_eq!(Bytecode { inner: code }.mutability().selfdestruct_offsets.len(), 64);
+}
+
+#[test]
+fn it_returns_partial_selectors_past_the_deadline() {
+    let bytecode = Bytecode { inner: [0x63, 0xaa, 0xbb, 0xcc, 0xdd, 0x14].repeat(10_000) };
+
+    let (selectors, complete) = bytecode.find_function_selectors_until(false, Some(Instant::now()));
+    assert!(!complete && selectors.is_empty());
+
+    let (selectors, complete) = bytecode.find_function_selectors_until(false, Some(Instant::now() + Duration::from_secs(60)));
+    assert!(complete);
+    assert_eq!(selectors, bytecode.find_function_selectors(false));
+}
+
+#[test]
+fn it_finds_the_event_topics_of_a_logging_contract() {
+    let transfer = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    let mask = format!("{}{}", "ff".repeat(4), "00".repeat(28));
+    // PUSH32 <Transfer topic>, PUSH32 <selector mask>, LOG3
+    let code = format!("0x7f{transfer}7f{mask}a3");
+    let topics = Bytecode::try_from(code.clone()).unwrap().find_event_topics();
+    assert_eq!(topics.into_iter().collect::<Vec<_>>(), vec![transfer]);
+
+    // Without any LOG1..LOG4 the pushed values aren't topics
+    let silent = code.trim_end_matches("a3").to_string();
+    assert!(Bytecode::try_from(silent).unwrap().find_event_topics().is_empty());
+}
+
+#[test]
+fn it_finds_the_selectors_of_custom_errors_on_revert_paths() {
+    // PUSH4 0xe450d38c, PUSH1 0xe0, SHL, PUSH1 0, MSTORE, PUSH1 4, PUSH1 0, REVERT
+    let shifted = "63e450d38c60e01b60005260046000fd";
+    // PUSH32 0x08c379a0 << 224, PUSH1 0, MSTORE, PUSH1 4, PUSH1 0, REVERT
+    let word = format!("7f08c379a0{}60005260046000fd", "00".repeat(28));
+    // The same shift without a REVERT, e.g. an interface id
+    let returned = "6301ffc9a760e01b60005260046000f3";
+
+    let bytecode = Bytecode::try_from(format!("0x{shifted}{word}{returned}")).unwrap();
+    assert_eq!(bytecode.find_error_selectors().into_iter().collect::<Vec<_>>(), vec!["08c379a0", "e450d38c"]);
+}
+
+#[test]
+fn it_tells_the_initializer_of_a_deployed_child_from_the_factory_functions() {
+    // A pair factory after UniswapV2Factory: createPair(address,address) deploys the pair with CREATE2 and calls
+    // initialize(address,address) on it, while skim(address) calls transfer(address,uint256) on a token
+    let bytecode = Bytecode::try_from(include_str!("fixtures/pair_factory.hex").trim().to_string()).unwrap();
+
+    assert_eq!(bytecode.find_child_init_selectors().into_iter().collect::<Vec<_>>(), vec!["485cc955"]);
+    let own = bytecode.find_function_selectors(false);
+    assert_eq!(own, HashSet::from(["c9c65396", "e6a43905", "574f2ba3", "bc25cf77"].map(String::from)));
+    // The deep scan sees every value, the generic call selector is left to it
+    assert!(bytecode.find_function_selectors(true).is_superset(&HashSet::from(["485cc955".to_string(), "a9059cbb".to_string()])));
+
+    // The same calldata without a creation before the call isn't attributed to a child
+    let call = "63485cc95560e01b5f525f80604481805af100";
+    assert!(Bytecode::try_from(format!("0x{call}")).unwrap().find_child_init_selectors().is_empty());
+    // Older compilers multiply by 1 << 224 instead of shifting, the created address being kept below the arguments of the call
+    let create = "5f5f5ff0";
+    let multiplied = format!("{create}63485cc9557c01{}025f525f5f60445f5f855af100", "00".repeat(28));
+    assert_eq!(Bytecode::try_from(format!("0x{multiplied}")).unwrap().find_child_init_selectors().len(), 1);
+    // The calldata built after a creation for a call to another contract, e.g. a token, isn't attributed to the child
+    let other = format!("{create}63485cc9557c01{}025f525f5f60445f5f73{}5af100", "00".repeat(28), "aa".repeat(20));
+    assert!(Bytecode::try_from(format!("0x{other}")).unwrap().find_child_init_selectors().is_empty());
+    // However far the call is from the creation
+    let distant = format!("{create}{}63485cc95560e01b5f525f5f60445f5f855af100", "5f50".repeat(200));
+    assert_eq!(Bytecode::try_from(format!("0x{distant}")).unwrap().find_child_init_selectors().len(), 1);
+}
+
+/// The `PUSH4` values of the decoded instructions, only the compared ones unless `deep`, which must be
+/// a subset of the ones of the byte scan, since push data can't be mistaken for an instruction.
+fn walked_selectors(bytecode: &Bytecode, deep: bool) -> HashSet<String> {
+    let instructions: Vec<_> = bytecode.instructions().collect();
+    instructions
+        .iter()
+        .enumerate()
+        .filter(|(idx, i)| i.opcode == 0x63 && i.immediate.len() == 4 && (deep || instructions.get(idx + 1).is_some_and(|next| next.opcode == 0x14)))
+        .map(|(_, i)| hex::encode(i.immediate))
+        .collect()
+}
+
+#[test]
+fn it_names_historical_and_undefined_opcodes() {
+    assert_eq!(Opcode(0xf2).name(), Some("CALLCODE"));
+    assert_eq!(Opcode(0x44).name(), Some("PREVRANDAO"));
+    assert_eq!(Opcode(0x44).aliases(), ["DIFFICULTY"]);
+    assert_eq!(Opcode(0xff).aliases(), ["SUICIDE"]);
+    assert_eq!(Opcode(0x20).aliases(), ["SHA3"]);
+    assert!(Opcode(0xf2).is_deprecated() && Opcode(0xff).is_deprecated());
+    assert_eq!(Opcode(0x7f).immediate_size(), 32);
+    assert_eq!(Opcode(0x5f).immediate_size(), 0);
+    assert!(Opcode(0xfe).is_defined());
+    assert_eq!(Opcode(0x0c).to_string(), "UNDEFINED(0x0c)");
+
+    // Every byte decodes to a single byte instruction, except PUSH1..PUSH32
+    let defined = (0..=255u8).filter(|op| Opcode(*op).is_defined()).count();
+    assert_eq!(defined, 149);
+}
+
+#[test]
+fn it_extracts_the_selectors_of_ancient_code_with_undefined_opcodes() {
+    let bytecode = Bytecode::try_from(include_str!("fixtures/frontier_callcode.hex").trim().to_string()).unwrap();
+
+    let selectors = bytecode.find_function_selectors(false);
+    let expected: HashSet<String> = ["a9059cbb", "70a08231", "18160ddd", "06fdde03"].map(String::from).into();
+    assert_eq!(selectors, expected);
+    // The undefined opcodes before and between the dispatcher entries don't end the walk
+    assert_eq!(walked_selectors(&bytecode, false), selectors);
+    assert_eq!(bytecode.dispatcher().style, DispatcherStyle::LinearEqChain);
+
+    let stats = bytecode.opcode_stats();
+    assert_eq!((stats.undefined, stats.deprecated), (19, 2));
+    assert_eq!(stats.undefined_offsets[..3], [39, 40, 41]);
+    assert!(bytecode.mutability().selfdestructable);
+}
+
+#[test]
+fn it_walks_random_bytes_without_missing_or_fabricating_selectors() {
+    // A xorshift generator, so the blob is the same on every run
+    let mut state = 0x2545f4914f6cdd1du64;
+    let code: Vec<u8> = (0..64 * 1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let bytecode = Bytecode { inner: code.clone() };
+
+    // The instructions cover every byte exactly once, in order, including the undefined ones
+    let mut pc = 0;
+    for instruction in bytecode.instructions() {
+        assert_eq!(instruction.offset, pc);
+        pc += 1 + Opcode(instruction.opcode).immediate_size();
+    }
+    assert!(pc >= code.len());
+
+    for deep in [false, true] {
+        let scanned = bytecode.find_function_selectors(deep);
+        let walked = walked_selectors(&bytecode, deep);
+        assert!(walked.is_subset(&scanned), "{:?}", walked.difference(&scanned).collect::<Vec<_>>());
+    }
+    assert!(bytecode.opcode_stats().undefined > 0);
+}
+
+#[test]
+fn it_recognizes_eip7702_delegation_designators() {
+    let designator = Bytecode::try_from("0xef010063c0c19a282a1b52b07dd5a65b58948a07dae32b".to_string()).unwrap();
+    assert_eq!(designator.delegation_target().as_deref(), Some("0x63c0c19a282a1b52b07dd5a65b58948a07dae32b"));
+
+    // The designator is exactly the prefix and an address, anything else is regular code
+    for code in ["0xef010063c0c19a282a1b52b07dd5a65b58948a07dae3", "0xef010063c0c19a282a1b52b07dd5a65b58948a07dae32b00", "0xef020063c0c19a282a1b52b07dd5a65b58948a07dae32b", "0x6080604052"] {
+        assert_eq!(Bytecode::try_from(code.to_string()).unwrap().delegation_target(), None, "{code}");
+    }
+}
+
+#[test]
+fn it_finds_pushed_constants() {
+    let slot = "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+    assert!(Bytecode::try_from(format!("0x7f{}54", &slot[2..])).unwrap().pushes(slot));
+    // The same bytes as the immediate of another instruction don't count
+    assert!(!Bytecode::try_from(format!("0x7e{}54", &slot[4..])).unwrap().pushes(slot));
+    // Shorter constants are matched with the PUSH of their size, e.g. a selector
+    assert!(Bytecode::try_from("0x635c60da1b60e01b".to_string()).unwrap().pushes("0x5c60da1b"));
+    assert!(!Bytecode::try_from("0x645c60da1b0060e01b".to_string()).unwrap().pushes("0x5c60da1b"));
+}
+
+#[test]
+fn it_recognizes_minimal_proxies() {
+    let target = |code: &str| Bytecode::try_from(code.to_string()).unwrap().minimal_proxy_target();
+
+    // The clones of real contracts, as their factories deploy them: an Instadapp DSA account of InstaAccountV2 by OpenZeppelin's
+    // EIP-1167 template, and the vanity form for Seaport 1.6, whose 7 leading zero bytes are left out with PUSH13
+    let instadapp = "0x363d3d373d3d3d363d732971adfa57b20e5a416ae5a708a8655a9c74f7235af43d82803e903d91602b57fd5bf3";
+    assert_eq!(target(instadapp).as_deref(), Some("0x2971adfa57b20e5a416ae5a708a8655a9c74f723"));
+    let seaport = "0x363d3d373d3d3d363d6c68f116a894984e2db1123eb3955af43d82803e903d91602457fd5bf3";
+    assert_eq!(target(seaport).as_deref(), Some("0x0000000000000068f116a894984e2db1123eb395"));
+    // EIP-7511, by Solady's PUSH0 clone of the Safe 1.3.0 singleton
+    let safe = "0x365f5f375f5f365f73d9db270c1b5e3bd161e8c8503c55ceabee7095525af43d5f5f3e5f3d91602a57fd5bf3";
+    assert_eq!(target(safe).as_deref(), Some("0xd9db270c1b5e3bd161e8c8503c55ceabee709552"));
+    // ClonesWithImmutableArgs of Permit2, followed by the UNI token address as args and their length plus 2
+    let args = "1f9840a85d5af5bf1d1762f925bdaddc4201f984";
+    let cwia = format!("0x3d3d3d3d363d3d376100166037363936610016013d73000000000022d473030f116ddee9f6b43ac78ba35af43d3d93803e603557fd5bf3{args}0016");
+    assert_eq!(target(&cwia).as_deref(), Some("0x000000000022d473030f116ddee9f6b43ac78ba3"));
+
+    // Code after a clone, or a different jump, is another contract
+    assert_eq!(target(&format!("{instadapp}00")), None);
+    assert_eq!(target(&instadapp.replace("602b57fd", "602b56fd")), None);
+    assert_eq!(target("0x6080604052"), None);
+}
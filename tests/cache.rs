@@ -0,0 +1,523 @@
+use serde_json::json;
+use clap::Parser;
+use sigmund::{config::Config, providers::Etherface, CacheEntry, Client, SignatureCache, Sigmund};
+use std::{collections::HashSet, time::Duration};
+use wiremock::{
+    matchers::{method, path, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// Mount a two page 4byte listing with three signatures on the mock server.
+async fn mount_listing(server: &MockServer) {
+    let first = json!({
+        "next": format!("{}/api/v1/signatures/?ordering=id&page=2", server.uri()),
+        "results": [
+            { "text_signature": "transfer(address,uint256)", "hex_signature": "0xa9059cbb" },
+            { "text_signature": "approve(address,uint256)", "hex_signature": "0x095ea7b3" },
+        ]
+    });
+    let second = json!({
+        "next": null,
+        "results": [{ "text_signature": "balanceOf(address)", "hex_signature": "0x70a08231" }]
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/signatures/"))
+        .and(query_param_is_missing("page"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(first))
+        .expect(1)
+        .mount(server)
+        .await;
+
+    // The second page fails once, then succeeds
+    Mock::given(method("GET"))
+        .and(path("/api/v1/signatures/"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/signatures/"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(second))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn it_warms_the_cache_and_resumes_after_a_failure() {
+    let server = MockServer::start().await;
+    mount_listing(&server).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let client = Client::new(&server.uri());
+    let listing = format!("{}/api/v1/signatures/?ordering=id", server.uri());
+
+    // The first run stops at the failing second page, keeping the progress of the first one
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    assert!(cache.warm(&client, &listing, "fourbyte", 3, Duration::ZERO).await.is_err());
+
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    assert_eq!(cache.entries.len(), 2);
+    assert!(cache.warm_cursor.is_some());
+
+    // The second run resumes from the second page without fetching the first one again
+    assert_eq!(cache.warm(&client, &listing, "fourbyte", 3, Duration::ZERO).await.unwrap(), 3);
+
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert_eq!(cache.entries.len(), 3);
+    assert!(cache.warm_cursor.is_none());
+    assert_eq!(cache.get("70a08231").unwrap().signatures, vec!["balanceOf(address)"]);
+    assert_eq!(cache.get("a9059cbb").unwrap().source, "fourbyte");
+}
+
+#[tokio::test]
+async fn it_stops_warming_at_the_requested_count() {
+    let server = MockServer::start().await;
+    mount_listing(&server).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let client = Client::new(&server.uri());
+    let listing = format!("{}/api/v1/signatures/?ordering=id", server.uri());
+
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    assert_eq!(cache.warm(&client, &listing, "fourbyte", 1, Duration::ZERO).await.unwrap(), 1);
+    assert_eq!(cache.entries.len(), 1);
+    assert!(cache.warm_cursor.is_none());
+}
+
+#[tokio::test]
+async fn it_restarts_warming_from_a_different_listing() {
+    let server = MockServer::start().await;
+    mount_listing(&server).await;
+
+    let mirror = json!({
+        "next": null,
+        "results": [{ "text_signature": "totalSupply()", "hex_signature": "0x18160ddd" }]
+    });
+    Mock::given(method("GET"))
+        .and(path("/mirror/signatures/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mirror))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let client = Client::new(&server.uri());
+    let listing = format!("{}/api/v1/signatures/?ordering=id", server.uri());
+
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    assert!(cache.warm(&client, &listing, "fourbyte", 3, Duration::ZERO).await.is_err());
+
+    // The cursor of the interrupted run belongs to the other listing, so the mirror is warmed from its first page
+    let mirror = format!("{}/mirror/signatures/", server.uri());
+    assert_eq!(cache.warm(&client, &mirror, "mirror", 3, Duration::ZERO).await.unwrap(), 1);
+
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert!(cache.get("70a08231").is_none());
+    assert_eq!(cache.get("18160ddd").unwrap().source, "mirror");
+    assert_eq!(cache.get("a9059cbb").unwrap().source, "fourbyte");
+}
+
+#[tokio::test]
+async fn it_gives_up_warming_past_the_rate_limit_wait_cap() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/signatures/"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "86400"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let client = Client::new(&server.uri()).with_rate_limit_wait(Duration::from_secs(10));
+    let listing = format!("{}/api/v1/signatures/?ordering=id", server.uri());
+
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    let error = cache.warm(&client, &listing, "fourbyte", 3, Duration::ZERO).await.unwrap_err();
+    assert!(error.to_string().contains("RateLimited"), "{error}");
+}
+
+#[test]
+fn it_reports_stats_and_clears_the_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &["transfer(address,uint256)".to_string()], "fourbyte");
+    cache.insert("deadbeef", &[], "fourbyte");
+    cache.save().unwrap();
+
+    let stats = SignatureCache::open(dir.path()).unwrap().stats();
+    assert_eq!(stats.entries, 2);
+    assert_eq!(stats.unresolved, 1);
+    assert_eq!(stats.ages.day, 2);
+    assert!(stats.size > 0);
+
+    cache.clear().unwrap();
+    assert!(!cache.path().exists());
+    assert_eq!(SignatureCache::open(dir.path()).unwrap().entries.len(), 0);
+}
+
+#[test]
+fn it_caps_the_signatures_stored_per_selector() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap().with_max_matches(3);
+
+    let texts: Vec<String> = (0..10).map(|i| format!("spam_{i}(uint256)")).collect();
+    cache.insert("a9059cbb", &texts, "fourbyte");
+    cache.insert("a9059cbb", &texts, "fourbyte");
+
+    assert_eq!(cache.get("a9059cbb").unwrap().signatures, texts[..3].to_vec());
+}
+
+#[tokio::test]
+async fn it_looks_each_selector_up_once_per_run() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "items": [{ "hash": "a9059cbb", "text": "transfer(address,uint256)" }] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET")).and(path("/v1/signatures/hash/all/deadbeef/1")).respond_with(ResponseTemplate::new(404)).expect(1).mount(&server).await;
+    Mock::given(method("GET")).and(path("/v1/signatures/hash/all/095ea7b3/1")).respond_with(ResponseTemplate::new(404)).expect(1).mount(&server).await;
+
+    // Without any cache, the contracts of a batch share the selectors they have in common
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let contracts = [["a9059cbb", "deadbeef"], ["a9059cbb", "095ea7b3"], ["deadbeef", "a9059cbb"]].map(|selectors| selectors.map(str::to_string).into_iter().collect::<HashSet<_>>());
+    for selectors in &contracts {
+        let texts: Vec<String> = client.get_signatures(&provider, selectors, false).await.unwrap().into_iter().map(|s| s.text).collect();
+        assert_eq!(texts, ["transfer(address,uint256)"]);
+    }
+
+    // Known and unknown selectors alike are answered from the memo
+    let signatures = client.get_signatures(&provider, &contracts[1], false).await.unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert!(client.failed_lookups().is_empty());
+}
+
+#[tokio::test]
+async fn it_resolves_cached_selectors_without_provider_calls() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/70a08231/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "items": [{ "hash": "70a08231", "text": "balanceOf(address)" }] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &["transfer(address,uint256)".to_string()], "fourbyte");
+
+    let client = Client::new("").with_cache(cache);
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let selectors = HashSet::from(["a9059cbb".to_string(), "70a08231".to_string()]);
+    let mut signatures = client.get_signatures(&provider, &selectors, false).await.unwrap();
+    signatures.sort_by(|a, b| a.text.cmp(&b.text));
+
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["balanceOf(address)", "transfer(address,uint256)"]);
+    assert!(signatures.iter().all(|s| s.verified));
+    assert_eq!(client.cache_hits().into_iter().collect::<Vec<_>>(), vec!["a9059cbb"]);
+
+    // The looked up selector is cached for the next run
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert_eq!(cache.get("70a08231").unwrap().signatures, vec!["balanceOf(address)"]);
+    assert_eq!(cache.get("70a08231").unwrap().source, "etherface");
+}
+
+#[test]
+fn it_starts_fresh_when_the_cache_is_corrupted() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("signatures.json"), "{\"entries\": {\"a9059cbb\"").unwrap();
+
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    assert!(cache.entries.is_empty());
+    assert_eq!(cache.warnings().len(), 1);
+
+    cache.insert("a9059cbb", &["transfer(address,uint256)".to_string()], "fourbyte");
+    cache.save().unwrap();
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert!(cache.warnings().is_empty());
+    assert_eq!(cache.entries.len(), 1);
+}
+
+#[tokio::test]
+async fn it_looks_all_matches_up_again_when_only_the_first_page_is_cached() {
+    let server = MockServer::start().await;
+    // The first page is fetched by the run caching it and again for all matches, the second one only for all matches
+    for (page, expected) in [(1, 2), (2, 1)] {
+        let body = match page {
+            1 => include_str!("fixtures/etherface_cae9ca51_page1.json"),
+            _ => include_str!("fixtures/etherface_cae9ca51_page2.json"),
+        };
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/signatures/hash/all/cae9ca51/{page}")))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+            .expect(expected)
+            .mount(&server)
+            .await;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let selectors = HashSet::from(["cae9ca51".to_string()]);
+    let lookup = |all_matches: bool| {
+        let client = Client::new("").with_cache(SignatureCache::open(dir.path()).unwrap());
+        let provider = Etherface::with_url(client.http().clone(), &server.uri()).with_max_pages(if all_matches { 10 } else { 1 });
+        let selectors = &selectors;
+        async move { client.get_signatures(&provider, selectors, all_matches).await.unwrap().into_iter().map(|s| s.text).collect::<Vec<_>>() }
+    };
+
+    assert_eq!(lookup(false).await, ["approveAndCall(address,uint256,bytes)"]);
+    assert!(!SignatureCache::open(dir.path()).unwrap().get("cae9ca51").unwrap().complete);
+
+    // The cached first page isn't enough for all matches, which are cached in full for the next runs
+    let all = ["approveAndCall(address,uint256,bytes)", "onHintFinanceCallback(address,address,uint256,bytes)"];
+    assert_eq!(lookup(true).await, all);
+    assert!(SignatureCache::open(dir.path()).unwrap().get("cae9ca51").unwrap().complete);
+    assert_eq!(lookup(true).await, all);
+    assert_eq!(lookup(false).await, all[..1]);
+}
+
+/// Mount an Etherface response for a selector, expecting the given number of lookups.
+async fn mount_etherface(server: &MockServer, selector: &str, texts: &[&str], expected: u64) {
+    let items: Vec<_> = texts.iter().map(|text| json!({ "hash": selector, "text": text })).collect();
+    let response = match items.is_empty() {
+        true => ResponseTemplate::new(404),
+        false => ResponseTemplate::new(200).set_body_json(json!({ "items": items })),
+    };
+
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/signatures/hash/all/{selector}/1")))
+        .respond_with(response)
+        .expect(expected)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn it_looks_up_expired_unknown_selectors_again() {
+    let server = MockServer::start().await;
+    mount_etherface(&server, "a9059cbb", &["transfer(address,uint256)"], 0).await;
+    mount_etherface(&server, "00000000", &[], 0).await;
+    mount_etherface(&server, "deadbeef", &[], 1).await;
+    mount_etherface(&server, "70a08231", &[], 1).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    let day = 86_400;
+    // Resolved entries are kept however old they are, unknown ones only within the TTL
+    cache.insert("a9059cbb", &["transfer(address,uint256)".to_string()], "etherface");
+    cache.entries.get_mut("a9059cbb").unwrap().updated_at -= 30 * day;
+    cache.insert("00000000", &[], "etherface");
+    cache.negative.get_mut("00000000").unwrap().updated_at -= 6 * day;
+    cache.insert("deadbeef", &[], "etherface");
+    cache.negative.get_mut("deadbeef").unwrap().updated_at -= 8 * day;
+
+    let client = Client::new("").with_cache(cache).with_negative_ttl(Duration::from_secs(7 * day));
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let selectors: HashSet<String> = ["a9059cbb", "00000000", "deadbeef", "70a08231"].map(String::from).into();
+    let signatures = client.get_signatures(&provider, &selectors, false).await.unwrap();
+
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(client.cache_hits().into_iter().collect::<Vec<_>>(), vec!["00000000", "a9059cbb"]);
+
+    // The looked up selectors are cached as unknown again, with a fresh timestamp
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    for selector in ["deadbeef", "70a08231"] {
+        let entry = cache.get(selector).unwrap();
+        assert!(entry.is_negative() && !entry.is_older_than(Duration::from_secs(day)));
+    }
+}
+
+#[tokio::test]
+async fn it_refreshes_the_cache_without_reading_it() {
+    let server = MockServer::start().await;
+    mount_etherface(&server, "a9059cbb", &["transfer(address,uint256)"], 1).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &["many_msg_babbage(bytes1)".to_string()], "fourbyte");
+
+    let client = Client::new("").with_cache(cache).with_cache_refresh(true);
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+    assert!(client.cache_hits().is_empty());
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert_eq!(cache.get("a9059cbb").unwrap().signatures, vec!["transfer(address,uint256)"]);
+    assert_eq!(cache.get("a9059cbb").unwrap().source, "etherface");
+}
+
+#[test]
+fn it_keeps_negative_entries_apart_and_invalidates_them() {
+    let dir = tempfile::tempdir().unwrap();
+    // Caches of earlier versions mix the unknown selectors with the resolved ones
+    let entry = |texts: &[&str]| json!({ "signatures": texts, "source": "etherface", "updated_at": 1 });
+    let old = json!({ "entries": { "a9059cbb": entry(&["transfer(address,uint256)"]), "deadbeef": entry(&[]) } });
+    std::fs::write(dir.path().join("signatures.json"), old.to_string()).unwrap();
+
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    assert_eq!(cache.entries.keys().collect::<Vec<_>>(), ["a9059cbb"]);
+    assert_eq!(cache.negative.keys().collect::<Vec<_>>(), ["deadbeef"]);
+    assert!(cache.get("deadbeef").unwrap().is_negative());
+
+    // A resolved selector isn't recorded as unknown, an unknown one is resolved once a provider knows it
+    cache.insert("a9059cbb", &[], "openchain");
+    assert!(!cache.get("a9059cbb").unwrap().is_negative());
+    cache.insert("deadbeef", &["beef()".to_string()], "openchain");
+    assert!(cache.negative.is_empty() && cache.entries.len() == 2);
+
+    assert!(cache.invalidate("deadbeef"));
+    assert!(!cache.invalidate("deadbeef"));
+    assert!(cache.get("deadbeef").is_none());
+}
+
+#[test]
+fn it_expires_negative_entries_after_the_ttl_or_when_the_clock_moved_back() {
+    let entry = CacheEntry { signatures: Vec::new(), source: "etherface".to_string(), updated_at: 1_000_000, complete: false };
+    let ttl = Duration::from_secs(3_600);
+
+    assert!(!entry.is_expired(ttl, 1_000_000));
+    assert!(!entry.is_expired(ttl, 1_003_600));
+    assert!(entry.is_expired(ttl, 1_003_601));
+    // An entry from the future can't be aged, so it isn't trusted
+    assert!(entry.is_expired(ttl, 999_999));
+}
+
+#[tokio::test]
+async fn it_refreshes_unresolved_selectors_only() {
+    let server = MockServer::start().await;
+    mount_etherface(&server, "a9059cbb", &["many_msg_babbage(bytes1)"], 0).await;
+    mount_etherface(&server, "70a08231", &["balanceOf(address)"], 1).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &["transfer(address,uint256)".to_string()], "etherface");
+    cache.insert("70a08231", &[], "etherface");
+
+    let client = Client::new("").with_cache(cache).with_negative_refresh(true);
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let selectors: HashSet<String> = ["a9059cbb", "70a08231"].map(String::from).into();
+    let mut texts: Vec<_> = client.get_signatures(&provider, &selectors, false).await.unwrap().into_iter().map(|s| s.text).collect();
+    texts.sort();
+
+    assert_eq!(texts, vec!["balanceOf(address)", "transfer(address,uint256)"]);
+    assert_eq!(client.cache_hits().into_iter().collect::<Vec<_>>(), vec!["a9059cbb"]);
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert!(cache.negative.is_empty());
+}
+
+#[cfg(feature = "offline")]
+#[tokio::test]
+async fn it_ignores_negative_entries_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &[], "etherface");
+    cache.save().unwrap();
+
+    // The bundled table is authoritative offline, a miss of the remote databases doesn't hide its entries
+    let args = ["sigmund", "--offline", "--cache-dir", dir.path().to_str().unwrap(), "lookup", "0xa9059cbb"];
+    let lookups = Sigmund::from_config(Config::parse_from(args)).unwrap().lookup(&["0xa9059cbb".to_string()]).await.unwrap();
+    assert_eq!(lookups[0].signatures[0].text, "transfer(address,uint256)");
+    assert_eq!(lookups[0].signatures[0].source, "offline");
+}
+
+#[tokio::test]
+async fn it_resolves_from_the_cache_only_without_providers() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &["transfer(address,uint256)".to_string()], "etherface");
+    cache.save().unwrap();
+
+    let args = ["sigmund", "--providers", "none", "--cache-dir", dir.path().to_str().unwrap(), "lookup", "0xa9059cbb", "0xdeadbeef"];
+    let hashes = ["0xa9059cbb".to_string(), "0xdeadbeef".to_string()];
+    let lookups = Sigmund::from_config(Config::parse_from(args)).unwrap().lookup(&hashes).await.unwrap();
+    assert_eq!(lookups[0].signatures[0].text, "transfer(address,uint256)");
+    assert!(lookups[1].signatures.is_empty());
+
+    // No database was asked, so the miss isn't cached as unknown
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert!(cache.negative.is_empty());
+}
+
+/// A provider standing for the network, which must not be reached.
+struct Unreachable;
+
+#[async_trait::async_trait]
+impl sigmund::providers::SignatureProvider for Unreachable {
+    fn name(&self) -> &str {
+        "unreachable"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<sigmund::Signature>, sigmund::providers::ProviderError> {
+        panic!("{selector} was looked up with --cache-only");
+    }
+}
+
+#[tokio::test]
+async fn it_sends_no_lookup_with_cache_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &["transfer(address,uint256)".to_string()], "etherface");
+    cache.save().unwrap();
+    let (bytecode, db, output) = (dir.path().join("bytecode"), dir.path().join("db.json"), dir.path().join("out.json"));
+    // transfer(address,uint256), balanceOf(address) and an unknown selector
+    std::fs::write(&bytecode, "0x60003560e01c8063a9059cbb14601757806370a08231146017578063deadbeef1460175700").unwrap();
+    std::fs::write(&db, r#"{"70a08231": "balanceOf(address)"}"#).unwrap();
+
+    let args = ["sigmund", "--signatures", "--cache-only", "--file", bytecode.to_str().unwrap(), "--cache-dir", dir.path().to_str().unwrap(), "--output", output.to_str().unwrap()];
+    Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(Unreachable)).execute().await.unwrap();
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(out["matches"], json!({ "a9059cbb": ["transfer(address,uint256)"] }));
+    assert_eq!(out["unresolved"], json!(["70a08231", "deadbeef"]));
+
+    // The local database is still consulted, while the public databases are left out
+    let flags = ["--force", "--local-db", db.to_str().unwrap(), "--providers", "openchain,fourbyte"];
+    Sigmund::from_config(Config::parse_from(args.iter().chain(&flags))).unwrap().execute().await.unwrap();
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(out["matches"], json!({ "a9059cbb": ["transfer(address,uint256)"], "70a08231": ["balanceOf(address)"] }));
+    assert_eq!(out["unresolved"], json!(["deadbeef"]));
+    assert!(out["failures"].is_null());
+    // Nothing was looked up, so the miss isn't cached as unknown
+    assert!(SignatureCache::open(dir.path()).unwrap().negative.is_empty());
+
+    assert!(Config::try_parse_from(["sigmund", "--signatures", "--cache-only", "--refresh-cache", "--file", "bytecode.txt"]).is_err());
+    assert!(Config::try_parse_from(["sigmund", "--cache-only", "--file", "bytecode.txt"]).is_err());
+}
+
+#[tokio::test]
+async fn it_prefers_the_local_db_without_dropping_the_cached_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(dir.path()).unwrap();
+    cache.insert("a9059cbb", &["remote(uint256)".to_string()], "etherface");
+    cache.insert("70a08231", &[], "etherface");
+    cache.save().unwrap();
+    let (bytecode, db, output) = (dir.path().join("bytecode"), dir.path().join("db.json"), dir.path().join("out.json"));
+    std::fs::write(&bytecode, "0x60003560e01c8063a9059cbb14601757806370a08231146017578063deadbeef1460175700").unwrap();
+    std::fs::write(&db, r#"{"a9059cbb": "transfer(address,uint256)", "70a08231": "balanceOf(address)"}"#).unwrap();
+
+    let args = ["sigmund", "--signatures", "--providers", "local", "--local-db", db.to_str().unwrap(), "--file", bytecode.to_str().unwrap()];
+    let args = args.into_iter().chain(["--cache-dir", dir.path().to_str().unwrap(), "--output", output.to_str().unwrap()]);
+    Sigmund::from_config(Config::parse_from(args)).unwrap().execute().await.unwrap();
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(out["matches"], json!({ "a9059cbb": ["transfer(address,uint256)"], "70a08231": ["balanceOf(address)"] }));
+
+    // The cached results of the remote databases are kept for the runs without the local database
+    let cache = SignatureCache::open(dir.path()).unwrap();
+    assert_eq!(cache.get("a9059cbb").unwrap().signatures, vec!["remote(uint256)"]);
+    assert!(cache.negative.contains_key("70a08231"));
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "keccak")]
+use sigmund::CandidateCheck;
+use std::collections::HashSet;
+
+fn selectors() -> HashSet<String> {
+    HashSet::from(["a9059cbb".to_string(), "70a08231".to_string(), "deadbeef".to_string()])
+}
+
+#[test]
+fn it_separates_matched_and_unmatched_candidates_and_selectors() {
+    let candidates = ["transfer(address, uint)", "balanceOf(address)", "pause()"].map(str::to_string);
+    let check = CandidateCheck::new(&candidates, &selectors());
+
+    let matched: Vec<_> = check.matched.iter().map(|s| (s.selector.as_str(), s.text.as_str())).collect();
+    assert_eq!(matched, vec![("a9059cbb", "transfer(address,uint256)"), ("70a08231", "balanceOf(address)")]);
+    assert_eq!(check.unmatched_candidates, vec!["pause()"]);
+    assert_eq!(check.unmatched_selectors, vec!["deadbeef"]);
+
+    let console = check.to_string();
+    assert!(console.contains("matched candidates (2):"));
+    assert!(console.contains("unmatched candidates (1):\n  pause()"));
+    assert!(console.contains("unmatched selectors (1):\n  deadbeef"));
+
+    let json = serde_json::to_value(&check).unwrap();
+    assert_eq!(json["matched"][1]["text"], "balanceOf(address)");
+    assert_eq!(json["unmatched_candidates"][0], "pause()");
+    assert_eq!(json["unmatched_selectors"][0], "deadbeef");
+}
+
+#[test]
+fn it_reads_candidates_from_a_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("candidates.txt");
+    std::fs::write(&path, "# ERC-20\ntransfer(address,uint256)\n\napprove(address,uint256)\n").unwrap();
+
+    let check = CandidateCheck::from_file(&path, &selectors()).unwrap();
+    assert_eq!(check.matched.len(), 1);
+    assert_eq!(check.unmatched_candidates, vec!["approve(address,uint256)"]);
+    assert_eq!(check.unmatched_selectors, vec!["70a08231", "deadbeef"]);
+}
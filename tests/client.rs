@@ -0,0 +1,444 @@
+use clap::Parser;
+use serde_json::json;
+use sigmund::{config::Config, providers::Etherface, Address, BlockId, Client, ClientError, ClientOptions, Pace, Sigmund};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use wiremock::{
+    matchers::{body_partial_json, body_string_contains, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn it_identifies_itself_on_rpc_and_provider_requests() {
+    let server = MockServer::start().await;
+    let user_agent = format!("sigmund/{} (+https://github.com/wavefnx/sigmund)", env!("CARGO_PKG_VERSION"));
+
+    Mock::given(method("POST"))
+        .and(header("user-agent", user_agent.as_str()))
+        .and(header("from", "ops@example.com"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x00" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .and(header("user-agent", user_agent.as_str()))
+        .and(header("from", "ops@example.com"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = ClientOptions { contact: Some("ops@example.com".to_string()), ..Default::default() };
+    let client = Client::with_options(&server.uri(), &options).unwrap();
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+
+    client.get_code("0x0000000000000000000000000000000000000001").await.unwrap();
+    client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+}
+
+#[tokio::test]
+async fn it_sends_a_custom_user_agent() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header("user-agent", "acme-scanner/1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x00" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = ClientOptions { user_agent: "acme-scanner/1.0".to_string(), ..Default::default() };
+    let client = Client::with_options(&server.uri(), &options).unwrap();
+    client.get_code("0x0000000000000000000000000000000000000001").await.unwrap();
+}
+
+#[test]
+fn it_rejects_invalid_header_values() {
+    let options = ClientOptions { contact: Some("line\nbreak".to_string()), ..Default::default() };
+    assert!(Client::with_options("http://localhost", &options).is_err());
+}
+
+/// Serves a single JSON-RPC response over TLS, with a certificate signed by the test CA.
+async fn serve_tls() -> u16 {
+    let identity = native_tls::Identity::from_pkcs8(
+        include_bytes!("fixtures/tls/server.pem"),
+        include_bytes!("fixtures/tls/server.key"),
+    )
+    .unwrap();
+    let acceptor = tokio_native_tls::TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            // Handshakes rejected by the client are expected, keep accepting
+            let Ok(mut stream) = acceptor.accept(stream).await else { continue };
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request).await;
+
+            let body = r#"{"jsonrpc":"2.0","id":1,"result":"0x00"}"#;
+            let response = format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}", body.len());
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+
+    port
+}
+
+#[tokio::test]
+async fn it_trusts_a_custom_ca_bundle() {
+    let port = serve_tls().await;
+    let url = format!("https://localhost:{port}");
+
+    let error = Client::new(&url).get_code("0x0000000000000000000000000000000000000001").await.err().unwrap();
+    assert!(error.to_string().contains("--ca-bundle"), "{error}");
+
+    let options = ClientOptions { ca_bundle: Some("tests/fixtures/tls/ca.pem".into()), ..Default::default() };
+    let client = Client::with_options(&url, &options).unwrap();
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, "0x00");
+}
+
+#[test]
+fn it_rejects_an_invalid_ca_bundle() {
+    let options = ClientOptions { ca_bundle: Some("tests/fixtures/tls/server.key".into()), ..Default::default() };
+    assert!(Client::with_options("http://localhost", &options).is_err());
+}
+
+#[tokio::test]
+async fn it_times_out_a_slow_endpoint() {
+    let server = MockServer::start().await;
+    let response = ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x00" }));
+    Mock::given(method("POST")).respond_with(response.set_delay(Duration::from_secs(2))).mount(&server).await;
+
+    let options = ClientOptions { timeout: Duration::from_millis(200), ..Default::default() };
+    let client = Client::with_options(&server.uri(), &options).unwrap();
+    let error = client.get_code("0x0000000000000000000000000000000000000001").await.err().unwrap();
+    assert!(matches!(error, ClientError::Timeout(_)), "{error}");
+    assert!(error.to_string().contains("--timeout"));
+
+    let options = ClientOptions { timeout: Duration::from_secs(10), ..Default::default() };
+    let client = Client::with_options(&server.uri(), &options).unwrap();
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, "0x00");
+}
+
+#[tokio::test]
+async fn it_detects_the_chain_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0xa4b1" })))
+        .mount(&server)
+        .await;
+
+    assert_eq!(Client::new(&server.uri()).chain_id().await.unwrap(), 42161);
+}
+
+#[tokio::test]
+async fn it_surfaces_the_error_objects_of_the_rpc() {
+    let server = MockServer::start().await;
+    let refused = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "method not allowed" } });
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(200).set_body_json(refused)).mount(&server).await;
+
+    let client = Client::new(&server.uri());
+    let error = client.get_code("0x0000000000000000000000000000000000000001").await.err().unwrap();
+    assert!(matches!(&error, ClientError::Rpc { code: -32601, message } if message == "method not allowed"), "{error}");
+    assert_eq!(error.to_string(), "RpcError: The RPC responded with error -32601: method not allowed");
+    assert!(matches!(client.chain_id().await, Err(ClientError::Rpc { code: -32601, .. })));
+
+    // Some gateways answer with an error status along with the error object, which is still the reason given
+    server.reset().await;
+    let limited = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32005, "message": "daily request count exceeded" } });
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(429).set_body_json(limited)).mount(&server).await;
+    let error = client.get_code("0x0000000000000000000000000000000000000001").await.err().unwrap();
+    assert!(error.to_string().contains("-32005: daily request count exceeded"), "{error}");
+
+    // A body that isn't a JSON-RPC response is reported with its status
+    server.reset().await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(503).set_body_string("<html>maintenance</html>")).mount(&server).await;
+    let error = client.get_code("0x0000000000000000000000000000000000000001").await.err().unwrap();
+    assert!(error.to_string().contains("503"), "{error}");
+
+    server.reset().await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": null }))).mount(&server).await;
+    assert!(matches!(client.get_code("0x0000000000000000000000000000000000000001").await, Err(ClientError::MissingResult("eth_getCode"))));
+}
+
+#[tokio::test]
+async fn it_reads_the_code_at_the_given_block() {
+    let server = MockServer::start().await;
+    let address = "0x0000000000000000000000000000000000000001";
+    let hash = "0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6";
+    Mock::given(body_partial_json(json!({ "method": "eth_getCode", "params": [address, "0x112a880"] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x6080" })))
+        .mount(&server)
+        .await;
+    Mock::given(body_partial_json(json!({ "method": "eth_getCode", "params": [address, { "blockHash": hash }] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x6001" })))
+        .mount(&server)
+        .await;
+    Mock::given(body_partial_json(json!({ "method": "eth_getCode", "params": [address, "0x1"] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": "missing trie node 1d5d0e (path ) state 0x1d5d0e is not available" } })))
+        .mount(&server)
+        .await;
+
+    // Decimal numbers and hex quantities are sent as hex quantities, and hashes as EIP-1898 objects
+    assert_eq!("18000000".parse(), Ok(BlockId::Number(18_000_000)));
+    assert_eq!("0x112A880".parse(), Ok(BlockId::Number(18_000_000)));
+    assert_eq!("Finalized".parse(), Ok(BlockId::Finalized));
+    assert!("0xzz".parse::<BlockId>().is_err() && "yesterday".parse::<BlockId>().is_err());
+
+    let client = Client::new(&server.uri()).with_block("18000000".parse().unwrap());
+    assert_eq!(client.get_code(address).await.unwrap().result, "0x6080");
+    let client = Client::new(&server.uri()).with_block(hash.parse().unwrap());
+    assert_eq!(client.get_code(address).await.unwrap().result, "0x6001");
+
+    // A pruned node is told apart from the other refusals, with a hint to use an archive node
+    let error = Client::new(&server.uri()).with_block(BlockId::Number(1)).get_code(address).await.err().unwrap();
+    assert!(matches!(&error, ClientError::ArchiveRequired { block: BlockId::Number(1), .. }), "{error}");
+    assert!(error.to_string().contains("archive node"), "{error}");
+}
+
+#[tokio::test]
+async fn it_correlates_the_batched_codes_by_id() {
+    let server = MockServer::start().await;
+    // The node answers the batch out of order, and refuses the code of the second address
+    let shuffled = json!([
+        { "jsonrpc": "2.0", "id": 3, "result": "0x6003" },
+        { "jsonrpc": "2.0", "id": 2, "error": { "code": -32000, "message": "execution aborted" } },
+        { "jsonrpc": "2.0", "id": 1, "result": "0x6001" },
+    ]);
+    Mock::given(body_string_contains("[{")).respond_with(ResponseTemplate::new(200).set_body_json(shuffled)).expect(1).mount(&server).await;
+
+    let addresses: Vec<Address> = (1..=3).map(|n| Address::try_from(format!("0x{n:040x}")).unwrap()).collect();
+    let codes = Client::new(&server.uri()).get_codes(&addresses).await;
+    assert_eq!(codes.len(), 3);
+    assert_eq!(codes[0].as_ref().unwrap().result, "0x6001");
+    assert!(matches!(&codes[1], Err(ClientError::Rpc { code: -32000, .. })));
+    assert_eq!(codes[2].as_ref().unwrap().result, "0x6003");
+}
+
+#[tokio::test]
+async fn it_requests_the_codes_one_at_a_time_when_batches_are_rejected() {
+    let server = MockServer::start().await;
+    Mock::given(body_string_contains("[{"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32600, "message": "batch requests are not supported" } })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    for n in 1..=2 {
+        Mock::given(body_partial_json(json!({ "method": "eth_getCode", "params": [format!("0x{n:040x}"), "latest"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x600{n}") })))
+            .expect(2)
+            .mount(&server)
+            .await;
+    }
+
+    // The batch is only tried once, the next calls go straight to the single requests
+    let client = Client::new(&server.uri());
+    let addresses: Vec<Address> = (1..=2).map(|n| Address::try_from(format!("0x{n:040x}")).unwrap()).collect();
+    for _ in 0..2 {
+        let codes: Vec<String> = client.get_codes(&addresses).await.into_iter().map(|code| code.unwrap().result).collect();
+        assert_eq!(codes, ["0x6001", "0x6002"]);
+    }
+}
+
+#[tokio::test]
+async fn it_fails_over_to_the_next_rpc_url() {
+    let (failing, limited, healthy) = (MockServer::start().await, MockServer::start().await, MockServer::start().await);
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(503)).expect(1).mount(&failing).await;
+    let limit = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32005, "message": "daily request count exceeded, request rate limited" } });
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(200).set_body_json(limit)).expect(1).mount(&limited).await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x00" })))
+        .expect(2)
+        .mount(&healthy)
+        .await;
+
+    let client = Client::new(&failing.uri()).with_fallback_urls(&[limited.uri(), healthy.uri()]);
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, "0x00");
+    let failovers = client.take_failovers();
+    assert_eq!(failovers.len(), 2);
+    assert_eq!((failovers[0].from.trim_end_matches('/'), failovers[0].to.trim_end_matches('/')), (failing.uri().as_str(), limited.uri().as_str()));
+    assert!(failovers[1].reason.contains("rate limited"), "{}", failovers[1]);
+
+    // The RPC that answered is kept for the rest of the run
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000002").await.unwrap().result, "0x00");
+    assert_eq!(client.failover_count(), 2);
+    assert!(client.take_failovers().is_empty());
+}
+
+#[tokio::test]
+async fn it_doesnt_fail_over_when_the_rpc_refuses_the_call() {
+    let (primary, fallback) = (MockServer::start().await, MockServer::start().await);
+    let refused = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32602, "message": "invalid argument 0" } });
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(200).set_body_json(refused)).mount(&primary).await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(200)).expect(0).mount(&fallback).await;
+
+    let client = Client::new(&primary.uri()).with_fallback_urls(&[fallback.uri()]);
+    assert!(matches!(client.get_code("0x0000000000000000000000000000000000000001").await, Err(ClientError::Rpc { code: -32602, .. })));
+    assert_eq!(client.failover_count(), 0);
+}
+
+#[tokio::test]
+async fn it_sends_rpc_and_provider_requests_through_the_proxy() {
+    // The mock acts as a plain HTTP proxy, the hosts of the URLs don't exist and are only reached through it
+    let proxy = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header("proxy-authorization", "Basic dXNlcjpzM2NyZXQ="))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x00" })))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .and(header("proxy-authorization", "Basic dXNlcjpzM2NyZXQ="))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    let url = proxy.uri().replace("http://", "http://user:s3cret@");
+    let options = ClientOptions { proxy: Some(url), ..Default::default() };
+    let client = Client::with_options("http://rpc.sigmund.invalid", &options).unwrap();
+    let provider = Etherface::with_url(client.http().clone(), "http://etherface.sigmund.invalid");
+
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, "0x00");
+    client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+
+    let options = ClientOptions { proxy: Some("ftp://proxy:21".to_string()), ..Default::default() };
+    assert!(matches!(Client::with_options("http://localhost", &options), Err(ClientError::Proxy(_))));
+}
+
+#[tokio::test]
+async fn it_sends_the_rpc_and_provider_headers_apart() {
+    let (rpc, api) = (MockServer::start().await, MockServer::start().await);
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x00" })))
+        .mount(&rpc)
+        .await;
+    Mock::given(method("GET")).respond_with(ResponseTemplate::new(404)).mount(&api).await;
+
+    let headers = |headers: &[(&'static str, &'static str)]| headers.iter().map(|&(name, value)| (name.parse().unwrap(), value.parse().unwrap())).collect();
+    let options = ClientOptions {
+        headers: headers(&[("x-tenant", "acme")]),
+        rpc_headers: headers(&[("x-auth", "rpc-token")]),
+        provider_headers: headers(&[("x-api-key", "provider-key")]),
+        ..Default::default()
+    };
+    let client = Client::with_options(&rpc.uri(), &options).unwrap();
+    let provider = Etherface::with_url(client.http().clone(), &api.uri());
+    client.get_code("0x0000000000000000000000000000000000000001").await.unwrap();
+    client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+
+    let rpc_request = &rpc.received_requests().await.unwrap()[0];
+    assert_eq!((rpc_request.headers["x-tenant"].to_str().unwrap(), rpc_request.headers["x-auth"].to_str().unwrap()), ("acme", "rpc-token"));
+    assert!(!rpc_request.headers.contains_key("x-api-key"));
+
+    let api_request = &api.received_requests().await.unwrap()[0];
+    assert_eq!((api_request.headers["x-tenant"].to_str().unwrap(), api_request.headers["x-api-key"].to_str().unwrap()), ("acme", "provider-key"));
+    assert!(!api_request.headers.contains_key("x-auth"));
+}
+
+#[tokio::test]
+async fn it_sends_the_rpc_auth_to_the_rpc_only() {
+    let (rpc, api) = (MockServer::start().await, MockServer::start().await);
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x60003560e01c8063a9059cbb14601057" })))
+        .mount(&rpc)
+        .await;
+    Mock::given(method("GET")).respond_with(ResponseTemplate::new(404)).expect(1).mount(&api).await;
+
+    let provider_url = format!("{}/lookup/{{selector}}", api.uri());
+    let args = ["sigmund", "--signatures", "--no-cache", "--address", "0x0000000000000000000000000000000000000001", "--rpc-url", &rpc.uri(), "--rpc-auth", "Bearer rpc-token"];
+    let config = Config::parse_from(args.into_iter().chain(["--provider", "none", "--provider-url", &provider_url]));
+    // The token can't leak through a serialized config
+    assert!(!serde_json::to_string(&config).unwrap().contains("rpc-token"));
+    Sigmund::from_config(config).unwrap().execute().await.unwrap();
+
+    assert_eq!(rpc.received_requests().await.unwrap()[0].headers["authorization"], "Bearer rpc-token");
+    assert!(!api.received_requests().await.unwrap()[0].headers.contains_key("authorization"));
+
+    // The credentials of the URL are sent with basic auth, and redacted once serialized
+    let url = rpc.uri().replacen("http://", "http://user:secret@", 1);
+    Client::new(&url).get_code("0x0000000000000000000000000000000000000001").await.unwrap();
+    assert_eq!(rpc.received_requests().await.unwrap()[1].headers["authorization"], "Basic dXNlcjpzZWNyZXQ=");
+    assert!(!serde_json::to_string(&Config::parse_from(["sigmund", "--rpc-url", &url, "config", "show"])).unwrap().contains("secret"));
+}
+
+#[tokio::test]
+async fn it_decompresses_gzip_responses() {
+    let server = MockServer::start().await;
+    let body = include_bytes!("fixtures/get_code.json.gz").to_vec();
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json").insert_header("content-encoding", "gzip"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::new(&server.uri());
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, "0x6080604052");
+}
+
+#[tokio::test]
+async fn it_refuses_a_response_over_the_size_limit() {
+    let server = MockServer::start().await;
+    // A misconfigured endpoint answering with a whole web page
+    let page = format!("<html>{}</html>", "<div>explorer</div>".repeat(64 * 1024));
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html")).mount(&server).await;
+
+    let options = ClientOptions { max_response_size: 1024 * 1024, ..Default::default() };
+    let client = Client::with_options(&server.uri(), &options).unwrap();
+    let error = client.get_code("0x0000000000000000000000000000000000000001").await.err().unwrap();
+    assert!(matches!(error, ClientError::ResponseTooLarge { limit: 1_048_576, .. }), "{error}");
+    assert!(error.to_string().contains("more than 1.0 MiB"), "{error}");
+
+    // The signature lookups are capped alike, failing the lookup instead of the run
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    Mock::given(method("GET")).respond_with(ResponseTemplate::new(200).set_body_raw("[".repeat(2 * 1024 * 1024), "application/json")).mount(&server).await;
+    client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+    assert!(client.failed_lookups()["a9059cbb"].contains("ResponseTooLargeError"));
+}
+
+#[tokio::test]
+async fn it_cancels_the_requests_in_flight_at_the_deadline() {
+    let server = MockServer::start().await;
+    let stalled = ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x6080" })).set_delay(Duration::from_secs(30));
+    Mock::given(method("POST")).respond_with(stalled).mount(&server).await;
+
+    let options = ClientOptions { deadline: Some(Instant::now() + Duration::from_millis(300)), ..Default::default() };
+    let client = Client::with_options(&server.uri(), &options).unwrap();
+    let start = Instant::now();
+    let error = client.get_code("0x0000000000000000000000000000000000000001").await.err().unwrap();
+    assert!(error.to_string().starts_with("DeadlineError"), "{error}");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(client.deadline_exceeded());
+
+    // Past the deadline no lookup is sent, and the selectors are left unresolved without failing
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    assert!(client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap().is_empty());
+    assert!(client.failed_lookups().is_empty());
+}
+
+#[tokio::test]
+async fn it_paces_from_the_last_slot_instead_of_bursting_after_a_pause() {
+    let pace = Pace::new(10);
+    for _ in 0..3 {
+        pace.wait().await;
+    }
+    // A page whose contracts take a while to analyze
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // The slots missed during the pause aren't caught up on, only the first request after it goes right away
+    let start = Instant::now();
+    for _ in 0..3 {
+        pace.wait().await;
+    }
+    assert!(start.elapsed() >= Duration::from_millis(190), "{:?}", start.elapsed());
+}
@@ -0,0 +1,14 @@
+//! The tests setting environment variables, which the parallel tests of the other files would otherwise read.
+//! Each test file runs in a process of its own, and this one holds a single test, so nothing runs concurrently.
+
+use sigmund::config::{Config, Origin};
+
+#[test]
+fn it_records_the_options_given_through_the_environment() {
+    std::env::set_var("SIGMUND_CA_BUNDLE", "/nonexistent/ca.pem");
+    let config = Config::try_parse_with_origins(["sigmund", "config", "show"]).unwrap();
+    std::env::remove_var("SIGMUND_CA_BUNDLE");
+
+    assert_eq!(config.origins["ca_bundle"], Origin::Env);
+    assert_eq!(config.report().problems, ["ConfigError: --ca-bundle `/nonexistent/ca.pem` doesn't exist"]);
+}
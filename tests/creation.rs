@@ -0,0 +1,63 @@
+use sigmund::{AbiValue, ConstructorArgs, ParamType};
+
+/// ERC-20 constructor arguments for `("Token", "TKN")`.
+const ERC20_ARGS: &str = concat!(
+    "0000000000000000000000000000000000000000000000000000000000000040",
+    "0000000000000000000000000000000000000000000000000000000000000080",
+    "0000000000000000000000000000000000000000000000000000000000000005",
+    "546f6b656e000000000000000000000000000000000000000000000000000000",
+    "0000000000000000000000000000000000000000000000000000000000000003",
+    "544b4e0000000000000000000000000000000000000000000000000000000000",
+);
+
+/// ERC-1967 proxy constructor arguments for `(implementation, initialize(owner))`.
+const PROXY_ARGS: &str = concat!(
+    "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+    "0000000000000000000000000000000000000000000000000000000000000040",
+    "0000000000000000000000000000000000000000000000000000000000000024",
+    "c4d66de8000000000000000000000000d8da6bf26964af9d7eed9e03e53415d3",
+    "7aa9604500000000000000000000000000000000000000000000000000000000",
+);
+
+#[test]
+fn it_decodes_erc20_constructor_strings_heuristically() {
+    let args = ConstructorArgs::decode_heuristic(&hex::decode(ERC20_ARGS).unwrap());
+    assert!(args.heuristic);
+    assert_eq!(args.args.len(), 2);
+    assert_eq!(args.args[0].value, AbiValue::String("Token".to_string()));
+    assert_eq!(args.args[1].value, AbiValue::String("TKN".to_string()));
+}
+
+#[test]
+fn it_decodes_proxy_constructor_heuristically() {
+    let args = ConstructorArgs::decode_heuristic(&hex::decode(PROXY_ARGS).unwrap());
+    assert_eq!(args.args.len(), 2);
+    assert_eq!(args.args[0].kind, "address");
+    assert_eq!(args.args[0].value, AbiValue::Address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string()));
+    assert_eq!(args.args[1].kind, "bytes");
+    assert_eq!(
+        args.args[1].value,
+        AbiValue::Bytes("0xc4d66de8000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045".to_string())
+    );
+}
+
+#[test]
+fn it_decodes_constructor_args_precisely_with_abi_inputs() {
+    let inputs = vec![
+        ("implementation".to_string(), ParamType::parse("address").unwrap()),
+        ("data".to_string(), ParamType::parse("bytes").unwrap()),
+    ];
+    let args = ConstructorArgs::decode(&hex::decode(PROXY_ARGS).unwrap(), &inputs).unwrap();
+    assert!(!args.heuristic);
+    assert_eq!(args.args[0].name.as_deref(), Some("implementation"));
+    assert_eq!(args.args[1].kind, "bytes");
+}
+
+#[test]
+fn it_splits_constructor_args_after_the_metadata_trailer() {
+    // runtime code, followed by a truncated `ipfs` metadata map of 11 bytes and its length
+    let code = format!("6080604052a264697066735822aabbcc000b{ERC20_ARGS}");
+    let code = hex::decode(code).unwrap();
+    let args = sigmund::split_constructor_args(&code).unwrap();
+    assert_eq!(hex::encode(args), ERC20_ARGS);
+}
@@ -0,0 +1,118 @@
+use clap::Parser;
+use serde_json::json;
+use sigmund::{config::Config, deployed_by, find_creations, Address, Client, Sigmund};
+#[cfg(feature = "keccak")]
+use sigmund::create_address;
+use wiremock::{
+    matchers::{body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const DEPLOYER: &str = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+
+fn deployer() -> Address {
+    Address::try_from(DEPLOYER.to_string()).unwrap()
+}
+
+/// Answers the RPC requests containing the given text with a `result` or an `error` member.
+async fn respond(server: &MockServer, contains: &str, member: &str, value: serde_json::Value) {
+    Mock::given(method("POST"))
+        .and(body_string_contains(contains))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, member: value })))
+        .mount(server)
+        .await;
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_derives_the_addresses_of_created_contracts() {
+    assert_eq!(create_address(&deployer(), 0).unwrap(), "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d");
+    assert_eq!(create_address(&deployer(), 1).unwrap(), "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8");
+    assert_eq!(create_address(&deployer(), 2).unwrap(), "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91");
+}
+
+#[tokio::test]
+async fn it_finds_the_creations_in_the_traces() {
+    let server = MockServer::start().await;
+    let traces = json!([
+        { "type": "create", "result": { "address": "0x00000000000000000000000000000000000000AA" } },
+        { "type": "call", "result": { "output": "0x" } },
+        { "type": "create", "error": "Reverted" },
+        { "type": "create", "result": { "address": "0x00000000000000000000000000000000000000bb" } },
+    ]);
+    respond(&server, "trace_filter", "result", traces).await;
+
+    let created = find_creations(&Client::new(&server.uri()), &deployer(), 1, 2).await.unwrap();
+    assert_eq!(created, vec!["0x00000000000000000000000000000000000000aa", "0x00000000000000000000000000000000000000bb"]);
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_analyzes_every_contract_of_a_deployer() {
+    let server = MockServer::start().await;
+    respond(&server, "trace_filter", "error", json!({ "code": -32601, "message": "the method trace_filter does not exist" })).await;
+
+    let transaction = |from: &str, to: Option<&str>, nonce: &str| json!({ "hash": format!("0x{nonce}"), "from": from, "to": to, "nonce": nonce });
+    let block_1 = json!({ "transactions": [
+        transaction("0x6AC7EA33F8831EA9DCC53393AAA88B25A785DBF0", None, "0x0"),
+        transaction(DEPLOYER, Some("0x00000000000000000000000000000000000000aa"), "0x1"),
+    ] });
+    let block_2 = json!({ "transactions": [
+        transaction("0x00000000000000000000000000000000000000cc", None, "0x0"),
+        transaction(DEPLOYER, None, "0x2"),
+    ] });
+    respond(&server, r#""params":["0x1",true]"#, "result", block_1).await;
+    respond(&server, r#""params":["0x2",true]"#, "result", block_2).await;
+    respond(&server, "eth_getCode", "result", json!("0x60003560e01c8063a9059cbb14601057")).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let args = ["sigmund", "--deployer", DEPLOYER, "--from-block", "1", "--to-block", "2", "--rpc-url", &server.uri()];
+    let args = args.into_iter().chain(["--output-dir", dir.path().to_str().unwrap(), "--no-cache"]);
+    Sigmund::from_config(Config::parse_from(args)).unwrap().execute().await.unwrap();
+
+    let mut files: Vec<String> = std::fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned()).collect();
+    files.sort();
+    assert_eq!(files, vec!["0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d.json", "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91.json"]);
+}
+
+#[tokio::test]
+async fn it_analyzes_the_contract_deployed_by_a_transaction() {
+    let server = MockServer::start().await;
+    let receipt = json!({ "from": DEPLOYER, "to": null, "contractAddress": "0xCD234A471B72BA2F1CCF0A70FCABA648A5EECD8D", "blockNumber": "0x1", "logs": [] });
+    respond(&server, "0x00000000000000000000000000000000000000000000000000000000000000c1", "result", receipt).await;
+    respond(&server, "eth_getCode", "result", json!("0x60003560e01c8063a9059cbb14601057")).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.json");
+    let hash = format!("0x{:0>64}", "C1");
+    let args = ["sigmund", "--tx", &hash, "--rpc-url", &server.uri(), "--output", output.to_str().unwrap(), "--no-cache"];
+    Sigmund::from_config(Config::parse_from(args)).unwrap().execute().await.unwrap();
+
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+    assert_eq!(out["meta"]["input_as_given"], "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d");
+    assert_eq!(out["selectors"], json!(["a9059cbb"]));
+
+    // The inputs are exclusive, and the hash is checked before any request
+    assert!(Config::try_parse_from(["sigmund", "--tx", &hash, "--address", DEPLOYER]).is_err());
+    assert!(Config::try_parse_from(["sigmund", "--tx", "0x1234"]).is_err());
+}
+
+#[tokio::test]
+async fn it_explains_transactions_that_created_no_contract() {
+    let server = MockServer::start().await;
+    let factory = "0x00000000000000000000000000000000000000fa";
+    let created = "0x00000000000000000000000000000000000000c2";
+    let receipt = json!({ "from": DEPLOYER, "to": factory, "contractAddress": null, "blockNumber": "0x10", "logs": [{ "address": factory }, { "address": created }] });
+    respond(&server, "0x00000000000000000000000000000000000000000000000000000000000000f1", "result", receipt).await;
+    respond(&server, "eth_getTransactionReceipt", "result", json!(null)).await;
+    let client = Client::new(&server.uri());
+
+    let error = deployed_by(&client, &format!("0x{:0>64}", "f1")).await.unwrap_err().to_string();
+    assert!(error.contains(&format!("called {factory} instead of creating a contract")), "{error}");
+    // The creations of the factory are traced from the factory, not from the account calling it
+    assert!(error.contains(&format!("--deployer {factory} --from-block 16 --to-block 16")), "{error}");
+    assert!(error.ends_with(&format!("emitted its logs: {created}")), "{error}");
+
+    let error = deployed_by(&client, &format!("0x{:0>64}", "f2")).await.unwrap_err().to_string();
+    assert!(error.contains("isn't mined yet"), "{error}");
+}
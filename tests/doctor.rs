@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use clap::Parser;
+use sigmund::{
+    config::Config,
+    providers::{Etherface, ProviderChain, ProviderError, SignatureProvider},
+    Client, Signature, Sigmund, PROBE_SELECTOR,
+};
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+use wiremock::{
+    matchers::{any, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[derive(Clone, Default)]
+struct Console(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Console {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A provider knowing `transfer(address,uint256)`.
+struct Working;
+
+#[async_trait]
+impl SignatureProvider for Working {
+    fn name(&self) -> &str {
+        "working"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        Ok(vec![Signature::verified("transfer(address,uint256)".to_string(), selector)])
+    }
+}
+
+/// A provider whose API is down.
+struct Dead;
+
+#[async_trait]
+impl SignatureProvider for Dead {
+    fn name(&self) -> &str {
+        "dead"
+    }
+
+    async fn resolve(&self, _selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        Err(ProviderError::Api("service unavailable".to_string()))
+    }
+}
+
+#[tokio::test]
+async fn it_checks_each_component_and_fails_if_one_does() {
+    let rpc = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0xa4b1" })))
+        .mount(&rpc)
+        .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    // The path of the RPC URL holds its API key, which the check doesn't show
+    let url = format!("{}/v2/KEY", rpc.uri());
+    let args = ["sigmund", "doctor", "--rpc-url", &url, "--cache-dir", dir.path().to_str().unwrap()];
+    let provider = ProviderChain::new(vec![Box::new(Working), Box::new(Dead)]);
+    let console = Console::default();
+    let sigmund = Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(provider)).with_console(Box::new(console.clone()));
+
+    let checks = sigmund.doctor(false).await;
+    let outcomes: Vec<(&str, bool)> = checks.iter().map(|check| (check.component.as_str(), check.passed)).collect();
+    let rpc_component = format!("rpc {}/REDACTED", rpc.uri());
+    assert_eq!(outcomes, [(rpc_component.as_str(), true), ("provider working", true), ("provider dead", false), ("cache directory", true)]);
+    assert_eq!(checks[0].detail, "chain id 42161");
+    assert_eq!(checks[1].detail, "resolved a9059cbb to transfer(address,uint256)");
+
+    let console = String::from_utf8(console.0.lock().unwrap().clone()).unwrap();
+    assert!(console.lines().any(|line| line.starts_with("FAIL  provider dead  (") && line.ends_with(&checks[2].detail)), "{console}");
+
+    let error = sigmund.execute().await.unwrap_err();
+    assert_eq!(error.to_string(), "1 of 4 checks failed");
+}
+
+#[tokio::test]
+async fn it_fails_a_remote_provider_that_does_not_resolve_the_probe() {
+    // A proxy answering with an HTML error page instead of the API
+    let server = MockServer::start().await;
+    Mock::given(any()).respond_with(ResponseTemplate::new(200).set_body_raw("<html>Bad gateway</html>", "text/html")).mount(&server).await;
+
+    let provider = Etherface::with_url(Client::new("").http().clone(), &server.uri());
+    let checks = provider.check(PROBE_SELECTOR).await;
+    assert!(!checks[0].passed, "{}", checks[0]);
+    assert_eq!(checks[0].detail, "responded without resolving a9059cbb, which it should know, check its URL");
+}
@@ -0,0 +1,38 @@
+use sigmund::format::{human_bytes, human_count, human_duration, human_rate, pct, plural, selector};
+use std::time::Duration;
+
+#[test]
+fn it_renders_durations_with_the_largest_fitting_unit() {
+    assert_eq!(human_duration(Duration::from_millis(850)), "850ms");
+    assert_eq!(human_duration(Duration::from_millis(2140)), "2.1s");
+    assert_eq!(human_duration(Duration::from_secs(185)), "3m 05s");
+    assert_eq!(human_duration(Duration::from_secs(7380)), "2h 03m");
+}
+
+#[test]
+fn it_renders_sizes_counts_and_percentages_independently_of_the_locale() {
+    assert_eq!(human_bytes(512), "512 B");
+    assert_eq!(human_bytes(1536), "1.5 KiB");
+    assert_eq!(human_bytes(3 * 1024 * 1024 + 200 * 1024), "3.2 MiB");
+
+    assert_eq!(human_count(0), "0");
+    assert_eq!(human_count(999), "999");
+    assert_eq!(human_count(10_000_000), "10,000,000");
+    assert_eq!(human_rate(3_900_000, Duration::from_secs(1)), "3,900,000/s");
+
+    assert_eq!(pct(0.425), "42.5%");
+    assert_eq!(pct(1.0), "100.0%");
+}
+
+#[test]
+fn it_truncates_hashes_to_their_selector() {
+    assert_eq!(selector("a9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b"), "a9059cbb");
+    assert_eq!(selector("a905"), "a905");
+}
+
+#[test]
+fn it_counts_in_the_singular_or_the_plural() {
+    assert_eq!(plural(1, "entry", "entries"), "1 entry");
+    assert_eq!(plural(0, "pivot", "pivots"), "0 pivots");
+    assert_eq!(plural(42, "entry", "entries"), "42 entries");
+}
@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use sigmund::{
+    config::Config,
+    providers::{ProviderError, SignatureProvider},
+    History, HistoryEntry, HistoryError, Signature, Sigmund,
+};
+use std::path::Path;
+
+fn entry(input: &str) -> HistoryEntry {
+    let args = ["sigmund", "--signatures", "--address", input].map(str::to_string);
+    HistoryEntry { code_hash: Some("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".to_string()), ..HistoryEntry::new(&args, input, 2) }
+}
+
+fn ids(history: &History) -> Vec<usize> {
+    history.entries().iter().map(|entry| entry.id).collect()
+}
+
+#[test]
+fn it_appends_runs_and_finds_them_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut history = History::open(dir.path()).unwrap();
+    for input in ["0x00000000000000000000000000000000000000aa", "0x00000000000000000000000000000000000000bb"] {
+        history.record(entry(input)).unwrap();
+    }
+
+    let history = History::open(dir.path()).unwrap();
+    assert_eq!(ids(&history), vec![1, 2]);
+    assert_eq!(history.get(2).unwrap().input, "0x00000000000000000000000000000000000000bb");
+    assert!(matches!(history.get(3), Err(HistoryError::NotFound(3))));
+    // Searches ignore the case and the 0x prefix, and look at the code hash as well
+    assert_eq!(history.search("00AA").len(), 1);
+    assert_eq!(history.search("0xc5d2460186f7").len(), 2);
+    assert!(history.search("0xdead").is_empty());
+}
+
+#[test]
+fn it_compacts_the_history_to_the_latest_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    // A line cut short by an interrupted run is skipped
+    std::fs::write(dir.path().join("history.jsonl"), "{\"id\": 1, \"times\n").unwrap();
+    let mut history = History::open(dir.path()).unwrap().with_max_entries(2);
+
+    for _ in 0..3 {
+        history.record(entry("0x00000000000000000000000000000000000000aa")).unwrap();
+    }
+    assert_eq!(ids(&history), vec![1, 2, 3]);
+    // The file holds more than twice as many lines as kept once the fourth run is recorded
+    history.record(entry("0x00000000000000000000000000000000000000aa")).unwrap();
+    let lines = std::fs::read_to_string(history.path()).unwrap().lines().count();
+    assert_eq!((ids(&history), lines), (vec![3, 4], 2));
+
+    // The runs keep their number after a compaction
+    history.record(entry("0x00000000000000000000000000000000000000aa")).unwrap();
+    assert_eq!(ids(&History::open(dir.path()).unwrap()), vec![3, 4, 5]);
+
+    history.clear().unwrap();
+    assert!(History::open(dir.path()).unwrap().entries().is_empty());
+}
+
+#[test]
+fn it_numbers_and_keeps_the_runs_of_concurrent_histories() {
+    let dir = tempfile::tempdir().unwrap();
+    // Both runs open the history before either of them records
+    let mut first = History::open(dir.path()).unwrap();
+    let mut second = History::open(dir.path()).unwrap().with_max_entries(2);
+
+    assert_eq!(first.record(entry("0x00000000000000000000000000000000000000aa")).unwrap(), 1);
+    assert_eq!(second.record(entry("0x00000000000000000000000000000000000000bb")).unwrap(), 2);
+    assert_eq!(first.record(entry("0x00000000000000000000000000000000000000cc")).unwrap(), 3);
+
+    // The compaction of the second history keeps the run the first one recorded in the meantime
+    second.compact().unwrap();
+    assert_eq!(ids(&History::open(dir.path()).unwrap()), vec![2, 3]);
+}
+
+#[test]
+fn it_reconstructs_the_config_of_a_run_without_its_secrets() {
+    let args = ["sigmund", "--signatures", "--etherscan", "--etherscan-key", "secret", "--etherscan-key=secret", "--address", "0x00000000000000000000000000000000000000aa"];
+    let args = args.into_iter().chain(["--header", "X-Tenant: secret", "--rpc-header=X-Auth: secret", "--provider-header", "Api-Key: secret", "--rpc-auth", "Bearer secret"]);
+    let args: Vec<String> = args.chain(["--rpc-url", "https://eth-mainnet.g.alchemy.com/v2/secret,https://rpc.example/?apikey=secret"]).map(str::to_string).collect();
+    let entry = HistoryEntry::new(&args, "0x00000000000000000000000000000000000000aa", 0);
+
+    assert!(!entry.args.join(" ").contains("secret"), "{:?}", entry.args);
+    let config = entry.config().unwrap();
+    assert!(config.signatures && config.etherscan);
+    assert_eq!(config.address.as_deref(), Some("0x00000000000000000000000000000000000000aa"));
+    assert!(config.header.is_empty() && config.rpc_header.is_empty() && config.provider_header.is_empty());
+    assert_eq!(config.rpc_url, ["https://eth-mainnet.g.alchemy.com/REDACTED", "https://rpc.example/?apikey=REDACTED"]);
+}
+
+/// A provider that only knows `transfer(address,uint256)`.
+struct TransferProvider;
+
+#[async_trait]
+impl SignatureProvider for TransferProvider {
+    fn name(&self) -> &str {
+        "transfer"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        match selector {
+            "a9059cbb" => Ok(vec![Signature::verified("transfer(address,uint256)".to_string(), selector)]),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+async fn run(dir: &Path, history: &[&str]) {
+    let file = dir.join("token.hex");
+    // A dispatcher comparing the selectors of `transfer(address,uint256)` and `balanceOf(address)`
+    std::fs::write(&file, "0x60003560e01c8063a9059cbb14601757806370a0823114601757").unwrap();
+
+    let args = [&["sigmund", "--signatures", "--no-cache", "--cache-dir", dir.to_str().unwrap(), "--file", file.to_str().unwrap()], history].concat();
+    let sigmund = Sigmund::from_config(Config::try_parse_with_origins(args).unwrap()).unwrap();
+    sigmund.with_provider(Box::new(TransferProvider)).with_console(Box::new(std::io::sink())).execute().await.unwrap();
+}
+
+#[tokio::test]
+async fn it_records_the_runs_only_with_history() {
+    let dir = tempfile::tempdir().unwrap();
+    run(dir.path(), &[]).await;
+    assert!(History::open(dir.path()).unwrap().entries().is_empty());
+
+    run(dir.path(), &["--history"]).await;
+    let history = History::open(dir.path()).unwrap();
+    let entry = &history.entries()[0];
+    assert!(entry.input.ends_with("token.hex"));
+    assert_eq!((entry.selectors, entry.coverage, entry.chain_id), (2, Some(0.5), None));
+    #[cfg(feature = "keccak")]
+    {
+        let code = hex::decode("60003560e01c8063a9059cbb14601757806370a0823114601757").unwrap();
+        assert_eq!(entry.code_hash, Some(hex::encode(sigmund::signature::keccak(&code))));
+    }
+    assert!(entry.config().unwrap().history);
+}
+
+#[test]
+fn it_resolves_the_relative_paths_of_a_run_against_its_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let args = ["sigmund", "--file", "token.hex", "--output", "/tmp/out.json", "--rpc-url", "geth.ipc", "scan", "--from-block", "1", "--to-block", "2", "--checkpoint", "scan.json"].map(str::to_string);
+    let entry = HistoryEntry { cwd: dir.path().to_path_buf(), ..HistoryEntry::new(&args, "token.hex", 0) };
+    let current = std::env::current_dir().unwrap();
+
+    let config = entry.config().unwrap();
+    assert_eq!(config.file, Some(dir.path().join("token.hex")));
+    assert_eq!(config.output.as_deref(), Some(Path::new("/tmp/out.json")));
+    assert_eq!(config.rpc_url, [dir.path().join("geth.ipc").to_string_lossy()]);
+    assert!(matches!(config.command, Some(sigmund::config::Command::Scan { checkpoint: Some(path), .. }) if path == dir.path().join("scan.json")));
+    // The working directory of the process is left alone
+    assert_eq!(std::env::current_dir().unwrap(), current);
+}
@@ -0,0 +1,265 @@
+use clap::Parser;
+use sigmund::{config::Config, input, ChainRegistry, ClientError, Input, InputFormat, Sigmund};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use wiremock::{
+    matchers::{body_partial_json, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const CODE: [u8; 5] = [0x60, 0x80, 0x60, 0x40, 0x52];
+
+/// Write the content to a file in the given directory and return its path.
+fn write(dir: &Path, content: &[u8]) -> PathBuf {
+    let path = dir.join("bytecode");
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn read(content: &[u8], format: Option<InputFormat>) -> (Vec<u8>, InputFormat) {
+    let dir = tempfile::tempdir().unwrap();
+    let (bytecode, format) = sigmund::input::read(&write(dir.path(), content), format).unwrap();
+    (bytecode.inner, format)
+}
+
+#[test]
+fn it_detects_hex_and_binary_files() {
+    assert_eq!(read(b"0x6080604052\n", None), (CODE.to_vec(), InputFormat::Hex));
+    assert_eq!(read(b"6080604052", None), (CODE.to_vec(), InputFormat::Hex));
+    assert_eq!(read(&CODE, None), (CODE.to_vec(), InputFormat::Binary));
+}
+
+#[test]
+fn it_detects_json_exports() {
+    assert_eq!(read(br#"{"bytecode": "0x6080604052"}"#, None), (CODE.to_vec(), InputFormat::Json));
+    assert_eq!(read(br#"{"jsonrpc": "2.0", "id": 1, "result": "0x6080604052"}"#, None), (CODE.to_vec(), InputFormat::Json));
+    assert_eq!(read(br#"{"deployedBytecode": {"object": "0x6080604052"}}"#, None), (CODE.to_vec(), InputFormat::Json));
+    assert_eq!(read(br#""0x6080604052""#, None), (CODE.to_vec(), InputFormat::Json));
+}
+
+#[test]
+fn it_detects_base64_that_decodes_to_evm_code() {
+    assert_eq!(read(b"YIBgQFI=", None), (CODE.to_vec(), InputFormat::Base64));
+
+    // Valid base64, but the decoded bytes don't start like EVM code
+    let dir = tempfile::tempdir().unwrap();
+    assert!(sigmund::input::read(&write(dir.path(), b"QUJD"), None).is_err());
+}
+
+#[test]
+fn it_prefers_hex_when_both_hex_and_base64_decode() {
+    // "60806040" is valid hex and valid base64 without padding
+    assert_eq!(read(b"60806040", None), (CODE[..4].to_vec(), InputFormat::Hex));
+
+    let (code, format) = read(b"60806040", Some(InputFormat::Base64));
+    assert_eq!(format, InputFormat::Base64);
+    assert_eq!(code, vec![0xeb, 0x4f, 0x34, 0xeb, 0x4e, 0x34]);
+}
+
+#[test]
+fn it_reports_content_invalid_in_the_requested_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write(dir.path(), br#"{"abi": []}"#);
+
+    let error = sigmund::input::read(&path, Some(InputFormat::Json)).err().unwrap();
+    assert!(error.to_string().contains("not valid json"));
+    assert!(sigmund::input::read(&path, Some(InputFormat::Hex)).is_err());
+}
+
+#[test]
+fn it_dedupes_inputs_referring_to_the_same_contract() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write(dir.path(), &CODE);
+    let mut inputs = vec![
+        Input::parse("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"),
+        Input::File(path.clone()),
+        Input::parse("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"),
+        Input::File(dir.path().join(".").join("bytecode")),
+        Input::parse("0x0000000000000000000000000000000000000001"),
+    ];
+
+    assert_eq!(input::dedupe(&mut inputs), 2);
+    // The first occurrence of each contract keeps its position
+    assert_eq!(
+        inputs,
+        vec![Input::Address("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED".to_string()), Input::File(path), Input::Address("0x0000000000000000000000000000000000000001".to_string())]
+    );
+}
+
+/// Runs sigmund on an address given through the flag and twice in an address file, in other casings,
+/// returning the number of `eth_getCode` requests and of output files.
+async fn run_duplicates(allow_duplicates: bool) -> (usize, usize) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x60003560e01c8063a9059cbb14601057" })))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("addresses.txt");
+    std::fs::write(&file, "# contracts\n0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED\n\n0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed\n").unwrap();
+    let outputs = dir.path().join("outputs");
+
+    let args = ["sigmund", "--address", "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "--address-file", file.to_str().unwrap(), "--rpc-url", &server.uri()];
+    let flags = ["--output-dir", outputs.to_str().unwrap(), "--ndjson"].into_iter().chain(allow_duplicates.then_some("--allow-duplicates"));
+    Sigmund::from_config(Config::parse_from(args.into_iter().chain(flags))).unwrap().execute().await.unwrap();
+
+    (server.received_requests().await.unwrap().len(), std::fs::read_dir(&outputs).unwrap().count())
+}
+
+#[tokio::test]
+async fn it_analyzes_a_contract_given_twice_once() {
+    assert_eq!(run_duplicates(false).await, (1, 1));
+    // Every occurrence is analyzed with --allow-duplicates, writing to the same file
+    assert_eq!(run_duplicates(true).await, (3, 1));
+}
+
+#[test]
+fn it_reads_the_chain_of_each_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("addresses.txt");
+    std::fs::write(&file, "arbitrum:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n10:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n\ngnosis:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n").unwrap();
+
+    let error = input::read_inputs(&file, &ChainRegistry::default()).unwrap_err().to_string();
+    assert!(error.starts_with("line 4: ChainError: Unknown chain `gnosis`"), "{error}");
+
+    // A chain given an RPC in the config file is known by its id
+    std::fs::write(&file, "arbitrum:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n10:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n100:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n").unwrap();
+    let chains = ChainRegistry::new(&BTreeMap::from([("100".to_string(), "http://localhost:8545".to_string())])).unwrap();
+    let inputs = input::read_inputs(&file, &chains).unwrap();
+    let names: Vec<String> = inputs.iter().map(ToString::to_string).collect();
+    assert_eq!(names, ["arbitrum:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "optimism:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "100:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"]);
+    assert_eq!(inputs[2].chain().map(|chain| chain.rpc_url.as_str()), Some("http://localhost:8545"));
+}
+
+/// Starts an RPC serving the given code for every address.
+async fn rpc_serving(code: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": code })))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn it_fetches_each_address_from_the_rpc_of_its_chain() {
+    let arbitrum = rpc_serving("0x60003560e01c8063a9059cbb14601057").await;
+    let gnosis = rpc_serving("0x60003560e01c806370a0823114601057").await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, format!("[rpc_urls]\narbitrum = \"{}\"\n100 = \"{}\"\n", arbitrum.uri(), gnosis.uri())).unwrap();
+    let file = dir.path().join("addresses.txt");
+    std::fs::write(&file, "100:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\narbitrum:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n").unwrap();
+    let outputs = dir.path().join("outputs");
+
+    let args = ["sigmund", "--address-file", file.to_str().unwrap(), "--output-dir", outputs.to_str().unwrap(), "--config", config.to_str().unwrap()];
+    Sigmund::from_config(Config::try_parse_with_origins(args).unwrap()).unwrap().execute().await.unwrap();
+
+    // The same address is a different contract on each chain, written to its own file
+    let read = |name: &str| serde_json::from_str::<serde_json::Value>(&std::fs::read_to_string(outputs.join(name)).unwrap()).unwrap();
+    let out = read("arbitrum_0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed.json");
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["chain"], serde_json::json!({ "id": 42161, "name": "arbitrum" }));
+    let out = read("100_0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed.json");
+    assert_eq!(out["selectors"], serde_json::json!(["70a08231"]));
+    assert_eq!(out["meta"]["chain"], serde_json::json!({ "id": 100, "name": "100" }));
+}
+
+#[tokio::test]
+async fn it_keeps_the_order_of_a_mixed_address_file() {
+    let mainnet = rpc_serving("0x60003560e01c8063a9059cbb14601057").await;
+    let arbitrum = rpc_serving("0x60003560e01c8063a9059cbb14601057").await;
+    let gnosis = rpc_serving("0x60003560e01c806370a0823114601057").await;
+    let sink = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(202)).mount(&sink).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, format!("[rpc_urls]\narbitrum = \"{}\"\n100 = \"{}\"\n", arbitrum.uri(), gnosis.uri())).unwrap();
+    let file = dir.path().join("addresses.txt");
+    std::fs::write(&file, "100:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359\narbitrum:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n").unwrap();
+
+    let args = ["sigmund", "--address-file", file.to_str().unwrap(), "--rpc-url", &mainnet.uri(), "--post-results", &sink.uri(), "--config", config.to_str().unwrap()];
+    Sigmund::from_config(Config::try_parse_with_origins(args).unwrap()).unwrap().execute().await.unwrap();
+
+    // The results are posted in the order of the file, not grouped by chain
+    let keys: Vec<String> = sink.received_requests().await.unwrap().iter().map(|request| request.headers["x-sigmund-contract"].to_str().unwrap().to_string()).collect();
+    assert_eq!(keys, ["100_0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359", "arbitrum_0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"]);
+}
+
+#[tokio::test]
+async fn it_fetches_the_mainnet_addresses_from_the_given_rpc() {
+    let server = rpc_serving("0x60003560e01c8063a9059cbb14601057").await;
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("addresses.txt");
+    std::fs::write(&file, "mainnet:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n").unwrap();
+    let outputs = dir.path().join("outputs");
+
+    let args = ["sigmund", "--address-file", file.to_str().unwrap(), "--rpc-url", &server.uri(), "--output-dir", outputs.to_str().unwrap()];
+    Sigmund::from_config(Config::try_parse_with_origins(args).unwrap()).unwrap().execute().await.unwrap();
+
+    let out = std::fs::read_to_string(outputs.join("mainnet_0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed.json")).unwrap();
+    let out: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["chain"], serde_json::json!({ "id": 1, "name": "mainnet" }));
+}
+
+#[tokio::test]
+async fn it_analyzes_on_the_rpc_of_the_chain_and_checks_its_chain_id() {
+    let server = MockServer::start().await;
+    Mock::given(body_partial_json(serde_json::json!({ "method": "eth_chainId" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x1" })))
+        .mount(&server)
+        .await;
+    Mock::given(body_partial_json(serde_json::json!({ "method": "eth_getCode" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x60003560e01c8063a9059cbb14601057" })))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, format!("[rpc_urls]\narbitrum = \"{}\"\n", server.uri())).unwrap();
+    let output = dir.path().join("out.json");
+    let sigmund = |flags: &[&str]| {
+        let args = [&["sigmund", "--chain", "arbitrum", "--address", "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "--config", config.to_str().unwrap()], flags].concat();
+        Sigmund::from_config(Config::try_parse_with_origins(args).unwrap()).unwrap()
+    };
+
+    // The RPC answers with the chain id of mainnet, which is warned about
+    sigmund(&["--output", output.to_str().unwrap()]).execute().await.unwrap();
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["chain"], serde_json::json!({ "id": 42161, "name": "arbitrum" }));
+    assert!(out["warnings"].as_array().unwrap().iter().any(|warning| warning["kind"] == "chain_mismatch"));
+
+    // or fails the run with --strict
+    let error = sigmund(&["--strict"]).execute().await.unwrap_err().to_string();
+    assert_eq!(error, "ChainError: The RPC of --chain arbitrum is on chain id 1, expected 42161");
+
+    assert!(Config::try_parse_from(["sigmund", "--chain", "base", "--rpc-url", "https://rpc.example"]).is_err());
+}
+
+#[tokio::test]
+async fn it_reports_addresses_without_code() {
+    for response in [serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }), serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x00" }), serde_json::json!({ "jsonrpc": "2.0", "id": 1 })] {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(200).set_body_json(&response)).mount(&server).await;
+
+        let args = ["sigmund", "--address", "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "--rpc-url", &server.uri()];
+        let error = Sigmund::from_config(Config::try_parse_from(args).unwrap()).unwrap().execute().await.unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(ClientError::NoCode { .. })), "{response}: {error}");
+        assert!(error.to_string().starts_with("NoCodeError: The address 0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed has no deployed code"), "{error}");
+    }
+
+    // A batch skips them instead of failing
+    let server = rpc_serving("0x").await;
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("addresses.txt");
+    std::fs::write(&file, "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\n0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359\n").unwrap();
+    let args = ["sigmund", "--address-file", file.to_str().unwrap(), "--rpc-url", &server.uri()];
+    Sigmund::from_config(Config::try_parse_from(args).unwrap()).unwrap().execute().await.unwrap();
+}
@@ -0,0 +1,73 @@
+#![cfg(unix)]
+
+use serde_json::{json, Value};
+use sigmund::{Address, Client};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixListener,
+};
+
+/// Answers `eth_getCode` with `0x60` followed by the last byte of the address, batches in reverse order,
+/// and every single request after a stale response to another request, written together with it without a newline.
+///
+/// Returns:
+/// The path of the socket, within `dir`, and the number of connections it accepted.
+fn ipc_node(dir: &tempfile::TempDir) -> (String, Arc<AtomicUsize>) {
+    let path = dir.path().join("geth.ipc").to_string_lossy().to_string();
+    let listener = UnixListener::bind(&path).unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let code = |request: &Value| {
+                    let address = request["params"][0].as_str().unwrap_or_default();
+                    json!({ "jsonrpc": "2.0", "id": request["id"], "result": format!("0x60{}", &address[address.len() - 2..]) })
+                };
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = BufReader::new(reader).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let request: Value = serde_json::from_str(&line).unwrap();
+                    let response = match request.as_array() {
+                        Some(batch) => Value::Array(batch.iter().rev().map(code).collect()).to_string(),
+                        None => format!("{}{}", json!({ "jsonrpc": "2.0", "id": 4242, "result": "0xdead" }), code(&request)),
+                    };
+                    writer.write_all(format!("{response}\n").as_bytes()).await.unwrap();
+                }
+            });
+        }
+    });
+    (path, connections)
+}
+
+#[tokio::test]
+async fn it_fetches_the_code_over_a_reused_ipc_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, connections) = ipc_node(&dir);
+    let client = Client::new(&path);
+
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, "0x6001");
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000002").await.unwrap().result, "0x6002");
+
+    // The responses of a batch are correlated by id, whatever their order
+    let addresses: Vec<Address> = (3..=5).map(|n| Address::try_from(format!("0x{n:040x}")).unwrap()).collect();
+    let codes: Vec<String> = client.get_codes(&addresses).await.into_iter().map(|code| code.unwrap().result).collect();
+    assert_eq!(codes, ["0x6003", "0x6004", "0x6005"]);
+
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn it_reports_a_missing_ipc_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reth.ipc").to_string_lossy().to_string();
+
+    let error = Client::new(&path).get_code("0x0000000000000000000000000000000000000001").await.err().unwrap().to_string();
+    assert!(error.starts_with(&format!("IpcError: {path}: ")), "{error}");
+}
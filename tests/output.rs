@@ -0,0 +1,354 @@
+use async_trait::async_trait;
+use clap::Parser;
+use sigmund::{
+    config::Config,
+    providers::{ProviderError, SignatureProvider},
+    Signature, Sigmund, SigmundOut,
+};
+#[cfg(feature = "keccak")]
+use sigmund::{signature, CandidateCheck, Page, SCHEMA_VERSION};
+#[cfg(feature = "keccak")]
+use std::collections::HashSet;
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(feature = "keccak")]
+fn out() -> SigmundOut {
+    let texts = ["transfer(address,uint256)", "balanceOf(address)", "approve(address,uint256)", "totalSupply()", "decimals()"];
+    let selectors: HashSet<String> = texts.iter().map(|text| signature::hash(text)[..8].to_string()).collect();
+    let signatures = texts.iter().map(|text| Signature::new(text.to_string(), signature::hash(text))).collect();
+
+    let mut out = SigmundOut::new(selectors.clone(), signatures);
+    out.sources = selectors.iter().map(|selector| (selector.clone(), "etherface".to_string())).collect();
+    out.candidates = Some(CandidateCheck::new(&["decimals()".to_string()], &selectors));
+    out
+}
+
+#[cfg(feature = "keccak")]
+fn page(number: usize, size: usize) -> SigmundOut {
+    let mut out = out();
+    out.paginate(number, size);
+    out
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_paginates_the_sorted_selectors() {
+    let pages: Vec<Vec<String>> = (1..=3).map(|number| page(number, 2).selectors.into_iter().collect()).collect();
+    assert_eq!(pages, vec![vec!["095ea7b3", "18160ddd"], vec!["313ce567", "70a08231"], vec!["a9059cbb"]]);
+
+    let second = page(2, 2);
+    assert_eq!(second.meta.page, Some(Page { page: 2, page_size: 2, total_pages: 3, total_selectors: 5 }));
+    assert!(second.signatures.iter().all(|s| second.selectors.contains(&s.selector)));
+    assert_eq!(second.signatures.len(), 2);
+    assert_eq!(second.sources.keys().collect::<Vec<_>>(), vec!["313ce567", "70a08231"]);
+
+    let candidates = second.candidates.unwrap();
+    assert_eq!(candidates.matched.len(), 1);
+    assert_eq!(candidates.unmatched_selectors, vec!["70a08231"]);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_returns_an_empty_page_beyond_the_last() {
+    let out = page(4, 2);
+    assert!(out.selectors.is_empty() && out.signatures.is_empty() && out.sources.is_empty());
+    assert_eq!(out.meta.page, Some(Page { page: 4, page_size: 2, total_pages: 3, total_selectors: 5 }));
+
+    let json = serde_json::to_value(&out).unwrap();
+    assert_eq!(json["meta"]["page"]["total_pages"], 3);
+    assert_eq!(json["meta"]["page"]["total_selectors"], 5);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_treats_a_zero_page_size_as_one() {
+    let out = page(1, 0);
+    assert_eq!(out.selectors.len(), 1);
+    assert_eq!(out.meta.page.unwrap().total_pages, 5);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_lists_only_the_unresolved_selectors_of_each_page() {
+    let unresolved = ["00000001", "2fffffff", "deadbeef"];
+    let pages: Vec<(Vec<String>, Vec<String>)> = (1..=3)
+        .map(|number| {
+            let mut out = out();
+            out.selectors.extend(unresolved.map(String::from));
+            out.unresolved = unresolved.map(String::from).to_vec();
+            out.paginate(number, 3);
+            (out.selectors.into_iter().collect(), out.unresolved)
+        })
+        .collect();
+
+    assert_eq!(
+        pages,
+        vec![
+            (vec!["00000001".to_string(), "095ea7b3".to_string(), "18160ddd".to_string()], vec!["00000001".to_string()]),
+            (vec!["2fffffff".to_string(), "313ce567".to_string(), "70a08231".to_string()], vec!["2fffffff".to_string()]),
+            (vec!["a9059cbb".to_string(), "deadbeef".to_string()], vec!["deadbeef".to_string()]),
+        ]
+    );
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_groups_the_matches_of_each_selector_in_rank_order() {
+    let selector = signature::hash("transfer(address,uint256)");
+    let mut transfer = Signature::verified("transfer(address,uint256)".to_string(), &selector[..8]);
+    transfer.variants = vec!["transfer(address,uint256,bytes)".to_string()];
+    let babbage = Signature::verified("many_msg_babbage(bytes1)".to_string(), &selector[..8]);
+    let decimals = Signature::new("decimals()".to_string(), signature::hash("decimals()"));
+    let out = SigmundOut::new(HashSet::new(), vec![transfer, babbage, decimals]);
+
+    let json = serde_json::to_value(&out).unwrap();
+    assert_eq!(json["meta"]["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["matches"]["a9059cbb"], serde_json::json!(["transfer(address,uint256)", "transfer(address,uint256,bytes)", "many_msg_babbage(bytes1)"]));
+    assert_eq!(json["matches"]["313ce567"], serde_json::json!(["decimals()"]));
+    // The flat list is kept for backward compatibility
+    assert_eq!(json["signatures"].as_array().unwrap().len(), 3);
+
+    // Several matches are listed under the selector, a single one stays on the selector line
+    let groups: Vec<String> = signature::group_by_selector(&out.signatures).iter().map(ToString::to_string).collect();
+    assert_eq!(groups.len(), 2);
+    assert!(groups[0].starts_with("\x1b[38;5;39m[313ce567]: ") && !groups[0].contains('\n'));
+    let lines: Vec<&str> = groups[1].lines().collect();
+    assert!(lines[0].ends_with("[a9059cbb]:"));
+    assert!(lines[1].starts_with("  ") && lines[1].contains("transfer(address,uint256) +1 variant"));
+    assert!(lines[2].starts_with("  ") && lines[2].contains("many_msg_babbage(bytes1)"));
+}
+
+/// A console capturing the streamed results.
+#[derive(Clone, Default)]
+struct Console(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Console {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A provider resolving `transfer(address,uint256)` much slower than `balanceOf(address)`.
+struct SlowProvider;
+
+#[async_trait]
+impl SignatureProvider for SlowProvider {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let text = match selector {
+            "a9059cbb" => {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                "transfer(address,uint256)"
+            }
+            "70a08231" => "balanceOf(address)",
+            _ => return Ok(Vec::new()),
+        };
+        Ok(vec![Signature::verified(text.to_string(), selector)])
+    }
+}
+
+#[tokio::test]
+async fn it_streams_selectors_then_signatures_in_completion_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bytecode");
+    // A dispatcher comparing the selectors of `transfer(address,uint256)` and `balanceOf(address)`
+    std::fs::write(&path, "0x60003560e01c8063a9059cbb14601757806370a0823114601757").unwrap();
+
+    let console = Console::default();
+    let config = Config::parse_from(["sigmund", "--signatures", "--no-cache", "--file", path.to_str().unwrap()]);
+    let sigmund = Sigmund::from_config(config).unwrap().with_provider(Box::new(SlowProvider)).with_console(Box::new(console.clone()));
+    sigmund.execute().await.unwrap();
+
+    let output = String::from_utf8(console.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 6);
+    // The selectors come first, then every signature in the order its lookup completed
+    assert!(lines[0].starts_with('{') && lines[0].contains("a9059cbb") && lines[0].contains("70a08231"));
+    assert!(lines[1].contains("balanceOf(address)"));
+    assert!(lines[2].contains("transfer(address,uint256)"));
+    // The summary closes the stream, sorted by selector
+    assert_eq!(lines[3], "resolved 2 of 2 selectors");
+    assert!(lines[4].contains("[70a08231]") && lines[5].contains("[a9059cbb]"));
+
+    // A JSON document can't be streamed, so nothing is printed to the console with --format json
+    let console = Console::default();
+    let config = Config::parse_from(["sigmund", "--signatures", "--no-cache", "--format", "json", "--file", path.to_str().unwrap()]);
+    let sigmund = Sigmund::from_config(config).unwrap().with_provider(Box::new(SlowProvider)).with_console(Box::new(console.clone()));
+    sigmund.execute().await.unwrap();
+    assert!(console.0.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn it_lists_the_unresolved_selectors_apart() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, output) = (dir.path().join("bytecode"), dir.path().join("out.json"));
+    // The dispatcher of `SlowProvider`, with two selectors no provider knows
+    std::fs::write(&path, "0x60003560e01c8063a9059cbb14601757806370a08231146017578063deadbeef146017578063000000011460175700").unwrap();
+
+    let console = Console::default();
+    let args = ["sigmund", "--signatures", "--no-cache", "--file", path.to_str().unwrap(), "--output", output.to_str().unwrap()];
+    let sigmund = Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(SlowProvider)).with_console(Box::new(console.clone()));
+    sigmund.execute().await.unwrap();
+
+    let console = String::from_utf8(console.0.lock().unwrap().clone()).unwrap();
+    assert!(console.contains("resolved 2 of 4 selectors"));
+    assert!(console.contains("unresolved:\n  [00000001]\n  [deadbeef]"));
+
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+    assert_eq!(out["unresolved"], serde_json::json!(["00000001", "deadbeef"]));
+    assert_eq!(out["signatures"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn it_retries_only_the_unresolved_selectors_of_a_previous_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, output) = (dir.path().join("bytecode"), dir.path().join("out.json"));
+    std::fs::write(&path, "0x60003560e01c8063a9059cbb14601757806370a08231146017578063deadbeef146017578063000000011460175700").unwrap();
+    let args = ["sigmund", "--signatures", "--no-cache", "--file", path.to_str().unwrap(), "--output", output.to_str().unwrap()];
+    Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(SlowProvider)).with_console(Box::new(std::io::sink())).execute().await.unwrap();
+    let before: SigmundOut = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(before.retry_selectors().into_iter().collect::<Vec<_>>(), vec!["00000001", "deadbeef"]);
+
+    // The previous output is updated in place, with the newly resolved selector merged in
+    let console = Console::default();
+    let args = ["sigmund", "--signatures", "--no-cache", "--retry-failed", output.to_str().unwrap()];
+    let sigmund = Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(CollidingProvider)).with_console(Box::new(console.clone()));
+    sigmund.execute().await.unwrap();
+    let console = String::from_utf8(console.0.lock().unwrap().clone()).unwrap();
+    assert!(console.contains("resolved 1 of 2 retried selectors"));
+
+    let after: SigmundOut = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(after.unresolved, vec!["00000001"]);
+    assert_eq!(after.matches["deadbeef"], vec!["withdraw(bytes)"]);
+    // The signatures resolved before are kept untouched, even though the new provider knows `transfer` as well
+    for signature in &before.signatures {
+        assert!(after.signatures.contains(signature));
+    }
+    assert_eq!(after.signatures.len(), before.signatures.len() + 1);
+    assert_eq!((after.selectors, after.sources.get("a9059cbb")), (before.selectors, before.sources.get("a9059cbb")));
+}
+
+/// A provider resolving a selector to a function-like match ranked before an error-like one.
+struct CollidingProvider;
+
+#[async_trait]
+impl SignatureProvider for CollidingProvider {
+    fn name(&self) -> &str {
+        "colliding"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let texts = match selector {
+            "a9059cbb" => vec!["transfer(address,uint256)"],
+            "deadbeef" => vec!["withdraw(bytes)", "Unauthorized(bytes)"],
+            _ => vec![],
+        };
+        Ok(texts.into_iter().map(|text| Signature::verified(text.to_string(), selector)).collect())
+    }
+}
+
+#[tokio::test]
+async fn it_lists_the_custom_errors_of_revert_paths_separately() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, output) = (dir.path().join("bytecode"), dir.path().join("out.json"));
+    // A dispatcher comparing the selector of `transfer(address,uint256)`, and a revert with the error 0xdeadbeef
+    std::fs::write(&path, "0x60003560e01c8063a9059cbb1460175763deadbeef60e01b60005260046000fd").unwrap();
+
+    let args = ["sigmund", "--signatures", "--errors", "--no-cache", "--file", path.to_str().unwrap(), "--output", output.to_str().unwrap()];
+    let sigmund = Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(CollidingProvider));
+    sigmund.with_console(Box::new(Console::default())).execute().await.unwrap();
+
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+    assert_eq!(out["signatures"].as_array().unwrap().len(), 1);
+    assert!(out["signatures"][0].get("kind").is_none());
+    // The selector is only reverted with, so the error-like match is ranked first
+    assert_eq!(out["errors"].as_array().unwrap().len(), 1);
+    assert_eq!(out["errors"][0]["text"], "Unauthorized(bytes)");
+    assert_eq!(out["errors"][0]["kind"], "error");
+}
+
+/// Runs sigmund with the given flags on a file with two selectors, returning its JSON output.
+async fn run_with(flags: &[&str]) -> serde_json::Value {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, output) = (dir.path().join("bytecode"), dir.path().join("out.json"));
+    std::fs::write(&path, "0x60003560e01c8063a9059cbb14601757806370a08231146017578063deadbeef146017578063000000011460175700").unwrap();
+    let args = ["sigmund", "--signatures", "--file", path.to_str().unwrap(), "--output", output.to_str().unwrap(), "--cache-dir", dir.path().to_str().unwrap()];
+    let config = Config::try_parse_with_origins(args.iter().chain(flags)).unwrap();
+    Sigmund::from_config(config).unwrap().with_provider(Box::new(CollidingProvider)).with_console(Box::new(std::io::sink())).execute().await.unwrap();
+    serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn it_produces_the_same_output_with_a_deprecated_flag() {
+    let mut deprecated = run_with(&["--cache-ttl", "2h"]).await;
+    let current = run_with(&["--negative-ttl", "2h"]).await;
+
+    // Only the deprecation warning tells them apart
+    let warnings = deprecated["warnings"].as_array_mut().unwrap();
+    assert_eq!(warnings.iter().filter(|w| w["kind"] == "deprecated_flag").count(), 1);
+    warnings.retain(|w| w["kind"] != "deprecated_flag");
+    assert_eq!(deprecated, current);
+
+    let quiet = run_with(&["--cache-ttl", "2h", "--no-deprecation-warnings"]).await;
+    assert_eq!(quiet, current);
+}
+
+#[tokio::test]
+async fn it_lists_the_child_init_selectors_of_a_factory_apart() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.json");
+    let args = ["sigmund", "--deep", "--file", "tests/fixtures/pair_factory.hex", "--output", output.to_str().unwrap()];
+    Sigmund::from_config(Config::parse_from(args)).unwrap().with_console(Box::new(std::io::sink())).execute().await.unwrap();
+
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+    assert_eq!(out["child_init_selectors"], serde_json::json!(["485cc955"]));
+    assert_eq!(out["selectors"], serde_json::json!(["574f2ba3", "a9059cbb", "bc25cf77", "c9c65396", "e6a43905"]));
+}
+
+/// A provider that never answers for `transfer(address,uint256)`.
+struct StalledProvider;
+
+#[async_trait]
+impl SignatureProvider for StalledProvider {
+    fn name(&self) -> &str {
+        "stalled"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        match selector {
+            "a9059cbb" => std::future::pending().await,
+            _ => SlowProvider.resolve(selector).await,
+        }
+    }
+}
+
+#[tokio::test]
+async fn it_writes_a_partial_output_at_the_deadline() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, output) = (dir.path().join("bytecode"), dir.path().join("out.json"));
+    std::fs::write(&path, "0x60003560e01c8063a9059cbb14601757806370a0823114601757").unwrap();
+
+    let args = ["sigmund", "--signatures", "--no-cache", "--deadline", "1s", "--file", path.to_str().unwrap(), "--output", output.to_str().unwrap()];
+    let sigmund = Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(StalledProvider)).with_console(Box::new(std::io::sink()));
+    let error = sigmund.execute().await.unwrap_err();
+    assert!(sigmund.deadline_exceeded());
+    assert!(error.to_string().contains("--deadline of 1.0s"));
+
+    // The stalled lookup is cancelled rather than failed, and what was resolved before is kept
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+    assert_eq!(out["partial"], true);
+    assert_eq!(out["matches"], serde_json::json!({ "70a08231": ["balanceOf(address)"] }));
+    assert_eq!(out["unresolved"], serde_json::json!(["a9059cbb"]));
+    assert!(out.get("failures").is_none());
+}
@@ -0,0 +1,946 @@
+use async_trait::async_trait;
+use clap::Parser;
+use sigmund::{
+    config::Config,
+    providers::{Etherface, JsonPath, Etherscan, Fourbyte, LocalDb, Openchain, ParseWarning, ParseWarnings, ProviderChain, ProviderError, SignatureProvider, Sourcify},
+    signature, Client, LookupFailure, RateLimits, Signature, SignatureCache, Sigmund,
+};
+#[cfg(feature = "keccak")]
+use sigmund::{providers::UrlTemplate, EventSignature, HashKind};
+#[cfg(feature = "keccak")]
+use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// A provider resolving every selector to the same two ranked matches.
+struct StaticProvider;
+
+#[async_trait]
+impl SignatureProvider for StaticProvider {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        match selector {
+            "a9059cbb" => Ok(vec![
+                Signature::new("transfer(address,uint256)".to_string(), selector.to_string()),
+                Signature::new("many_msg_babbage(bytes1)".to_string(), selector.to_string()),
+            ]),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+fn selectors() -> HashSet<String> {
+    HashSet::from(["a9059cbb".to_string(), "deadbeef".to_string()])
+}
+
+#[tokio::test]
+async fn it_keeps_the_first_ranked_match_of_a_custom_provider() {
+    let signatures = Client::new("").get_signatures(&StaticProvider, &selectors(), false).await.unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+}
+
+#[tokio::test]
+async fn it_keeps_all_matches_of_a_custom_provider() {
+    let signatures = Client::new("").get_signatures(&StaticProvider, &selectors(), true).await.unwrap();
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["transfer(address,uint256)", "many_msg_babbage(bytes1)"]);
+}
+
+#[tokio::test]
+async fn it_keeps_the_top_matches_of_each_selector() {
+    let signatures = Client::new("").get_signatures(&StaticProvider, &selectors(), true).await.unwrap();
+    let top = |n: usize| signature::top_matches(signatures.clone(), n).into_iter().map(|s| s.text).collect::<Vec<_>>();
+
+    assert_eq!(top(1), vec!["transfer(address,uint256)"]);
+    // Selectors with fewer matches than requested keep all of them
+    assert_eq!(top(5), vec!["transfer(address,uint256)", "many_msg_babbage(bytes1)"]);
+}
+
+/// A provider resolving `a9059cbb` and rejecting the lookup of any other selector.
+struct BrokenProvider;
+
+#[async_trait]
+impl SignatureProvider for BrokenProvider {
+    fn name(&self) -> &str {
+        "broken"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        match selector {
+            "a9059cbb" => StaticProvider.resolve(selector).await,
+            _ => Err(ProviderError::Api("invalid API key".to_string())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn it_collects_failed_lookups_without_failing_the_others() {
+    let client = Client::new("");
+    let signatures = client.get_signatures(&BrokenProvider, &selectors(), false).await.unwrap();
+
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+    let failure = LookupFailure { selector: "deadbeef".to_string(), reason: "ProviderApiError: invalid API key".to_string() };
+    assert_eq!(client.take_failures(), vec![failure]);
+    // The failures are taken, so the next contract of a batch only reports its own
+    assert!(client.take_failures().is_empty());
+}
+
+#[tokio::test]
+async fn it_fails_lookups_with_strict_only() {
+    let sigmund = |strict: &[&str]| {
+        let args = [&["sigmund", "lookup", "0xa9059cbb", "0xdeadbeef", "--no-cache"], strict].concat();
+        Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(BrokenProvider)).with_console(Box::new(std::io::sink()))
+    };
+    let hashes = ["0xa9059cbb".to_string(), "0xdeadbeef".to_string()];
+
+    let lookups = sigmund(&[]).lookup(&hashes).await.unwrap();
+    assert_eq!(lookups[0].signatures[0].text, "transfer(address,uint256)");
+    assert!(lookups[1].signatures.is_empty());
+
+    let error = sigmund(&["--strict"]).lookup(&hashes).await.unwrap_err();
+    assert_eq!(error.to_string(), "1 lookups failed with --strict, e.g. deadbeef: ProviderApiError: invalid API key");
+}
+
+/// A provider that only knows the `deadbeef` selector.
+struct FallbackProvider;
+
+#[async_trait]
+impl SignatureProvider for FallbackProvider {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        match selector {
+            "deadbeef" => Ok(vec![Signature::new("beef()".to_string(), selector.to_string())]),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn it_falls_back_through_the_chain_and_attributes_sources() {
+    let chain = ProviderChain::new(vec![Box::new(StaticProvider), Box::new(FallbackProvider)]);
+    let mut selectors = selectors();
+    selectors.insert("00000000".to_string());
+
+    let mut signatures = Client::new("").get_signatures(&chain, &selectors, false).await.unwrap();
+    signatures.sort_by(|a, b| a.text.cmp(&b.text));
+
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["beef()", "transfer(address,uint256)"]);
+    assert_eq!(chain.source_of("a9059cbb").as_deref(), Some("static"));
+    assert_eq!(chain.source_of("deadbeef").as_deref(), Some("fallback"));
+    assert_eq!(chain.source_of("00000000"), None);
+}
+
+/// A provider that is down, counting the requests sent to it.
+struct DownProvider(Arc<AtomicUsize>);
+
+#[async_trait]
+impl SignatureProvider for DownProvider {
+    fn name(&self) -> &str {
+        "down"
+    }
+
+    async fn resolve(&self, _selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Err(ProviderError::Api("service unavailable".to_string()))
+    }
+}
+
+#[tokio::test]
+async fn it_skips_a_provider_failing_too_many_requests_in_a_row() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let chain = ProviderChain::new(vec![Box::new(DownProvider(requests.clone())), Box::new(StaticProvider)]).with_circuit_breaker(3);
+    let mut selectors: HashSet<String> = (0..9).map(|i| format!("{i:08x}")).collect();
+    selectors.insert("a9059cbb".to_string());
+
+    // The selectors fall through to the next provider, which is the only one queried once the first one is tripped
+    let client = Client::new("").with_concurrency(1);
+    let signatures = client.get_signatures(&chain, &selectors, false).await.unwrap();
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+    assert_eq!(chain.tripped_providers(), vec!["down"]);
+    // The selectors the next provider doesn't know may be known to the skipped one, so they failed instead of being unknown
+    let failures = client.take_failures();
+    assert_eq!(failures.len(), 9);
+    assert!(failures.iter().any(|failure| failure.reason.starts_with("ProviderCircuitOpenError: down is skipped")));
+}
+
+#[tokio::test]
+async fn it_records_the_provider_of_each_signature_through_the_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = ProviderChain::new(vec![Box::new(StaticProvider), Box::new(FallbackProvider)]);
+    let client = Client::new("").with_cache(SignatureCache::open(dir.path()).unwrap());
+    let sources = |signatures: Vec<Signature>| signatures.into_iter().map(|s| (s.selector, s.source)).collect::<BTreeMap<_, _>>();
+
+    let resolved = sources(client.get_signatures(&chain, &selectors(), false).await.unwrap());
+    let expected = BTreeMap::from([("a9059cbb".to_string(), "static".to_string()), ("deadbeef".to_string(), "fallback".to_string())]);
+    assert_eq!(resolved, expected);
+
+    // Cache hits keep the provider they were cached from
+    let client = Client::new("").with_cache(SignatureCache::open(dir.path()).unwrap());
+    assert_eq!(sources(client.get_signatures(&chain, &selectors(), false).await.unwrap()), expected);
+    assert_eq!(client.cache_hits().len(), 2);
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_resolves_from_a_local_json_db_and_warns_about_invalid_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("signatures.json");
+    let json = r#"{"0xa9059cbb": "transfer(address,uint256)", "deadbeef": "pause()", "abc": "x()"}"#;
+    std::fs::write(&path, json).unwrap();
+
+    let db = LocalDb::open(&path).unwrap();
+    assert_eq!(db.warnings().len(), 2);
+
+    let signatures = db.resolve("a9059cbb").await.unwrap();
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+    assert_eq!(signatures[0].hash, "a9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b");
+    assert!(db.resolve("deadbeef").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn it_prefers_local_csv_entries_over_remote_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("signatures.csv");
+    std::fs::write(&path, "selector,signature\n8456cb59,\"pause()\"\na9059cbb,transfer(address,uint256)\n").unwrap();
+
+    let chain = ProviderChain::new(vec![Box::new(LocalDb::open(&path).unwrap()), Box::new(StaticProvider)]);
+    let selectors = HashSet::from(["a9059cbb".to_string(), "8456cb59".to_string()]);
+    let signatures = Client::new("").get_signatures(&chain, &selectors, true).await.unwrap();
+
+    assert_eq!(signatures.len(), 2);
+    assert_eq!(chain.source_of("a9059cbb").as_deref(), Some("local"));
+    assert_eq!(chain.source_of("8456cb59").as_deref(), Some("local"));
+}
+
+#[cfg(feature = "offline")]
+#[tokio::test]
+async fn it_resolves_common_selectors_offline() {
+    let offline = sigmund::providers::Offline;
+    assert!(offline.len() > 2000);
+
+    let mut selectors = selectors();
+    selectors.insert("70a08231".to_string());
+    let signatures = Client::new("").get_signatures(&offline, &selectors, false).await.unwrap();
+
+    let mut texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    texts.sort();
+    assert_eq!(texts, vec!["balanceOf(address)", "transfer(address,uint256)"]);
+}
+
+#[cfg(feature = "offline")]
+#[test]
+fn it_bundles_the_compressed_table_of_the_signature_list() {
+    use std::io::Read;
+
+    let mut table = String::new();
+    flate2::read::GzDecoder::new(&include_bytes!("../data/common_signatures.txt.gz")[..]).read_to_string(&mut table).unwrap();
+    assert_eq!(table, include_str!("../data/common_signatures.txt"), "regenerate the table with `gzip -9nkf data/common_signatures.txt`");
+}
+
+/// Mount the two page 4byte fixture for the collided `cae9ca51` selector.
+async fn mount_fourbyte(server: &MockServer) {
+    let page1 = include_str!("fixtures/fourbyte_cae9ca51_page1.json").replace("{{NEXT}}", &format!("{}/page2", server.uri()));
+    let page2 = include_str!("fixtures/fourbyte_cae9ca51_page2.json").replace("{{PREVIOUS}}", &server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/signatures/"))
+        .and(query_param("hex_signature", "0xcae9ca51"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(page1, "application/json"))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/page2"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(page2, "application/json"))
+        .mount(server)
+        .await;
+}
+
+/// Mount the two page Etherface fixture for the collided `cae9ca51` selector.
+async fn mount_etherface_pages(server: &MockServer) {
+    for page in [1, 2] {
+        let body = match page {
+            1 => include_str!("fixtures/etherface_cae9ca51_page1.json"),
+            _ => include_str!("fixtures/etherface_cae9ca51_page2.json"),
+        };
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/signatures/hash/all/cae9ca51/{page}")))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn it_merges_the_etherface_pages_of_all_matches() {
+    let server = MockServer::start().await;
+    mount_etherface_pages(&server).await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri()).with_max_pages(10);
+    let signatures = client.get_signatures(&provider, &HashSet::from(["cae9ca51".to_string()]), true).await.unwrap();
+
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["approveAndCall(address,uint256,bytes)", "onHintFinanceCallback(address,address,uint256,bytes)"]);
+}
+
+#[tokio::test]
+async fn it_only_fetches_the_first_etherface_page_by_default() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/cae9ca51/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(include_str!("fixtures/etherface_cae9ca51_page1.json"), "application/json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    assert_eq!(provider.resolve("cae9ca51").await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn it_parses_the_captured_responses_of_every_provider_as_expected() {
+    let server = MockServer::start().await;
+    mount_etherface_pages(&server).await;
+    mount_fourbyte(&server).await;
+    Mock::given(path("/signature-database/v1/lookup"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(include_str!("fixtures/openchain_lookup.json"), "application/json"))
+        .mount(&server)
+        .await;
+
+    // The fixtures are responses of the live APIs, refreshing them catches a change of their schema
+    let (client, warnings) = (Client::new(""), ParseWarnings::default());
+    let etherface = Etherface::with_url(client.http().clone(), &server.uri()).with_max_pages(2).with_parse_warnings(warnings.clone());
+    let fourbyte = Fourbyte::with_url(client.http().clone(), &server.uri()).with_parse_warnings(warnings.clone());
+    let openchain = Openchain::with_url(client.http().clone(), &server.uri()).with_parse_warnings(warnings.clone());
+    assert_eq!(etherface.resolve("cae9ca51").await.unwrap().len(), 2);
+    assert_eq!(fourbyte.resolve("cae9ca51").await.unwrap().len(), 3);
+    assert_eq!(openchain.resolve("a9059cbb").await.unwrap().len(), 1);
+    assert_eq!(warnings.take(), vec![]);
+}
+
+#[tokio::test]
+async fn it_counts_the_responses_that_drifted_from_the_expected_schema() {
+    let server = MockServer::start().await;
+    Mock::given(path("/v1/signatures/hash/all/cae9ca51/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(include_str!("fixtures/etherface_cae9ca51_drifted.json"), "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(path("/v1/signatures/hash/all/a9059cbb/1")).respond_with(ResponseTemplate::new(200).set_body_raw("<html>maintenance</html>", "text/html")).mount(&server).await;
+
+    let warnings = ParseWarnings::default();
+    let etherface = Etherface::with_url(Client::new("").http().clone(), &server.uri()).with_parse_warnings(warnings.clone());
+    // The wrapped page is unwrapped and its unknown fields ignored, only the item without a text is skipped
+    let signatures = etherface.resolve("cae9ca51").await.unwrap();
+    assert_eq!(signatures.iter().map(|s| s.text.as_str()).collect::<Vec<_>>(), vec!["approveAndCall(address,uint256,bytes)"]);
+    assert!(etherface.resolve("a9059cbb").await.unwrap().is_empty());
+
+    let warning = ParseWarning { provider: "etherface".to_string(), count: 3, first: "the page is wrapped in `data`".to_string() };
+    assert_eq!(warnings.take(), vec![warning]);
+    // The counts are taken, so the next contract of a batch only reports its own
+    assert!(warnings.take().is_empty());
+}
+
+#[tokio::test]
+async fn it_follows_fourbyte_pages_and_orders_by_id() {
+    let server = MockServer::start().await;
+    mount_fourbyte(&server).await;
+
+    let client = Client::new("");
+    let provider = Fourbyte::with_url(client.http().clone(), &server.uri());
+    let signatures = provider.resolve("cae9ca51").await.unwrap();
+
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec![
+            "approveAndCall(address,uint256,bytes)",
+            "approveAndCall(address,uint256,bytes)",
+            "onHintFinanceCallback(address,address,uint256,bytes)"
+        ]
+    );
+    assert_eq!(signatures[0].selector, "cae9ca51");
+    assert_eq!(signatures[0].first_seen.unwrap().to_rfc3339(), "2018-05-12T10:42:11+00:00");
+}
+
+#[tokio::test]
+async fn it_keeps_the_oldest_fourbyte_match_for_collided_selectors() {
+    let server = MockServer::start().await;
+    mount_fourbyte(&server).await;
+
+    let client = Client::new("");
+    let provider = Fourbyte::with_url(client.http().clone(), &server.uri());
+    let selectors = HashSet::from(["cae9ca51".to_string()]);
+    let signatures = client.get_signatures(&provider, &selectors, false).await.unwrap();
+
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].text, "approveAndCall(address,uint256,bytes)");
+}
+
+/// A provider resolving `cae9ca51` to a common signature and a collision with it.
+struct CollidedProvider;
+
+#[async_trait]
+impl SignatureProvider for CollidedProvider {
+    fn name(&self) -> &str {
+        "collided"
+    }
+
+    async fn resolve(&self, selector: &str) -> Result<Vec<Signature>, ProviderError> {
+        let texts = ["approveAndCall(address,uint256,bytes)", "onHintFinanceFlashloan(address,address,uint256,bool,bytes)"];
+        Ok(texts.iter().map(|text| Signature::new(text.to_string(), selector.to_string())).collect())
+    }
+}
+
+#[tokio::test]
+async fn it_ranks_the_functions_of_the_given_abi_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let abi = dir.path().join("abi.json");
+    let inputs = r#"[{"type": "address"}, {"type": "address"}, {"type": "uint256"}, {"type": "bool"}, {"type": "bytes"}]"#;
+    std::fs::write(&abi, format!(r#"{{"abi": [{{"type": "function", "name": "onHintFinanceFlashloan", "inputs": {inputs}}}]}}"#)).unwrap();
+
+    let lookup = |args: &[&str]| {
+        let args = [&["sigmund", "--no-cache", "--all-matches"], args, &["lookup", "0xcae9ca51"]].concat();
+        Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(CollidedProvider)).with_console(Box::new(std::io::sink()))
+    };
+    let hashes = ["0xcae9ca51".to_string()];
+    let texts = |lookups: Vec<sigmund::HashLookup>| lookups[0].signatures.iter().map(|s| s.text.clone()).collect::<Vec<_>>();
+
+    let common = texts(lookup(&[]).lookup(&hashes).await.unwrap());
+    assert_eq!(common, vec!["approveAndCall(address,uint256,bytes)", "onHintFinanceFlashloan(address,address,uint256,bool,bytes)"]);
+    // The function of the contract's ABI outranks the common signature
+    let ranked = texts(lookup(&["--abi", abi.to_str().unwrap()]).lookup(&hashes).await.unwrap());
+    assert_eq!(ranked, vec!["onHintFinanceFlashloan(address,address,uint256,bool,bytes)", "approveAndCall(address,uint256,bytes)"]);
+}
+
+#[tokio::test]
+async fn it_resolves_openchain_selectors_in_a_single_request() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/signature-database/v1/lookup"))
+        .and(query_param("filter", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(include_str!("fixtures/openchain_lookup.json"), "application/json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    let provider = Openchain::with_url(client.http().clone(), &server.uri());
+    let selectors = HashSet::from(["a9059cbb".to_string(), "70a08231".to_string(), "deadbeef".to_string()]);
+    let mut signatures = client.get_signatures(&provider, &selectors, true).await.unwrap();
+    signatures.sort_by(|a, b| a.text.cmp(&b.text));
+
+    // The spam entry flagged by openchain is filtered out, unknown selectors are omitted
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["balanceOf(address)", "transfer(address,uint256)"]);
+    assert_eq!(signatures[1].selector, "a9059cbb");
+}
+
+#[tokio::test]
+async fn it_batches_many_selectors_into_few_requests() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/signature-database/v1/lookup"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"ok":true,"result":{"function":{}}}"#, "application/json"))
+        .expect(1..=3)
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    let provider = Openchain::with_url(client.http().clone(), &server.uri());
+    let selectors: HashSet<String> = (0..120u32).map(|i| format!("{i:08x}")).collect();
+    let signatures = client.get_signatures(&provider, &selectors, false).await.unwrap();
+    assert!(signatures.is_empty());
+
+    // Every selector is looked up exactly once
+    let mut requested: Vec<String> = Vec::new();
+    for request in server.received_requests().await.unwrap() {
+        let (_, functions) = request.url.query_pairs().find(|(key, _)| key == "function").unwrap();
+        requested.extend(functions.split(',').map(|s| s.trim_start_matches("0x").to_string()));
+    }
+    let mut expected: Vec<String> = selectors.into_iter().collect();
+    requested.sort();
+    expected.sort();
+    assert_eq!(requested, expected);
+}
+
+#[tokio::test]
+async fn it_caps_oversized_responses_after_ranking() {
+    let server = MockServer::start().await;
+    let items: Vec<_> = (0..200u64)
+        .map(|i| serde_json::json!({ "hash": "a9059cbb", "text": format!("spam_{i}(uint256)"), "popularity": i }))
+        .collect();
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "items": items })))
+        .mount(&server)
+        .await;
+
+    let client = Client::new("").with_max_matches(50);
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let selectors = HashSet::from(["a9059cbb".to_string()]);
+    let signatures = client.get_signatures(&provider, &selectors, true).await.unwrap();
+
+    // The most popular matches survive and the truncation is recorded on the first one
+    assert_eq!(signatures.len(), 50);
+    assert_eq!(signatures[0].text, "spam_199(uint256)");
+    assert_eq!(signatures[49].text, "spam_150(uint256)");
+    assert_eq!(signatures[0].truncated, Some(150));
+    assert!(signatures[1..].iter().all(|s| s.truncated.is_none()));
+
+    let signatures = client.get_signatures(&provider, &selectors, false).await.unwrap();
+    assert_eq!(signatures[0].truncated, None);
+}
+
+/// Mount an Etherface response listing `transfer(address,uint256)` for `a9059cbb`.
+async fn mount_etherface_transfer(server: &MockServer) {
+    let items = serde_json::json!({ "items": [{ "hash": "a9059cbb", "text": "transfer(address,uint256)" }] });
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(items))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn it_retries_transient_lookup_failures() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    mount_etherface_transfer(&server).await;
+
+    let client = Client::new("").with_retries(3, Duration::from_millis(1));
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+    assert!(client.failed_lookups().is_empty());
+}
+
+#[tokio::test]
+async fn it_leaves_selectors_unresolved_once_retries_are_exhausted() {
+    let server = MockServer::start().await;
+    mount_etherface_transfer(&server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/deadbeef/1"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    // Unknown selectors respond with a 404, which isn't retried
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/00000000/1"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::new("").with_retries(2, Duration::from_millis(1));
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let mut selectors = selectors();
+    selectors.insert("00000000".to_string());
+    let signatures = client.get_signatures(&provider, &selectors, false).await.unwrap();
+
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(client.failed_lookups().keys().collect::<Vec<_>>(), vec!["deadbeef"]);
+}
+
+#[tokio::test]
+async fn it_waits_for_rate_limited_lookups_and_counts_them() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    mount_etherface_transfer(&server).await;
+
+    // Rate limited responses don't count as retries
+    let client = Client::new("").with_retries(0, Duration::from_millis(1));
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+    assert_eq!(client.rate_limits().events, 2);
+    assert!(client.failed_lookups().is_empty());
+}
+
+#[tokio::test]
+async fn it_gives_up_on_rate_limited_lookups_past_the_wait_cap() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/signatures/"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "3600"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::new("").with_rate_limit_wait(Duration::from_secs(10));
+    let provider = Fourbyte::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &HashSet::from(["a9059cbb".to_string()]), false).await.unwrap();
+
+    assert!(signatures.is_empty());
+    assert_eq!(client.rate_limits(), RateLimits { events: 1, waited: Duration::ZERO });
+    assert!(client.failed_lookups()["a9059cbb"].contains("429"));
+}
+
+/// Serves empty Etherface responses after a delay, recording the peak number of requests in flight.
+async fn serve_counting(peak: Arc<AtomicUsize>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let in_flight = Arc::new(AtomicUsize::new(0));
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let (peak, in_flight) = (peak.clone(), in_flight.clone());
+            tokio::spawn(async move {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).await;
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                let body = r#"{"items":[]}"#;
+                let response = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}", body.len());
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    url
+}
+
+#[tokio::test]
+async fn it_limits_the_lookups_in_flight() {
+    let peak = Arc::new(AtomicUsize::new(0));
+    let url = serve_counting(peak.clone()).await;
+
+    let client = Client::new("").with_concurrency(4);
+    let selectors: HashSet<String> = (0..40u32).map(|i| format!("{i:08x}")).collect();
+
+    let provider = Etherface::with_url(client.http().clone(), &url);
+    client.get_signatures(&provider, &selectors, false).await.unwrap();
+    assert!((1..=4).contains(&peak.load(Ordering::SeqCst)), "{peak:?}");
+
+    // The limit holds for the fallbacks of a chain as well
+    peak.store(0, Ordering::SeqCst);
+    let chain = ProviderChain::new(vec![Box::new(StaticProvider), Box::new(Etherface::with_url(client.http().clone(), &url))]);
+    client.get_signatures(&chain, &selectors, false).await.unwrap();
+    assert!((1..=4).contains(&peak.load(Ordering::SeqCst)), "{peak:?}");
+}
+
+#[tokio::test]
+async fn it_reads_the_functions_of_a_verified_abi() {
+    let server = MockServer::start().await;
+    let abi = r#"[{"type":"constructor","inputs":[]},{"type":"function","name":"transfer","inputs":[{"type":"address"},{"type":"uint256"}]},{"type":"event","name":"Transfer","inputs":[]},{"name":"fill","inputs":[{"type":"tuple[]","components":[{"type":"address"},{"type":"bytes32"}]}]}]"#;
+    Mock::given(method("GET"))
+        .and(query_param("action", "getabi"))
+        .and(query_param("address", "0x00000000000000000000000000000000000000aa"))
+        .and(query_param("apikey", "secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "1", "message": "OK", "result": abi })))
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    let etherscan = Etherscan::with_url(client.http().clone(), &server.uri(), Some("secret".to_string()));
+    let functions = etherscan.functions("0x00000000000000000000000000000000000000aa").await.unwrap();
+    assert_eq!(functions, Some(vec!["transfer(address,uint256)".to_string(), "fill((address,bytes32)[])".to_string()]));
+}
+
+#[tokio::test]
+async fn it_rotates_to_the_next_key_when_one_is_rate_limited() {
+    let server = MockServer::start().await;
+    let abi = r#"[{"type":"function","name":"transfer","inputs":[{"type":"address"},{"type":"uint256"}]}]"#;
+    // The first key is only tried once, then skipped for its cooldown
+    Mock::given(query_param("apikey", "first"))
+        .respond_with(ResponseTemplate::new(429))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(query_param("apikey", "second"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "1", "message": "OK", "result": abi })))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    let etherscan = Etherscan::with_url(client.http().clone(), &server.uri(), vec!["first".to_string(), "second".to_string()]);
+    for _ in 0..3 {
+        let functions = etherscan.functions("0x00000000000000000000000000000000000000aa").await.unwrap();
+        assert_eq!(functions, Some(vec!["transfer(address,uint256)".to_string()]));
+    }
+}
+
+#[tokio::test]
+async fn it_keeps_the_keys_out_of_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).respond_with(ResponseTemplate::new(429)).expect(2).mount(&server).await;
+
+    // Once every key is rate limited, the error names the request without its key
+    let client = Client::new("");
+    let etherscan = Etherscan::with_url(client.http().clone(), &server.uri(), vec!["first".to_string(), "second".to_string()]);
+    let error = etherscan.functions("0x00000000000000000000000000000000000000aa").await.unwrap_err().to_string();
+    assert!(error.contains("apikey=REDACTED") && !error.contains("first") && !error.contains("second"), "{error}");
+}
+
+#[tokio::test]
+async fn it_distinguishes_unverified_contracts_from_api_errors() {
+    let server = MockServer::start().await;
+    Mock::given(query_param("address", "0x00000000000000000000000000000000000000aa"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(
+            serde_json::json!({ "status": "0", "message": "NOTOK", "result": "Contract source code not verified" }),
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(query_param("address", "0x00000000000000000000000000000000000000bb"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" })))
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    let etherscan = Etherscan::with_url(client.http().clone(), &server.uri(), None);
+    assert_eq!(etherscan.functions("0x00000000000000000000000000000000000000aa").await.unwrap(), None);
+
+    let error = etherscan.functions("0x00000000000000000000000000000000000000bb").await.unwrap_err();
+    assert!(matches!(error, ProviderError::Api(_)), "{error}");
+}
+
+#[tokio::test]
+async fn it_reads_the_abi_from_the_sourcify_metadata() {
+    let server = MockServer::start().await;
+    let metadata = serde_json::json!({ "output": { "abi": [{ "type": "function", "name": "balanceOf", "inputs": [{ "type": "address" }] }] } });
+    let files = serde_json::json!({ "status": "partial", "files": [{ "name": "metadata.json", "content": metadata.to_string() }] });
+    Mock::given(path("/files/any/10/0x00000000000000000000000000000000000000aa"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(files))
+        .mount(&server)
+        .await;
+    Mock::given(path("/files/any/10/0x00000000000000000000000000000000000000bb"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({ "error": "Files have not been found!" })))
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    let sourcify = Sourcify::with_url(client.http().clone(), &server.uri());
+    let functions = sourcify.functions(10, "0x00000000000000000000000000000000000000aa").await.unwrap();
+    assert_eq!(functions, Some(vec!["balanceOf(address)".to_string()]));
+    // Unknown contracts fall through to the signature databases
+    assert_eq!(sourcify.functions(10, "0x00000000000000000000000000000000000000bb").await.unwrap(), None);
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_resolves_through_a_url_template() {
+    let server = MockServer::start().await;
+    let results = serde_json::json!({ "results": [{ "signature": "transfer(address,uint256)" }, { "signature": "collision(bytes)" }] });
+    Mock::given(path("/api/sig/a9059cbb")).respond_with(ResponseTemplate::new(200).set_body_json(results)).mount(&server).await;
+    Mock::given(path("/api/sig/70a08231")).respond_with(ResponseTemplate::new(404)).mount(&server).await;
+
+    let client = Client::new("");
+    let template = format!("{}/api/sig/{{selector}}", server.uri());
+    let provider = UrlTemplate::new(client.http().clone(), &template, JsonPath::parse("results[].signature").unwrap());
+
+    let signatures = provider.resolve("a9059cbb").await.unwrap();
+    assert_eq!(signatures.iter().map(|s| s.text.as_str()).collect::<Vec<_>>(), vec!["transfer(address,uint256)", "collision(bytes)"]);
+    assert!(signatures[0].verified && !signatures[1].verified);
+
+    // Selectors the custom database doesn't know fall back to the next provider
+    assert!(provider.resolve("70a08231").await.unwrap().is_empty());
+    let missing = UrlTemplate::new(client.http().clone(), &format!("{}/missing/{{selector}}", server.uri()), JsonPath::default());
+    let chain = ProviderChain::new(vec![Box::new(missing), Box::new(StaticProvider)]);
+    assert!(!chain.resolve("a9059cbb").await.unwrap().is_empty());
+    assert_eq!(chain.source_of("a9059cbb").as_deref(), Some("static"));
+}
+
+#[test]
+fn it_parses_json_paths() {
+    let value = serde_json::json!({ "data": { "matches": [["a()", "b()"], ["c()"]] } });
+    let select = |path: &str| JsonPath::parse(path).unwrap().select(&value).into_iter().cloned().collect::<Vec<_>>();
+
+    assert_eq!(select("data.matches[1][0]"), vec![serde_json::json!("c()")]);
+    assert_eq!(select("data.matches[][]").len(), 3);
+    assert_eq!(select("data.missing[]"), Vec::<serde_json::Value>::new());
+    assert_eq!(select(""), vec![value.clone()]);
+
+    assert!(JsonPath::parse("data..matches").is_err());
+    assert!(JsonPath::parse("data.matches[x]").is_err());
+}
+
+const TRANSFER_TOPIC: &str = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_resolves_event_topics_from_fourbyte_and_openchain() {
+    let server = MockServer::start().await;
+    let fourbyte = serde_json::json!({
+        "next": null,
+        "results": [
+            { "id": 2, "text_signature": "Crafted(uint256)", "hex_signature": format!("0x{TRANSFER_TOPIC}") },
+            { "id": 1, "text_signature": "Transfer(address,address,uint256)", "hex_signature": format!("0x{TRANSFER_TOPIC}") }
+        ]
+    });
+    Mock::given(method("GET"))
+        .and(path("/api/v1/event-signatures/"))
+        .and(query_param("hex_signature", format!("0x{TRANSFER_TOPIC}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fourbyte))
+        .mount(&server)
+        .await;
+    let openchain = serde_json::json!({ "result": { "event": { format!("0x{TRANSFER_TOPIC}"): [{ "name": "Transfer(address,address,uint256)", "filtered": false }] } } });
+    Mock::given(method("GET"))
+        .and(path("/signature-database/v1/lookup"))
+        .and(query_param("event", format!("0x{TRANSFER_TOPIC}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(openchain))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/signature-database/v1/lookup"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "result": { "event": {} } })))
+        .mount(&server)
+        .await;
+
+    let client = Client::new("");
+    // Texts that don't hash to the topic are dropped, since a topic is the whole hash
+    let events = Fourbyte::with_url(client.http().clone(), &server.uri()).resolve_event(TRANSFER_TOPIC).await.unwrap();
+    assert_eq!(events, vec![EventSignature { text: "Transfer(address,address,uint256)".to_string(), topic: TRANSFER_TOPIC.to_string() }]);
+    let events = Openchain::with_url(client.http().clone(), &server.uri()).resolve_event(TRANSFER_TOPIC).await.unwrap();
+    assert_eq!(events[0].text, "Transfer(address,address,uint256)");
+
+    // Providers without an event database fall through to the next one
+    let chain = ProviderChain::new(vec![Box::new(StaticProvider), Box::new(Openchain::with_url(client.http().clone(), &server.uri()))]);
+    let topics = BTreeSet::from([TRANSFER_TOPIC.to_string(), "11".repeat(32)]);
+    let resolved = client.get_event_signatures(&chain, &topics).await.unwrap();
+    assert_eq!(resolved[TRANSFER_TOPIC].len(), 1);
+    assert!(resolved[&"11".repeat(32)].is_empty());
+}
+
+#[tokio::test]
+async fn it_lists_the_events_and_unresolved_topics_of_a_contract() {
+    let server = MockServer::start().await;
+    let openchain = serde_json::json!({ "result": { "event": { format!("0x{TRANSFER_TOPIC}"): [{ "name": "Transfer(address,address,uint256)", "filtered": false }] } } });
+    Mock::given(method("GET"))
+        .and(query_param("event", format!("0x{TRANSFER_TOPIC}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(openchain))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "result": { "function": {}, "event": {} } })))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let (code, output) = (dir.path().join("bytecode"), dir.path().join("out.json"));
+    let unknown = "ab".repeat(16) + &"cd".repeat(16);
+    // PUSH32 <Transfer topic>, PUSH32 <unknown topic>, LOG3
+    std::fs::write(&code, format!("0x7f{TRANSFER_TOPIC}7f{unknown}a3")).unwrap();
+
+    let args = ["sigmund", "--signatures", "--events", "--no-cache", "--file", code.to_str().unwrap(), "--output", output.to_str().unwrap()];
+    let sigmund = Sigmund::from_config(Config::parse_from(args)).unwrap();
+    let client = Client::new("");
+    sigmund.with_provider(Box::new(Openchain::with_url(client.http().clone(), &server.uri()))).execute().await.unwrap();
+
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+    assert_eq!(out["events"]["signatures"], serde_json::json!([{ "text": "Transfer(address,address,uint256)", "topic": TRANSFER_TOPIC }]));
+    assert_eq!(out["events"]["unresolved"], serde_json::json!([unknown]));
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_looks_up_selectors_and_full_hashes_by_length() {
+    let server = MockServer::start().await;
+    let transfer = signature::hash("transfer(address,uint256)");
+    // The colliding text shares the selector of the hash, but not the rest of it
+    let items = serde_json::json!({ "items": [
+        { "hash": transfer, "text": "many_msg_babbage(bytes1)" },
+        { "hash": transfer, "text": "transfer(address,uint256)" }
+    ] });
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/signatures/hash/function/{transfer}/1")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(items))
+        .expect(1)
+        .mount(&server)
+        .await;
+    mount_etherface_transfer(&server).await;
+
+    let args = ["sigmund", "lookup", "0xa9059cbb", &format!("0x{}", transfer.to_uppercase()), "--no-cache"];
+    let client = Client::new("");
+    let sigmund = Sigmund::from_config(Config::parse_from(args)).unwrap().with_provider(Box::new(Etherface::with_url(client.http().clone(), &server.uri())));
+    let lookups = sigmund.lookup(&["0xa9059cbb".to_string(), transfer.clone()]).await.unwrap();
+
+    assert_eq!((lookups[0].kind, lookups[1].kind), (HashKind::Selector, HashKind::Full));
+    assert!(!lookups[0].signatures[0].full_hash_verified);
+    let texts: Vec<_> = lookups[1].signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["transfer(address,uint256)"]);
+    assert!(lookups[1].signatures[0].full_hash_verified);
+    assert!(lookups[1].signatures[0].to_string().contains("(full hash)"));
+
+    assert!(sigmund.lookup(&["0xa9059c".to_string()]).await.is_err());
+}
+
+#[tokio::test]
+async fn it_resolves_event_topics_through_etherface() {
+    let server = MockServer::start().await;
+    let topic = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    Mock::given(path(format!("/v1/signatures/hash/event/{topic}/1")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "items": [{ "hash": topic, "text": "Transfer(address,address,uint256)" }] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = Etherface::with_url(Client::new("").http().clone(), &server.uri());
+    let events = provider.resolve_event(topic).await.unwrap();
+    assert_eq!(events.iter().map(|event| event.text.as_str()).collect::<Vec<_>>(), ["Transfer(address,address,uint256)"]);
+    assert!(provider.resolves_events() && !LocalDb::default().resolves_events());
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_leaves_the_events_out_of_the_etherface_full_hash_matches() {
+    let server = MockServer::start().await;
+    let topic = signature::hash("Transfer(address,address,uint256)");
+    // Every kind is listed together, but the full hash of an event isn't the one of a function
+    Mock::given(path(format!("/v1/signatures/hash/all/{topic}/1")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "items": [{ "hash": topic, "text": "Transfer(address,address,uint256)" }] })))
+        .mount(&server)
+        .await;
+    Mock::given(path(format!("/v1/signatures/hash/function/{topic}/1"))).respond_with(ResponseTemplate::new(404)).expect(1).mount(&server).await;
+
+    let provider = Etherface::with_url(Client::new("").http().clone(), &server.uri());
+    assert!(provider.resolve_hash(&topic).await.unwrap().is_empty());
+}
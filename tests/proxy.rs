@@ -0,0 +1,233 @@
+use clap::Parser;
+use sigmund::{config::Config, Bytecode, Hop, HopKind, ResolutionChain, Sigmund, Target, TargetError, ADMIN_SLOT, BEACON_SLOT, IMPLEMENTATION_SLOT};
+use wiremock::{
+    matchers::{body_partial_json, body_string_contains},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const FACET: &str = "0x00000000000000000000000000000000000000f1";
+
+fn bytecode(hex: &str) -> Bytecode {
+    Bytecode::try_from(hex.to_string()).unwrap()
+}
+
+/// A beacon proxy resolved to its beacon and implementation.
+fn chain() -> ResolutionChain {
+    let mut chain = ResolutionChain::new(Some("0x00000000000000000000000000000000000000a1".to_string()), bytecode("0x01"));
+    chain.hops.push(Hop { kind: HopKind::Beacon, address: Some("0x00000000000000000000000000000000000000b1".to_string()), bytecode: bytecode("0x02") });
+    chain.hops.push(Hop { kind: HopKind::Implementation, address: Some("0x00000000000000000000000000000000000000c1".to_string()), bytecode: bytecode("0x03") });
+    chain
+}
+
+#[test]
+fn it_selects_each_hop_of_the_chain() {
+    let chain = chain();
+    assert_eq!(chain.select(&Target::Proxy).unwrap().bytecode.inner, vec![0x01]);
+    assert_eq!(chain.select(&Target::Beacon).unwrap().bytecode.inner, vec![0x02]);
+    assert_eq!(chain.select(&Target::Implementation).unwrap().bytecode.inner, vec![0x03]);
+}
+
+#[test]
+fn it_defaults_to_the_input_when_it_is_not_a_proxy() {
+    let chain = ResolutionChain::new(None, bytecode("0x01"));
+    assert_eq!(chain.select(&Target::Implementation).unwrap().kind, HopKind::Input);
+    assert_eq!(chain.select(&Target::Beacon).err(), Some(TargetError::Missing("beacon".to_string())));
+}
+
+#[test]
+fn it_requires_enumerated_facets_to_select_a_facet() {
+    let mut chain = chain();
+    let target: Target = format!("facet:{FACET}").parse().unwrap();
+    assert_eq!(chain.select(&target).err(), Some(TargetError::FacetsNotEnumerated));
+
+    chain.hops.push(Hop { kind: HopKind::Facet, address: Some(FACET.to_string()), bytecode: bytecode("0x04") });
+    chain.facets_enumerated = true;
+    let target: Target = format!("facet:{}", FACET.to_uppercase().replace("0X", "0x")).parse().unwrap();
+    assert_eq!(chain.select(&target).unwrap().bytecode.inner, vec![0x04]);
+}
+
+#[test]
+fn it_parses_targets() {
+    assert_eq!("beacon".parse::<Target>().unwrap(), Target::Beacon);
+    assert_eq!(format!("facet:{FACET}").parse::<Target>().unwrap().to_string(), format!("facet:{FACET}"));
+    assert!("facet:0x123".parse::<Target>().is_err());
+    assert!("admin".parse::<Target>().is_err());
+}
+
+/// Serves the code of each address with `eth_getCode`.
+async fn rpc_serving(codes: &[(&str, &str)]) -> MockServer {
+    let server = MockServer::start().await;
+    for (address, code) in codes {
+        answer(&server, "eth_getCode", address, code).await;
+    }
+    server
+}
+
+/// Answers the calls of a method whose parameters contain the given text, e.g. an address or a slot.
+async fn answer(server: &MockServer, method: &str, param: &str, result: &str) {
+    Mock::given(body_partial_json(serde_json::json!({ "method": method })))
+        .and(body_string_contains(param))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result })))
+        .mount(server)
+        .await;
+}
+
+/// Analyzes an address through the RPC with the given flags, returning the JSON output.
+async fn analyze(server: &MockServer, address: &str, flags: &[&str]) -> serde_json::Value {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.json");
+    let uri = server.uri();
+    let args = [&["sigmund", "--address", address, "--rpc-url", &uri, "--output", output.to_str().unwrap()], flags].concat();
+    Sigmund::from_config(Config::try_parse_from(args).unwrap()).unwrap().execute().await.unwrap();
+    serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn it_follows_the_eip7702_delegation_of_an_eoa() {
+    let eoa = "0x00000000000000000000000000000000000000e0";
+    let delegate = "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b";
+    let server = rpc_serving(&[(eoa, &format!("0xef0100{}", &delegate[2..])), (delegate, "0x60003560e01c8063a9059cbb14601057")]).await;
+
+    let out = analyze(&server, eoa, &[]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["target"]["kind"], "delegate");
+    assert_eq!(out["meta"]["resolution"], serde_json::json!([{ "kind": "input", "address": eoa, "selectors": [] }, { "kind": "delegate", "address": delegate, "selectors": ["a9059cbb"] }]));
+
+    // The designator itself has no selector
+    let out = analyze(&server, eoa, &["--no-follow"]).await;
+    assert_eq!(out["selectors"], serde_json::json!([]));
+    assert!(out["meta"].get("resolution").is_none());
+}
+
+#[tokio::test]
+async fn it_follows_an_eip1967_proxy_to_its_implementation() {
+    let proxy = "0x00000000000000000000000000000000000000a1";
+    let implementation = "0x00000000000000000000000000000000000000c1";
+    // upgradeTo(address) and a load of the implementation slot
+    let code = format!("0x60003560e01c80633659cfe6146010577f{}54", &IMPLEMENTATION_SLOT[2..]);
+    let server = rpc_serving(&[(proxy, &code), (implementation, "0x60003560e01c8063a9059cbb14601057")]).await;
+    answer(&server, "eth_getStorageAt", IMPLEMENTATION_SLOT, &format!("0x{:0>64}", &implementation[2..])).await;
+    answer(&server, "eth_getStorageAt", ADMIN_SLOT, &format!("0x{:0>64}", "ad")).await;
+
+    let out = analyze(&server, proxy, &[]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["resolution"][0], serde_json::json!({ "kind": "input", "address": proxy, "selectors": ["3659cfe6"] }));
+    assert_eq!(out["meta"]["resolution"][1], serde_json::json!({ "kind": "implementation", "address": implementation, "selectors": ["a9059cbb"] }));
+    assert_eq!(out["meta"]["proxy_admin"], "0x00000000000000000000000000000000000000ad");
+
+    let out = analyze(&server, proxy, &["--no-follow"]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["3659cfe6"]));
+}
+
+#[tokio::test]
+async fn it_analyzes_an_uninitialized_eip1967_proxy_itself() {
+    let proxy = "0x00000000000000000000000000000000000000a1";
+    // upgradeTo(address) and a delegation to the implementation loaded from its slot
+    let code = format!("0x60003560e01c80633659cfe6146010577f{}545af4", &IMPLEMENTATION_SLOT[2..]);
+    let server = rpc_serving(&[(proxy, &code)]).await;
+    answer(&server, "eth_getStorageAt", IMPLEMENTATION_SLOT, &format!("0x{:0>64}", "")).await;
+    answer(&server, "eth_getStorageAt", BEACON_SLOT, &format!("0x{:0>64}", "")).await;
+
+    let out = analyze(&server, proxy, &[]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["3659cfe6"]));
+    assert!(out["meta"].get("resolution").is_none());
+    let unresolved = |warning: &serde_json::Value| warning["kind"] == "proxy_unresolved" && warning["message"].as_str().unwrap().contains("implementation and beacon slots");
+    assert!(out["warnings"].as_array().unwrap().iter().any(unresolved), "{}", out["warnings"]);
+}
+
+#[tokio::test]
+async fn it_follows_an_eip1967_proxy_with_an_empty_implementation_slot_through_its_beacon() {
+    let proxy = "0x00000000000000000000000000000000000000a1";
+    let beacon = "0x00000000000000000000000000000000000000b1";
+    let implementation = "0x00000000000000000000000000000000000000c1";
+    // A load of the implementation slot only, the proxy delegating to the beacon set in the beacon slot
+    let code = format!("0x60003560e01c80633659cfe6146010577f{}545af4", &IMPLEMENTATION_SLOT[2..]);
+    let server = rpc_serving(&[(proxy, &code), (beacon, "0x60003560e01c80635c60da1b14601057"), (implementation, "0x60003560e01c8063a9059cbb14601057")]).await;
+    answer(&server, "eth_getStorageAt", IMPLEMENTATION_SLOT, &format!("0x{:0>64}", "")).await;
+    answer(&server, "eth_getStorageAt", BEACON_SLOT, &format!("0x{:0>64}", &beacon[2..])).await;
+    answer(&server, "eth_call", beacon, &format!("0x{:0>64}", &implementation[2..])).await;
+
+    let out = analyze(&server, proxy, &[]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["resolution"][1]["kind"], "beacon");
+    assert_eq!(out["meta"]["resolution"][2], serde_json::json!({ "kind": "implementation", "address": implementation, "selectors": ["a9059cbb"] }));
+}
+
+#[tokio::test]
+async fn it_analyzes_a_uups_implementation_itself_without_warnings() {
+    let implementation = "0x00000000000000000000000000000000000000c1";
+    let clone = "0x00000000000000000000000000000000000000a1";
+    // proxiableUUID(), upgradeToAndCall(address,bytes) and transfer(address,uint256), returning the implementation slot
+    // from proxiableUUID() and delegating to the new implementation from upgradeToAndCall()
+    let code = format!(
+        "0x60003560e01c806352d1902d1460255780634f1ef28614604d578063a9059cbb14605557005b7f{}5f5260205ff35b5f5f5f5f855af45b00",
+        &IMPLEMENTATION_SLOT[2..]
+    );
+    let clone_code = format!("0x363d3d373d3d3d363d73{}5af43d82803e903d91602b57fd5bf3", &implementation[2..]);
+    let server = rpc_serving(&[(implementation, &code), (clone, &clone_code)]).await;
+    answer(&server, "eth_getStorageAt", IMPLEMENTATION_SLOT, &format!("0x{:0>64}", "")).await;
+
+    // Its own empty slots don't make it an uninitialized proxy, and its beacon slot isn't read
+    let reads = || async { server.received_requests().await.unwrap().iter().filter(|request| String::from_utf8_lossy(&request.body).contains("eth_getStorageAt")).count() };
+    let out = analyze(&server, implementation, &[]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["4f1ef286", "52d1902d", "a9059cbb"]));
+    assert!(out["warnings"].as_array().is_none_or(|warnings| warnings.iter().all(|warning| warning["kind"] != "proxy_unresolved")), "{}", out["warnings"]);
+    assert_eq!(reads().await, 1);
+
+    // Nor when it's the target of a minimal proxy
+    let out = analyze(&server, clone, &[]).await;
+    assert_eq!(out["meta"]["target"]["kind"], "implementation");
+    assert_eq!(out["selectors"], serde_json::json!(["4f1ef286", "52d1902d", "a9059cbb"]));
+    assert!(out["warnings"].as_array().is_none_or(|warnings| warnings.iter().all(|warning| warning["kind"] != "proxy_unresolved")), "{}", out["warnings"]);
+}
+
+#[tokio::test]
+async fn it_analyzes_the_target_of_a_minimal_proxy() {
+    let clone = "0x00000000000000000000000000000000000000a1";
+    let target = "0x00000000000000000000000000000000000000c1";
+    let code = format!("0x363d3d373d3d3d363d73{}5af43d82803e903d91602b57fd5bf3", &target[2..]);
+    let server = rpc_serving(&[(clone, &code), (target, "0x60003560e01c8063a9059cbb14601057")]).await;
+
+    let out = analyze(&server, clone, &[]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["target"]["address"], target);
+    assert_eq!(out["meta"]["resolution"][0]["kind"], "input");
+    assert_eq!(out["meta"]["resolution"][1]["kind"], "implementation");
+}
+
+#[tokio::test]
+async fn it_follows_a_beacon_proxy_to_the_implementation_of_its_beacon() {
+    let proxy = "0x00000000000000000000000000000000000000a1";
+    let beacon = "0x00000000000000000000000000000000000000b1";
+    let implementation = "0x00000000000000000000000000000000000000c1";
+    // A load of the beacon slot, and a beacon dispatching implementation()
+    let code = format!("0x7f{}54", &BEACON_SLOT[2..]);
+    let server = rpc_serving(&[(proxy, &code), (beacon, "0x60003560e01c80635c60da1b14601057"), (implementation, "0x60003560e01c8063a9059cbb14601057")]).await;
+    answer(&server, "eth_getStorageAt", BEACON_SLOT, &format!("0x{:0>64}", &beacon[2..])).await;
+    answer(&server, "eth_call", beacon, &format!("0x{:0>64}", &implementation[2..])).await;
+
+    let out = analyze(&server, proxy, &[]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["a9059cbb"]));
+    assert_eq!(out["meta"]["resolution"][1], serde_json::json!({ "kind": "beacon", "address": beacon, "selectors": ["5c60da1b"] }));
+    assert_eq!(out["meta"]["resolution"][2], serde_json::json!({ "kind": "implementation", "address": implementation, "selectors": ["a9059cbb"] }));
+
+    let out = analyze(&server, proxy, &["--target", "beacon"]).await;
+    assert_eq!(out["selectors"], serde_json::json!(["5c60da1b"]));
+}
+
+#[tokio::test]
+async fn it_analyzes_a_beacon_proxy_itself_when_its_beacon_reverts() {
+    let proxy = "0x00000000000000000000000000000000000000a1";
+    let beacon = "0x00000000000000000000000000000000000000b1";
+    // A call of implementation() on another contract, the beacon held in the beacon slot
+    let server = rpc_serving(&[(proxy, "0x635c60da1b60e01b5f52"), (beacon, "0x60003560e01c80635c60da1b14601057")]).await;
+    answer(&server, "eth_getStorageAt", BEACON_SLOT, &format!("0x{:0>64}", &beacon[2..])).await;
+    Mock::given(body_partial_json(serde_json::json!({ "method": "eth_call" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": 3, "message": "execution reverted" } })))
+        .mount(&server)
+        .await;
+
+    let out = analyze(&server, proxy, &[]).await;
+    assert_eq!(out["meta"]["target"]["kind"], "input");
+    assert!(out["warnings"].as_array().unwrap().iter().any(|warning| warning["kind"] == "proxy_unresolved" && warning["message"].as_str().unwrap().contains("reverted")), "{}", out["warnings"]);
+}
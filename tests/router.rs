@@ -0,0 +1,52 @@
+use serde_json::json;
+use sigmund::{Client, RouterError, RouterProbe};
+use wiremock::{
+    matchers::{body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const ROUTER: &str = "0x0000000000000000000000000000000000000001";
+
+/// Respond to the `eth_call` of a selector with the given JSON-RPC response.
+async fn mount_call(server: &MockServer, selector: &str, response: serde_json::Value) {
+    Mock::given(method("POST"))
+        .and(body_string_contains(format!(r#""data":"0x{selector}"#)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(server)
+        .await;
+}
+
+fn revert(data: &str) -> serde_json::Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": 3, "message": "execution reverted", "data": data } })
+}
+
+#[tokio::test]
+async fn it_probes_the_selectors_a_router_accepts() {
+    let server = MockServer::start().await;
+    // Unknown selectors revert with `FunctionNotFound(bytes4)`
+    mount_call(&server, "a9059cbb", json!({ "jsonrpc": "2.0", "id": 1, "result": "0x01" })).await;
+    mount_call(&server, "70a08231", revert("0x")).await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(revert("0x5416eb98")))
+        .mount(&server)
+        .await;
+
+    let client = Client::new(&server.uri());
+    let selectors = ["a9059cbb", "70a08231", "095ea7b3"].map(String::from);
+    let probe = RouterProbe::run(&client, ROUTER, &selectors).await.unwrap();
+
+    assert_eq!(probe.accepted, vec!["70a08231", "a9059cbb"]);
+    assert_eq!(probe.to_string(), "storage router accepts 2 of 3 probed selectors");
+}
+
+#[tokio::test]
+async fn it_refuses_to_probe_a_router_accepting_everything() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" })))
+        .mount(&server)
+        .await;
+
+    let result = RouterProbe::run(&Client::new(&server.uri()), ROUTER, &["a9059cbb".to_string()]).await;
+    assert!(matches!(result, Err(RouterError::AcceptsEverything)));
+}
@@ -0,0 +1,104 @@
+use clap::Parser;
+use serde_json::json;
+use sigmund::{config::Config, Sigmund};
+use wiremock::{
+    matchers::{body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// Answers the RPC requests containing the given text with the given result.
+async fn respond(server: &MockServer, contains: &str, result: serde_json::Value) {
+    Mock::given(method("POST"))
+        .and(body_string_contains(contains))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": result })))
+        .mount(server)
+        .await;
+}
+
+/// Scans blocks 1 to 5 of the RPC, writing the output of each contract to `dir` and the checkpoint to `checkpoint`.
+async fn scan(server: &MockServer, dir: &std::path::Path, checkpoint: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    let uri = server.uri();
+    let args = ["sigmund", "--output-dir", dir.to_str().unwrap(), "--rpc-url", &uri, "--no-cache", "scan", "--from-block", "1", "--to-block", "5"];
+    let args = args.into_iter().chain(["--blocks-per-page", "2", "--rate", "1000", "--checkpoint", checkpoint.to_str().unwrap()]);
+    Sigmund::from_config(Config::parse_from(args)).unwrap().execute().await
+}
+
+#[tokio::test]
+async fn it_analyzes_the_contracts_created_in_a_block_range_and_resumes_after_its_checkpoint() {
+    let server = MockServer::start().await;
+    let transaction = |hash: &str, to: Option<&str>| json!({ "hash": hash, "from": "0x00000000000000000000000000000000000000ee", "to": to, "nonce": "0x0" });
+    let receipt = |address: &str, status: &str| json!({ "from": "0x00000000000000000000000000000000000000ee", "to": null, "contractAddress": address, "blockNumber": "0x1", "status": status, "logs": [] });
+    // A creation and a call in block 1, a creation that reverted in block 2, and the next blocks not mined yet
+    respond(&server, r#""params":["0x1",true]"#, json!({ "transactions": [transaction("0xa1", None), transaction("0xa2", Some("0x00000000000000000000000000000000000000aa"))] })).await;
+    respond(&server, r#""params":["0x2",true]"#, json!({ "transactions": [transaction("0xb1", None)] })).await;
+    for block in ["0x3", "0x4", "0x5"] {
+        respond(&server, &format!(r#""params":["{block}",true]"#), json!(null)).await;
+    }
+    respond(&server, r#""params":["0xa1"]"#, receipt("0x00000000000000000000000000000000000000C1", "0x1")).await;
+    respond(&server, r#""params":["0xb1"]"#, receipt("0x00000000000000000000000000000000000000c2", "0x0")).await;
+    respond(&server, "eth_getCode", json!("0x60003560e01c8063a9059cbb14601057")).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let checkpoint = dir.path().join("checkpoint");
+    let outputs = dir.path().join("contracts");
+    scan(&server, &outputs, &checkpoint).await.unwrap();
+
+    let files: Vec<String> = std::fs::read_dir(&outputs).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned()).collect();
+    assert_eq!(files, vec!["0x00000000000000000000000000000000000000c1.json"]);
+    assert_eq!(std::fs::read_to_string(&checkpoint).unwrap(), "2\n");
+
+    // Once block 3 is mined, the scan resumes with it instead of scanning blocks 1 and 2 again
+    server.reset().await;
+    respond(&server, r#""params":["0x3",true]"#, json!({ "transactions": [transaction("0xc1", None)] })).await;
+    respond(&server, r#""params":["0x4",true]"#, json!({ "transactions": [] })).await;
+    respond(&server, r#""params":["0x5",true]"#, json!({ "transactions": [] })).await;
+    respond(&server, r#""params":["0xc1"]"#, receipt("0x00000000000000000000000000000000000000c3", "0x1")).await;
+    respond(&server, "eth_getCode", json!("0x60003560e01c8063a9059cbb14601057")).await;
+    scan(&server, &outputs, &checkpoint).await.unwrap();
+
+    assert!(outputs.join("0x00000000000000000000000000000000000000c3.json").exists());
+    assert_eq!(std::fs::read_to_string(&checkpoint).unwrap(), "5\n");
+    let requests = server.received_requests().await.unwrap();
+    assert!(!requests.iter().any(|request| String::from_utf8_lossy(&request.body).contains(r#""params":["0x1",true]"#)));
+}
+
+#[tokio::test]
+async fn it_keeps_the_checkpoint_before_the_first_failed_contract() {
+    let server = MockServer::start().await;
+    let transaction = |hash: &str| json!({ "hash": hash, "from": "0x00000000000000000000000000000000000000ee", "to": null, "nonce": "0x0" });
+    let receipt = |address: &str| json!({ "from": "0x00000000000000000000000000000000000000ee", "to": null, "contractAddress": address, "blockNumber": "0x1", "status": "0x1", "logs": [] });
+    // A creation in blocks 1, 3 and 5, the code of the one of block 3 failing to be fetched
+    for (block, hash, address) in [("0x1", "0xa1", "c1"), ("0x3", "0xa3", "c3"), ("0x5", "0xa5", "c5")] {
+        respond(&server, &format!(r#""params":["{block}",true]"#), json!({ "transactions": [transaction(hash)] })).await;
+        respond(&server, &format!(r#""params":["{hash}"]"#), receipt(&format!("0x{address:0>40}"))).await;
+    }
+    for block in ["0x2", "0x4"] {
+        respond(&server, &format!(r#""params":["{block}",true]"#), json!({ "transactions": [] })).await;
+    }
+    Mock::given(method("POST"))
+        .and(body_string_contains("eth_getCode"))
+        .and(body_string_contains("00000000000000000000000000000000000000c3"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    respond(&server, "eth_getCode", json!("0x60003560e01c8063a9059cbb14601057")).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let checkpoint = dir.path().join("checkpoint");
+    let outputs = dir.path().join("contracts");
+    let error = scan(&server, &outputs, &checkpoint).await.unwrap_err().to_string();
+    assert_eq!(error, "1 of 3 created contracts failed to be analyzed");
+
+    // The contracts after the failed one are analyzed, but a resumed scan starts over from its block
+    assert!(outputs.join("0x00000000000000000000000000000000000000c5.json").exists());
+    assert_eq!(std::fs::read_to_string(&checkpoint).unwrap(), "2\n");
+}
+
+#[test]
+fn it_refuses_a_reversed_block_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.json");
+    let config = Config::try_parse_from(["sigmund", "--output", output.to_str().unwrap(), "scan", "--from-block", "5", "--to-block", "1"]).unwrap();
+    let error = Sigmund::from_config(config).err().unwrap().to_string();
+    assert!(error.contains("scan --to-block"), "{error}");
+}
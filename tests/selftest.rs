@@ -0,0 +1,11 @@
+use sigmund::selftest;
+
+#[test]
+fn it_passes_every_selftest_check() {
+    let checks = selftest::run();
+    let failed: Vec<String> = checks.iter().filter(|check| !check.passed).map(ToString::to_string).collect();
+    assert!(failed.is_empty(), "failed self-test checks:\n{}", failed.join("\n"));
+
+    // Every fixture is checked, whatever the features
+    assert_eq!(checks.iter().filter(|check| check.component.starts_with("selectors ")).count(), 4);
+}
@@ -0,0 +1,321 @@
+use serde_json::json;
+use sigmund::{
+    providers::{Etherface, SignatureItem},
+    signature, Client, ParamType, ParseError, ParsedSignature, RankHints, Signature,
+};
+#[cfg(feature = "keccak")]
+use sigmund::TextSelector;
+use std::collections::HashSet;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const TRANSFER_HASH: &str = "a9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b";
+
+/// Mount an Etherface response for the `transfer` hash on the mock server.
+async fn mount_etherface(server: &MockServer, items: serde_json::Value) {
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "items": items })))
+        .mount(server)
+        .await;
+}
+
+fn selectors() -> HashSet<String> {
+    HashSet::from(["a9059cbb".to_string()])
+}
+
+#[tokio::test]
+async fn it_keeps_provider_timestamps() {
+    let server = MockServer::start().await;
+    let items = json!([{
+        "hash": TRANSFER_HASH,
+        "text": "transfer(address,uint256)",
+        "added_at": "2022-01-02T03:04:05.123456",
+        "last_seen": "2024-05-06T07:08:09Z"
+    }]);
+    mount_etherface(&server, items).await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &selectors(), false).await.unwrap();
+
+    let signature = &signatures[0];
+    assert_eq!(signature.first_seen.unwrap().to_rfc3339(), "2022-01-02T03:04:05.123456+00:00");
+    assert_eq!(signature.last_seen.unwrap().to_rfc3339(), "2024-05-06T07:08:09+00:00");
+    assert!(format!("{signature:#}").contains("first seen 2022-01-02"));
+
+    let json = serde_json::to_value(signature).unwrap();
+    assert_eq!(json["last_seen"], "2024-05-06T07:08:09Z");
+}
+
+#[tokio::test]
+async fn it_omits_missing_timestamps() {
+    let server = MockServer::start().await;
+    mount_etherface(&server, json!([{ "hash": TRANSFER_HASH, "text": "transfer(address,uint256)" }])).await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &selectors(), false).await.unwrap();
+
+    assert_eq!(signatures[0].first_seen, None);
+    let json = serde_json::to_value(&signatures[0]).unwrap();
+    assert!(json.get("first_seen").is_none());
+    assert!(json.get("last_seen").is_none());
+}
+
+#[tokio::test]
+async fn it_breaks_popularity_ties_by_recency() {
+    let server = MockServer::start().await;
+    let items = json!([
+        { "hash": TRANSFER_HASH, "text": "old(uint256)", "popularity": 5, "last_seen": "2020-01-01T00:00:00Z" },
+        { "hash": TRANSFER_HASH, "text": "rare(uint256)", "popularity": 1, "last_seen": "2024-01-01T00:00:00Z" },
+        { "hash": TRANSFER_HASH, "text": "new(uint256)", "popularity": 5, "last_seen": "2023-01-01T00:00:00Z" }
+    ]);
+    mount_etherface(&server, items).await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &selectors(), true).await.unwrap();
+
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["new(uint256)", "old(uint256)", "rare(uint256)"]);
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_collapses_duplicates_and_groups_variants_by_name() {
+    let server = MockServer::start().await;
+    let items = json!([
+        { "hash": TRANSFER_HASH, "text": "transfer(address,uint256)" },
+        { "hash": TRANSFER_HASH, "text": "transfer(address, uint)" },
+        { "hash": TRANSFER_HASH, "text": "many_msg_babbage(bytes1)" },
+        { "hash": TRANSFER_HASH, "text": "transfer(address,uint256,bytes)" },
+        { "hash": TRANSFER_HASH, "text": "transfer(address,uint256, bytes)" },
+        { "hash": TRANSFER_HASH, "text": "transfer(address)" }
+    ]);
+    mount_etherface(&server, items).await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &selectors(), true).await.unwrap();
+
+    // Expanded, only the entries identical after canonicalization are collapsed
+    let texts: Vec<_> = signatures.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["transfer(address,uint256)", "many_msg_babbage(bytes1)", "transfer(address,uint256,bytes)", "transfer(address)"]);
+
+    let grouped = signature::group_variants(signatures);
+    assert_eq!(grouped.len(), 2);
+    assert_eq!(grouped[0].variants, vec!["transfer(address,uint256,bytes)", "transfer(address)"]);
+    assert!(grouped[1].variants.is_empty());
+
+    // The source is shown last, dimmed
+    assert!(grouped[0].to_string().ends_with("transfer(address,uint256) +2 variants \x1b[2mvia etherface\x1b[22m"));
+    assert!(!grouped[1].to_string().contains("variant"));
+
+    let json = serde_json::to_value(&grouped).unwrap();
+    assert_eq!(json[0]["variants"][1], "transfer(address)");
+    assert!(json[1].get("variants").is_none());
+}
+
+#[test]
+fn it_canonicalizes_whitespace_and_aliases() {
+    assert_eq!(signature::canonicalize("transfer(address, uint)"), "transfer(address,uint256)");
+    assert_eq!(signature::canonicalize("f((uint,byte)[], int)"), "f((uint256,bytes1)[],int256)");
+    assert_eq!(signature::canonicalize(" pause( ) "), "pause()");
+}
+
+#[test]
+fn it_canonicalizes_names_tuples_and_arrays() {
+    let cases = [
+        ("transfer(address to, uint256 amount)", "transfer(address,uint256)"),
+        ("transfer( address  to ,uint amount )", "transfer(address,uint256)"),
+        ("multicall(bytes[] calldata data)", "multicall(bytes[])"),
+        ("withdraw(address payable recipient)", "withdraw(address)"),
+        ("Transfer(address indexed from, address indexed to, uint value)", "Transfer(address,address,uint256)"),
+        ("f(byte b, int i)", "f(bytes1,int256)"),
+        ("f(uint [ 2 ] [] grid)", "f(uint256[2][])"),
+        ("f(function callback)", "f(function)"),
+        ("f(((address,uint256)[],bytes))", "f(((address,uint256)[],bytes))"),
+        ("f(tuple(address to, uint amount)[] memory orders, bytes data)", "f((address,uint256)[],bytes)"),
+        ("f(tuple(tuple(uint a, int b) inner, byte[2][] c)[3] nested)", "f(((uint256,int256),bytes1[2][])[3])"),
+        ("f(() empty, ()[] empties)", "f((),()[])"),
+        ("f()", "f()"),
+        // Unparseable texts are only stripped of whitespace
+        ("f(uint7 x)", "f(uint7x)"),
+        ("f(address, uint256) returns (bool)", "f(address,uint256)returns(bool)"),
+        ("f((address,uint256)", "f((address,uint256)"),
+        ("not a signature", "notasignature"),
+    ];
+    for (text, canonical) in cases {
+        assert_eq!(signature::canonicalize(text), canonical, "{text}");
+    }
+}
+
+#[test]
+fn it_parses_signatures_into_their_parameter_types() {
+    use ParamType::*;
+    let parse = |text: &str| Signature::verified(text.to_string(), "00000000").parse().unwrap();
+
+    assert_eq!(parse("pause()"), ParsedSignature { name: "pause".to_string(), inputs: vec![] });
+    assert_eq!(parse("transfer(address to, uint amount)").inputs, vec![Address, Uint(256)]);
+    // Multi-dimensional arrays apply their dimensions from the left, `uint8[2][]` is a dynamic array of `uint8[2]`
+    assert_eq!(parse("f(uint8[2][] grid, bool[][3])").inputs, vec![Array(Box::new(FixedArray(Box::new(Uint(8)), 2))), FixedArray(Box::new(Array(Box::new(Bool))), 3)]);
+    // Nested tuples, in both spellings
+    let order = Tuple(vec![Array(Box::new(Tuple(vec![Address, Uint(256)]))), Bytes]);
+    assert_eq!(parse("f(((address,uint256)[],bytes))").inputs, vec![order.clone()]);
+    assert_eq!(parse("f(tuple(tuple(address a, uint b)[] legs, bytes data) order)").inputs, vec![order]);
+    assert_eq!(parse("f((),(()))").inputs, vec![Tuple(vec![]), Tuple(vec![Tuple(vec![])])]);
+    assert_eq!(parse("execute(function callback, string memory reason)").to_string(), "execute(function,string)");
+}
+
+#[test]
+fn it_refuses_malformed_signatures_while_parsing() {
+    let cases = [
+        ("transfer", ParseError::MissingParameters("transfer".to_string())),
+        ("(address)", ParseError::InvalidName("(address)".to_string())),
+        ("1f(address)", ParseError::InvalidName("1f(address)".to_string())),
+        ("f((address,uint256)", ParseError::UnbalancedParentheses("f((address,uint256)".to_string())),
+        ("f(uint7)", ParseError::InvalidParameter { text: "f(uint7)".to_string(), param: "uint7".to_string() }),
+        ("f(address,)", ParseError::InvalidParameter { text: "f(address,)".to_string(), param: String::new() }),
+        ("f(uint256[x])", ParseError::InvalidParameter { text: "f(uint256[x])".to_string(), param: "uint256[x]".to_string() }),
+        ("f(address to-be)", ParseError::InvalidParameter { text: "f(address to-be)".to_string(), param: "address to-be".to_string() }),
+    ];
+    for (text, error) in cases {
+        assert_eq!(ParsedSignature::parse(text), Err(error), "{text}");
+    }
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_verifies_and_dedups_texts_by_their_canonical_form() {
+    let named = Signature::verified("transfer(address to, uint amount)".to_string(), "a9059cbb");
+    assert!(named.verified);
+    assert_eq!(named.hash, signature::hash("transfer(address,uint256)"));
+    assert!(named.verify().unwrap());
+
+    let matches = ["transfer(address,uint256)", "transfer(address to, uint256 value)", "transfer(address, uint)"];
+    let deduped = signature::dedup(matches.iter().map(|text| Signature::verified(text.to_string(), "a9059cbb")).collect());
+    assert_eq!(deduped.iter().map(|s| s.text.as_str()).collect::<Vec<_>>(), ["transfer(address,uint256)"]);
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_verifies_provider_results_locally() {
+    let server = MockServer::start().await;
+    let items = json!([
+        { "hash": TRANSFER_HASH, "text": "bogus(uint256)" },
+        { "hash": TRANSFER_HASH, "text": "transfer(address,uint256)" }
+    ]);
+    mount_etherface(&server, items).await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &selectors(), true).await.unwrap();
+
+    // The mismatching entry is kept, but flagged and ranked after the verified one
+    assert_eq!(signatures[0].text, "transfer(address,uint256)");
+    assert!(signatures[0].verified);
+    assert_eq!(signatures[1].text, "bogus(uint256)");
+    assert!(!signatures[1].verified);
+    assert_eq!(signatures[1].selector, "a9059cbb");
+    assert_eq!(signatures[1].hash, signature::hash("bogus(uint256)"));
+    assert!(signatures[1].to_string().contains("(unverified)"));
+
+    let json = serde_json::to_value(&signatures).unwrap();
+    assert_eq!(json[0]["verified"], true);
+    assert_eq!(json[1]["verified"], false);
+}
+
+#[cfg(feature = "keccak")]
+#[tokio::test]
+async fn it_verifies_provider_results_against_the_requested_selector() {
+    let server = MockServer::start().await;
+    // The entry reports the hash of its own text, which isn't the one of the requested selector
+    let bogus = signature::hash("bogus(uint256)");
+    mount_etherface(&server, json!([{ "hash": bogus, "text": "bogus(uint256)" }, { "hash": TRANSFER_HASH, "text": "transfer(address,uint256)" }])).await;
+
+    let client = Client::new("");
+    let provider = Etherface::with_url(client.http().clone(), &server.uri());
+    let signatures = client.get_signatures(&provider, &selectors(), true).await.unwrap();
+
+    let bogus = signatures.iter().find(|s| s.text == "bogus(uint256)").unwrap();
+    assert_eq!(bogus.selector, "a9059cbb");
+    assert!(!bogus.verified);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_verifies_a_signature_against_its_selector() {
+    let signature = Signature::verified("transfer(address,uint256)".to_string(), "a9059cbb");
+    assert_eq!(signature.verify(), Ok(true));
+    assert_eq!(signature::try_hash("transfer(address,uint256)").unwrap(), signature::hash("transfer(address,uint256)"));
+
+    let spoofed = Signature { text: "transfer(address)".to_string(), ..signature };
+    assert_eq!(spoofed.verify(), Ok(false));
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_ranks_error_like_names_first_for_revert_only_selectors() {
+    let mut matches: Vec<Signature> = ["transfer(bytes)", "InsufficientBalance(uint256,uint256)", "approve(bytes)"]
+        .map(|text| Signature::verified(text.to_string(), "deadbeef"))
+        .into();
+    matches.push(Signature::verified("transfer(address,uint256)".to_string(), "a9059cbb"));
+    matches[3].selector = "deadbeef".to_string();
+
+    signature::rank_errors(&mut matches);
+    let texts: Vec<_> = matches.iter().map(|s| s.text.as_str()).collect();
+    // Matches that don't hash to the selector stay last, the stable sort keeps the provider order otherwise
+    assert_eq!(texts, vec!["transfer(address,uint256)", "InsufficientBalance(uint256,uint256)", "transfer(bytes)", "approve(bytes)"]);
+}
+
+#[test]
+fn it_ranks_collided_matches_by_heuristics_before_popularity() {
+    let item = |text: &str, popularity: u64| -> SignatureItem { serde_json::from_value(json!({ "hash": "cae9ca51", "text": text, "popularity": popularity })).unwrap() };
+    let mut items = vec![
+        item("watch_tg_invmru_1186(address,bool)", 90),
+        item("onHintFinanceFlashloan(address,address,uint256,bool,bytes)", 10),
+        item("approveAndCall(address,uint256,bytes)", 20),
+        item("many_msg_babbage(bytes1)", 80),
+        item("customCall(address,uint256,bytes)", 30),
+    ];
+
+    signature::rank(&mut items, &RankHints::default());
+    let texts: Vec<_> = items.iter().map(|item| item.text.as_str()).collect();
+    // The common signature comes first, clean names are then ordered by popularity, and spam-like ones last
+    assert_eq!(
+        texts,
+        vec![
+            "approveAndCall(address,uint256,bytes)",
+            "customCall(address,uint256,bytes)",
+            "onHintFinanceFlashloan(address,address,uint256,bool,bytes)",
+            "watch_tg_invmru_1186(address,bool)",
+            "many_msg_babbage(bytes1)",
+        ]
+    );
+
+    // A match found in the verified ABI of the contract is exact
+    signature::rank(&mut items, &RankHints::with_abi(["onHintFinanceFlashloan(address, address, uint256, bool, bytes)"]));
+    assert_eq!(items[0].text, "onHintFinanceFlashloan(address,address,uint256,bool,bytes)");
+    assert!(signature::score("many_msg_babbage(bytes1)", &RankHints::default()) < signature::score("customCall(uint256)", &RankHints::default()));
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_computes_the_selectors_of_signature_texts_locally() {
+    let transfer = TextSelector::compute(" transfer(address to, uint amount) ").unwrap();
+    assert_eq!(transfer.canonical, "transfer(address,uint256)");
+    assert_eq!(transfer.selector, "0xa9059cbb");
+    assert_eq!(transfer.to_string(), "0xa9059cbb 0xa9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b transfer(address,uint256)");
+
+    let texts = ["Transfer(address indexed from, address indexed to, uint256 value)".to_string(), "pause()".to_string()];
+    let selectors = sigmund::selector(&texts, false, true).unwrap();
+    assert_eq!(selectors.iter().map(ToString::to_string).collect::<Vec<_>>(), ["0xddf252ad Transfer(address,address,uint256)", "0x8456cb59 pause()"]);
+    assert!(serde_json::to_value(&selectors).unwrap()[0].get("hash").is_none());
+
+    // A malformed text fails the command rather than printing the selector of a text nobody means
+    let error = sigmund::selector(&["f(uint7)".to_string()], false, false).unwrap_err();
+    assert!(error.to_string().contains("`uint7` isn't a valid parameter"), "{error}");
+}
@@ -0,0 +1,134 @@
+use sigmund::{Confidence, DeadLetter, DirectorySink, EvmoleSink, HttpSink, OutputSink, SigmundOut};
+#[cfg(feature = "offline")]
+use sigmund::{config::Config, Sigmund, UnknownSelector};
+#[cfg(feature = "keccak")]
+use sigmund::{MinimalOut, Signature, Warning, WarningKind};
+use std::collections::HashSet;
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn out() -> SigmundOut {
+    SigmundOut::new(HashSet::from(["a9059cbb".to_string()]), Vec::new())
+}
+
+#[tokio::test]
+async fn it_posts_results_and_retries_server_errors() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/ingest"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/ingest"))
+        .and(header("content-type", "application/json"))
+        .and(header("x-sigmund-contract", "0x0000130d512ca69ca38add5b9ab2f9deff95c882"))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let sink = HttpSink::new(reqwest::Client::new(), &format!("{}/ingest", server.uri()));
+    sink.write("0x0000130d512ca69ca38add5b9ab2f9deff95c882", &out()).await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(body["selectors"], serde_json::json!(["a9059cbb"]));
+}
+
+#[tokio::test]
+async fn it_dead_letters_rejected_results() {
+    let server = MockServer::start().await;
+
+    // Client errors aren't retried
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(400)).expect(2).mount(&server).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let dead_letter = dir.path().join("failed.ndjson");
+    let sink = HttpSink::new(reqwest::Client::new(), &server.uri());
+    assert!(sink.write("token", &out()).await.is_err());
+
+    let sink = DeadLetter::new(Box::new(sink), &dead_letter);
+    sink.write("token", &out()).await.unwrap();
+
+    let record: serde_json::Value = serde_json::from_str(std::fs::read_to_string(&dead_letter).unwrap().trim()).unwrap();
+    assert_eq!(record["sink"], "http");
+    assert_eq!(record["key"], "token");
+    assert_eq!(record["output"]["selectors"], serde_json::json!(["a9059cbb"]));
+}
+
+#[tokio::test]
+async fn it_writes_a_file_per_contract() {
+    let dir = tempfile::tempdir().unwrap();
+    let sink = DirectorySink::new(dir.path().join("results"));
+    sink.write("0xabc", &out()).await.unwrap();
+    sink.write("../escape", &out()).await.unwrap();
+
+    assert!(dir.path().join("results/0xabc.json").exists());
+    assert_eq!(sink.path_of("../escape"), dir.path().join("results/.._escape.json"));
+    assert!(sink.path_of("../escape").exists());
+}
+
+#[test]
+fn it_sweeps_the_evmole_confidence_thresholds() {
+    let mut out = SigmundOut::new(HashSet::from(["a9059cbb", "70a08231", "deadbeef"].map(str::to_string)), Vec::new());
+    out.confidence = [("a9059cbb", Confidence::High), ("70a08231", Confidence::Medium), ("deadbeef", Confidence::Low)]
+        .map(|(selector, confidence)| (selector.to_string(), confidence))
+        .into();
+
+    let single = EvmoleSink::new(false).render("token", &out);
+    assert_eq!(single, serde_json::json!({ "token": ["70a08231", "a9059cbb", "deadbeef"] }));
+
+    let sweep = EvmoleSink::new(true).render("token", &out);
+    let set = |threshold: &str| -> HashSet<String> { serde_json::from_value(sweep[threshold]["token"].clone()).unwrap() };
+    let (high, medium, low) = (set("high"), set("medium"), set("low"));
+
+    assert_eq!(high, HashSet::from(["a9059cbb".to_string()]));
+    assert!(high.is_subset(&medium) && medium.is_subset(&low));
+    assert_eq!((medium.len(), low.len()), (2, 3));
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn it_keeps_the_minimal_json_format_unchanged() {
+    let signatures = ["transfer(address,uint256)", "many_msg_babbage(bytes1)", "balanceOf(address)"].map(|text| Signature::new(text.to_string(), sigmund::signature::hash(text)));
+    let mut out = SigmundOut::new(HashSet::from(["a9059cbb", "70a08231", "deadbeef"].map(str::to_string)), signatures.to_vec());
+    out.unresolved = vec!["deadbeef".to_string()];
+    out.warnings.push(Warning::new(WarningKind::Selfdestruct, "the code contains a SELFDESTRUCT"));
+
+    // The format is frozen, a change of this golden file is a breaking change for the tools wrapping sigmund
+    let json = serde_json::to_string(&MinimalOut::from(&out)).unwrap();
+    assert_eq!(json, include_str!("fixtures/json_min.json").trim_end());
+}
+
+#[cfg(feature = "offline")]
+#[tokio::test]
+async fn it_resolves_the_exported_unknown_selectors_once_filled_in() {
+    let dir = tempfile::tempdir().unwrap();
+    let (code, export, output) = (dir.path().join("counter"), dir.path().join("unknown.json"), dir.path().join("out.json"));
+    std::fs::write(&code, "0x60003560e01c8063a9059cbb1460175780633fb5c1cb14601757806312345678146017575b00").unwrap();
+    let sigmund = |flags: &[&str]| {
+        let args = ["sigmund", "--signatures", "--offline", "--file", code.to_str().unwrap(), "--output", output.to_str().unwrap(), "--force", "--no-cache"];
+        let config = Config::try_parse_with_origins(args.iter().chain(flags)).unwrap();
+        Sigmund::from_config(config).unwrap().with_console(Box::new(std::io::sink()))
+    };
+
+    sigmund(&["--export-unknown", export.to_str().unwrap()]).execute().await.unwrap();
+    let mut unknown: Vec<UnknownSelector> = serde_json::from_str(&std::fs::read_to_string(&export).unwrap()).unwrap();
+    assert_eq!(unknown.iter().map(|entry| entry.selector.as_str()).collect::<Vec<_>>(), ["0x12345678", "0x3fb5c1cb"]);
+    assert!(unknown.iter().all(|entry| entry.text.is_empty() && entry.seen_in[0].contract == "counter"));
+
+    // Only the filled in entry resolves, the other one is still unknown
+    unknown[1].text = "setNumber(uint256)".to_string();
+    std::fs::write(&export, serde_json::to_string(&unknown).unwrap()).unwrap();
+    sigmund(&["--local-db", export.to_str().unwrap()]).execute().await.unwrap();
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(out["matches"]["3fb5c1cb"], serde_json::json!(["setNumber(uint256)"]));
+    assert_eq!(out["unresolved"], serde_json::json!(["12345678"]));
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "sqlite")]
+
+use clap::Parser;
+use sigmund::{
+    config::{Command, Config},
+    providers::{ImportSummary, JsonPath, ProviderChain, SignatureProvider, SqliteProvider, UrlTemplate, IMPORT_BATCH_SIZE},
+    signature, Client, SignatureCache,
+};
+use std::{collections::HashSet, fmt::Write};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// Imports the CSV content into a new database in the given directory and returns the summary.
+fn import(dir: &std::path::Path, csv: &str) -> ImportSummary {
+    std::fs::write(dir.join("dump.csv"), csv).unwrap();
+    SqliteProvider::import(&dir.join("dump.csv"), &dir.join("sigs.sqlite")).unwrap()
+}
+
+#[tokio::test]
+async fn it_imports_a_dump_and_resolves_in_import_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = "selector,signature\n# comment\n0xa9059cbb,transfer(address,uint256)\na9059cbb,\"many_msg_babbage(bytes1)\"\n8456cb59,pause()\nabc,x()\n8456cb59,unpause()\n";
+    let summary = import(dir.path(), csv);
+    assert_eq!(summary, ImportSummary { imported: 3, duplicates: 0, skipped: 2 });
+
+    // Importing the same dump again keeps the existing entries
+    assert_eq!(import(dir.path(), csv), ImportSummary { imported: 0, duplicates: 3, skipped: 2 });
+
+    let db = SqliteProvider::open(&dir.path().join("sigs.sqlite")).unwrap();
+    let texts: Vec<_> = db.resolve("a9059cbb").await.unwrap().into_iter().map(|s| s.text).collect();
+    assert_eq!(texts, vec!["transfer(address,uint256)", "many_msg_babbage(bytes1)"]);
+    assert!(db.contains("8456cb59") && !db.contains("deadbeef"));
+    assert!(db.resolve("deadbeef").await.unwrap().is_empty());
+}
+
+#[test]
+fn it_imports_dumps_larger_than_a_batch() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut csv = String::new();
+    for i in 0..=IMPORT_BATCH_SIZE {
+        let text = format!("f{i}()");
+        writeln!(csv, "{},{text}", &signature::hash(&text)[..8]).unwrap();
+    }
+
+    assert_eq!(import(dir.path(), &csv).imported, IMPORT_BATCH_SIZE + 1);
+}
+
+#[test]
+fn it_refuses_a_missing_database() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(SqliteProvider::open(&dir.path().join("missing.sqlite")).is_err());
+
+    // An empty file is a valid SQLite database, but without the signature table
+    std::fs::write(dir.path().join("empty.sqlite"), "").unwrap();
+    assert!(SqliteProvider::open(&dir.path().join("empty.sqlite")).is_err());
+}
+
+#[tokio::test]
+async fn it_resolves_imported_selectors_without_network_or_cache_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    import(dir.path(), "a9059cbb,transfer(address,uint256)\n8456cb59,pause()\n");
+
+    // Only the selector the database doesn't know reaches the remote database
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/sig/70a08231"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(["balanceOf(address)"]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let client = Client::new("").with_cache(SignatureCache::open(cache_dir.path()).unwrap());
+    let remote = UrlTemplate::new(client.http().clone(), &format!("{}/sig/{{selector}}", server.uri()), JsonPath::default());
+    let chain = ProviderChain::new(vec![Box::new(SqliteProvider::open(&dir.path().join("sigs.sqlite")).unwrap()), Box::new(remote)]);
+
+    let selectors: HashSet<String> = ["a9059cbb", "8456cb59", "70a08231"].map(String::from).into();
+    let signatures = client.get_signatures(&chain, &selectors, false).await.unwrap();
+    assert_eq!(signatures.len(), 3);
+    assert_eq!(chain.source_of("a9059cbb").as_deref(), Some("sqlite"));
+    assert_eq!(chain.source_of("70a08231").as_deref(), Some("custom"));
+
+    // The imported entries aren't duplicated into the cache, the remote ones are cached as usual
+    let cache = SignatureCache::open(cache_dir.path()).unwrap();
+    assert!(cache.get("a9059cbb").is_none());
+    assert!(cache.get("70a08231").is_some());
+}
+
+#[test]
+fn it_parses_the_import_command() {
+    let config = Config::try_parse_from(["sigmund", "import-db", "dump.csv", "--db", "sigs.sqlite"]).unwrap();
+    assert!(matches!(config.command, Some(Command::ImportDb { .. })));
+    assert!(Config::try_parse_from(["sigmund", "import-db", "dump.csv"]).is_err());
+}
+
+#[tokio::test]
+async fn it_prefers_the_imported_entries_without_dropping_the_cached_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    import(dir.path(), "a9059cbb,transfer(address,uint256)\n");
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut cache = SignatureCache::open(cache_dir.path()).unwrap();
+    cache.insert("a9059cbb", &["remote(uint256)".to_string()], "etherface");
+    cache.insert("70a08231", &[], "etherface");
+    cache.save().unwrap();
+
+    let client = Client::new("").with_cache(SignatureCache::open(cache_dir.path()).unwrap());
+    let chain = ProviderChain::new(vec![Box::new(SqliteProvider::open(&dir.path().join("sigs.sqlite")).unwrap())]);
+    let selectors: HashSet<String> = ["a9059cbb", "70a08231"].map(String::from).into();
+    let signatures = client.get_signatures(&chain, &selectors, false).await.unwrap();
+    assert_eq!(signatures.iter().map(|s| s.text.as_str()).collect::<Vec<_>>(), ["transfer(address,uint256)"]);
+
+    // The cached results of the remote databases are kept for the runs without the database
+    let cache = SignatureCache::open(cache_dir.path()).unwrap();
+    assert_eq!(cache.get("a9059cbb").unwrap().signatures, vec!["remote(uint256)"]);
+    assert!(cache.negative.contains_key("70a08231"));
+}
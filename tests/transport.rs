@@ -0,0 +1,96 @@
+use serde_json::json;
+use sigmund::{providers::Etherface, Client, ClientError, ClientOptions, Transport, TransportMode};
+use std::collections::HashSet;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn client(url: &str, transport: TransportMode) -> Client {
+    let options = ClientOptions { contact: Some("ops@example.com".to_string()), transport, ..Default::default() };
+    Client::with_options(url, &options).unwrap()
+}
+
+#[tokio::test]
+async fn it_replays_a_recording_without_network_access() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = MockServer::start().await;
+    let url = server.uri();
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x6080" })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/signatures/hash/all/a9059cbb/1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("set-cookie", "session=secret")
+                .set_body_json(json!({ "items": [{ "hash": "a9059cbb", "text": "transfer(address,uint256)" }] })),
+        )
+        .mount(&server)
+        .await;
+
+    let selectors = HashSet::from(["a9059cbb".to_string()]);
+    let recording = client(&url, TransportMode::Record(dir.path().to_path_buf()));
+    let provider = Etherface::with_url(recording.http().clone(), &url);
+    let code = recording.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result;
+    let signatures = recording.get_signatures(&provider, &selectors, false).await.unwrap();
+    drop(server);
+
+    let files = std::fs::read_dir(dir.path()).unwrap().count();
+    assert_eq!(files, 2);
+    let recorded: String = std::fs::read_dir(dir.path()).unwrap().map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap()).collect();
+    assert!(recorded.contains("eth_getCode") && recorded.contains("transfer(address,uint256)"));
+    assert!(!recorded.contains("secret") && !recorded.contains("ops@example.com"));
+
+    let replaying = client(&url, TransportMode::Replay(dir.path().to_path_buf()));
+    let provider = Etherface::with_url(replaying.http().clone(), &url);
+    assert_eq!(replaying.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, code);
+    assert_eq!(replaying.get_signatures(&provider, &selectors, false).await.unwrap(), signatures);
+
+    // Requests missing from the recording fail instead of reaching the network
+    let error = replaying.get_code("0x0000000000000000000000000000000000000002").await.err().unwrap();
+    assert!(matches!(error, ClientError::Transport(_)), "{error}");
+    assert!(replaying.get_signatures(&provider, &HashSet::from(["70a08231".to_string()]), false).await.unwrap().is_empty());
+    assert!(replaying.failed_lookups().contains_key("70a08231"));
+}
+
+#[tokio::test]
+async fn it_redacts_secrets_in_recorded_urls() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).respond_with(ResponseTemplate::new(200).set_body_string("{}")).mount(&server).await;
+
+    let transport = Transport::with_mode(reqwest::Client::new(), &TransportMode::Record(dir.path().to_path_buf())).unwrap();
+    transport.get(&format!("{}/api?module=contract&apikey=hunter2", server.uri())).await.unwrap();
+
+    let entry = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap();
+    let recorded = std::fs::read_to_string(entry.path()).unwrap();
+    assert!(recorded.contains("module=contract&apikey=REDACTED") && !recorded.contains("hunter2"));
+
+    // The redacted URL still matches the request when replaying
+    let transport = Transport::with_mode(reqwest::Client::new(), &TransportMode::Replay(dir.path().to_path_buf())).unwrap();
+    let response = transport.get(&format!("{}/api?module=contract&apikey=other", server.uri())).await.unwrap();
+    assert_eq!(response.body, b"{}");
+}
+
+#[tokio::test]
+async fn it_redacts_the_path_keys_of_rpc_urls() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(200).set_body_string("{}")).mount(&server).await;
+
+    let transport = Transport::with_mode(reqwest::Client::new(), &TransportMode::Record(dir.path().to_path_buf())).unwrap();
+    let response = transport.post(&format!("{}/v2/ALCHEMYKEY", server.uri()), "{}".to_string()).await.unwrap();
+    assert_eq!(response.url, format!("{}/REDACTED", server.uri()));
+
+    let entry = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap();
+    let recorded = std::fs::read_to_string(entry.path()).unwrap();
+    assert!(recorded.contains("/REDACTED") && !recorded.contains("ALCHEMYKEY"), "{recorded}");
+
+    // Another key of the same endpoint replays the same interaction
+    let transport = Transport::with_mode(reqwest::Client::new(), &TransportMode::Replay(dir.path().to_path_buf())).unwrap();
+    let response = transport.post(&format!("{}/v3/INFURAKEY", server.uri()), "{}".to_string()).await.unwrap();
+    assert_eq!(response.body, b"{}");
+}
@@ -0,0 +1,85 @@
+use clap::Parser;
+use serde_json::json;
+use sigmund::{config::Config, CallVerdict, CallVerification, Client, Sigmund};
+use wiremock::{
+    matchers::{body_partial_json, body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const CONTRACT: &str = "0x0000000000000000000000000000000000000001";
+
+/// Respond to the `eth_call` of a bare selector with the given JSON-RPC response.
+async fn mount_call(server: &MockServer, selector: &str, response: serde_json::Value) {
+    Mock::given(method("POST"))
+        .and(body_string_contains(format!(r#""data":"0x{selector}""#)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(server)
+        .await;
+}
+
+fn revert(data: &str) -> serde_json::Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": 3, "message": "execution reverted", "data": data } })
+}
+
+/// A contract whose fallback reverts unknown selectors with `Unknown()`.
+async fn contract() -> MockServer {
+    let server = MockServer::start().await;
+    mount_call(&server, "a9059cbb", json!({ "jsonrpc": "2.0", "id": 1, "result": "0x01" })).await;
+    mount_call(&server, "70a08231", revert("0x08c379a0")).await;
+    mount_call(&server, "095ea7b3", revert("0x")).await;
+    Mock::given(body_partial_json(json!({ "method": "eth_call" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(revert("0x6ea98d0b")))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn it_classifies_the_selectors_against_an_unknown_one() {
+    let server = contract().await;
+    let selectors = ["a9059cbb", "70a08231", "095ea7b3", "deadbeef"].map(String::from);
+    let verification = CallVerification::run(&Client::new(&server.uri()), CONTRACT, &selectors, 1000).await.unwrap();
+
+    assert_eq!(verification.verdicts["a9059cbb"], CallVerdict::Executes);
+    assert_eq!(verification.verdicts["70a08231"], CallVerdict::RevertsWithData);
+    assert_eq!(verification.verdicts["095ea7b3"], CallVerdict::RevertsEmpty);
+    assert_eq!(verification.verdicts["deadbeef"], CallVerdict::LikeUnknown);
+    assert!(!selectors.contains(&verification.baseline));
+    assert_eq!(verification.to_string(), "call verification: 1 of 4 selectors answered like an unknown selector");
+}
+
+#[tokio::test]
+async fn it_lowers_the_confidence_of_selectors_answered_like_an_unknown_one() {
+    let server = contract().await;
+    // A dispatcher branch for transfer(address,uint256), and a PUSH4 of a constant compared like one
+    Mock::given(body_partial_json(json!({ "method": "eth_getCode" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x60003560e01c8063a9059cbb146010578063deadbeef14601057" })))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.json");
+    let uri = server.uri();
+    let args = ["sigmund", "--address", CONTRACT, "--rpc-url", &uri, "--verify-calls", "--verify-calls-rate", "1000", "--output", output.to_str().unwrap()];
+    Sigmund::from_config(Config::try_parse_from(args).unwrap()).unwrap().execute().await.unwrap();
+    let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+
+    assert_eq!(out["confidence"], json!({ "a9059cbb": "high", "deadbeef": "low" }));
+    assert_eq!(out["verification"]["verdicts"], json!({ "a9059cbb": "executes", "deadbeef": "like_unknown" }));
+}
+
+#[tokio::test]
+async fn it_leaves_the_empty_reverts_ambiguous_when_the_unknown_selector_reverts_empty_too() {
+    let server = MockServer::start().await;
+    mount_call(&server, "a9059cbb", json!({ "jsonrpc": "2.0", "id": 1, "result": "0x01" })).await;
+    Mock::given(body_partial_json(json!({ "method": "eth_call" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(revert("0x")))
+        .mount(&server)
+        .await;
+    let selectors = ["a9059cbb", "095ea7b3"].map(String::from);
+    let verification = CallVerification::run(&Client::new(&server.uri()), CONTRACT, &selectors, 1000).await.unwrap();
+
+    assert_eq!(verification.verdicts["a9059cbb"], CallVerdict::Executes);
+    assert_eq!(verification.verdicts["095ea7b3"], CallVerdict::RevertsEmpty);
+    assert_eq!(verification.like_unknown().count(), 0);
+}
@@ -0,0 +1,34 @@
+use clap::Parser;
+use serde_json::json;
+use sigmund::{config::Config, Bytecode, Warning, WarningKind};
+
+#[test]
+fn it_serializes_warnings_with_snake_case_kinds() {
+    let warning = Warning::new(WarningKind::NoDispatcherFound, "no function dispatcher found");
+    let json = serde_json::to_value(&warning).unwrap();
+    assert_eq!(json, json!({ "kind": "no_dispatcher_found", "message": "no function dispatcher found", "severity": "warning" }));
+    assert_eq!(WarningKind::InvalidLocalDbEntry.to_string(), "invalid_local_db_entry");
+}
+
+#[test]
+fn it_renders_mutability_warnings_from_the_same_structures() {
+    let bytecode = Bytecode::try_from("0x600035808080f55033ff".to_string()).unwrap();
+    let mutability = bytecode.mutability();
+    let warnings = mutability.warnings();
+
+    let kinds: Vec<_> = warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(kinds, vec![WarningKind::MetamorphicSuspect, WarningKind::Selfdestruct]);
+
+    let console = mutability.to_string();
+    for warning in &warnings {
+        assert!(console.contains(&format!("warning: {}", warning.message)));
+    }
+}
+
+#[test]
+fn it_parses_fatal_warning_kinds() {
+    let args = ["sigmund", "--file", "code.hex", "--fail-on-warning", "selfdestruct,no_dispatcher_found"];
+    let config = Config::try_parse_from(args).unwrap();
+    assert_eq!(config.fail_on_warning, vec![WarningKind::Selfdestruct, WarningKind::NoDispatcherFound]);
+    assert!(Config::try_parse_from(["sigmund", "--file", "code.hex", "--fail-on-warning", "unknown"]).is_err());
+}
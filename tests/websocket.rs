@@ -0,0 +1,83 @@
+#![cfg(feature = "ws")]
+
+use clap::Parser;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use sigmund::{config::Config, Address, Client};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Answers `eth_getCode` with `0x60` followed by the last byte of the address, batches in reverse order,
+/// and every single request after a stale response to another request.
+///
+/// Returns:
+/// The `ws://` URL of the node, and the number of connections it accepted.
+async fn websocket_node() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            tokio::spawn(async move {
+                let code = |request: &Value| {
+                    let address = request["params"][0].as_str().unwrap_or_default();
+                    json!({ "jsonrpc": "2.0", "id": request["id"], "result": format!("0x60{}", &address[address.len() - 2..]) })
+                };
+                while let Some(Ok(Message::Text(text))) = socket.next().await {
+                    let request: Value = serde_json::from_str(&text).unwrap();
+                    let response = match request.as_array() {
+                        Some(batch) => Value::Array(batch.iter().rev().map(code).collect()),
+                        None => {
+                            let stale = json!({ "jsonrpc": "2.0", "id": 4242, "result": "0xdead" });
+                            socket.send(Message::Text(stale.to_string())).await.unwrap();
+                            code(&request)
+                        }
+                    };
+                    socket.send(Message::Text(response.to_string())).await.unwrap();
+                }
+            });
+        }
+    });
+    (url, connections)
+}
+
+#[tokio::test]
+async fn it_fetches_the_code_over_a_reused_websocket() {
+    let (url, connections) = websocket_node().await;
+    let client = Client::new(&url);
+
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000001").await.unwrap().result, "0x6001");
+    assert_eq!(client.get_code("0x0000000000000000000000000000000000000002").await.unwrap().result, "0x6002");
+
+    // The responses of a batch are correlated by id, whatever their order
+    let addresses: Vec<Address> = (3..=5).map(|n| Address::try_from(format!("0x{n:040x}")).unwrap()).collect();
+    let codes: Vec<String> = client.get_codes(&addresses).await.into_iter().map(|code| code.unwrap().result).collect();
+    assert_eq!(codes, ["0x6003", "0x6004", "0x6005"]);
+
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn it_reports_an_unreachable_websocket() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://user:secret@{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let error = Client::new(&url).get_code("0x0000000000000000000000000000000000000001").await.err().unwrap().to_string();
+    assert!(error.starts_with("WebSocketError: ws://user:REDACTED@"), "{error}");
+}
+
+#[test]
+fn it_refuses_a_proxy_the_websocket_would_bypass() {
+    let config = Config::try_parse_from(["sigmund", "--address", "0x0000000000000000000000000000000000000001", "--rpc-url", "ws://127.0.0.1:8546", "--proxy", "http://proxy:3128"]).unwrap();
+    let error = config.validate().err().unwrap().to_string();
+    assert!(error.contains("--proxy requires an http(s) --rpc-url"), "{error}");
+}